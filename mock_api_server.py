@@ -74,6 +74,9 @@ class PredictionAPIHandler(BaseHTTPRequestHandler):
         print(f"  Row Count: {row_count}")
         print(f"  Port: {port}")
         print(f"  Timestamp: {data.get('timestamp', 'unknown')}")
+        for channel in data.get('channels', []):
+            unit = f" [{channel['unit']}]" if channel.get('unit') else ""
+            print(f"  Channel: {channel.get('name')}{unit} ({len(channel.get('values', []))} values)")
     
     def send_success_response(self, request_data):
         """Send a successful prediction response"""
@@ -0,0 +1,229 @@
+#!/usr/bin/env python3
+"""
+Mock ASTM Host
+
+Stands in for a LIS that speaks ASTM E1394 over LIS1-A framing. Listens for
+results sent by the app, and can download orders to the app's order port.
+
+Usage:
+    python mock_astm_host.py [--port PORT] [--nak-rate RATE]
+    python mock_astm_host.py --send-order PATIENT_ID SPECIMEN_ID TEST_TYPE [--order-port PORT]
+
+Examples:
+    python mock_astm_host.py                          # Receive results on port 5000
+    python mock_astm_host.py --nak-rate 0.3           # NAK 30% of frames to exercise retransmission
+    python mock_astm_host.py --send-order MRN-7 S1001 covid19
+    python mock_astm_host.py --send-order MRN-7 S1001 covid19 --cancel
+"""
+
+import argparse
+import random
+import socket
+import socketserver
+from datetime import datetime
+
+STX, ETX, EOT, ENQ, ACK, LF, CR, NAK, ETB = 0x02, 0x03, 0x04, 0x05, 0x06, 0x0A, 0x0D, 0x15, 0x17
+MAX_FRAME_TEXT = 240
+
+
+def checksum(body):
+    """Modulo-256 sum of frame number, text and terminator as two hex digits"""
+    return f"{sum(body) % 256:02X}".encode()
+
+
+def build_frames(records):
+    """Frame each record, splitting long ones into ETB frames"""
+    frames = []
+    number = 1
+    for record in records:
+        text = record.encode('utf-8') + bytes([CR])
+        chunks = [text[i:i + MAX_FRAME_TEXT] for i in range(0, len(text), MAX_FRAME_TEXT)]
+        for i, chunk in enumerate(chunks):
+            terminator = ETX if i == len(chunks) - 1 else ETB
+            body = bytes([ord('0') + number]) + chunk + bytes([terminator])
+            frames.append(bytes([STX]) + body + checksum(body) + bytes([CR, LF]))
+            number = (number + 1) % 8
+    return frames
+
+
+class ASTMHandler(socketserver.BaseRequestHandler):
+    """Receiver side of LIS1-A for one connection"""
+
+    # Class variable set by command line args
+    nak_rate = 0.0
+
+    def handle(self):
+        buffer = b''
+        records = []
+        record = b''
+        while True:
+            chunk = self.request.recv(4096)
+            if not chunk:
+                return
+            buffer += chunk
+
+            while buffer:
+                byte = buffer[0]
+                if byte == ENQ:
+                    buffer = buffer[1:]
+                    print(f"\n[ASTM] ENQ from {self.client_address[0]}, replying ACK")
+                    records, record = [], b''
+                    self.request.sendall(bytes([ACK]))
+                elif byte == STX:
+                    end = buffer.find(bytes([LF]))
+                    if end < 0:
+                        break
+                    frame, buffer = buffer[1:end + 1], buffer[end + 1:]
+                    body, received = frame[:-4], frame[-4:-2]
+                    if received.upper() != checksum(body) or random.random() < self.nak_rate:
+                        print(f"[ASTM] NAK frame {chr(body[0])}")
+                        self.request.sendall(bytes([NAK]))
+                        continue
+                    record += body[1:-1]
+                    if body[-1] == ETX:
+                        records.append(record.decode('utf-8', errors='replace').rstrip('\r'))
+                        record = b''
+                    self.request.sendall(bytes([ACK]))
+                elif byte == EOT:
+                    buffer = buffer[1:]
+                    print("[ASTM] Message received:")
+                    for r in records:
+                        print(f"  {r}")
+                else:
+                    buffer = buffer[1:]
+
+
+def wait_for(sock, expected):
+    """Read single control characters until one of `expected` arrives"""
+    while True:
+        data = sock.recv(1)
+        if not data:
+            raise ConnectionError("App closed the connection")
+        if data[0] in expected:
+            return data[0]
+
+
+def send_order(host, port, patient_id, specimen_id, test_type, cancel):
+    """Download one order to the app as the LIS1-A sender"""
+    now = datetime.now().strftime('%Y%m%d%H%M%S')
+    records = [
+        f"H|\\^&|||MOCK-LIS|||||EBERS||P|LIS2-A2|{now}",
+        f"P|1|{patient_id}|||Doe^Jane||19800101",
+        f"O|1|{specimen_id}||^^^{test_type}|R|{now}|||||{'C' if cancel else 'N'}",
+        "L|1|N",
+    ]
+    with socket.create_connection((host, port), timeout=15) as sock:
+        sock.sendall(bytes([ENQ]))
+        if wait_for(sock, (ACK, NAK)) != ACK:
+            print("[ASTM] App is busy")
+            return
+        for frame in build_frames(records):
+            for _ in range(6):
+                sock.sendall(frame)
+                if wait_for(sock, (ACK, NAK)) == ACK:
+                    break
+            else:
+                print("[ASTM] App refused a frame 6 times")
+                sock.sendall(bytes([EOT]))
+                return
+        sock.sendall(bytes([EOT]))
+    action = "Cancellation" if cancel else "Order"
+    print(f"[ASTM] {action} {specimen_id} ({test_type}) for patient {patient_id} sent")
+
+
+def run_host(port=5000, nak_rate=0.0):
+    """Run the mock ASTM host"""
+    ASTMHandler.nak_rate = nak_rate
+
+    socketserver.ThreadingTCPServer.allow_reuse_address = True
+    server = socketserver.ThreadingTCPServer(('', port), ASTMHandler)
+
+    print("=" * 60)
+    print("Mock ASTM Host")
+    print("=" * 60)
+    print(f"Listening on: localhost:{port}")
+    print(f"NAK rate: {nak_rate:.1%}")
+    print("\nIn Settings > ASTM Host, use:")
+    print(f"  Transport: TCP  Host: localhost  Port: {port}")
+    print("\nPress Ctrl+C to stop the host")
+    print("=" * 60)
+    print()
+
+    try:
+        server.serve_forever()
+    except KeyboardInterrupt:
+        print("\n\n[ASTM] Host stopped by user")
+        server.shutdown()
+
+
+def main():
+    parser = argparse.ArgumentParser(
+        description='Mock ASTM E1394 host for testing result upload and order download',
+        formatter_class=argparse.RawDescriptionHelpFormatter,
+        epilog="""
+Examples:
+  %(prog)s                                        Receive results on port 5000
+  %(prog)s --nak-rate 0.3                         NAK 30%% of frames
+  %(prog)s --send-order MRN-7 S1001 covid19       Download an order to the app
+        """
+    )
+
+    parser.add_argument(
+        '--port',
+        type=int,
+        default=5000,
+        help='Port to receive results on (default: 5000)'
+    )
+
+    parser.add_argument(
+        '--nak-rate',
+        type=float,
+        default=0.0,
+        help='Fraction of frames answered with NAK (default: 0.0)'
+    )
+
+    parser.add_argument(
+        '--send-order',
+        nargs=3,
+        metavar=('PATIENT_ID', 'SPECIMEN_ID', 'TEST_TYPE'),
+        help='Send one order to the app instead of listening'
+    )
+
+    parser.add_argument(
+        '--cancel',
+        action='store_true',
+        help='Send the order as a cancellation'
+    )
+
+    parser.add_argument(
+        '--order-host',
+        default='localhost',
+        help='Host running the app (default: localhost)'
+    )
+
+    parser.add_argument(
+        '--order-port',
+        type=int,
+        default=5001,
+        help="App's order port (default: 5001)"
+    )
+
+    args = parser.parse_args()
+
+    # Validate arguments
+    for port in (args.port, args.order_port):
+        if port < 1 or port > 65535:
+            parser.error("Port must be between 1 and 65535")
+
+    if args.nak_rate < 0 or args.nak_rate > 1:
+        parser.error("NAK rate must be between 0.0 and 1.0")
+
+    if args.send_order:
+        patient_id, specimen_id, test_type = args.send_order
+        send_order(args.order_host, args.order_port, patient_id, specimen_id, test_type, args.cancel)
+    else:
+        run_host(port=args.port, nak_rate=args.nak_rate)
+
+
+if __name__ == '__main__':
+    main()
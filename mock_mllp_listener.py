@@ -0,0 +1,179 @@
+#!/usr/bin/env python3
+"""
+Mock MLLP Listener
+
+Stands in for a laboratory information system when testing HL7 result
+delivery. Prints every message received and replies with an HL7 ACK.
+
+Usage:
+    python mock_mllp_listener.py [--port PORT] [--ack CODE] [--delay SECONDS] [--drop-rate RATE]
+
+Examples:
+    python mock_mllp_listener.py                    # Listen on default port 2575, accept everything
+    python mock_mllp_listener.py --ack AE           # Reject every message as an application error
+    python mock_mllp_listener.py --delay 40         # Reply too late, to exercise the ACK timeout
+    python mock_mllp_listener.py --drop-rate 0.5    # Close the connection without an ACK half the time
+"""
+
+import argparse
+import random
+import socketserver
+import time
+from datetime import datetime, timezone
+
+START_BLOCK = b'\x0b'
+END_BLOCK = b'\x1c\x0d'
+
+
+class MLLPHandler(socketserver.BaseRequestHandler):
+    """Handler for one MLLP connection"""
+
+    # Class variables set by command line args
+    ack_code = 'AA'
+    response_delay = 0
+    drop_rate = 0.0
+
+    def handle(self):
+        """Read framed messages until the sender disconnects"""
+        buffer = b''
+        while True:
+            chunk = self.request.recv(4096)
+            if not chunk:
+                return
+            buffer += chunk
+
+            while END_BLOCK in buffer:
+                frame, buffer = buffer.split(END_BLOCK, 1)
+                message = frame.split(START_BLOCK, 1)[-1].decode('utf-8', errors='replace')
+                self.log_message(message)
+
+                if self.response_delay > 0:
+                    print(f"[MLLP] Waiting {self.response_delay}s before replying...")
+                    time.sleep(self.response_delay)
+
+                if random.random() < self.drop_rate:
+                    print("[MLLP] Dropping connection without an ACK")
+                    return
+
+                ack = self.build_ack(message)
+                self.request.sendall(START_BLOCK + ack.encode('utf-8') + END_BLOCK)
+
+    def log_message(self, message):
+        """Print the received message one segment per line"""
+        print(f"\n[MLLP] Received message from {self.client_address[0]}:")
+        for segment in message.split('\r'):
+            if segment:
+                print(f"  {segment}")
+
+    def build_ack(self, message):
+        """Build an ACK echoing the message control ID"""
+        msh = next((s for s in message.split('\r') if s.startswith('MSH|')), '')
+        fields = msh.split('|')
+
+        def field(index):
+            return fields[index] if len(fields) > index else ''
+
+        control_id = field(9)
+        timestamp = datetime.now(timezone.utc).strftime('%Y%m%d%H%M%S+0000')
+        text = '' if self.ack_code == 'AA' else 'Rejected by mock listener'
+
+        segments = [
+            '|'.join([
+                'MSH', '^~\\&', field(4), field(5), field(2), field(3), timestamp, '',
+                'ACK^R01^ACK', f"ACK{int(time.time())}", 'P', field(11) or '2.5.1',
+            ]),
+            '|'.join(['MSA', self.ack_code, control_id, text]),
+        ]
+        print(f"[MLLP] Replying {self.ack_code} for message {control_id}")
+        return '\r'.join(segments) + '\r'
+
+
+def run_listener(port=2575, ack_code='AA', delay=0, drop_rate=0.0):
+    """Run the mock MLLP listener"""
+    # Set class variables for handler
+    MLLPHandler.ack_code = ack_code
+    MLLPHandler.response_delay = delay
+    MLLPHandler.drop_rate = drop_rate
+
+    socketserver.ThreadingTCPServer.allow_reuse_address = True
+    server = socketserver.ThreadingTCPServer(('', port), MLLPHandler)
+
+    print("=" * 60)
+    print("Mock MLLP Listener")
+    print("=" * 60)
+    print(f"Listening on: localhost:{port}")
+    print(f"ACK code: {ack_code}")
+    print(f"Response delay: {delay}s")
+    print(f"Drop rate: {drop_rate:.1%}")
+    print("\nIn Settings > Laboratory Information System, use:")
+    print(f"  Host: localhost  Port: {port}")
+    print("\nPress Ctrl+C to stop the listener")
+    print("=" * 60)
+    print()
+
+    try:
+        server.serve_forever()
+    except KeyboardInterrupt:
+        print("\n\n[MLLP] Listener stopped by user")
+        server.shutdown()
+
+
+def main():
+    parser = argparse.ArgumentParser(
+        description='Mock MLLP listener for testing HL7 result delivery',
+        formatter_class=argparse.RawDescriptionHelpFormatter,
+        epilog="""
+Examples:
+  %(prog)s                          Listen on default port 2575
+  %(prog)s --port 6661              Listen on port 6661
+  %(prog)s --ack AE                 Reply with an application error
+  %(prog)s --ack AR                 Reply with a temporary rejection (retried)
+  %(prog)s --drop-rate 0.2          Drop 20%% of connections without replying
+        """
+    )
+
+    parser.add_argument(
+        '--port',
+        type=int,
+        default=2575,
+        help='Port to listen on (default: 2575)'
+    )
+
+    parser.add_argument(
+        '--ack',
+        choices=['AA', 'AE', 'AR'],
+        default='AA',
+        help='Acknowledgement code to reply with (default: AA)'
+    )
+
+    parser.add_argument(
+        '--delay',
+        type=float,
+        default=0,
+        help='Seconds to wait before replying (default: 0)'
+    )
+
+    parser.add_argument(
+        '--drop-rate',
+        type=float,
+        default=0.0,
+        help='Fraction of messages answered by closing the connection (default: 0.0)'
+    )
+
+    args = parser.parse_args()
+
+    # Validate arguments
+    if args.port < 1 or args.port > 65535:
+        parser.error("Port must be between 1 and 65535")
+
+    if args.delay < 0:
+        parser.error("Delay must be non-negative")
+
+    if args.drop_rate < 0 or args.drop_rate > 1:
+        parser.error("Drop rate must be between 0.0 and 1.0")
+
+    run_listener(port=args.port, ack_code=args.ack, delay=args.delay, drop_rate=args.drop_rate)
+
+
+if __name__ == '__main__':
+    main()
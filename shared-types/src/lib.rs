@@ -84,6 +84,16 @@ pub enum TestType {
 }
 
 impl TestType {
+    /// Every supported test type, in display order
+    pub fn all() -> [TestType; 4] {
+        [
+            TestType::InfectiousDisease,
+            TestType::Covid19,
+            TestType::Flu,
+            TestType::Strep,
+        ]
+    }
+
     pub fn as_str(&self) -> &'static str {
         match self {
             TestType::InfectiousDisease => "infectious_disease",
@@ -111,8 +121,14 @@ pub enum TestStatus {
     Pending,
     #[serde(rename = "in_progress")]
     InProgress,
+    /// Result is in but has to be signed off before release
+    #[serde(rename = "awaiting_review")]
+    AwaitingReview,
     #[serde(rename = "completed")]
     Completed,
+    /// A reviewer rejected the result and ordered a retest
+    #[serde(rename = "rejected")]
+    Rejected,
     #[serde(rename = "error")]
     Error,
     #[serde(rename = "cancelled")]
@@ -124,7 +140,9 @@ impl TestStatus {
         match self {
             TestStatus::Pending => "pending",
             TestStatus::InProgress => "in_progress",
+            TestStatus::AwaitingReview => "awaiting_review",
             TestStatus::Completed => "completed",
+            TestStatus::Rejected => "rejected",
             TestStatus::Error => "error",
             TestStatus::Cancelled => "cancelled",
         }
@@ -134,7 +152,9 @@ impl TestStatus {
         match s {
             "pending" => Ok(TestStatus::Pending),
             "in_progress" => Ok(TestStatus::InProgress),
+            "awaiting_review" => Ok(TestStatus::AwaitingReview),
             "completed" => Ok(TestStatus::Completed),
+            "rejected" => Ok(TestStatus::Rejected),
             "error" => Ok(TestStatus::Error),
             "cancelled" => Ok(TestStatus::Cancelled),
             _ => Err(format!("Unknown test status: {}", s)),
@@ -153,7 +173,23 @@ pub enum DetectionResult {
     Inconclusive,
 }
 
+/// Probability at or above which a result is positive
+pub const POSITIVE_THRESHOLD: f64 = 0.7;
+/// Probability at or above which a non-positive result is inconclusive
+pub const INCONCLUSIVE_THRESHOLD: f64 = 0.3;
+
 impl DetectionResult {
+    /// Classify a detection probability against the fixed thresholds
+    pub fn from_probability(probability: f64) -> Self {
+        if probability >= POSITIVE_THRESHOLD {
+            DetectionResult::Positive
+        } else if probability >= INCONCLUSIVE_THRESHOLD {
+            DetectionResult::Inconclusive
+        } else {
+            DetectionResult::Negative
+        }
+    }
+
     pub fn as_str(&self) -> &'static str {
         match self {
             DetectionResult::Positive => "positive",
@@ -162,6 +198,15 @@ impl DetectionResult {
         }
     }
 
+    /// Capitalised name for display and outgoing messages
+    pub fn label(&self) -> &'static str {
+        match self {
+            DetectionResult::Positive => "Positive",
+            DetectionResult::Negative => "Negative",
+            DetectionResult::Inconclusive => "Inconclusive",
+        }
+    }
+
     pub fn from_str(s: &str) -> Result<Self, String> {
         match s {
             "positive" => Ok(DetectionResult::Positive),
@@ -198,6 +243,47 @@ pub struct Test {
     pub updated_at: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub completed_at: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quality_report: Option<DataQualityReport>,
+    /// Username of the operator who ran the test
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub operated_by: Option<String>,
+    /// Who released the result: the reviewer, or the operator when review is off
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub released_by: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub released_at: Option<String>,
+    /// Review steps in the order they happened
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub review_log: Vec<ReviewEvent>,
+    /// UUID of the rejected test this one repeats
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retest_of: Option<String>,
+    /// Sample identifier from the LIS order this test fulfils
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub specimen_id: Option<String>,
+    /// Number on the tube and cartridge barcodes, assigned when the test is stored
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub accession_number: Option<String>,
+    /// Downsampled first channel of the preprocessed signal, for thumbnails
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub signal_preview: Vec<f64>,
+}
+
+impl Test {
+    /// Only released results may leave the system in reports or exports
+    pub fn is_released(&self) -> bool {
+        self.released_at.is_some()
+    }
+
+    /// A result is recorded; from here only review may change the test
+    pub fn has_result(&self) -> bool {
+        self.is_released()
+            || matches!(
+                self.status.as_str(),
+                "awaiting_review" | "completed" | "rejected"
+            )
+    }
 }
 
 #[cfg(feature = "backend")]
@@ -225,6 +311,15 @@ impl Test {
             created_at: now.clone(),
             updated_at: now,
             completed_at: None,
+            quality_report: None,
+            operated_by: None,
+            released_by: None,
+            released_at: None,
+            review_log: Vec::new(),
+            retest_of: None,
+            specimen_id: None,
+            accession_number: None,
+            signal_preview: Vec::new(),
         }
     }
 
@@ -260,6 +355,133 @@ impl Test {
         self.error_message = Some(error_message);
         self.touch();
     }
+
+    /// Hold a completed result until a reviewer signs it off
+    pub fn mark_awaiting_review(&mut self, operator: String) {
+        self.status = "awaiting_review".to_string();
+        self.released_by = None;
+        self.released_at = None;
+        self.record_review(ReviewEvent::new(ReviewAction::Submitted, operator));
+    }
+
+    /// Release a completed result without review
+    pub fn mark_released(&mut self, operator: String) {
+        self.release(ReviewEvent::new(ReviewAction::Released, operator));
+    }
+
+    /// Release the result as measured
+    pub fn mark_accepted(&mut self, reviewer: String) {
+        self.release(ReviewEvent::new(ReviewAction::Accepted, reviewer));
+    }
+
+    /// Replace the measured result and release it; the original stays in the log
+    pub fn mark_overridden(&mut self, reviewer: String, result: String, reason: String) {
+        let mut event = ReviewEvent::new(ReviewAction::Overridden, reviewer);
+        event.reason = Some(reason);
+        event.previous_result = self.detection_result.replace(result.clone());
+        event.new_result = Some(result);
+        self.release(event);
+    }
+
+    /// Withhold the result for good, pointing at the retest that replaces it
+    pub fn mark_rejected(&mut self, reviewer: String, reason: String, retest_uuid: String) {
+        let mut event = ReviewEvent::new(ReviewAction::Rejected, reviewer);
+        event.reason = Some(reason);
+        event.retest_uuid = Some(retest_uuid);
+        self.status = "rejected".to_string();
+        self.record_review(event);
+    }
+
+    /// A pending test of the same type for the same patient, linked back to this one
+    pub fn new_retest(&self) -> Self {
+        let mut retest = Self::new_pending(self.patient_id, self.test_type.clone(), None, None);
+        retest.retest_of = Some(self.uuid.clone());
+        retest.specimen_id = self.specimen_id.clone();
+        retest
+    }
+
+    fn release(&mut self, event: ReviewEvent) {
+        self.status = "completed".to_string();
+        self.released_by = Some(event.by.clone());
+        self.released_at = Some(event.at.clone());
+        self.record_review(event);
+    }
+
+    fn record_review(&mut self, event: ReviewEvent) {
+        self.updated_at = event.at.clone();
+        self.review_log.push(event);
+    }
+}
+
+/// What happened at one step of a result's review
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum ReviewAction {
+    /// Completed and queued for review
+    #[serde(rename = "submitted")]
+    Submitted,
+    /// Released on completion because review is not required
+    #[serde(rename = "released")]
+    Released,
+    #[serde(rename = "accepted")]
+    Accepted,
+    #[serde(rename = "overridden")]
+    Overridden,
+    #[serde(rename = "rejected")]
+    Rejected,
+}
+
+impl ReviewAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReviewAction::Submitted => "submitted",
+            ReviewAction::Released => "released",
+            ReviewAction::Accepted => "accepted",
+            ReviewAction::Overridden => "overridden",
+            ReviewAction::Rejected => "rejected",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ReviewAction::Submitted => "Submitted for review",
+            ReviewAction::Released => "Released without review",
+            ReviewAction::Accepted => "Accepted",
+            ReviewAction::Overridden => "Result overridden",
+            ReviewAction::Rejected => "Rejected, retest ordered",
+        }
+    }
+}
+
+/// One entry in a test's review log
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ReviewEvent {
+    pub action: ReviewAction,
+    /// Username of whoever took the step
+    pub by: String,
+    pub at: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub previous_result: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub new_result: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retest_uuid: Option<String>,
+}
+
+#[cfg(feature = "backend")]
+impl ReviewEvent {
+    pub fn new(action: ReviewAction, by: String) -> Self {
+        Self {
+            action,
+            by,
+            at: chrono::Utc::now().to_rfc3339(),
+            reason: None,
+            previous_result: None,
+            new_result: None,
+            retest_uuid: None,
+        }
+    }
 }
 
 // ============================================================================
@@ -272,3 +494,948 @@ pub struct TestWithPatient {
     pub test: Test,
     pub patient: Patient,
 }
+
+// ============================================================================
+// DEVICE MODEL
+// ============================================================================
+
+/// Serial parity setting
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum Parity {
+    #[serde(rename = "none")]
+    None,
+    #[serde(rename = "odd")]
+    Odd,
+    #[serde(rename = "even")]
+    Even,
+}
+
+impl Parity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Parity::None => "none",
+            Parity::Odd => "odd",
+            Parity::Even => "even",
+        }
+    }
+
+    pub fn from_str(s: &str) -> Result<Self, String> {
+        match s {
+            "none" => Ok(Parity::None),
+            "odd" => Ok(Parity::Odd),
+            "even" => Ok(Parity::Even),
+            _ => Err(format!("Unknown parity: {}", s)),
+        }
+    }
+}
+
+/// Serial flow control setting
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum FlowControl {
+    #[serde(rename = "none")]
+    None,
+    #[serde(rename = "software")]
+    Software,
+    #[serde(rename = "hardware")]
+    Hardware,
+}
+
+impl FlowControl {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FlowControl::None => "none",
+            FlowControl::Software => "software",
+            FlowControl::Hardware => "hardware",
+        }
+    }
+
+    pub fn from_str(s: &str) -> Result<Self, String> {
+        match s {
+            "none" => Ok(FlowControl::None),
+            "software" => Ok(FlowControl::Software),
+            "hardware" => Ok(FlowControl::Hardware),
+            _ => Err(format!("Unknown flow control: {}", s)),
+        }
+    }
+}
+
+/// Serial line settings applied when a device's port is opened
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SerialProfile {
+    pub baud_rate: u32,
+    pub data_bits: u8,
+    pub parity: Parity,
+    pub stop_bits: u8,
+    pub flow_control: FlowControl,
+    pub read_timeout_ms: u64,
+    pub chunk_size: usize,
+}
+
+impl Default for SerialProfile {
+    /// 8N1 at 115200 baud, no flow control
+    fn default() -> Self {
+        Self {
+            baud_rate: 115_200,
+            data_bits: 8,
+            parity: Parity::None,
+            stop_bits: 1,
+            flow_control: FlowControl::None,
+            read_timeout_ms: 50,
+            chunk_size: 1024,
+        }
+    }
+}
+
+impl SerialProfile {
+    /// Check that every setting is one the serial driver accepts
+    pub fn validate(&self) -> Result<(), String> {
+        if self.baud_rate == 0 {
+            return Err("Baud rate must be greater than zero".to_string());
+        }
+        if !(5..=8).contains(&self.data_bits) {
+            return Err(format!("Data bits must be 5-8, got {}", self.data_bits));
+        }
+        if !(1..=2).contains(&self.stop_bits) {
+            return Err(format!("Stop bits must be 1 or 2, got {}", self.stop_bits));
+        }
+        if !(1..=10_000).contains(&self.read_timeout_ms) {
+            return Err(format!(
+                "Read timeout must be 1-10000 ms, got {}",
+                self.read_timeout_ms
+            ));
+        }
+        if !(1..=65_536).contains(&self.chunk_size) {
+            return Err(format!(
+                "Chunk size must be 1-65536 bytes, got {}",
+                self.chunk_size
+            ));
+        }
+        Ok(())
+    }
+
+    /// Short line description, e.g. "115200 8N1"
+    pub fn summary(&self) -> String {
+        let parity = match self.parity {
+            Parity::None => 'N',
+            Parity::Odd => 'O',
+            Parity::Even => 'E',
+        };
+        format!(
+            "{} {}{}{}",
+            self.baud_rate, self.data_bits, parity, self.stop_bits
+        )
+    }
+}
+
+/// Role a CSV column plays in a device's output
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ColumnRole {
+    #[serde(rename = "timestamp")]
+    Timestamp,
+    #[serde(rename = "index")]
+    Index,
+    #[serde(rename = "channel")]
+    Channel,
+    #[serde(rename = "ignore")]
+    Ignore,
+}
+
+impl ColumnRole {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ColumnRole::Timestamp => "timestamp",
+            ColumnRole::Index => "index",
+            ColumnRole::Channel => "channel",
+            ColumnRole::Ignore => "ignore",
+        }
+    }
+
+    pub fn from_str(s: &str) -> Result<Self, String> {
+        match s {
+            "timestamp" => Ok(ColumnRole::Timestamp),
+            "index" => Ok(ColumnRole::Index),
+            "channel" => Ok(ColumnRole::Channel),
+            "ignore" => Ok(ColumnRole::Ignore),
+            _ => Err(format!("Unknown column role: {}", s)),
+        }
+    }
+}
+
+/// A single column in a device's CSV output
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ColumnDef {
+    pub name: String,
+    pub role: ColumnRole,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unit: Option<String>,
+    /// Lowest and highest value the device can output (its rails or ADC
+    /// range); readings pinned there count as saturated
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limits: Option<(f64, f64)>,
+}
+
+/// Ordered column layout of a device's CSV output
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ColumnSchema {
+    pub columns: Vec<ColumnDef>,
+}
+
+impl Default for ColumnSchema {
+    /// Legacy single-channel layout: `timestamp,index,value`
+    fn default() -> Self {
+        Self::parse_spec("timestamp, index, value").expect("default schema is valid")
+    }
+}
+
+impl ColumnSchema {
+    /// Parse a compact spec such as `timestamp, index, temp [C], od_450 [AU] (0..4095), -`
+    ///
+    /// `timestamp` and `index` name those columns, `-` skips a column, and any
+    /// other name is a data channel with an optional unit in brackets and
+    /// optional output limits in parentheses.
+    pub fn parse_spec(spec: &str) -> Result<Self, String> {
+        let mut columns = Vec::new();
+
+        for part in spec.split(',') {
+            let part = part.trim();
+            let (part, limits) = match part.strip_suffix(')').and_then(|p| p.rsplit_once('(')) {
+                Some((head, range)) => {
+                    let limits = parse_limits(range)
+                        .map_err(|e| format!("Invalid limits in column '{}': {}", part, e))?;
+                    (head.trim(), Some(limits))
+                }
+                None => (part, None),
+            };
+            let (name, unit) = match part.split_once('[') {
+                Some((name, rest)) => {
+                    let unit = rest
+                        .strip_suffix(']')
+                        .ok_or_else(|| format!("Unclosed unit in column '{}'", part))?
+                        .trim();
+                    (name.trim(), (!unit.is_empty()).then(|| unit.to_string()))
+                }
+                None => (part, None),
+            };
+
+            let role = match name.to_ascii_lowercase().as_str() {
+                "timestamp" => ColumnRole::Timestamp,
+                "index" => ColumnRole::Index,
+                "-" => ColumnRole::Ignore,
+                _ => ColumnRole::Channel,
+            };
+            columns.push(ColumnDef {
+                name: name.to_string(),
+                role,
+                unit,
+                limits,
+            });
+        }
+
+        let schema = Self { columns };
+        schema.validate()?;
+        Ok(schema)
+    }
+
+    /// Inverse of `parse_spec`
+    pub fn to_spec(&self) -> String {
+        self.columns
+            .iter()
+            .map(|c| {
+                let mut spec = match (&c.role, &c.unit) {
+                    (ColumnRole::Ignore, _) => return "-".to_string(),
+                    (_, Some(unit)) => format!("{} [{}]", c.name, unit),
+                    (_, None) => c.name.clone(),
+                };
+                if let Some((low, high)) = c.limits {
+                    spec.push_str(&format!(" ({}..{})", low, high));
+                }
+                spec
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// Check for exactly one timestamp and index column and named channels
+    pub fn validate(&self) -> Result<(), String> {
+        let count = |role: ColumnRole| self.columns.iter().filter(|c| c.role == role).count();
+        if count(ColumnRole::Timestamp) != 1 {
+            return Err("Schema needs exactly one timestamp column".to_string());
+        }
+        if count(ColumnRole::Index) != 1 {
+            return Err("Schema needs exactly one index column".to_string());
+        }
+        if count(ColumnRole::Channel) == 0 {
+            return Err("Schema needs at least one channel column".to_string());
+        }
+
+        let mut names: Vec<String> = Vec::new();
+        for column in self.columns.iter().filter(|c| c.role != ColumnRole::Ignore) {
+            let name = column.name.to_ascii_lowercase();
+            if name.is_empty() {
+                return Err("Column names cannot be empty".to_string());
+            }
+            if names.contains(&name) {
+                return Err(format!("Duplicate column name: {}", column.name));
+            }
+            names.push(name);
+        }
+        Ok(())
+    }
+
+    /// Data channel columns, in output order
+    pub fn channels(&self) -> impl Iterator<Item = &ColumnDef> {
+        self.columns
+            .iter()
+            .filter(|c| c.role == ColumnRole::Channel)
+    }
+
+    /// Whether the device emits more than the legacy single value
+    pub fn is_multi_channel(&self) -> bool {
+        self.channels().count() > 1
+    }
+}
+
+/// Parse output limits written as `low..high`
+fn parse_limits(range: &str) -> Result<(f64, f64), String> {
+    let (low, high) = range
+        .split_once("..")
+        .ok_or_else(|| "expected low..high".to_string())?;
+    let parse = |v: &str| {
+        v.trim()
+            .parse::<f64>()
+            .ok()
+            .filter(|v| v.is_finite())
+            .ok_or_else(|| format!("'{}' is not a number", v.trim()))
+    };
+    let (low, high) = (parse(low)?, parse(high)?);
+    if low >= high {
+        return Err("the low limit must be below the high limit".to_string());
+    }
+    Ok((low, high))
+}
+
+/// Represents a configured serial reader in the database
+/// Uses string types for cross-platform compatibility (WASM/native)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Device {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<i64>,
+    pub device_id: String,
+    pub label: String,
+    pub port: String,
+    pub enabled: bool,
+    #[serde(default)]
+    pub profile: SerialProfile,
+    #[serde(default)]
+    pub schema: ColumnSchema,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[cfg(feature = "backend")]
+impl Device {
+    /// Create a new enabled device record with the default serial profile
+    pub fn new(device_id: String, label: String, port: String) -> Self {
+        let now = chrono::Utc::now().to_rfc3339();
+        Self {
+            id: None,
+            device_id,
+            label,
+            port,
+            enabled: true,
+            profile: SerialProfile::default(),
+            schema: ColumnSchema::default(),
+            created_at: now.clone(),
+            updated_at: now,
+        }
+    }
+
+    /// Update the updated_at timestamp
+    pub fn touch(&mut self) {
+        self.updated_at = chrono::Utc::now().to_rfc3339();
+    }
+}
+
+/// Live acquisition status of a monitored device, emitted as `serial:device_status`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DeviceStatus {
+    pub device_id: String,
+    pub label: String,
+    pub port: String,
+    pub connected: bool,
+    pub collecting: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub active_test_uuid: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_data_at: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+}
+
+impl DeviceStatus {
+    /// Initial status for a device that has not connected yet
+    pub fn offline(device_id: String, label: String, port: String) -> Self {
+        Self {
+            device_id,
+            label,
+            port,
+            connected: false,
+            collecting: false,
+            active_test_uuid: None,
+            last_data_at: None,
+            last_error: None,
+        }
+    }
+}
+
+// ============================================================================
+// DATA QUALITY MODEL
+// ============================================================================
+
+/// A CSV row that could not be used, with the reason it was rejected
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RejectedRow {
+    pub line: usize,
+    pub reason: String,
+}
+
+/// Limits a dataset must meet before it is sent for detection
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct QualityThresholds {
+    pub min_accepted_rows: usize,
+    pub max_rejected_ratio: f64,
+    pub max_missing_ratio: f64,
+    pub max_saturation_ratio: f64,
+    pub max_non_monotonic_ratio: f64,
+}
+
+impl Default for QualityThresholds {
+    fn default() -> Self {
+        Self {
+            min_accepted_rows: 10,
+            max_rejected_ratio: 0.2,
+            max_missing_ratio: 0.1,
+            max_saturation_ratio: 0.1,
+            max_non_monotonic_ratio: 0.05,
+        }
+    }
+}
+
+/// Summary of how a raw CSV dataset was ingested, stored with its test
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct DataQualityReport {
+    pub total_rows: usize,
+    pub accepted_rows: usize,
+    pub rejected_rows: usize,
+    pub repaired_rows: usize,
+    pub header_detected: bool,
+    pub duplicate_indices: usize,
+    pub out_of_order_rows: usize,
+    /// Number of separate runs of missing indices
+    pub index_gaps: usize,
+    /// Total number of indices missing from the sequence
+    pub missing_indices: usize,
+    pub non_monotonic_timestamps: usize,
+    pub saturated_samples: usize,
+    pub saturation_ratio: f64,
+    /// First rejected rows, capped to keep the report small
+    #[serde(default)]
+    pub rejected: Vec<RejectedRow>,
+    /// Threshold violations; detection is blocked when any are present
+    #[serde(default)]
+    pub issues: Vec<String>,
+    pub passed: bool,
+    /// Limits the dataset was checked against
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub thresholds: Option<QualityThresholds>,
+}
+
+impl DataQualityReport {
+    /// One-line summary for logs and error messages
+    pub fn summary(&self) -> String {
+        format!(
+            "{}/{} rows accepted, {} repaired, {} missing, {:.1}% saturated",
+            self.accepted_rows,
+            self.total_rows,
+            self.repaired_rows,
+            self.missing_indices,
+            self.saturation_ratio * 100.0
+        )
+    }
+}
+
+// ============================================================================
+// PREPROCESSING MODEL
+// ============================================================================
+
+/// Scaling applied by the normalisation step
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum NormalizeMethod {
+    #[serde(rename = "zscore")]
+    ZScore,
+    #[serde(rename = "minmax")]
+    MinMax,
+}
+
+impl NormalizeMethod {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NormalizeMethod::ZScore => "zscore",
+            NormalizeMethod::MinMax => "minmax",
+        }
+    }
+
+    pub fn from_str(s: &str) -> Result<Self, String> {
+        match s {
+            "zscore" => Ok(NormalizeMethod::ZScore),
+            "minmax" => Ok(NormalizeMethod::MinMax),
+            _ => Err(format!("Unknown normalisation: {}", s)),
+        }
+    }
+}
+
+/// One signal preprocessing step, applied to every data channel
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "step", rename_all = "snake_case")]
+pub enum PreprocessStep {
+    /// Subtract the mean of the first `window` samples
+    Baseline { window: usize },
+    /// Centred moving average over `window` samples
+    MovingAverage { window: usize },
+    /// Savitzky-Golay smoothing with an odd `window` and polynomial `order`
+    SavitzkyGolay { window: usize, order: usize },
+    /// Replace samples more than `z` robust deviations from the median
+    Outliers { z: f64 },
+    /// Linearly resample onto a fixed `interval` in device timestamp units
+    Resample { interval: f64 },
+    /// Scale each channel with the given method
+    Normalize { method: NormalizeMethod },
+}
+
+impl PreprocessStep {
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            PreprocessStep::Baseline { window } | PreprocessStep::MovingAverage { window } => {
+                if *window == 0 {
+                    return Err("Window must be at least 1 sample".to_string());
+                }
+            }
+            PreprocessStep::SavitzkyGolay { window, order } => {
+                if window.is_multiple_of(2) || *window < 3 {
+                    return Err(format!(
+                        "Savitzky-Golay window must be odd and at least 3, got {}",
+                        window
+                    ));
+                }
+                if order >= window {
+                    return Err(format!(
+                        "Savitzky-Golay order must be below the window, got {}",
+                        order
+                    ));
+                }
+            }
+            PreprocessStep::Outliers { z } => {
+                if !z.is_finite() || *z <= 0.0 {
+                    return Err("Outlier threshold must be greater than zero".to_string());
+                }
+            }
+            PreprocessStep::Resample { interval } => {
+                if !interval.is_finite() || *interval <= 0.0 {
+                    return Err("Resample interval must be greater than zero".to_string());
+                }
+            }
+            PreprocessStep::Normalize { .. } => {}
+        }
+        Ok(())
+    }
+
+    /// Compact form, e.g. `savgol(7, 2)`
+    pub fn to_spec(&self) -> String {
+        match self {
+            PreprocessStep::Baseline { window } => format!("baseline({})", window),
+            PreprocessStep::MovingAverage { window } => format!("moving_average({})", window),
+            PreprocessStep::SavitzkyGolay { window, order } => {
+                format!("savgol({}, {})", window, order)
+            }
+            PreprocessStep::Outliers { z } => format!("outliers({})", z),
+            PreprocessStep::Resample { interval } => format!("resample({})", interval),
+            PreprocessStep::Normalize { method } => format!("normalize({})", method.as_str()),
+        }
+    }
+
+    /// Parse the compact form produced by `to_spec`
+    pub fn parse_spec(spec: &str) -> Result<Self, String> {
+        let spec = spec.trim();
+        let (name, args) = spec
+            .strip_suffix(')')
+            .and_then(|s| s.split_once('('))
+            .ok_or_else(|| format!("Expected name(args), got '{}'", spec))?;
+        let args: Vec<&str> = args.split(',').map(|a| a.trim()).collect();
+
+        let arg = |i: usize| {
+            args.get(i)
+                .filter(|a| !a.is_empty())
+                .copied()
+                .ok_or_else(|| format!("Missing argument {} for {}", i + 1, name.trim()))
+        };
+        let usize_arg = |i: usize| {
+            let raw = arg(i)?;
+            raw.parse::<usize>()
+                .map_err(|_| format!("Invalid number for {}: {}", name.trim(), raw))
+        };
+        let f64_arg = |i: usize| {
+            let raw = arg(i)?;
+            raw.parse::<f64>()
+                .map_err(|_| format!("Invalid number for {}: {}", name.trim(), raw))
+        };
+
+        let step = match name.trim() {
+            "baseline" => PreprocessStep::Baseline {
+                window: usize_arg(0)?,
+            },
+            "moving_average" => PreprocessStep::MovingAverage {
+                window: usize_arg(0)?,
+            },
+            "savgol" => PreprocessStep::SavitzkyGolay {
+                window: usize_arg(0)?,
+                order: usize_arg(1)?,
+            },
+            "outliers" => PreprocessStep::Outliers { z: f64_arg(0)? },
+            "resample" => PreprocessStep::Resample {
+                interval: f64_arg(0)?,
+            },
+            "normalize" => PreprocessStep::Normalize {
+                method: NormalizeMethod::from_str(arg(0)?)?,
+            },
+            other => return Err(format!("Unknown preprocessing step: {}", other)),
+        };
+        step.validate()?;
+        Ok(step)
+    }
+}
+
+/// Ordered preprocessing steps applied before a dataset is sent for detection
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct PreprocessingPipeline {
+    pub steps: Vec<PreprocessStep>,
+}
+
+impl PreprocessingPipeline {
+    pub fn validate(&self) -> Result<(), String> {
+        self.steps.iter().try_for_each(|step| step.validate())
+    }
+
+    /// Steps joined with `|`, e.g. `outliers(5) | baseline(10) | savgol(7, 2)`
+    pub fn to_spec(&self) -> String {
+        self.steps
+            .iter()
+            .map(|step| step.to_spec())
+            .collect::<Vec<_>>()
+            .join(" | ")
+    }
+
+    /// Parse the form produced by `to_spec`; an empty spec means no steps
+    pub fn parse_spec(spec: &str) -> Result<Self, String> {
+        let steps = spec
+            .split('|')
+            .filter(|part| !part.trim().is_empty())
+            .map(PreprocessStep::parse_spec)
+            .collect::<Result<Vec<_>, String>>()?;
+        Ok(Self { steps })
+    }
+}
+
+/// Preprocessing configured for one test type
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TestTypePipeline {
+    pub test_type: TestType,
+    pub pipeline: PreprocessingPipeline,
+}
+
+// ============================================================================
+// USER MODEL
+// ============================================================================
+
+/// Role of an operator account, deciding what it may do
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum Role {
+    #[serde(rename = "operator")]
+    Operator,
+    #[serde(rename = "reviewer")]
+    Reviewer,
+    #[serde(rename = "admin")]
+    Admin,
+}
+
+impl Role {
+    /// Every role, from least to most privileged
+    pub fn all() -> [Role; 3] {
+        [Role::Operator, Role::Reviewer, Role::Admin]
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::Operator => "operator",
+            Role::Reviewer => "reviewer",
+            Role::Admin => "admin",
+        }
+    }
+
+    pub fn from_str(s: &str) -> Result<Self, String> {
+        match s {
+            "operator" => Ok(Role::Operator),
+            "reviewer" => Ok(Role::Reviewer),
+            "admin" => Ok(Role::Admin),
+            _ => Err(format!("Unknown role: {}", s)),
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Role::Operator => "Operator",
+            Role::Reviewer => "Reviewer",
+            Role::Admin => "Administrator",
+        }
+    }
+
+    /// Whether accounts with this role may perform `permission`
+    pub fn can(&self, permission: Permission) -> bool {
+        match self {
+            Role::Operator => matches!(permission, Permission::RunTests),
+            Role::Reviewer => {
+                matches!(permission, Permission::RunTests | Permission::ReviewResults)
+            }
+            Role::Admin => true,
+        }
+    }
+}
+
+/// Action guarded by a role check in the backend
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum Permission {
+    /// Register patients, start tests and record their results
+    #[serde(rename = "run_tests")]
+    RunTests,
+    /// Release completed results and export them in bulk
+    #[serde(rename = "review_results")]
+    ReviewResults,
+    /// Add, change or remove devices and their ports
+    #[serde(rename = "manage_devices")]
+    ManageDevices,
+    /// Change thresholds, preprocessing, logging, backups and encryption
+    #[serde(rename = "manage_settings")]
+    ManageSettings,
+    /// Create accounts and change roles
+    #[serde(rename = "manage_users")]
+    ManageUsers,
+}
+
+impl Permission {
+    /// What the permission allows, for error messages
+    pub fn describe(&self) -> &'static str {
+        match self {
+            Permission::RunTests => "run tests",
+            Permission::ReviewResults => "release results",
+            Permission::ManageDevices => "change devices",
+            Permission::ManageSettings => "change settings",
+            Permission::ManageUsers => "manage users",
+        }
+    }
+}
+
+/// An operator account; the password hash never leaves the backend
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct User {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<i64>,
+    pub uuid: String,
+    pub username: String,
+    pub display_name: String,
+    pub role: Role,
+    pub active: bool,
+    pub created_at: String,
+    pub updated_at: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_login_at: Option<String>,
+    /// Whether a PIN is set for unlocking an idle session
+    #[serde(default)]
+    pub has_pin: bool,
+}
+
+#[cfg(feature = "backend")]
+impl User {
+    /// Create a new active account
+    pub fn new(username: String, display_name: String, role: Role) -> Self {
+        let now = chrono::Utc::now().to_rfc3339();
+        Self {
+            id: None,
+            uuid: uuid::Uuid::new_v4().to_string(),
+            username,
+            display_name,
+            role,
+            active: true,
+            created_at: now.clone(),
+            updated_at: now,
+            last_login_at: None,
+            has_pin: false,
+        }
+    }
+
+    /// Update the updated_at timestamp
+    pub fn touch(&mut self) {
+        self.updated_at = chrono::Utc::now().to_rfc3339();
+    }
+}
+
+// ============================================================================
+// ERROR MODEL
+// ============================================================================
+
+/// Category of a failed command, so the UI can react to it specifically
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum ErrorCode {
+    #[serde(rename = "not_found")]
+    NotFound,
+    #[serde(rename = "validation")]
+    Validation,
+    #[serde(rename = "conflict")]
+    Conflict,
+    #[serde(rename = "database")]
+    Database,
+    #[serde(rename = "serial")]
+    Serial,
+    #[serde(rename = "detection_api")]
+    DetectionApi,
+    #[serde(rename = "io")]
+    Io,
+    #[serde(rename = "unauthorized")]
+    Unauthorized,
+    #[serde(rename = "forbidden")]
+    Forbidden,
+    #[serde(rename = "internal")]
+    Internal,
+}
+
+impl ErrorCode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorCode::NotFound => "not_found",
+            ErrorCode::Validation => "validation",
+            ErrorCode::Conflict => "conflict",
+            ErrorCode::Database => "database",
+            ErrorCode::Serial => "serial",
+            ErrorCode::DetectionApi => "detection_api",
+            ErrorCode::Io => "io",
+            ErrorCode::Unauthorized => "unauthorized",
+            ErrorCode::Forbidden => "forbidden",
+            ErrorCode::Internal => "internal",
+        }
+    }
+
+    pub fn from_str(s: &str) -> Result<Self, String> {
+        match s {
+            "not_found" => Ok(ErrorCode::NotFound),
+            "validation" => Ok(ErrorCode::Validation),
+            "conflict" => Ok(ErrorCode::Conflict),
+            "database" => Ok(ErrorCode::Database),
+            "serial" => Ok(ErrorCode::Serial),
+            "detection_api" => Ok(ErrorCode::DetectionApi),
+            "io" => Ok(ErrorCode::Io),
+            "unauthorized" => Ok(ErrorCode::Unauthorized),
+            "forbidden" => Ok(ErrorCode::Forbidden),
+            "internal" => Ok(ErrorCode::Internal),
+            _ => Err(format!("Unknown error code: {}", s)),
+        }
+    }
+}
+
+/// Error returned by every backend command
+///
+/// `message` is written for the operator and never contains driver output;
+/// the underlying cause goes in `details` for logs and support.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AppError {
+    pub code: ErrorCode,
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub details: Option<String>,
+}
+
+impl AppError {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            details: None,
+        }
+    }
+
+    /// Attach the technical cause of the error
+    pub fn with_details(mut self, details: impl std::fmt::Display) -> Self {
+        self.details = Some(details.to_string());
+        self
+    }
+
+    /// A record looked up by `id` does not exist, e.g. `not_found("Patient", uuid)`
+    pub fn not_found(what: &str, id: &str) -> Self {
+        Self::new(ErrorCode::NotFound, format!("{} not found: {}", what, id))
+    }
+
+    pub fn validation(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::Validation, message)
+    }
+
+    pub fn conflict(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::Conflict, message)
+    }
+
+    /// A database operation failed; `cause` is kept out of the user message
+    pub fn database(message: impl Into<String>, cause: impl std::fmt::Display) -> Self {
+        Self::new(ErrorCode::Database, message).with_details(cause)
+    }
+
+    pub fn serial(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::Serial, message)
+    }
+
+    pub fn detection_api(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::DetectionApi, message)
+    }
+
+    pub fn io(message: impl Into<String>, cause: impl std::fmt::Display) -> Self {
+        Self::new(ErrorCode::Io, message).with_details(cause)
+    }
+
+    /// No operator is signed in
+    pub fn unauthorized(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::Unauthorized, message)
+    }
+
+    /// The signed-in operator's role does not allow the action
+    pub fn forbidden(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::Forbidden, message)
+    }
+
+    pub fn internal(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::Internal, message)
+    }
+
+    /// Whether repeating the same action may succeed without changing input
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self.code,
+            ErrorCode::Database | ErrorCode::Serial | ErrorCode::DetectionApi | ErrorCode::Io
+        )
+    }
+}
+
+impl std::fmt::Display for AppError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.details {
+            Some(details) => write!(f, "{}: {}", self.message, details),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+impl std::error::Error for AppError {}
@@ -0,0 +1,1099 @@
+use crate::db_orm::Database;
+use crate::device_registry::DeviceRegistry;
+use crate::encryption::EncryptionState;
+use crate::label_printer;
+use crate::models::{
+    AppError, DbState, DetectionResult, Patient, ReviewAction, Test, TestStatus, TestType,
+    TestWithPatient,
+};
+use crate::outbox::{Delivery, OutboundMessage};
+use crate::report::probability;
+use chrono::{DateTime, NaiveDate, Utc};
+use sea_orm::DatabaseConnection;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::net::{IpAddr, SocketAddr};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tracing::{error, info, warn};
+
+#[cfg(desktop)]
+use tauri_plugin_serialplugin::{commands, desktop_api, state as serial_state};
+
+/// Setting holding the JSON-encoded `AstmSettings`
+pub const ASTM_SETTINGS_KEY: &str = "astm_settings";
+/// Outbox protocol name for ASTM E1394 over LIS1-A
+pub const PROTOCOL: &str = "astm";
+
+// LIS1-A control characters
+const STX: u8 = 0x02;
+const ETX: u8 = 0x03;
+const EOT: u8 = 0x04;
+const ENQ: u8 = 0x05;
+const ACK: u8 = 0x06;
+const LF: u8 = 0x0a;
+const CR: u8 = 0x0d;
+const NAK: u8 = 0x15;
+const ETB: u8 = 0x17;
+
+/// Longest frame text; longer records are split into intermediate frames
+const MAX_FRAME_TEXT: usize = 240;
+/// Times a NAKed frame is sent before the transfer is abandoned
+const MAX_FRAME_ATTEMPTS: usize = 6;
+/// How long the receiver waits for the next frame before giving up
+const RECEIVER_TIMEOUT: Duration = Duration::from_secs(30);
+/// Longest frame accepted from the host
+const MAX_FRAME_BYTES: usize = 64 * 1024;
+/// How often the order listener notices changed settings
+const LISTENER_TICK: Duration = Duration::from_secs(15);
+/// Serial reads are short so the overall reply timeout is honoured
+#[cfg(desktop)]
+const SERIAL_READ_MS: u64 = 500;
+
+/// How the host is reached
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum AstmTransport {
+    Tcp,
+    Serial,
+}
+
+/// Where results are sent and whether orders are taken from the host
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AstmSettings {
+    pub enabled: bool,
+    pub transport: AstmTransport,
+    #[serde(default)]
+    pub host: String,
+    pub port: u16,
+    /// Port name, e.g. COM4 or /dev/ttyUSB1; must not be a detection device's port
+    #[serde(default)]
+    pub serial_port: String,
+    pub baud_rate: u32,
+    /// Sender name in the header record
+    pub sender_id: String,
+    #[serde(default)]
+    pub receiver_id: String,
+    /// Seconds to wait for each acknowledgement from the host
+    pub reply_timeout_secs: u64,
+    /// Listen for order downloads from the host over TCP
+    #[serde(default)]
+    pub accept_orders: bool,
+    /// Local address the order listener binds to; orders are only taken from `host`
+    #[serde(default = "default_order_address")]
+    pub order_address: String,
+    pub order_port: u16,
+}
+
+fn default_order_address() -> String {
+    "127.0.0.1".to_string()
+}
+
+impl Default for AstmSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            transport: AstmTransport::Tcp,
+            host: String::new(),
+            port: 5000,
+            serial_port: String::new(),
+            baud_rate: 9600,
+            sender_id: "EBERS".to_string(),
+            receiver_id: String::new(),
+            reply_timeout_secs: 15,
+            accept_orders: false,
+            order_address: default_order_address(),
+            order_port: 5001,
+        }
+    }
+}
+
+impl AstmSettings {
+    /// Trim the text fields and check the chosen transport is usable
+    pub fn normalized(mut self) -> Result<Self, AppError> {
+        self.host = self.host.trim().to_string();
+        self.serial_port = self.serial_port.trim().to_string();
+        self.sender_id = self.sender_id.trim().to_string();
+        self.receiver_id = self.receiver_id.trim().to_string();
+        self.order_address = self.order_address.trim().to_string();
+        if self.order_address.is_empty() {
+            self.order_address = default_order_address();
+        }
+        if self.order_address.parse::<IpAddr>().is_err() {
+            return Err(AppError::validation(
+                "Order listen address must be an IP address, e.g. 127.0.0.1",
+            ));
+        }
+        if self.accept_orders && self.host.is_empty() {
+            return Err(AppError::validation(
+                "Enter the LIS host name or address; orders are only accepted from it",
+            ));
+        }
+        if self.enabled {
+            match self.transport {
+                AstmTransport::Tcp if self.host.is_empty() => {
+                    return Err(AppError::validation("Enter the LIS host name or address"))
+                }
+                AstmTransport::Serial if self.serial_port.is_empty() => {
+                    return Err(AppError::validation("Choose the serial port of the LIS"))
+                }
+                _ => {}
+            }
+        }
+        if self.port == 0 || self.order_port == 0 {
+            return Err(AppError::validation("Port must be between 1 and 65535"));
+        }
+        if self.baud_rate == 0 {
+            return Err(AppError::validation("Baud rate is required"));
+        }
+        if self.sender_id.is_empty() {
+            return Err(AppError::validation("Sender ID is required"));
+        }
+        if !(1..=300).contains(&self.reply_timeout_secs) {
+            return Err(AppError::validation(
+                "Reply timeout must be between 1 and 300 seconds",
+            ));
+        }
+        Ok(self)
+    }
+}
+
+pub async fn load_settings(db: &DatabaseConnection) -> Result<AstmSettings, AppError> {
+    match Database::get_setting(db, ASTM_SETTINGS_KEY.to_string()).await? {
+        Some(json) => serde_json::from_str(&json)
+            .map_err(|e| AppError::internal("Failed to parse ASTM settings").with_details(e)),
+        None => Ok(AstmSettings::default()),
+    }
+}
+
+pub async fn save_settings(
+    db: &DatabaseConnection,
+    settings: &AstmSettings,
+) -> Result<(), AppError> {
+    let json = serde_json::to_string(settings)
+        .map_err(|e| AppError::internal("Failed to encode ASTM settings").with_details(e))?;
+    Database::save_setting(db, ASTM_SETTINGS_KEY.to_string(), json).await
+}
+
+// ----------------------------------------------------------------------------
+// Records
+// ----------------------------------------------------------------------------
+
+/// Build the H, P, O, R, C and L records for a released result
+///
+/// Records are separated by carriage returns, as they are framed. The order
+/// carries the LIS specimen ID when the test came from a downloaded order.
+pub fn result_message(
+    data: &TestWithPatient,
+    settings: &AstmSettings,
+    control_id: &str,
+    now: DateTime<Utc>,
+) -> String {
+    let test = &data.test;
+    let patient = &data.patient;
+    let result = test
+        .detection_result
+        .as_deref()
+        .and_then(|r| DetectionResult::from_str(r).ok());
+    let test_id = format!("^^^{}", escape(&test.test_type));
+
+    let mut records = vec![
+        record(&[
+            "H",
+            "\\^&",
+            &escape(control_id),
+            "",
+            &escape(&settings.sender_id),
+            "",
+            "",
+            "",
+            "",
+            &escape(&settings.receiver_id),
+            "",
+            "P",
+            "LIS2-A2",
+            &now.format("%Y%m%d%H%M%S").to_string(),
+        ]),
+        record(&[
+            "P",
+            "1",
+            &escape(
+                patient
+                    .patient_id_number
+                    .as_deref()
+                    .unwrap_or(&patient.uuid),
+            ),
+            "",
+            "",
+            &format!(
+                "{}^{}",
+                escape(&patient.last_name),
+                escape(&patient.first_name)
+            ),
+            "",
+            &patient
+                .date_of_birth
+                .as_deref()
+                .map(astm_date)
+                .unwrap_or_default(),
+        ]),
+        record(&[
+            "O",
+            "1",
+            &escape(test.specimen_id.as_deref().unwrap_or(&test.uuid)),
+            &escape(&test.uuid),
+            &test_id,
+            "R",
+            &astm_time(&test.created_at),
+            "",
+            "",
+            "",
+            "",
+            "",
+            "",
+            "",
+            "",
+            "",
+            "",
+            "",
+            "",
+            "",
+            "",
+            "",
+            "",
+            "",
+            "",
+            "F",
+        ]),
+        result_record(
+            1,
+            &test_id,
+            result.as_ref().map(|r| r.label()).unwrap_or_default(),
+            "",
+            match result {
+                Some(DetectionResult::Positive) => "A",
+                Some(DetectionResult::Negative) => "N",
+                _ => "",
+            },
+            test,
+        ),
+    ];
+
+    if let Some(event) = test
+        .review_log
+        .iter()
+        .rev()
+        .find(|e| e.action == ReviewAction::Overridden)
+    {
+        records.push(record(&[
+            "C",
+            "1",
+            "L",
+            &escape(&format!(
+                "Measured result {} overridden by {}: {}",
+                event.previous_result.as_deref().unwrap_or("-"),
+                event.by,
+                event.reason.as_deref().unwrap_or("-")
+            )),
+            "G",
+        ]));
+    }
+
+    let mut sequence = 1;
+    for (name, value) in [
+        ("CONFIDENCE", test.confidence),
+        ("PROBABILITY", probability(test.raw_response.as_deref())),
+    ] {
+        if let Some(value) = value {
+            sequence += 1;
+            records.push(result_record(
+                sequence,
+                &format!("{}^{}", test_id, name),
+                &format!("{:.1}", value * 100.0),
+                "%",
+                "",
+                test,
+            ));
+        }
+    }
+
+    records.push("L|1|N".to_string());
+    let mut message = records.join("\r");
+    message.push('\r');
+    message
+}
+
+/// One R record; status, operator, times and instrument come from the test
+fn result_record(
+    sequence: usize,
+    test_id: &str,
+    value: &str,
+    units: &str,
+    abnormal_flag: &str,
+    test: &Test,
+) -> String {
+    record(&[
+        "R",
+        &sequence.to_string(),
+        test_id,
+        &escape(value),
+        units,
+        "",
+        abnormal_flag,
+        "",
+        "F",
+        "",
+        &escape(test.released_by.as_deref().unwrap_or_default()),
+        &astm_time(&test.created_at),
+        &test
+            .completed_at
+            .as_deref()
+            .map(astm_time)
+            .unwrap_or_default(),
+        &escape(test.device_id.as_deref().unwrap_or_default()),
+    ])
+}
+
+/// Join fields into a record, dropping empty trailing fields
+fn record(fields: &[&str]) -> String {
+    let used = fields
+        .iter()
+        .rposition(|field| !field.is_empty())
+        .map_or(0, |i| i + 1);
+    fields[..used].join("|")
+}
+
+/// Escape the default ASTM delimiters; line breaks become spaces
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&E&"),
+            '|' => escaped.push_str("&F&"),
+            '^' => escaped.push_str("&S&"),
+            '\\' => escaped.push_str("&R&"),
+            '\r' | '\n' => escaped.push(' '),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// RFC 3339 timestamp as an ASTM date and time in UTC
+fn astm_time(timestamp: &str) -> String {
+    DateTime::parse_from_rfc3339(timestamp)
+        .map(|t| t.with_timezone(&Utc).format("%Y%m%d%H%M%S").to_string())
+        .unwrap_or_default()
+}
+
+/// `YYYY-MM-DD` as an ASTM date; anything else is left out
+fn astm_date(date: &str) -> String {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .map(|d| d.format("%Y%m%d").to_string())
+        .unwrap_or_default()
+}
+
+// ----------------------------------------------------------------------------
+// Framing
+// ----------------------------------------------------------------------------
+
+/// Split a message into LIS1-A frames, one or more per record
+///
+/// Frame numbers run 1 to 7 then wrap to 0 across the whole message.
+pub fn frames(message: &str) -> Vec<Vec<u8>> {
+    let mut frames = Vec::new();
+    let mut number: u8 = 1;
+    for record in message.split_terminator('\r') {
+        let mut text = record.as_bytes().to_vec();
+        text.push(CR);
+        let chunks: Vec<&[u8]> = text.chunks(MAX_FRAME_TEXT).collect();
+        for (i, chunk) in chunks.iter().enumerate() {
+            let terminator = if i + 1 == chunks.len() { ETX } else { ETB };
+            let mut body = Vec::with_capacity(chunk.len() + 2);
+            body.push(b'0' + number);
+            body.extend_from_slice(chunk);
+            body.push(terminator);
+
+            let mut frame = Vec::with_capacity(body.len() + 5);
+            frame.push(STX);
+            frame.extend_from_slice(&body);
+            frame.extend_from_slice(checksum(&body).as_bytes());
+            frame.extend_from_slice(&[CR, LF]);
+            frames.push(frame);
+            number = (number + 1) % 8;
+        }
+    }
+    frames
+}
+
+/// Modulo-256 sum of the frame number, text and terminator, as two hex digits
+fn checksum(body: &[u8]) -> String {
+    let sum = body.iter().fold(0u8, |sum, &b| sum.wrapping_add(b));
+    format!("{:02X}", sum)
+}
+
+/// Check a received frame (without STX) and return its number, text and whether it ends a record
+fn parse_frame(frame: &[u8]) -> Result<(u8, &[u8], bool), String> {
+    // number + terminator + checksum + CR LF
+    if frame.len() < 6 || !frame.ends_with(&[CR, LF]) {
+        return Err("Frame is truncated".to_string());
+    }
+    let body = &frame[..frame.len() - 4];
+    let received = String::from_utf8_lossy(&frame[frame.len() - 4..frame.len() - 2]);
+    if !received.eq_ignore_ascii_case(&checksum(body)) {
+        return Err(format!("Bad checksum {}", received));
+    }
+    let number = body[0]
+        .checked_sub(b'0')
+        .filter(|n| *n < 8)
+        .ok_or("Bad frame number")?;
+    let last = match body[body.len() - 1] {
+        ETX => true,
+        ETB => false,
+        _ => return Err("Frame has no ETX or ETB".to_string()),
+    };
+    Ok((number, &body[1..body.len() - 1], last))
+}
+
+// ----------------------------------------------------------------------------
+// Transport
+// ----------------------------------------------------------------------------
+
+/// An open connection to the host
+enum Link {
+    Tcp(TcpStream),
+    #[cfg(desktop)]
+    Serial {
+        app: AppHandle,
+        port: String,
+    },
+}
+
+impl Link {
+    async fn open(
+        app: &AppHandle,
+        settings: &AstmSettings,
+        timeout: Duration,
+    ) -> Result<Self, String> {
+        match settings.transport {
+            AstmTransport::Tcp => {
+                let address = format!("{}:{}", settings.host, settings.port);
+                let stream = tokio::time::timeout(timeout, TcpStream::connect(&address))
+                    .await
+                    .map_err(|_| format!("Timed out connecting to {}", address))?
+                    .map_err(|e| format!("Cannot connect to {}: {}", address, e))?;
+                Ok(Link::Tcp(stream))
+            }
+            #[cfg(desktop)]
+            AstmTransport::Serial => {
+                let (app, port, baud_rate) = (
+                    app.clone(),
+                    settings.serial_port.clone(),
+                    settings.baud_rate,
+                );
+                tokio::task::spawn_blocking(move || {
+                    commands::open(
+                        app.clone(),
+                        app.state::<desktop_api::SerialPort<tauri::Wry>>().clone(),
+                        port.clone(),
+                        baud_rate,
+                        Some(serial_state::DataBits::Eight),
+                        Some(serial_state::FlowControl::None),
+                        Some(serial_state::Parity::None),
+                        Some(serial_state::StopBits::One),
+                        Some(SERIAL_READ_MS),
+                    )
+                    .map(|_| Link::Serial {
+                        app,
+                        port: port.clone(),
+                    })
+                    .map_err(|e| format!("Cannot open {}: {}", port, e))
+                })
+                .await
+                .map_err(|e| e.to_string())?
+            }
+            #[cfg(not(desktop))]
+            AstmTransport::Serial => {
+                let _ = app;
+                Err("Serial ports are not available on this platform".to_string())
+            }
+        }
+    }
+
+    async fn write(&mut self, bytes: &[u8]) -> Result<(), String> {
+        match self {
+            Link::Tcp(stream) => stream
+                .write_all(bytes)
+                .await
+                .map_err(|e| format!("Failed to send: {}", e)),
+            #[cfg(desktop)]
+            Link::Serial { app, port } => {
+                let (app, port, bytes) = (app.clone(), port.clone(), bytes.to_vec());
+                tokio::task::spawn_blocking(move || {
+                    commands::write_binary(
+                        app.clone(),
+                        app.state::<desktop_api::SerialPort<tauri::Wry>>().clone(),
+                        port,
+                        bytes,
+                    )
+                })
+                .await
+                .map_err(|e| e.to_string())?
+                .map(|_| ())
+                .map_err(|e| format!("Failed to send: {}", e))
+            }
+        }
+    }
+
+    /// Read whatever arrives next, waiting at most `timeout`
+    async fn read(&mut self, timeout: Duration) -> Result<Vec<u8>, String> {
+        match self {
+            Link::Tcp(stream) => {
+                let mut chunk = [0u8; 1024];
+                let read = tokio::time::timeout(timeout, stream.read(&mut chunk))
+                    .await
+                    .map_err(|_| "Timed out waiting for the host".to_string())?
+                    .map_err(|e| format!("Failed to read: {}", e))?;
+                if read == 0 {
+                    return Err("Host closed the connection".to_string());
+                }
+                Ok(chunk[..read].to_vec())
+            }
+            #[cfg(desktop)]
+            Link::Serial { app, port } => {
+                let deadline = tokio::time::Instant::now() + timeout;
+                loop {
+                    let (app, port) = (app.clone(), port.clone());
+                    // Read errors include the plugin's own timeout, so keep polling
+                    let data = tokio::task::spawn_blocking(move || {
+                        commands::read(
+                            app.clone(),
+                            app.state::<desktop_api::SerialPort<tauri::Wry>>().clone(),
+                            port,
+                            Some(SERIAL_READ_MS),
+                            Some(1024),
+                        )
+                    })
+                    .await
+                    .map_err(|e| e.to_string())?;
+                    if let Ok(data) = data {
+                        if !data.is_empty() {
+                            return Ok(data.into_bytes());
+                        }
+                    }
+                    if tokio::time::Instant::now() >= deadline {
+                        return Err("Timed out waiting for the host".to_string());
+                    }
+                }
+            }
+        }
+    }
+
+    async fn close(self) {
+        match self {
+            Link::Tcp(mut stream) => {
+                let _ = stream.shutdown().await;
+            }
+            #[cfg(desktop)]
+            Link::Serial { app, port } => {
+                let _ = tokio::task::spawn_blocking(move || {
+                    commands::close(
+                        app.clone(),
+                        app.state::<desktop_api::SerialPort<tauri::Wry>>().clone(),
+                        port,
+                    )
+                })
+                .await;
+            }
+        }
+    }
+}
+
+/// Wait for the host's next ACK, NAK, EOT or ENQ, skipping anything else
+async fn reply(
+    link: &mut Link,
+    pending: &mut VecDeque<u8>,
+    timeout: Duration,
+) -> Result<u8, String> {
+    let deadline = tokio::time::Instant::now() + timeout;
+    loop {
+        while let Some(byte) = pending.pop_front() {
+            if matches!(byte, ACK | NAK | EOT | ENQ) {
+                return Ok(byte);
+            }
+        }
+        let remaining = deadline.saturating_duration_since(tokio::time::Instant::now());
+        if remaining.is_zero() {
+            return Err("Timed out waiting for the host".to_string());
+        }
+        pending.extend(link.read(remaining).await?);
+    }
+}
+
+/// Send a queued message to the host and report how it went
+pub async fn deliver(
+    app: &AppHandle,
+    settings: &AstmSettings,
+    message: &OutboundMessage,
+) -> Delivery {
+    let timeout = Duration::from_secs(settings.reply_timeout_secs);
+    let mut link = match Link::open(app, settings, timeout).await {
+        Ok(link) => link,
+        Err(e) => return Delivery::Retry(e),
+    };
+    let delivery = transfer(&mut link, &message.payload, timeout).await;
+    link.close().await;
+    delivery
+}
+
+/// Establishment, transfer and termination phases of one message
+async fn transfer(link: &mut Link, payload: &str, timeout: Duration) -> Delivery {
+    let mut pending = VecDeque::new();
+    if let Err(e) = link.write(&[ENQ]).await {
+        return Delivery::Retry(e);
+    }
+    match reply(link, &mut pending, timeout).await {
+        Ok(ACK) => {}
+        Ok(NAK) => return Delivery::Retry("Host is busy".to_string()),
+        Ok(ENQ) => return Delivery::Retry("Host is sending; will try again".to_string()),
+        Ok(_) => return Delivery::Retry("Host ended the session".to_string()),
+        Err(e) => return Delivery::Retry(e),
+    }
+
+    for frame in frames(payload) {
+        let mut attempts = 0;
+        loop {
+            attempts += 1;
+            if let Err(e) = link.write(&frame).await {
+                return Delivery::Retry(e);
+            }
+            match reply(link, &mut pending, timeout).await {
+                Ok(ACK) => break,
+                Ok(EOT) => {
+                    let _ = link.write(&[EOT]).await;
+                    return Delivery::Retry("Host interrupted the transfer".to_string());
+                }
+                Ok(_) if attempts < MAX_FRAME_ATTEMPTS => continue,
+                Ok(_) => {
+                    let _ = link.write(&[EOT]).await;
+                    return Delivery::Retry(format!(
+                        "Host refused a frame {} times",
+                        MAX_FRAME_ATTEMPTS
+                    ));
+                }
+                Err(e) => {
+                    let _ = link.write(&[EOT]).await;
+                    return Delivery::Retry(e);
+                }
+            }
+        }
+    }
+
+    match link.write(&[EOT]).await {
+        Ok(()) => Delivery::Accepted {
+            ack_code: "ACK".to_string(),
+        },
+        Err(e) => Delivery::Retry(e),
+    }
+}
+
+// ----------------------------------------------------------------------------
+// Order downloads
+// ----------------------------------------------------------------------------
+
+/// What a downloaded message did
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct OrderSummary {
+    pub created: usize,
+    pub cancelled: usize,
+    pub skipped: usize,
+    /// Tests created for new orders, so their labels can be printed
+    pub created_tests: Vec<String>,
+}
+
+/// Accept order downloads from the host for the life of the app
+///
+/// The listener follows the settings: it starts, stops or moves address
+/// within one tick of them changing. Connections from anywhere but the
+/// configured host are dropped.
+pub fn spawn_order_listener(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut bound: Option<(SocketAddr, TcpListener)> = None;
+        loop {
+            let wanted = match wanted_order_listener(&app).await {
+                Ok(wanted) => wanted,
+                Err(e) => {
+                    error!("Failed to read ASTM settings: {}", e);
+                    None
+                }
+            };
+            let address = wanted.as_ref().map(|(address, _)| *address);
+            if bound.as_ref().map(|(a, _)| *a) != address {
+                bound = None;
+                if let Some(address) = address {
+                    match TcpListener::bind(address).await {
+                        Ok(listener) => {
+                            info!("Accepting ASTM orders on {}", address);
+                            bound = Some((address, listener));
+                        }
+                        Err(e) => error!("Cannot listen for ASTM orders on {}: {}", address, e),
+                    }
+                }
+            }
+
+            match (&bound, wanted) {
+                (Some((_, listener)), Some((_, host))) => {
+                    tokio::select! {
+                        accepted = listener.accept() => match accepted {
+                            Ok((stream, peer)) => {
+                                let app = app.clone();
+                                tauri::async_runtime::spawn(async move {
+                                    if !is_host(&host, peer.ip()).await {
+                                        warn!(
+                                            "Dropped ASTM connection from {}; orders are only accepted from {}",
+                                            peer, host
+                                        );
+                                        return;
+                                    }
+                                    info!("ASTM host connected from {}", peer);
+                                    receive_orders(&app, Link::Tcp(stream)).await;
+                                });
+                            }
+                            Err(e) => warn!("Failed to accept ASTM connection: {}", e),
+                        },
+                        _ = tokio::time::sleep(LISTENER_TICK) => {}
+                    }
+                }
+                _ => tokio::time::sleep(LISTENER_TICK).await,
+            }
+        }
+    });
+}
+
+/// Address to listen on and the host allowed to connect, if orders are accepted
+async fn wanted_order_listener(app: &AppHandle) -> Result<Option<(SocketAddr, String)>, AppError> {
+    // Nothing can be read until an encrypted database has been unlocked
+    if !app.state::<EncryptionState>().lock().unwrap().unlocked {
+        return Ok(None);
+    }
+    let db = app.state::<DbState>();
+    let db = db.lock().await;
+    let settings = load_settings(&*db).await?;
+    if !settings.accept_orders {
+        return Ok(None);
+    }
+    let address = settings
+        .order_address
+        .parse::<IpAddr>()
+        .map_err(|e| AppError::validation("Invalid order listen address").with_details(e))?;
+    Ok(Some((
+        SocketAddr::new(address, settings.order_port),
+        settings.host,
+    )))
+}
+
+/// Whether a peer address is one the configured host name resolves to
+async fn is_host(host: &str, peer: IpAddr) -> bool {
+    match tokio::net::lookup_host((host, 0)).await {
+        Ok(mut addresses) => {
+            addresses.any(|address| address.ip().to_canonical() == peer.to_canonical())
+        }
+        Err(e) => {
+            warn!("Cannot resolve ASTM host {}: {}", host, e);
+            false
+        }
+    }
+}
+
+/// Act as the LIS1-A receiver until the host disconnects or goes quiet
+async fn receive_orders(app: &AppHandle, mut link: Link) {
+    let mut pending: VecDeque<u8> = VecDeque::new();
+    let mut in_session = false;
+    let mut expected: u8 = 1;
+    let mut record = Vec::new();
+    let mut records = Vec::new();
+
+    loop {
+        let byte = match pending.pop_front() {
+            Some(byte) => byte,
+            None => match link.read(RECEIVER_TIMEOUT).await {
+                Ok(data) => {
+                    pending.extend(data);
+                    continue;
+                }
+                Err(_) => break,
+            },
+        };
+
+        match byte {
+            ENQ => {
+                in_session = true;
+                expected = 1;
+                record.clear();
+                records.clear();
+                if link.write(&[ACK]).await.is_err() {
+                    break;
+                }
+            }
+            STX if in_session => {
+                let mut frame = Vec::new();
+                let mut complete = false;
+                while frame.len() < MAX_FRAME_BYTES {
+                    let next = match pending.pop_front() {
+                        Some(b) => b,
+                        None => match link.read(RECEIVER_TIMEOUT).await {
+                            Ok(data) => {
+                                pending.extend(data);
+                                continue;
+                            }
+                            Err(_) => break,
+                        },
+                    };
+                    frame.push(next);
+                    if next == LF {
+                        complete = true;
+                        break;
+                    }
+                }
+                if !complete {
+                    break;
+                }
+                let answer = match parse_frame(&frame) {
+                    Ok((number, text, last)) if number == expected => {
+                        record.extend_from_slice(text);
+                        if last {
+                            records.push(
+                                String::from_utf8_lossy(&record)
+                                    .trim_end_matches('\r')
+                                    .to_string(),
+                            );
+                            record.clear();
+                        }
+                        expected = (expected + 1) % 8;
+                        ACK
+                    }
+                    // The host missed our ACK and sent the same frame again
+                    Ok((number, _, _)) if (number + 1) % 8 == expected => ACK,
+                    Ok((number, _, _)) => {
+                        warn!(
+                            "ASTM frame {} out of sequence, expected {}",
+                            number, expected
+                        );
+                        NAK
+                    }
+                    Err(e) => {
+                        warn!("Rejected ASTM frame: {}", e);
+                        NAK
+                    }
+                };
+                if link.write(&[answer]).await.is_err() {
+                    break;
+                }
+            }
+            EOT if in_session => {
+                in_session = false;
+                let message = std::mem::take(&mut records);
+                let db_state = app.state::<DbState>();
+                let db = db_state.lock().await;
+                let registry = app.state::<DeviceRegistry>();
+                match apply_orders(&*db, &registry, &message).await {
+                    Ok(summary) => {
+                        info!(
+                            "ASTM orders applied: {} created, {} cancelled, {} skipped",
+                            summary.created, summary.cancelled, summary.skipped
+                        );
+                        for test_uuid in &summary.created_tests {
+                            label_printer::print_new_test(app, &*db, test_uuid).await;
+                        }
+                    }
+                    Err(e) => error!("Failed to apply ASTM orders: {}", e),
+                }
+            }
+            _ => {}
+        }
+    }
+    link.close().await;
+}
+
+/// Delimiters declared by a message's header record
+struct Delimiters {
+    field: char,
+    repeat: char,
+    component: char,
+    escape: char,
+}
+
+impl Delimiters {
+    fn from_header(header: &str) -> Self {
+        let mut chars = header.chars().skip(1);
+        Self {
+            field: chars.next().unwrap_or('|'),
+            repeat: chars.next().unwrap_or('\\'),
+            component: chars.next().unwrap_or('^'),
+            escape: chars.next().unwrap_or('&'),
+        }
+    }
+
+    fn unescape(&self, text: &str) -> String {
+        let e = self.escape;
+        text.replace(&format!("{e}F{e}"), &self.field.to_string())
+            .replace(&format!("{e}S{e}"), &self.component.to_string())
+            .replace(&format!("{e}R{e}"), &self.repeat.to_string())
+            .replace(&format!("{e}E{e}"), &e.to_string())
+    }
+}
+
+/// Patient named by the latest P record
+struct OrderPatient {
+    id_number: String,
+    first_name: String,
+    last_name: String,
+    date_of_birth: Option<String>,
+}
+
+/// Create pending tests for new orders and cancel pending ones the host withdrew
+///
+/// Patients are matched on their ID number and registered if unknown. An
+/// order already on file for the same specimen and test type is skipped. A
+/// cancelled order gives up the device it was started on.
+pub async fn apply_orders(
+    db: &DatabaseConnection,
+    registry: &DeviceRegistry,
+    records: &[String],
+) -> Result<OrderSummary, AppError> {
+    let mut summary = OrderSummary::default();
+    let mut delimiters = Delimiters::from_header("H|\\^&");
+    let mut patient: Option<OrderPatient> = None;
+
+    for record in records {
+        match record.chars().next() {
+            Some('H') => delimiters = Delimiters::from_header(record),
+            Some('P') => {
+                let fields: Vec<&str> = record.split(delimiters.field).collect();
+                let field =
+                    |i: usize| delimiters.unescape(fields.get(i).copied().unwrap_or_default());
+                let id_number = [field(2), field(3)]
+                    .into_iter()
+                    .find(|id| !id.trim().is_empty())
+                    .unwrap_or_default();
+                let name = field(5);
+                let mut name = name.split(delimiters.component);
+                patient = Some(OrderPatient {
+                    id_number: id_number.trim().to_string(),
+                    last_name: name.next().unwrap_or_default().trim().to_string(),
+                    first_name: name.next().unwrap_or_default().trim().to_string(),
+                    date_of_birth: NaiveDate::parse_from_str(
+                        field(7).get(..8).unwrap_or_default(),
+                        "%Y%m%d",
+                    )
+                    .ok()
+                    .map(|d| d.format("%Y-%m-%d").to_string()),
+                });
+            }
+            Some('O') => {
+                let fields: Vec<&str> = record.split(delimiters.field).collect();
+                let raw = |i: usize| fields.get(i).copied().unwrap_or_default();
+                let specimen_id = delimiters.unescape(
+                    raw(2)
+                        .split(delimiters.component)
+                        .next()
+                        .unwrap_or_default(),
+                );
+                let cancel = raw(11).eq_ignore_ascii_case("C");
+                let Some(order_patient) = patient.as_ref().filter(|p| !p.id_number.is_empty())
+                else {
+                    warn!("ASTM order {} has no patient ID; skipped", specimen_id);
+                    summary.skipped += 1;
+                    continue;
+                };
+                if specimen_id.is_empty() {
+                    warn!("ASTM order without a specimen ID; skipped");
+                    summary.skipped += 1;
+                    continue;
+                }
+
+                for universal_id in raw(4).split(delimiters.repeat) {
+                    let code = delimiters.unescape(
+                        universal_id
+                            .split(delimiters.component)
+                            .nth(3)
+                            .unwrap_or_default(),
+                    );
+                    let Ok(test_type) = TestType::from_str(&code.trim().to_lowercase()) else {
+                        warn!(
+                            "ASTM order {} asks for unknown test '{}'; skipped",
+                            specimen_id, code
+                        );
+                        summary.skipped += 1;
+                        continue;
+                    };
+                    let existing =
+                        Database::get_test_by_specimen_id(db, &specimen_id, test_type.as_str())
+                            .await?;
+
+                    if cancel {
+                        match existing {
+                            Some(mut test) if test.status == TestStatus::Pending.as_str() => {
+                                test.status = TestStatus::Cancelled.as_str().to_string();
+                                test.updated_at = Utc::now().to_rfc3339();
+                                Database::update_test(db, &test).await?;
+                                registry.release_test(&test.uuid);
+                                info!(
+                                    "Order {} {} cancelled by the LIS",
+                                    specimen_id,
+                                    test_type.as_str()
+                                );
+                                summary.cancelled += 1;
+                            }
+                            _ => summary.skipped += 1,
+                        }
+                        continue;
+                    }
+                    if existing.is_some() {
+                        summary.skipped += 1;
+                        continue;
+                    }
+
+                    let patient_id = order_patient_id(db, order_patient).await?;
+                    let mut test =
+                        Test::new_pending(patient_id, test_type.as_str().to_string(), None, None);
+                    test.specimen_id = Some(specimen_id.clone());
+                    Database::insert_test(db, &mut test).await?;
+                    info!(
+                        "Order {} {} received from the LIS as test {}",
+                        specimen_id,
+                        test_type.as_str(),
+                        test.uuid
+                    );
+                    summary.created += 1;
+                    summary.created_tests.push(test.uuid);
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(summary)
+}
+
+/// Database ID of the ordered patient, registering them if they are new
+async fn order_patient_id(db: &DatabaseConnection, order: &OrderPatient) -> Result<i64, AppError> {
+    if let Some(patient) = Database::get_patient_by_id_number(db, &order.id_number).await? {
+        return patient
+            .id
+            .ok_or_else(|| AppError::internal("Patient has no ID"));
+    }
+    let patient = Patient::new(
+        order.first_name.clone(),
+        order.last_name.clone(),
+        order.date_of_birth.clone(),
+        Some(order.id_number.clone()),
+        None,
+        None,
+        Some("Registered from a LIS order".to_string()),
+    );
+    let id = Database::insert_patient(db, &patient).await?;
+    info!("Patient {} registered from a LIS order", order.id_number);
+    Ok(id)
+}
@@ -0,0 +1,265 @@
+use crate::models::{AppError, Permission, User};
+use argon2::password_hash::rand_core::OsRng;
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::Argon2;
+use serde::Serialize;
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+const MIN_PASSWORD_CHARS: usize = 8;
+const MAX_USERNAME_CHARS: usize = 32;
+const PIN_DIGITS: std::ops::RangeInclusive<usize> = 4..=8;
+/// Wrong PINs or passwords tolerated on the lock screen before signing out
+const MAX_UNLOCK_ATTEMPTS: u32 = 5;
+
+/// Setting holding the inactivity period before the session locks
+pub const AUTO_LOCK_SETTING: &str = "auto_lock_minutes";
+pub const DEFAULT_AUTO_LOCK_MINUTES: u32 = 10;
+/// Longest configurable inactivity period; 0 turns auto-lock off
+pub const MAX_AUTO_LOCK_MINUTES: u32 = 240;
+
+/// Stored secrets of an account, never sent to the UI
+pub struct Credentials {
+    pub password_hash: String,
+    pub pin_hash: Option<String>,
+}
+
+impl Credentials {
+    /// Whether `secret` is the account's password or its PIN
+    pub fn verify(&self, secret: &str) -> bool {
+        verify_password(secret, &self.password_hash)
+            || self
+                .pin_hash
+                .as_deref()
+                .is_some_and(|hash| verify_password(secret, hash))
+    }
+}
+
+/// The operator signed in to this app instance, managed as `SessionState`
+///
+/// A session locks after `auto_lock` without activity; while locked every
+/// guarded command is refused until the same operator unlocks it.
+pub struct Session {
+    pub user: Option<User>,
+    locked: bool,
+    last_activity: Instant,
+    auto_lock: Option<Duration>,
+    failed_unlocks: u32,
+}
+
+pub type SessionState = std::sync::Mutex<Session>;
+
+impl Default for Session {
+    fn default() -> Self {
+        Self {
+            user: None,
+            locked: false,
+            last_activity: Instant::now(),
+            auto_lock: auto_lock_duration(DEFAULT_AUTO_LOCK_MINUTES),
+            failed_unlocks: 0,
+        }
+    }
+}
+
+/// `None` when auto-lock is off
+pub fn auto_lock_duration(minutes: u32) -> Option<Duration> {
+    (minutes > 0).then(|| Duration::from_secs(u64::from(minutes) * 60))
+}
+
+impl Session {
+    pub fn sign_in(&mut self, user: User) {
+        self.user = Some(user);
+        self.locked = false;
+        self.failed_unlocks = 0;
+        self.last_activity = Instant::now();
+    }
+
+    pub fn sign_out(&mut self) -> Option<User> {
+        self.locked = false;
+        self.failed_unlocks = 0;
+        self.user.take()
+    }
+
+    pub fn set_auto_lock(&mut self, auto_lock: Option<Duration>) {
+        self.auto_lock = auto_lock;
+    }
+
+    pub fn auto_lock_minutes(&self) -> u32 {
+        self.auto_lock
+            .map(|period| (period.as_secs() / 60) as u32)
+            .unwrap_or(0)
+    }
+
+    /// Lock now if the inactivity period has passed; returns whether locked
+    pub fn is_locked(&mut self) -> bool {
+        if self.user.is_some() && !self.locked {
+            if let Some(period) = self.auto_lock {
+                if self.last_activity.elapsed() >= period {
+                    self.lock();
+                }
+            }
+        }
+        self.locked
+    }
+
+    pub fn lock(&mut self) {
+        if let Some(user) = &self.user {
+            if !self.locked {
+                info!("Session of {} locked", user.username);
+            }
+            self.locked = true;
+        }
+    }
+
+    /// Record operator activity, postponing the auto-lock
+    pub fn touch(&mut self) -> Result<(), AppError> {
+        self.user()?;
+        self.last_activity = Instant::now();
+        Ok(())
+    }
+
+    /// Unlock with the signed-in operator's password or PIN
+    ///
+    /// Too many wrong attempts sign the operator out.
+    pub fn unlock(&mut self, credentials: &Credentials, secret: &str) -> Result<User, AppError> {
+        let user = self
+            .user
+            .clone()
+            .ok_or_else(|| AppError::unauthorized("Sign in to continue"))?;
+        if credentials.verify(secret) {
+            self.locked = false;
+            self.failed_unlocks = 0;
+            self.last_activity = Instant::now();
+            info!("Session of {} unlocked", user.username);
+            return Ok(user);
+        }
+
+        self.failed_unlocks += 1;
+        warn!(
+            "Failed unlock attempt {} for {}",
+            self.failed_unlocks, user.username
+        );
+        if self.failed_unlocks >= MAX_UNLOCK_ATTEMPTS {
+            self.sign_out();
+            return Err(AppError::unauthorized(
+                "Too many failed attempts; sign in again",
+            ));
+        }
+        Err(AppError::validation("Incorrect PIN or password"))
+    }
+
+    /// The signed-in operator, or an `unauthorized` error when signed out or locked
+    pub fn user(&mut self) -> Result<User, AppError> {
+        if self.is_locked() {
+            return Err(AppError::unauthorized("The session is locked"));
+        }
+        self.user
+            .clone()
+            .ok_or_else(|| AppError::unauthorized("Sign in to continue"))
+    }
+
+    /// The signed-in operator, if their role allows `permission`
+    pub fn require(&mut self, permission: Permission) -> Result<User, AppError> {
+        let user = self.user()?;
+        if !user.role.can(permission) {
+            warn!(
+                "{} ({}) was refused permission to {}",
+                user.username,
+                user.role.as_str(),
+                permission.describe()
+            );
+            return Err(AppError::forbidden(format!(
+                "{}s cannot {}",
+                user.role.label(),
+                permission.describe()
+            )));
+        }
+        Ok(user)
+    }
+}
+
+/// Guard for commands open to any signed-in operator
+pub fn require_user(session: &SessionState) -> Result<User, AppError> {
+    session.lock().unwrap().user()
+}
+
+/// Guard for commands limited to roles holding `permission`
+pub fn require(session: &SessionState, permission: Permission) -> Result<User, AppError> {
+    session.lock().unwrap().require(permission)
+}
+
+/// Returned by `get_auth_status` so the UI knows which screen to show
+#[derive(Debug, Clone, Serialize)]
+pub struct AuthStatus {
+    pub user: Option<User>,
+    /// No accounts exist yet; the first one becomes the administrator
+    pub needs_setup: bool,
+    /// The signed-in operator must unlock before continuing
+    pub locked: bool,
+    /// Inactivity period before the session locks; 0 when auto-lock is off
+    pub auto_lock_minutes: u32,
+}
+
+/// Hash a password with Argon2id and a random salt, in PHC string form
+pub fn hash_password(password: &str) -> Result<String, AppError> {
+    let salt = SaltString::generate(&mut OsRng);
+    Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .map(|hash| hash.to_string())
+        .map_err(|e| AppError::internal("Failed to hash password").with_details(e))
+}
+
+/// Check a password against a stored PHC hash; a corrupt hash never matches
+pub fn verify_password(password: &str, hash: &str) -> bool {
+    match PasswordHash::new(hash) {
+        Ok(parsed) => Argon2::default()
+            .verify_password(password.as_bytes(), &parsed)
+            .is_ok(),
+        Err(e) => {
+            warn!("Stored password hash is unreadable: {}", e);
+            false
+        }
+    }
+}
+
+pub fn validate_password(password: &str) -> Result<(), AppError> {
+    if password.chars().count() < MIN_PASSWORD_CHARS {
+        return Err(AppError::validation(format!(
+            "Password must be at least {} characters",
+            MIN_PASSWORD_CHARS
+        )));
+    }
+    Ok(())
+}
+
+/// Trim and lower-case a username, rejecting anything but letters, digits, `.`, `_` and `-`
+pub fn normalize_username(username: &str) -> Result<String, AppError> {
+    let username = username.trim().to_lowercase();
+    if username.is_empty() || username.chars().count() > MAX_USERNAME_CHARS {
+        return Err(AppError::validation(format!(
+            "Username must be 1 to {} characters",
+            MAX_USERNAME_CHARS
+        )));
+    }
+    if !username
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-'))
+    {
+        return Err(AppError::validation(
+            "Username may only contain letters, digits, '.', '_' and '-'",
+        ));
+    }
+    Ok(username)
+}
+
+/// A PIN is 4 to 8 digits
+pub fn validate_pin(pin: &str) -> Result<(), AppError> {
+    if !PIN_DIGITS.contains(&pin.len()) || !pin.chars().all(|c| c.is_ascii_digit()) {
+        return Err(AppError::validation(format!(
+            "PIN must be {} to {} digits",
+            PIN_DIGITS.start(),
+            PIN_DIGITS.end()
+        )));
+    }
+    Ok(())
+}
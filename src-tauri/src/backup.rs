@@ -0,0 +1,493 @@
+use crate::db_orm::Database;
+use crate::encryption::{self, DatabaseKey, EncryptionState};
+use crate::migrations::{self, SchemaVersion};
+use crate::models::{AppError, DbState};
+use sea_orm::{ConnectionTrait, DatabaseConnection, DbBackend, Statement};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use tauri::{AppHandle, Manager};
+use tracing::{error, info, warn};
+
+/// Folder under the app data dir holding backups
+pub const BACKUP_DIR: &str = "backups";
+/// Setting holding the JSON-encoded `BackupSchedule`
+pub const BACKUP_SCHEDULE_SETTING: &str = "backup_schedule";
+
+/// Backup files are named `ebers-<kind>-<timestamp>.db`
+const BACKUP_PREFIX: &str = "ebers-";
+const BACKUP_SUFFIX: &str = ".db";
+/// How often the scheduler checks whether a backup is due
+const SCHEDULER_TICK: Duration = Duration::from_secs(15 * 60);
+
+/// Why a backup was taken; only scheduled backups are pruned
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BackupKind {
+    Scheduled,
+    Manual,
+    PreRestore,
+}
+
+impl BackupKind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            BackupKind::Scheduled => "auto",
+            BackupKind::Manual => "manual",
+            BackupKind::PreRestore => "pre-restore",
+        }
+    }
+}
+
+/// A backup file on disk
+#[derive(Debug, Clone, Serialize)]
+pub struct BackupInfo {
+    pub path: String,
+    pub file_name: String,
+    pub kind: String,
+    pub size_bytes: u64,
+    pub created_at: Option<String>,
+}
+
+/// Automatic backup settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupSchedule {
+    pub enabled: bool,
+    pub interval_hours: u32,
+    /// Scheduled backups kept; older ones are deleted
+    pub keep: usize,
+}
+
+impl Default for BackupSchedule {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            interval_hours: 24,
+            keep: 14,
+        }
+    }
+}
+
+impl BackupSchedule {
+    pub fn validate(&self) -> Result<(), AppError> {
+        if self.interval_hours == 0 {
+            return Err(AppError::validation(
+                "Backup interval must be at least one hour",
+            ));
+        }
+        if self.keep == 0 {
+            return Err(AppError::validation("At least one backup must be kept"));
+        }
+        Ok(())
+    }
+}
+
+/// Outcome of `restore_database`
+#[derive(Debug, Clone, Serialize)]
+pub struct RestoreReport {
+    pub restored_from: String,
+    /// Snapshot of the data that was replaced
+    pub safety_backup: BackupInfo,
+    pub schema: SchemaVersion,
+}
+
+/// Result of `PRAGMA integrity_check`
+#[derive(Debug, Clone, Serialize)]
+pub struct IntegrityReport {
+    pub ok: bool,
+    pub problems: Vec<String>,
+}
+
+// ----------------------------------------------------------------------------
+// SNAPSHOTS
+// ----------------------------------------------------------------------------
+
+/// Write a consistent copy of the live database to `dest`
+///
+/// Runs online; readers and writers on other connections are not blocked.
+/// An encrypted database is exported under the same `key`, so backups are
+/// never less protected than the live file.
+pub async fn snapshot(
+    db: &DatabaseConnection,
+    dest: &Path,
+    key: Option<&DatabaseKey>,
+) -> Result<BackupInfo, AppError> {
+    if dest.exists() {
+        return Err(AppError::conflict(format!(
+            "Backup destination already exists: {}",
+            dest.display()
+        )));
+    }
+    if let Some(parent) = dest.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| AppError::io("Failed to create backup directory", e))?;
+    }
+
+    match key {
+        Some(key) => encryption::export(db, dest, Some(key)).await?,
+        None => {
+            let dest_str = dest.to_string_lossy().to_string();
+            db.execute(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                "VACUUM INTO ?",
+                [dest_str.into()],
+            ))
+            .await
+            .map_err(|e| AppError::database("Failed to back up database", e))?;
+        }
+    }
+
+    info!("Database backed up to {}", dest.display());
+    Ok(backup_info(dest))
+}
+
+/// Snapshot into `dir` under a timestamped name
+pub async fn backup_to_dir(
+    db: &DatabaseConnection,
+    dir: &Path,
+    kind: BackupKind,
+    key: Option<&DatabaseKey>,
+) -> Result<BackupInfo, AppError> {
+    let file_name = format!(
+        "{}{}-{}{}",
+        BACKUP_PREFIX,
+        kind.as_str(),
+        chrono::Utc::now().format("%Y%m%d-%H%M%S%.3f"),
+        BACKUP_SUFFIX
+    );
+    snapshot(db, &dir.join(file_name), key).await
+}
+
+/// Backups in `dir`, newest first
+pub fn list_backups(dir: &Path) -> Vec<BackupInfo> {
+    let mut backups: Vec<(SystemTime, BackupInfo)> = fs::read_dir(dir)
+        .map(|entries| entries.filter_map(|e| e.ok()).collect::<Vec<_>>())
+        .unwrap_or_default()
+        .into_iter()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| {
+                    name.starts_with(BACKUP_PREFIX) && name.ends_with(BACKUP_SUFFIX)
+                })
+        })
+        .map(|path| {
+            let modified = fs::metadata(&path)
+                .and_then(|m| m.modified())
+                .unwrap_or(SystemTime::UNIX_EPOCH);
+            (modified, backup_info(&path))
+        })
+        .collect();
+
+    backups.sort_by_key(|(modified, _)| std::cmp::Reverse(*modified));
+    backups.into_iter().map(|(_, info)| info).collect()
+}
+
+/// Delete scheduled backups beyond the newest `keep`
+pub fn prune(dir: &Path, keep: usize) -> usize {
+    let mut removed = 0;
+    for backup in list_backups(dir)
+        .into_iter()
+        .filter(|b| b.kind == BackupKind::Scheduled.as_str())
+        .skip(keep)
+    {
+        match fs::remove_file(&backup.path) {
+            Ok(()) => removed += 1,
+            Err(e) => warn!("Failed to remove old backup {}: {}", backup.path, e),
+        }
+    }
+    removed
+}
+
+fn backup_info(path: &Path) -> BackupInfo {
+    let file_name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    // Files copied in by hand keep working but are never pruned
+    let kind = file_name
+        .strip_prefix(BACKUP_PREFIX)
+        .and_then(|rest| {
+            [
+                BackupKind::Scheduled,
+                BackupKind::Manual,
+                BackupKind::PreRestore,
+            ]
+            .iter()
+            .map(|k| k.as_str())
+            .find(|k| rest.strip_prefix(k).is_some_and(|r| r.starts_with('-')))
+        })
+        .unwrap_or("external")
+        .to_string();
+    let metadata = fs::metadata(path).ok();
+
+    BackupInfo {
+        path: path.to_string_lossy().to_string(),
+        file_name,
+        kind,
+        size_bytes: metadata.as_ref().map(|m| m.len()).unwrap_or(0),
+        created_at: metadata
+            .and_then(|m| m.modified().ok())
+            .map(|t| chrono::DateTime::<chrono::Utc>::from(t).to_rfc3339()),
+    }
+}
+
+// ----------------------------------------------------------------------------
+// INTEGRITY & RESTORE
+// ----------------------------------------------------------------------------
+
+/// Run SQLite's full consistency check
+pub async fn integrity_check(db: &DatabaseConnection) -> Result<IntegrityReport, AppError> {
+    let rows = db
+        .query_all(Statement::from_string(
+            DbBackend::Sqlite,
+            "PRAGMA integrity_check",
+        ))
+        .await
+        .map_err(|e| AppError::database("Failed to check database integrity", e))?;
+
+    let problems: Vec<String> = rows
+        .iter()
+        .filter_map(|row| row.try_get_by_index::<String>(0).ok())
+        .filter(|line| line != "ok")
+        .collect();
+
+    Ok(IntegrityReport {
+        ok: problems.is_empty(),
+        problems,
+    })
+}
+
+/// Replace the live database at `db_path` with the backup at `source`
+///
+/// The backup is copied aside, integrity-checked and migrated to the current
+/// schema before anything is touched. The live data is snapshotted into
+/// `backups_dir` first, and put back if the restored file will not open.
+/// A plain backup restored while encryption is on is encrypted with `key`.
+/// Callers must hold the database lock for the whole call.
+pub async fn restore(
+    current: &mut DatabaseConnection,
+    db_path: &Path,
+    backups_dir: &Path,
+    source: &Path,
+    key: Option<&DatabaseKey>,
+) -> Result<RestoreReport, AppError> {
+    let plaintext = encryption::is_plaintext(source)?;
+    if !plaintext && key.is_none() {
+        return Err(AppError::validation(format!(
+            "Not a plain SQLite database: {}",
+            source.display()
+        ))
+        .with_details("Encrypted backups can only be restored while encryption is enabled"));
+    }
+
+    let staging = sibling(db_path, ".restore");
+    remove_database_files(&staging)?;
+    fs::copy(source, &staging).map_err(|e| AppError::io("Failed to copy backup", e))?;
+
+    let open_key = if plaintext { None } else { key };
+    let schema = match validate_staged(&staging, open_key, key).await {
+        Ok(schema) => schema,
+        Err(e) => {
+            let _ = remove_database_files(&staging);
+            return Err(e);
+        }
+    };
+
+    let safety_backup = backup_to_dir(current, backups_dir, BackupKind::PreRestore, key).await?;
+
+    // Nothing else can reach the connection while the caller holds the lock
+    let old = std::mem::replace(current, DatabaseConnection::Disconnected);
+    if let Err(e) = old.close().await {
+        warn!("Failed to close database cleanly before restore: {}", e);
+    }
+
+    let swapped = remove_database_files(db_path).and_then(|_| {
+        fs::rename(&staging, db_path).map_err(|e| AppError::io("Failed to replace database", e))
+    });
+    let reopened = match swapped {
+        Ok(()) => Database::open(db_path, key).await,
+        Err(e) => Err(e),
+    };
+
+    match reopened {
+        Ok(db) => {
+            *current = db;
+            info!(
+                "Database restored from {}; previous data saved to {}",
+                source.display(),
+                safety_backup.path
+            );
+            Ok(RestoreReport {
+                restored_from: source.to_string_lossy().to_string(),
+                safety_backup,
+                schema,
+            })
+        }
+        Err(e) => {
+            error!("Restore failed, putting previous database back: {}", e);
+            remove_database_files(db_path)?;
+            fs::copy(&safety_backup.path, db_path)
+                .map_err(|e| AppError::io("Failed to put previous database back", e))?;
+            *current = Database::open(db_path, key).await?;
+            Err(e)
+        }
+    }
+}
+
+/// Integrity-check and migrate a staged copy, returning its schema state
+///
+/// The copy is opened with `open_key` and, if it differs from `key`,
+/// re-exported under `key` so it matches the live database's protection.
+async fn validate_staged(
+    path: &Path,
+    open_key: Option<&DatabaseKey>,
+    key: Option<&DatabaseKey>,
+) -> Result<SchemaVersion, AppError> {
+    let db = Database::connect(path, open_key).await.map_err(|e| {
+        let message = match open_key {
+            Some(_) => "Backup is not encrypted with the current key",
+            None => "Backup cannot be opened",
+        };
+        AppError::validation(message).with_details(e.message)
+    })?;
+    let converted = sibling(path, ".converted");
+
+    let result = async {
+        // A badly damaged file fails the check itself rather than reporting problems
+        let integrity = integrity_check(&db).await.map_err(|e| {
+            AppError::validation("Backup failed the integrity check")
+                .with_details(e.details.unwrap_or(e.message))
+        })?;
+        if !integrity.ok {
+            return Err(AppError::validation("Backup failed the integrity check")
+                .with_details(integrity.problems.join("; ")));
+        }
+
+        // Also rejects backups written by a newer version of the app
+        let schema = migrations::run(&db).await.map_err(|e| {
+            AppError::validation("Backup schema cannot be upgraded").with_details(e)
+        })?;
+        if !schema.drift.is_empty() {
+            let tables: Vec<&str> = schema.drift.iter().map(|d| d.table.as_str()).collect();
+            return Err(
+                AppError::validation("Backup does not match the application schema")
+                    .with_details(tables.join(", ")),
+            );
+        }
+
+        if open_key.is_none() && key.is_some() {
+            remove_database_files(&converted)?;
+            encryption::export(&db, &converted, key).await?;
+        }
+        Ok(schema)
+    }
+    .await;
+
+    if let Err(e) = db.close().await {
+        warn!("Failed to close staged backup: {}", e);
+    }
+    if result.is_ok() && converted.exists() {
+        remove_database_files(path)?;
+        fs::rename(&converted, path)
+            .map_err(|e| AppError::io("Failed to stage encrypted backup", e))?;
+    } else {
+        let _ = remove_database_files(&converted);
+    }
+    result
+}
+
+/// `ebers.db` -> `ebers.db<suffix>`
+pub fn sibling(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(suffix);
+    path.with_file_name(name)
+}
+
+/// Remove a database file and any leftover WAL/shared-memory files
+pub fn remove_database_files(path: &Path) -> Result<(), AppError> {
+    for file in [
+        path.to_path_buf(),
+        sibling(path, "-wal"),
+        sibling(path, "-shm"),
+    ] {
+        match fs::remove_file(&file) {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(AppError::io("Failed to remove old database file", e)),
+        }
+    }
+    Ok(())
+}
+
+// ----------------------------------------------------------------------------
+// SCHEDULE
+// ----------------------------------------------------------------------------
+
+pub async fn load_schedule(db: &DatabaseConnection) -> Result<BackupSchedule, AppError> {
+    match Database::get_setting(db, BACKUP_SCHEDULE_SETTING.to_string()).await? {
+        Some(json) => serde_json::from_str(&json)
+            .map_err(|e| AppError::internal("Failed to parse backup schedule").with_details(e)),
+        None => Ok(BackupSchedule::default()),
+    }
+}
+
+pub async fn save_schedule(
+    db: &DatabaseConnection,
+    schedule: &BackupSchedule,
+) -> Result<(), AppError> {
+    schedule.validate()?;
+    let json = serde_json::to_string(schedule)
+        .map_err(|e| AppError::internal("Failed to encode backup schedule").with_details(e))?;
+    Database::save_setting(db, BACKUP_SCHEDULE_SETTING.to_string(), json).await
+}
+
+/// Take scheduled backups in the background for the life of the app
+pub fn spawn_scheduler(app: AppHandle, backups_dir: PathBuf) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            if let Err(e) = run_scheduled_backup(&app, &backups_dir).await {
+                error!("Scheduled backup failed: {}", e);
+            }
+            tokio::time::sleep(SCHEDULER_TICK).await;
+        }
+    });
+}
+
+/// Back up if the newest scheduled backup is older than the interval
+async fn run_scheduled_backup(app: &AppHandle, backups_dir: &Path) -> Result<(), AppError> {
+    let db_state = app.state::<DbState>();
+    let db = db_state.lock().await;
+
+    // Nothing to back up until an encrypted database has been unlocked
+    let key = {
+        let encryption = app.state::<EncryptionState>();
+        let encryption = encryption.lock().unwrap();
+        if !encryption.unlocked {
+            return Ok(());
+        }
+        encryption.key.clone()
+    };
+
+    let schedule = load_schedule(&db).await?;
+    if !schedule.enabled {
+        return Ok(());
+    }
+
+    let interval = Duration::from_secs(u64::from(schedule.interval_hours) * 3600);
+    let last = list_backups(backups_dir)
+        .into_iter()
+        .find(|b| b.kind == BackupKind::Scheduled.as_str())
+        .and_then(|b| fs::metadata(&b.path).and_then(|m| m.modified()).ok());
+    if last.is_some_and(|t| t.elapsed().unwrap_or_default() < interval) {
+        return Ok(());
+    }
+
+    backup_to_dir(&db, backups_dir, BackupKind::Scheduled, key.as_ref()).await?;
+    let removed = prune(backups_dir, schedule.keep);
+    if removed > 0 {
+        info!("Removed {} old scheduled backups", removed);
+    }
+    Ok(())
+}
@@ -0,0 +1,282 @@
+use crate::models::SerialProfile;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+/// Capture file format version written in each header
+const CAPTURE_VERSION: u32 = 1;
+/// Size at which a capture file is closed and a new one started
+const MAX_CAPTURE_BYTES: u64 = 10 * 1024 * 1024;
+/// Capture files kept per device; older ones are deleted on rotation
+const MAX_CAPTURE_FILES: usize = 20;
+
+/// One line of a capture file (newline-delimited JSON)
+///
+/// `t_us` is the arrival time in microseconds since the file's `started_at`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum CaptureRecord {
+    Header {
+        version: u32,
+        device_id: String,
+        port: String,
+        profile: SerialProfile,
+        started_at: String,
+    },
+    Connected {
+        t_us: u64,
+    },
+    Data {
+        t_us: u64,
+        data: String,
+    },
+    Disconnected {
+        t_us: u64,
+    },
+}
+
+impl CaptureRecord {
+    /// Arrival time of an event record; headers are at time zero
+    pub fn t_us(&self) -> u64 {
+        match self {
+            CaptureRecord::Header { .. } => 0,
+            CaptureRecord::Connected { t_us }
+            | CaptureRecord::Data { t_us, .. }
+            | CaptureRecord::Disconnected { t_us } => *t_us,
+        }
+    }
+}
+
+/// A capture file read back for replay
+pub struct Capture {
+    pub device_id: String,
+    pub port: String,
+    pub profile: SerialProfile,
+    pub started_at: String,
+    pub events: Vec<CaptureRecord>,
+}
+
+/// Capture file listing entry
+#[derive(Debug, Clone, Serialize)]
+pub struct CaptureInfo {
+    pub path: String,
+    pub device_id: String,
+    pub size_bytes: u64,
+    pub modified_at: Option<String>,
+}
+
+/// Writes everything a reader thread receives to rotating capture files
+pub struct CaptureWriter {
+    dir: PathBuf,
+    device_id: String,
+    port: String,
+    profile: SerialProfile,
+    file: Option<File>,
+    bytes_written: u64,
+    started: Instant,
+}
+
+impl CaptureWriter {
+    /// Create a writer for a device under `<captures_root>/<device_id>/`
+    pub fn new(
+        captures_root: &Path,
+        device_id: &str,
+        port: &str,
+        profile: &SerialProfile,
+    ) -> Result<Self, String> {
+        let dir = captures_root.join(safe_file_name(device_id));
+        fs::create_dir_all(&dir)
+            .map_err(|e| format!("Failed to create capture directory: {}", e))?;
+
+        Ok(Self {
+            dir,
+            device_id: device_id.to_string(),
+            port: port.to_string(),
+            profile: profile.clone(),
+            file: None,
+            bytes_written: 0,
+            started: Instant::now(),
+        })
+    }
+
+    pub fn connected(&mut self) -> Result<(), String> {
+        let t_us = self.elapsed_us();
+        self.write(&CaptureRecord::Connected { t_us })
+    }
+
+    pub fn data(&mut self, chunk: &str) -> Result<(), String> {
+        let t_us = self.elapsed_us();
+        self.write(&CaptureRecord::Data {
+            t_us,
+            data: chunk.to_string(),
+        })
+    }
+
+    pub fn disconnected(&mut self) -> Result<(), String> {
+        let t_us = self.elapsed_us();
+        self.write(&CaptureRecord::Disconnected { t_us })
+    }
+
+    fn elapsed_us(&self) -> u64 {
+        self.started.elapsed().as_micros() as u64
+    }
+
+    /// Append a record, rotating to a new file when the current one is full
+    fn write(&mut self, record: &CaptureRecord) -> Result<(), String> {
+        if self.file.is_none() || self.bytes_written >= MAX_CAPTURE_BYTES {
+            self.rotate()?;
+        }
+
+        let mut line = serde_json::to_string(record)
+            .map_err(|e| format!("Failed to serialize capture record: {}", e))?;
+        line.push('\n');
+
+        // Flushed per record so a crash loses nothing already received
+        let file = self.file.as_mut().ok_or("Capture file is not open")?;
+        file.write_all(line.as_bytes())
+            .and_then(|_| file.flush())
+            .map_err(|e| format!("Failed to write capture: {}", e))?;
+        self.bytes_written += line.len() as u64;
+        Ok(())
+    }
+
+    /// Start a new capture file with its own header and prune old files
+    fn rotate(&mut self) -> Result<(), String> {
+        let now = chrono::Utc::now();
+        let path = self.dir.join(format!(
+            "capture-{}.ndjson",
+            now.format("%Y%m%dT%H%M%S%.3fZ")
+        ));
+        self.file = Some(
+            OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&path)
+                .map_err(|e| format!("Failed to open capture file: {}", e))?,
+        );
+        self.bytes_written = 0;
+        self.started = Instant::now();
+
+        self.write(&CaptureRecord::Header {
+            version: CAPTURE_VERSION,
+            device_id: self.device_id.clone(),
+            port: self.port.clone(),
+            profile: self.profile.clone(),
+            started_at: now.to_rfc3339(),
+        })?;
+
+        prune_captures(&self.dir);
+        Ok(())
+    }
+}
+
+/// Delete the oldest capture files beyond the retention limit
+fn prune_captures(dir: &Path) {
+    let mut files = capture_files(dir);
+    if files.len() <= MAX_CAPTURE_FILES {
+        return;
+    }
+    // Names embed the start time, so lexical order is chronological
+    files.sort();
+    for old in &files[..files.len() - MAX_CAPTURE_FILES] {
+        let _ = fs::remove_file(old);
+    }
+}
+
+fn capture_files(dir: &Path) -> Vec<PathBuf> {
+    fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok().map(|e| e.path()))
+                .filter(|path| path.extension().is_some_and(|ext| ext == "ndjson"))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// List capture files for all devices, newest first
+pub fn list_captures(captures_root: &Path) -> Vec<CaptureInfo> {
+    let mut captures: Vec<CaptureInfo> = fs::read_dir(captures_root)
+        .map(|entries| entries.filter_map(|e| e.ok()).collect::<Vec<_>>())
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|entry| entry.path().is_dir())
+        .flat_map(|device_dir| {
+            let device_id = device_dir.file_name().to_string_lossy().to_string();
+            capture_files(&device_dir.path())
+                .into_iter()
+                .map(move |path| {
+                    let metadata = fs::metadata(&path).ok();
+                    CaptureInfo {
+                        path: path.to_string_lossy().to_string(),
+                        device_id: device_id.clone(),
+                        size_bytes: metadata.as_ref().map(|m| m.len()).unwrap_or(0),
+                        modified_at: metadata
+                            .and_then(|m| m.modified().ok())
+                            .map(|t| chrono::DateTime::<chrono::Utc>::from(t).to_rfc3339()),
+                    }
+                })
+        })
+        .collect();
+
+    captures.sort_by(|a, b| b.modified_at.cmp(&a.modified_at));
+    captures
+}
+
+/// Read a capture file back, requiring a header as its first record
+pub fn read_capture(path: &Path) -> Result<Capture, String> {
+    let file = File::open(path).map_err(|e| format!("Failed to open capture: {}", e))?;
+    let mut records = BufReader::new(file)
+        .lines()
+        .enumerate()
+        .filter_map(|(i, line)| match line {
+            Ok(line) if line.trim().is_empty() => None,
+            Ok(line) => Some(
+                serde_json::from_str::<CaptureRecord>(&line)
+                    .map_err(|e| format!("Invalid capture record at line {}: {}", i + 1, e)),
+            ),
+            Err(e) => Some(Err(format!("Failed to read capture: {}", e))),
+        });
+
+    let (device_id, port, profile, started_at) = match records.next().transpose()? {
+        Some(CaptureRecord::Header {
+            version,
+            device_id,
+            port,
+            profile,
+            started_at,
+        }) => {
+            if version > CAPTURE_VERSION {
+                return Err(format!("Unsupported capture version: {}", version));
+            }
+            (device_id, port, profile, started_at)
+        }
+        _ => return Err("Capture file has no header".to_string()),
+    };
+
+    let events = records.collect::<Result<Vec<_>, String>>()?;
+
+    Ok(Capture {
+        device_id,
+        port,
+        profile,
+        started_at,
+        events,
+    })
+}
+
+/// Reduce a device ID to characters safe for a directory name
+fn safe_file_name(device_id: &str) -> String {
+    device_id
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
@@ -1,7 +1,32 @@
-use crate::db_orm::Database;
-use crate::models::{DbState, DetectionResult, Patient, Test, TestStatus, TestWithPatient};
+use crate::astm::{self, AstmSettings};
+use crate::auth::{self, AuthStatus, SessionState};
+use crate::backup::{self, BackupInfo, BackupSchedule, IntegrityReport, RestoreReport};
+use crate::db_orm::{Database, DATABASE_FILE};
+use crate::device_registry::DeviceRegistry;
+use crate::encryption::{
+    self, DatabaseConfig, DatabaseKey, EncryptionMode, EncryptionState, EncryptionStatus,
+};
+use crate::export::{self, ExportOptions, ExportSummary};
+use crate::fhir::{self, FhirSettings};
+use crate::hl7::{self, Hl7Settings};
+use crate::label_printer::{self, LabelPrinterSettings};
+use crate::labels::{self, ScanMatch};
+use crate::logging::{self, LogSnapshot, LogState};
+use crate::migrations::SchemaVersion;
+use crate::models::{
+    AppError, DbState, DetectionResult, Patient, Permission, PreprocessingPipeline, Role, Test,
+    TestStatus, TestType, TestTypePipeline, TestWithPatient, User,
+};
+use crate::outbox::{self, OutboundMessage};
+use crate::patient_import::{self, ImportReport, PatientColumnMapping};
+use crate::qc::{self, ControlLevel, ControlMaterial, DeviceQc, LeveyJennings, QcRun, QcSettings};
+use crate::report::{self, ReportSettings};
+use crate::worklist::{self, OrderImportReport};
+use sea_orm::DatabaseConnection;
 use serde::Deserialize;
-use tauri::State;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager, State};
+use tracing::{debug, error, info, warn};
 
 // ============================================================================
 // SETTINGS COMMANDS
@@ -9,30 +34,767 @@ use tauri::State;
 
 #[tauri::command]
 pub async fn save_setting(
+    session: State<'_, SessionState>,
     db_state: State<'_, DbState>,
     key: String,
     value: String,
-) -> Result<(), String> {
+) -> Result<(), AppError> {
+    auth::require(&session, Permission::ManageSettings)?;
     let db = db_state.lock().await;
     Database::save_setting(&*db, key.clone(), value.clone()).await?;
-    println!("Saved setting: {} = {}", key, value);
+    debug!("Saved setting: {}", key);
     Ok(())
 }
 
+/// Settings holding credentials or keys; only settings managers may read them
+const SECRET_SETTINGS: &[&str] = &[fhir::FHIR_SETTINGS_KEY, export::PSEUDONYM_KEY_SETTING];
+
 #[tauri::command]
 pub async fn get_setting(
+    session: State<'_, SessionState>,
     db_state: State<'_, DbState>,
     key: String,
-) -> Result<Option<String>, String> {
+) -> Result<Option<String>, AppError> {
+    if SECRET_SETTINGS.contains(&key.as_str()) {
+        auth::require(&session, Permission::ManageSettings)?;
+    } else {
+        auth::require_user(&session)?;
+    }
     let db = db_state.lock().await;
     Database::get_setting(&*db, key).await
 }
 
 // ============================================================================
-// PATIENT COMMANDS
+// LOGGING COMMANDS
+// ============================================================================
+
+/// Setting holding the log filter applied at startup
+pub const LOG_FILTER_SETTING: &str = "log_filter";
+
+/// Apply the saved log filter unless `EBERS_LOG` overrides it for this run
+pub async fn apply_saved_log_filter(db: &DatabaseConnection, log_state: &LogState) {
+    if std::env::var(logging::FILTER_ENV).is_ok() {
+        return;
+    }
+    if let Ok(Some(filter)) = Database::get_setting(db, LOG_FILTER_SETTING.to_string()).await {
+        if let Err(e) = log_state.set_filter(&filter) {
+            warn!("{}; keeping the default filter", e);
+        }
+    }
+}
+
+/// Return the most recent log lines so support can inspect them from the UI
+#[tauri::command]
+pub async fn get_logs(
+    session: State<'_, SessionState>,
+    log_state: State<'_, LogState>,
+    lines: Option<usize>,
+) -> Result<LogSnapshot, AppError> {
+    auth::require(&session, Permission::ManageSettings)?;
+    log_state
+        .tail(lines.unwrap_or(1000))
+        .map_err(|e| AppError::io("Failed to read logs", e))
+}
+
+/// Change the log filter, e.g. `info,ebers_lib::serial_handler=debug`
+///
+/// An empty filter restores the default. The filter is saved and reapplied
+/// on the next start.
+#[tauri::command]
+pub async fn set_log_filter(
+    session: State<'_, SessionState>,
+    db_state: State<'_, DbState>,
+    log_state: State<'_, LogState>,
+    filter: String,
+) -> Result<String, AppError> {
+    auth::require(&session, Permission::ManageSettings)?;
+    let filter = match filter.trim() {
+        "" => logging::DEFAULT_FILTER.to_string(),
+        spec => spec.to_string(),
+    };
+    log_state
+        .set_filter(&filter)
+        .map_err(AppError::validation)?;
+
+    let db = db_state.lock().await;
+    Database::save_setting(&*db, LOG_FILTER_SETTING.to_string(), filter.clone()).await?;
+    info!("Log filter set to {}", filter);
+    Ok(filter)
+}
+
+// ============================================================================
+// DATABASE COMMANDS
+// ============================================================================
+
+/// Report the applied schema version, pending migrations and any drift
+#[tauri::command]
+pub async fn get_schema_version(
+    session: State<'_, SessionState>,
+    db_state: State<'_, DbState>,
+) -> Result<SchemaVersion, AppError> {
+    auth::require_user(&session)?;
+    let db = db_state.lock().await;
+    Database::schema_version(&*db).await
+}
+
+fn app_data_dir(app: &AppHandle) -> Result<PathBuf, AppError> {
+    app.path()
+        .app_data_dir()
+        .map_err(|e| AppError::io("Failed to get app data dir", e))
+}
+
+/// Key the open database uses, for backups and restores
+fn current_key(encryption_state: &EncryptionState) -> Option<DatabaseKey> {
+    encryption_state.lock().unwrap().key.clone()
+}
+
+/// Run SQLite's integrity check on the live database
+#[tauri::command]
+pub async fn check_database_integrity(
+    session: State<'_, SessionState>,
+    db_state: State<'_, DbState>,
+) -> Result<IntegrityReport, AppError> {
+    auth::require_user(&session)?;
+    let db = db_state.lock().await;
+    backup::integrity_check(&*db).await
+}
+
+/// Snapshot the live database, to `destination` or into the backups folder
+#[tauri::command]
+pub async fn backup_database(
+    app: AppHandle,
+    session: State<'_, SessionState>,
+    db_state: State<'_, DbState>,
+    encryption_state: State<'_, EncryptionState>,
+    destination: Option<String>,
+) -> Result<BackupInfo, AppError> {
+    auth::require(&session, Permission::ManageSettings)?;
+    let key = current_key(&encryption_state);
+    let db = db_state.lock().await;
+    match destination.filter(|d| !d.trim().is_empty()) {
+        Some(dest) => backup::snapshot(&*db, Path::new(dest.trim()), key.as_ref()).await,
+        None => {
+            let dir = app_data_dir(&app)?.join(backup::BACKUP_DIR);
+            backup::backup_to_dir(&*db, &dir, backup::BackupKind::Manual, key.as_ref()).await
+        }
+    }
+}
+
+/// List backups in the backups folder, newest first
+#[tauri::command]
+pub async fn list_backups(
+    app: AppHandle,
+    session: State<'_, SessionState>,
+) -> Result<Vec<BackupInfo>, AppError> {
+    auth::require(&session, Permission::ManageSettings)?;
+    Ok(backup::list_backups(
+        &app_data_dir(&app)?.join(backup::BACKUP_DIR),
+    ))
+}
+
+/// Replace all data with a validated backup
+///
+/// The current data is saved as a `pre-restore` backup first.
+#[tauri::command]
+pub async fn restore_database(
+    app: AppHandle,
+    session: State<'_, SessionState>,
+    db_state: State<'_, DbState>,
+    encryption_state: State<'_, EncryptionState>,
+    path: String,
+) -> Result<RestoreReport, AppError> {
+    auth::require(&session, Permission::ManageSettings)?;
+    let data_dir = app_data_dir(&app)?;
+    let key = current_key(&encryption_state);
+    let mut db = db_state.lock().await;
+    info!("Restoring database from {}", path);
+    backup::restore(
+        &mut db,
+        &data_dir.join(DATABASE_FILE),
+        &data_dir.join(backup::BACKUP_DIR),
+        Path::new(&path),
+        key.as_ref(),
+    )
+    .await
+}
+
+#[tauri::command]
+pub async fn get_backup_schedule(
+    session: State<'_, SessionState>,
+    db_state: State<'_, DbState>,
+) -> Result<BackupSchedule, AppError> {
+    auth::require_user(&session)?;
+    let db = db_state.lock().await;
+    backup::load_schedule(&*db).await
+}
+
+#[tauri::command]
+pub async fn save_backup_schedule(
+    session: State<'_, SessionState>,
+    db_state: State<'_, DbState>,
+    schedule: BackupSchedule,
+) -> Result<BackupSchedule, AppError> {
+    auth::require(&session, Permission::ManageSettings)?;
+    let db = db_state.lock().await;
+    backup::save_schedule(&*db, &schedule).await?;
+    info!(
+        "Backup schedule saved: enabled={}, every {}h, keep {}",
+        schedule.enabled, schedule.interval_hours, schedule.keep
+    );
+    Ok(schedule)
+}
+
+// ============================================================================
+// ENCRYPTION COMMANDS
+// ============================================================================
+
+#[tauri::command]
+pub async fn get_encryption_status(
+    app: AppHandle,
+    encryption_state: State<'_, EncryptionState>,
+) -> Result<EncryptionStatus, AppError> {
+    let data_dir = app_data_dir(&app)?;
+    Ok(encryption_state.lock().unwrap().status(&data_dir))
+}
+
+/// Open a passphrase-protected database with the operator's passphrase
+#[tauri::command]
+pub async fn unlock_database(
+    app: AppHandle,
+    db_state: State<'_, DbState>,
+    encryption_state: State<'_, EncryptionState>,
+    log_state: State<'_, LogState>,
+    passphrase: String,
+) -> Result<EncryptionStatus, AppError> {
+    let data_dir = app_data_dir(&app)?;
+    if encryption_state.lock().unwrap().unlocked {
+        return Ok(encryption_state.lock().unwrap().status(&data_dir));
+    }
+
+    let key = DatabaseKey::from_passphrase(&passphrase);
+    let mut db = db_state.lock().await;
+    let opened = Database::open(&data_dir.join(DATABASE_FILE), Some(&key)).await?;
+    apply_saved_log_filter(&opened, &log_state).await;
+    *db = opened;
+
+    let mut encryption = encryption_state.lock().unwrap();
+    encryption.key = Some(key);
+    encryption.unlocked = true;
+    info!("Database unlocked");
+    Ok(encryption.status(&data_dir))
+}
+
+/// Switch the database between plain, keyring-key and passphrase protection
+///
+/// The database is re-written under the new key. Choosing `passphrase`
+/// again changes the passphrase. Existing backups keep the protection they
+/// were written with.
+#[tauri::command]
+pub async fn set_database_encryption(
+    app: AppHandle,
+    session: State<'_, SessionState>,
+    db_state: State<'_, DbState>,
+    encryption_state: State<'_, EncryptionState>,
+    mode: EncryptionMode,
+    passphrase: Option<String>,
+) -> Result<EncryptionStatus, AppError> {
+    auth::require(&session, Permission::ManageSettings)?;
+    let data_dir = app_data_dir(&app)?;
+    let (current_mode, old_key) = {
+        let encryption = encryption_state.lock().unwrap();
+        if !encryption.unlocked {
+            return Err(AppError::validation("Unlock the database first"));
+        }
+        (encryption.mode, encryption.key.clone())
+    };
+    if mode == current_mode && mode != EncryptionMode::Passphrase {
+        return Err(AppError::validation(format!(
+            "Database encryption is already set to {:?}",
+            mode
+        )));
+    }
+
+    let new_key = match mode {
+        EncryptionMode::None => None,
+        EncryptionMode::Passphrase => {
+            let passphrase = passphrase.unwrap_or_default();
+            encryption::validate_passphrase(&passphrase)?;
+            Some(DatabaseKey::from_passphrase(&passphrase))
+        }
+        EncryptionMode::Keyring => Some(encryption::create_keyring_key(&data_dir)?),
+    };
+
+    let mut db = db_state.lock().await;
+    let rekeyed = encryption::rekey(
+        &mut db,
+        &data_dir.join(DATABASE_FILE),
+        old_key.as_ref(),
+        new_key.as_ref(),
+        || encryption::save_config(&data_dir, &DatabaseConfig { encryption: mode }),
+    )
+    .await;
+    if let Err(e) = rekeyed {
+        if mode == EncryptionMode::Keyring {
+            encryption::delete_keyring_key(&data_dir);
+        }
+        return Err(e);
+    }
+
+    if current_mode == EncryptionMode::Keyring {
+        encryption::delete_keyring_key(&data_dir);
+    }
+
+    let mut encryption = encryption_state.lock().unwrap();
+    encryption.mode = mode;
+    encryption.key = new_key;
+    info!(
+        "Database encryption changed from {:?} to {:?}",
+        current_mode, mode
+    );
+    Ok(encryption.status(&data_dir))
+}
+
 // ============================================================================
+// ACCOUNT COMMANDS
+// ============================================================================
+
+/// Inactivity period an administrator saved, or the default
+async fn saved_auto_lock_minutes(db: &DatabaseConnection) -> u32 {
+    match Database::get_setting(db, auth::AUTO_LOCK_SETTING.to_string()).await {
+        Ok(Some(value)) => value.parse().unwrap_or(auth::DEFAULT_AUTO_LOCK_MINUTES),
+        _ => auth::DEFAULT_AUTO_LOCK_MINUTES,
+    }
+}
+
+/// Start a session for `user` with the saved auto-lock period
+async fn sign_in(session: &SessionState, db: &DatabaseConnection, user: User) {
+    let auto_lock = auth::auto_lock_duration(saved_auto_lock_minutes(db).await);
+    let mut session = session.lock().unwrap();
+    session.set_auto_lock(auto_lock);
+    session.sign_in(user);
+}
+
+/// Who is signed in, whether the session is locked, and whether the first
+/// administrator still has to be created
+#[tauri::command]
+pub async fn get_auth_status(
+    session: State<'_, SessionState>,
+    db_state: State<'_, DbState>,
+) -> Result<AuthStatus, AppError> {
+    let (user, locked, auto_lock_minutes) = {
+        let mut session = session.lock().unwrap();
+        let locked = session.is_locked();
+        (session.user.clone(), locked, session.auto_lock_minutes())
+    };
+    let db = db_state.lock().await;
+    Ok(AuthStatus {
+        user,
+        needs_setup: Database::count_users(&*db).await? == 0,
+        locked,
+        auto_lock_minutes,
+    })
+}
+
+#[tauri::command]
+pub async fn login(
+    session: State<'_, SessionState>,
+    db_state: State<'_, DbState>,
+    username: String,
+    password: String,
+) -> Result<User, AppError> {
+    let db = db_state.lock().await;
+    let credentials = Database::get_user_credentials(&*db, username.trim()).await?;
+
+    // Unknown accounts and wrong passwords get the same answer
+    let mut user = match credentials {
+        Some((user, credentials))
+            if auth::verify_password(&password, &credentials.password_hash) =>
+        {
+            user
+        }
+        _ => {
+            warn!("Failed sign-in for {}", username.trim());
+            return Err(AppError::validation("Incorrect username or password"));
+        }
+    };
+    if !user.active {
+        warn!("Sign-in refused for deactivated account {}", user.username);
+        return Err(AppError::forbidden("This account has been deactivated"));
+    }
+
+    user.last_login_at = Some(Database::record_login(&*db, &user.uuid).await?);
+    sign_in(&session, &db, user.clone()).await;
+    info!("{} signed in as {}", user.username, user.role.as_str());
+    Ok(user)
+}
+
+#[tauri::command]
+pub async fn logout(session: State<'_, SessionState>) -> Result<(), AppError> {
+    if let Some(user) = session.lock().unwrap().sign_out() {
+        info!("{} signed out", user.username);
+    }
+    Ok(())
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateUserRequest {
+    pub username: String,
+    pub display_name: String,
+    pub role: Role,
+    pub password: String,
+}
+
+/// Validate a new account and store it with its password hashed
+async fn insert_new_user(
+    db: &DatabaseConnection,
+    request: CreateUserRequest,
+) -> Result<User, AppError> {
+    let username = auth::normalize_username(&request.username)?;
+    let display_name = request.display_name.trim().to_string();
+    if display_name.is_empty() {
+        return Err(AppError::validation("Display name is required"));
+    }
+    auth::validate_password(&request.password)?;
+
+    let mut user = User::new(username, display_name, request.role);
+    let hash = auth::hash_password(&request.password)?;
+    user.id = Some(Database::insert_user(db, &user, hash).await?);
+    Ok(user)
+}
+
+/// Create the first account as administrator and sign it in
+///
+/// Only allowed while no accounts exist.
+#[tauri::command]
+pub async fn create_initial_admin(
+    session: State<'_, SessionState>,
+    db_state: State<'_, DbState>,
+    user_data: CreateUserRequest,
+) -> Result<User, AppError> {
+    let db = db_state.lock().await;
+    if Database::count_users(&*db).await? > 0 {
+        return Err(AppError::conflict(
+            "An administrator account already exists",
+        ));
+    }
+
+    let user = insert_new_user(
+        &*db,
+        CreateUserRequest {
+            role: Role::Admin,
+            ..user_data
+        },
+    )
+    .await?;
+    sign_in(&session, &db, user.clone()).await;
+    info!("Created initial administrator {}", user.username);
+    Ok(user)
+}
+
+#[tauri::command]
+pub async fn list_users(
+    session: State<'_, SessionState>,
+    db_state: State<'_, DbState>,
+) -> Result<Vec<User>, AppError> {
+    auth::require(&session, Permission::ManageUsers)?;
+    let db = db_state.lock().await;
+    Database::get_all_users(&*db).await
+}
+
+#[tauri::command]
+pub async fn create_user(
+    session: State<'_, SessionState>,
+    db_state: State<'_, DbState>,
+    user_data: CreateUserRequest,
+) -> Result<User, AppError> {
+    let admin = auth::require(&session, Permission::ManageUsers)?;
+    let db = db_state.lock().await;
+    let user = insert_new_user(&*db, user_data).await?;
+    info!(
+        "{} created account {} as {}",
+        admin.username,
+        user.username,
+        user.role.as_str()
+    );
+    Ok(user)
+}
 
 #[derive(Debug, Deserialize)]
+pub struct UpdateUserRequest {
+    pub uuid: String,
+    pub display_name: String,
+    pub role: Role,
+    pub active: bool,
+}
+
+/// Change an account's name, role or active flag
+///
+/// The last active administrator cannot be demoted or deactivated, and
+/// administrators cannot deactivate themselves.
+#[tauri::command]
+pub async fn update_user(
+    session: State<'_, SessionState>,
+    db_state: State<'_, DbState>,
+    user_data: UpdateUserRequest,
+) -> Result<User, AppError> {
+    let admin = auth::require(&session, Permission::ManageUsers)?;
+    let db = db_state.lock().await;
+
+    let (mut user, _) = Database::get_user_credentials_by_uuid(&*db, &user_data.uuid)
+        .await?
+        .ok_or_else(|| AppError::not_found("User", &user_data.uuid))?;
+
+    let display_name = user_data.display_name.trim().to_string();
+    if display_name.is_empty() {
+        return Err(AppError::validation("Display name is required"));
+    }
+    if user.uuid == admin.uuid && !user_data.active {
+        return Err(AppError::validation(
+            "You cannot deactivate your own account",
+        ));
+    }
+    let loses_admin = user.role == Role::Admin
+        && user.active
+        && (user_data.role != Role::Admin || !user_data.active);
+    if loses_admin && Database::count_active_admins(&*db).await? <= 1 {
+        return Err(AppError::conflict(
+            "At least one active administrator is required",
+        ));
+    }
+
+    user.display_name = display_name;
+    user.role = user_data.role;
+    user.active = user_data.active;
+    user.touch();
+    Database::update_user(&*db, &user).await?;
+
+    // Keep the signed-in operator's own role current
+    {
+        let mut session = session.lock().unwrap();
+        if session.user.as_ref().map(|u| u.uuid.as_str()) == Some(user.uuid.as_str()) {
+            session.user = Some(user.clone());
+        }
+    }
+
+    info!(
+        "{} updated account {}: role {}, active {}",
+        admin.username,
+        user.username,
+        user.role.as_str(),
+        user.active
+    );
+    Ok(user)
+}
+
+/// Set a new password for another operator's account
+#[tauri::command]
+pub async fn reset_user_password(
+    session: State<'_, SessionState>,
+    db_state: State<'_, DbState>,
+    uuid: String,
+    password: String,
+) -> Result<(), AppError> {
+    let admin = auth::require(&session, Permission::ManageUsers)?;
+    auth::validate_password(&password)?;
+    let hash = auth::hash_password(&password)?;
+
+    let db = db_state.lock().await;
+    Database::set_password_hash(&*db, &uuid, hash).await?;
+    info!("{} reset the password of account {}", admin.username, uuid);
+    Ok(())
+}
+
+/// Change the signed-in operator's own password
+#[tauri::command]
+pub async fn change_password(
+    session: State<'_, SessionState>,
+    db_state: State<'_, DbState>,
+    current_password: String,
+    new_password: String,
+) -> Result<(), AppError> {
+    let user = auth::require_user(&session)?;
+    let db = db_state.lock().await;
+
+    verify_current_password(&db, &user, &current_password).await?;
+    auth::validate_password(&new_password)?;
+
+    Database::set_password_hash(&*db, &user.uuid, auth::hash_password(&new_password)?).await?;
+    info!("{} changed their password", user.username);
+    Ok(())
+}
+
+async fn verify_current_password(
+    db: &DatabaseConnection,
+    user: &User,
+    password: &str,
+) -> Result<(), AppError> {
+    let (_, credentials) = Database::get_user_credentials_by_uuid(db, &user.uuid)
+        .await?
+        .ok_or_else(|| AppError::not_found("User", &user.uuid))?;
+    if !auth::verify_password(password, &credentials.password_hash) {
+        return Err(AppError::validation("Current password is incorrect"));
+    }
+    Ok(())
+}
+
+/// Set the signed-in operator's unlock PIN, or remove it with `None`
+#[tauri::command]
+pub async fn set_pin(
+    session: State<'_, SessionState>,
+    db_state: State<'_, DbState>,
+    current_password: String,
+    pin: Option<String>,
+) -> Result<User, AppError> {
+    let mut user = auth::require_user(&session)?;
+    let db = db_state.lock().await;
+    verify_current_password(&db, &user, &current_password).await?;
+
+    let pin_hash = match pin.as_deref().map(str::trim) {
+        Some(pin) => {
+            auth::validate_pin(pin)?;
+            Some(auth::hash_password(pin)?)
+        }
+        None => None,
+    };
+    user.has_pin = pin_hash.is_some();
+    Database::set_pin_hash(&*db, &user.uuid, pin_hash).await?;
+    session.lock().unwrap().user = Some(user.clone());
+
+    info!(
+        "{} {} their unlock PIN",
+        user.username,
+        if user.has_pin { "set" } else { "removed" }
+    );
+    Ok(user)
+}
+
+// ============================================================================
+// SESSION LOCK COMMANDS
+// ============================================================================
+
+/// Record operator activity so the session does not auto-lock
+///
+/// Returns the current auto-lock period so the UI's timer follows changes.
+#[tauri::command]
+pub async fn touch_session(session: State<'_, SessionState>) -> Result<u32, AppError> {
+    let mut session = session.lock().unwrap();
+    session.touch()?;
+    Ok(session.auto_lock_minutes())
+}
+
+/// Lock the session now, e.g. when the UI's inactivity timer runs out
+#[tauri::command]
+pub async fn lock_session(session: State<'_, SessionState>) -> Result<(), AppError> {
+    session.lock().unwrap().lock();
+    Ok(())
+}
+
+/// Unlock the session with the signed-in operator's PIN or password
+#[tauri::command]
+pub async fn unlock_session(
+    session: State<'_, SessionState>,
+    db_state: State<'_, DbState>,
+    secret: String,
+) -> Result<User, AppError> {
+    let uuid = session
+        .lock()
+        .unwrap()
+        .user
+        .as_ref()
+        .map(|user| user.uuid.clone())
+        .ok_or_else(|| AppError::unauthorized("Sign in to continue"))?;
+
+    let db = db_state.lock().await;
+    let credentials = match Database::get_user_credentials_by_uuid(&*db, &uuid).await? {
+        Some((user, credentials)) if user.active => credentials,
+        _ => {
+            session.lock().unwrap().sign_out();
+            return Err(AppError::unauthorized(
+                "This account is no longer active; sign in again",
+            ));
+        }
+    };
+
+    session.lock().unwrap().unlock(&credentials, &secret)
+}
+
+/// Change how long the app may sit idle before it locks; 0 turns auto-lock off
+#[tauri::command]
+pub async fn set_auto_lock_minutes(
+    session: State<'_, SessionState>,
+    db_state: State<'_, DbState>,
+    minutes: u32,
+) -> Result<u32, AppError> {
+    let admin = auth::require(&session, Permission::ManageSettings)?;
+    if minutes > auth::MAX_AUTO_LOCK_MINUTES {
+        return Err(AppError::validation(format!(
+            "Auto-lock can be at most {} minutes",
+            auth::MAX_AUTO_LOCK_MINUTES
+        )));
+    }
+
+    let db = db_state.lock().await;
+    Database::save_setting(
+        &*db,
+        auth::AUTO_LOCK_SETTING.to_string(),
+        minutes.to_string(),
+    )
+    .await?;
+    session
+        .lock()
+        .unwrap()
+        .set_auto_lock(auth::auto_lock_duration(minutes));
+
+    info!("{} set auto-lock to {} minutes", admin.username, minutes);
+    Ok(minutes)
+}
+
+// ============================================================================
+// PREPROCESSING COMMANDS
+// ============================================================================
+
+#[tauri::command]
+pub async fn get_preprocessing_pipelines(
+    session: State<'_, SessionState>,
+    db_state: State<'_, DbState>,
+) -> Result<Vec<TestTypePipeline>, AppError> {
+    auth::require_user(&session)?;
+    let db = db_state.lock().await;
+    let mut pipelines = Vec::new();
+    for test_type in TestType::all() {
+        let pipeline = Database::get_preprocessing_pipeline(&*db, &test_type).await?;
+        pipelines.push(TestTypePipeline {
+            test_type,
+            pipeline,
+        });
+    }
+    Ok(pipelines)
+}
+
+#[tauri::command]
+pub async fn save_preprocessing_pipeline(
+    session: State<'_, SessionState>,
+    db_state: State<'_, DbState>,
+    test_type: TestType,
+    pipeline: PreprocessingPipeline,
+) -> Result<(), AppError> {
+    auth::require(&session, Permission::ManageSettings)?;
+    pipeline.validate().map_err(AppError::validation)?;
+    let db = db_state.lock().await;
+    Database::save_preprocessing_pipeline(&*db, &test_type, &pipeline).await?;
+    info!(
+        "Saved preprocessing for {}: {}",
+        test_type.as_str(),
+        pipeline.to_spec()
+    );
+    Ok(())
+}
+
+// ============================================================================
+// PATIENT COMMANDS
+// ============================================================================
+
+#[derive(Debug, Clone, Deserialize)]
 pub struct CreatePatientRequest {
     pub first_name: String,
     pub last_name: String,
@@ -48,12 +810,96 @@ pub struct CreatePatientRequest {
     pub notes: Option<String>,
 }
 
+impl CreatePatientRequest {
+    /// Trim every field, drop empty optional ones and check what is left
+    ///
+    /// Returns every problem found rather than the first, so an import can
+    /// report all of a row's errors at once.
+    pub fn normalized(self) -> Result<Self, Vec<String>> {
+        let optional = |value: Option<String>| {
+            value
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty())
+        };
+        let request = Self {
+            first_name: self.first_name.trim().to_string(),
+            last_name: self.last_name.trim().to_string(),
+            date_of_birth: optional(self.date_of_birth),
+            patient_id_number: optional(self.patient_id_number),
+            email: optional(self.email),
+            phone: optional(self.phone),
+            notes: optional(self.notes),
+        };
+
+        let mut errors = Vec::new();
+        for (label, value) in [
+            ("First name", &request.first_name),
+            ("Last name", &request.last_name),
+        ] {
+            if value.is_empty() {
+                errors.push(format!("{} is required", label));
+            } else if value.chars().count() > 100 {
+                errors.push(format!("{} is longer than 100 characters", label));
+            }
+        }
+        if let Some(dob) = &request.date_of_birth {
+            match chrono::NaiveDate::parse_from_str(dob, "%Y-%m-%d") {
+                Ok(date) if date > chrono::Utc::now().date_naive() => {
+                    errors.push("Date of birth is in the future".to_string())
+                }
+                Ok(date) if date < chrono::NaiveDate::from_ymd_opt(1900, 1, 1).unwrap() => {
+                    errors.push("Date of birth is before 1900".to_string())
+                }
+                Ok(_) => {}
+                Err(_) => errors.push(format!("Date of birth '{}' is not YYYY-MM-DD", dob)),
+            }
+        }
+        if let Some(id) = &request.patient_id_number {
+            if id.chars().count() > 64 {
+                errors.push("Patient ID is longer than 64 characters".to_string());
+            }
+        }
+        if let Some(email) = &request.email {
+            let valid = email.split_once('@').is_some_and(|(local, domain)| {
+                !local.is_empty()
+                    && domain.contains('.')
+                    && !domain.starts_with('.')
+                    && !domain.ends_with('.')
+                    && !domain.contains('@')
+            }) && !email.contains(char::is_whitespace);
+            if !valid {
+                errors.push(format!("Email '{}' is not a valid address", email));
+            }
+        }
+        if let Some(phone) = &request.phone {
+            let digits = phone.chars().filter(char::is_ascii_digit).count();
+            let allowed = phone
+                .chars()
+                .all(|c| c.is_ascii_digit() || " +-().".contains(c));
+            if !allowed || digits < 5 {
+                errors.push(format!("Phone '{}' is not a valid number", phone));
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(request)
+        } else {
+            Err(errors)
+        }
+    }
+}
+
 #[tauri::command]
 pub async fn create_patient(
+    session: State<'_, SessionState>,
     db_state: State<'_, DbState>,
     patient_data: CreatePatientRequest,
-) -> Result<Patient, String> {
-    println!("create_patient command called");
+) -> Result<Patient, AppError> {
+    auth::require(&session, Permission::RunTests)?;
+    debug!("create_patient command called");
+    let patient_data = patient_data
+        .normalized()
+        .map_err(|errors| AppError::validation(errors.join("; ")))?;
     let pool = db_state.lock().await;
 
     let mut patient = Patient::new(
@@ -69,31 +915,66 @@ pub async fn create_patient(
     let id = Database::insert_patient(&*pool, &patient).await?;
     patient.id = Some(id);
 
-    println!("Created patient with id: {}", id);
+    info!("Created patient with id: {}", id);
     Ok(patient)
 }
 
 #[tauri::command]
 pub async fn get_patient_by_uuid(
+    session: State<'_, SessionState>,
     db_state: State<'_, DbState>,
     uuid: String,
-) -> Result<Option<Patient>, String> {
+) -> Result<Option<Patient>, AppError> {
+    auth::require_user(&session)?;
     let pool = db_state.lock().await;
     Database::get_patient_by_uuid(&*pool, &uuid).await
 }
 
 #[tauri::command]
-pub async fn get_all_patients(db_state: State<'_, DbState>) -> Result<Vec<Patient>, String> {
-    println!("get_all_patients command called");
+pub async fn get_all_patients(
+    session: State<'_, SessionState>,
+    db_state: State<'_, DbState>,
+) -> Result<Vec<Patient>, AppError> {
+    auth::require_user(&session)?;
+    debug!("get_all_patients command called");
     let pool = db_state.lock().await;
     let result = Database::get_all_patients(&*pool).await;
     match &result {
-        Ok(patients) => println!("Successfully fetched {} patients", patients.len()),
-        Err(e) => println!("Error fetching patients: {}", e),
+        Ok(patients) => debug!("Fetched {} patients", patients.len()),
+        Err(e) => error!("Error fetching patients: {}", e),
     }
     result
 }
 
+/// Preview or import patients from a CSV file
+///
+/// Without a mapping, or with `dry_run`, nothing is written: the report lists
+/// each row's errors and duplicates. A bare file name is read from the user's
+/// documents folder.
+#[tauri::command]
+pub async fn import_patients(
+    app: AppHandle,
+    session: State<'_, SessionState>,
+    db_state: State<'_, DbState>,
+    path: String,
+    mapping: Option<PatientColumnMapping>,
+    dry_run: bool,
+) -> Result<ImportReport, AppError> {
+    let user = auth::require(&session, Permission::ManageSettings)?;
+    let path = input_path(&app, &path)?;
+    let db = db_state.lock().await;
+    let report = patient_import::import_patients(&*db, &path, mapping, dry_run).await?;
+    if !report.dry_run {
+        info!(
+            "{} imported {} patients from {}",
+            user.username,
+            report.imported,
+            path.display()
+        );
+    }
+    Ok(report)
+}
+
 // ============================================================================
 // TEST COMMANDS
 // ============================================================================
@@ -108,10 +989,14 @@ pub struct CreateTestRequest {
 
 #[tauri::command]
 pub async fn create_test(
+    app: AppHandle,
+    session: State<'_, SessionState>,
     db_state: State<'_, DbState>,
+    registry: State<'_, DeviceRegistry>,
     test_data: CreateTestRequest,
-) -> Result<Test, String> {
-    println!(
+) -> Result<Test, AppError> {
+    let operator = auth::require(&session, Permission::RunTests)?;
+    debug!(
         "create_test command called for patient: {}",
         test_data.patient_uuid
     );
@@ -120,9 +1005,18 @@ pub async fn create_test(
     // Get patient by UUID
     let patient = Database::get_patient_by_uuid(&*pool, &test_data.patient_uuid)
         .await?
-        .ok_or_else(|| format!("Patient not found: {}", test_data.patient_uuid))?;
+        .ok_or_else(|| AppError::not_found("Patient", &test_data.patient_uuid))?;
 
-    let patient_id = patient.id.ok_or("Patient has no ID")?;
+    let patient_id = patient
+        .id
+        .ok_or_else(|| AppError::internal("Patient has no ID"))?;
+
+    if let Some(device_id) = &test_data.device_id {
+        Database::get_device(&*pool, device_id)
+            .await?
+            .ok_or_else(|| AppError::not_found("Device", device_id))?;
+        qc::check_device(&*pool, device_id, &test_data.test_type).await?;
+    }
 
     let mut test = Test::new_pending(
         patient_id,
@@ -130,47 +1024,92 @@ pub async fn create_test(
         test_data.device_id,
         test_data.firmware_version,
     );
+    test.operated_by = Some(operator.username);
+
+    // Route the device's next dataset to this test; a busy or stopped device
+    // refuses it before anything is stored
+    if let Some(device_id) = &test.device_id {
+        registry.assign_test(device_id, &test.uuid)?;
+    }
 
-    let id = Database::insert_test(&*pool, &test).await?;
+    let id = match Database::insert_test(&*pool, &mut test).await {
+        Ok(id) => id,
+        Err(e) => {
+            registry.release_test(&test.uuid);
+            return Err(e);
+        }
+    };
     test.id = Some(id);
 
-    println!("Created test with id: {} and uuid: {}", id, test.uuid);
+    label_printer::print_new_test(&app, &*pool, &test.uuid).await;
+
+    info!(
+        "Created test with id: {} and uuid: {} for {}",
+        id,
+        test.uuid,
+        test.operated_by.as_deref().unwrap_or_default()
+    );
     Ok(test)
 }
 
 #[tauri::command]
 pub async fn get_test_by_uuid(
+    session: State<'_, SessionState>,
     db_state: State<'_, DbState>,
     uuid: String,
-) -> Result<Option<Test>, String> {
+) -> Result<Option<Test>, AppError> {
+    auth::require_user(&session)?;
     let pool = db_state.lock().await;
     Database::get_test_by_uuid(&*pool, &uuid).await
 }
 
 #[tauri::command]
-pub async fn get_all_tests(db_state: State<'_, DbState>) -> Result<Vec<TestWithPatient>, String> {
-    println!("get_all_tests command called");
+pub async fn get_all_tests(
+    session: State<'_, SessionState>,
+    db_state: State<'_, DbState>,
+) -> Result<Vec<TestWithPatient>, AppError> {
+    auth::require_user(&session)?;
+    debug!("get_all_tests command called");
     let pool = db_state.lock().await;
     let result = Database::get_all_tests_with_patients(&*pool).await;
     match &result {
-        Ok(tests) => println!("Successfully fetched {} tests", tests.len()),
-        Err(e) => println!("Error fetching tests: {}", e),
+        Ok(tests) => debug!("Fetched {} tests", tests.len()),
+        Err(e) => error!("Error fetching tests: {}", e),
     }
     result
 }
 
 #[tauri::command]
 pub async fn update_test_status(
+    session: State<'_, SessionState>,
     db_state: State<'_, DbState>,
+    registry: State<'_, DeviceRegistry>,
     test_uuid: String,
     status: TestStatus,
-) -> Result<(), String> {
-    println!("update_test_status called: {} -> {:?}", test_uuid, status);
+) -> Result<(), AppError> {
+    auth::require(&session, Permission::RunTests)?;
+    // Results only reach these states through completion and review
+    if matches!(
+        status,
+        TestStatus::AwaitingReview | TestStatus::Completed | TestStatus::Rejected
+    ) {
+        return Err(AppError::validation(format!(
+            "Tests cannot be set to {} directly",
+            status.as_str()
+        )));
+    }
+    info!("Test {} status -> {}", test_uuid, status.as_str());
     let pool = db_state.lock().await;
 
     let mut test = Database::get_test_by_uuid(&*pool, &test_uuid)
         .await?
-        .ok_or_else(|| format!("Test not found: {}", test_uuid))?;
+        .ok_or_else(|| AppError::not_found("Test", &test_uuid))?;
+    if test.has_result() {
+        return Err(AppError::conflict(format!(
+            "Test is {} and its result is recorded",
+            test.status
+        )));
+    }
 
     match status {
         TestStatus::InProgress => test.mark_in_progress(),
@@ -181,6 +1120,11 @@ pub async fn update_test_status(
         }
     }
 
+    // A finished test no longer owns its device
+    if matches!(status, TestStatus::Error | TestStatus::Cancelled) {
+        registry.release_test(&test.uuid);
+    }
+
     Database::update_test(&*pool, &test).await
 }
 
@@ -192,23 +1136,1003 @@ pub struct CompleteTestRequest {
     pub raw_response: String,
 }
 
+/// Setting that holds completed results for review instead of releasing them
+pub const REVIEW_SETTING: &str = "require_result_review";
+
+async fn review_required(db: &DatabaseConnection) -> bool {
+    matches!(
+        Database::get_setting(db, REVIEW_SETTING.to_string()).await,
+        Ok(Some(value)) if value == "true"
+    )
+}
+
+/// Store the result; it is released at once unless review is required
 #[tauri::command]
 pub async fn complete_test(
+    session: State<'_, SessionState>,
     db_state: State<'_, DbState>,
+    registry: State<'_, DeviceRegistry>,
     data: CompleteTestRequest,
-) -> Result<(), String> {
-    println!("complete_test called for: {}", data.test_uuid);
+) -> Result<Test, AppError> {
+    let operator = auth::require(&session, Permission::RunTests)?;
+    info!("Completing test {}", data.test_uuid);
     let pool = db_state.lock().await;
 
     let mut test = Database::get_test_by_uuid(&*pool, &data.test_uuid)
         .await?
-        .ok_or_else(|| format!("Test not found: {}", data.test_uuid))?;
+        .ok_or_else(|| AppError::not_found("Test", &data.test_uuid))?;
+    // A stored result is only changed through review
+    if !matches!(
+        TestStatus::from_str(&test.status),
+        Ok(TestStatus::Pending | TestStatus::InProgress)
+    ) {
+        return Err(AppError::conflict(format!(
+            "Test is {}, it can no longer be completed",
+            test.status
+        )));
+    }
 
     test.mark_completed(
         data.detection_result.as_str().to_string(),
         data.confidence,
         data.raw_response,
     );
+    if review_required(&*pool).await {
+        test.mark_awaiting_review(operator.username);
+    } else {
+        test.mark_released(operator.username);
+    }
 
-    Database::update_test(&*pool, &test).await
+    registry.release_test(&test.uuid);
+
+    Database::update_test(&*pool, &test).await?;
+    info!("Test {} is {}", test.uuid, test.status);
+    if test.is_released() {
+        outbox::queue_result(&*pool, &test.uuid).await;
+    }
+    Ok(test)
+}
+
+/// Load a test that is waiting for a reviewer's decision
+async fn test_awaiting_review(db: &DatabaseConnection, test_uuid: &str) -> Result<Test, AppError> {
+    let test = Database::get_test_by_uuid(db, test_uuid)
+        .await?
+        .ok_or_else(|| AppError::not_found("Test", test_uuid))?;
+
+    if test.status == TestStatus::AwaitingReview.as_str() {
+        return Ok(test);
+    }
+    match &test.released_by {
+        Some(released_by) => Err(AppError::conflict(format!(
+            "Test was already released by {}",
+            released_by
+        ))),
+        None => Err(AppError::validation(format!(
+            "Test is {}, not awaiting review",
+            test.status
+        ))),
+    }
+}
+
+fn require_reason(reason: &str) -> Result<String, AppError> {
+    let reason = reason.trim();
+    if reason.is_empty() {
+        return Err(AppError::validation("A reason is required"));
+    }
+    Ok(reason.to_string())
+}
+
+/// Completed results queued for review, oldest first
+#[tauri::command]
+pub async fn get_tests_awaiting_review(
+    session: State<'_, SessionState>,
+    db_state: State<'_, DbState>,
+) -> Result<Vec<TestWithPatient>, AppError> {
+    auth::require(&session, Permission::ReviewResults)?;
+    let pool = db_state.lock().await;
+    Database::get_tests_with_patients_by_status(&*pool, TestStatus::AwaitingReview).await
+}
+
+/// Accept a result as measured and release it
+#[tauri::command]
+pub async fn release_test(
+    session: State<'_, SessionState>,
+    db_state: State<'_, DbState>,
+    test_uuid: String,
+) -> Result<Test, AppError> {
+    let reviewer = auth::require(&session, Permission::ReviewResults)?;
+    let pool = db_state.lock().await;
+
+    let mut test = test_awaiting_review(&*pool, &test_uuid).await?;
+    test.mark_accepted(reviewer.username);
+    Database::update_test(&*pool, &test).await?;
+    outbox::queue_result(&*pool, &test.uuid).await;
+
+    info!(
+        "Test {} released by {}",
+        test.uuid,
+        test.released_by.as_deref().unwrap_or_default()
+    );
+    Ok(test)
+}
+
+/// Release a result with a reviewer's correction; the measured result is kept in the review log
+#[tauri::command]
+pub async fn override_test_result(
+    session: State<'_, SessionState>,
+    db_state: State<'_, DbState>,
+    test_uuid: String,
+    detection_result: DetectionResult,
+    reason: String,
+) -> Result<Test, AppError> {
+    let reviewer = auth::require(&session, Permission::ReviewResults)?;
+    let reason = require_reason(&reason)?;
+    let pool = db_state.lock().await;
+
+    let mut test = test_awaiting_review(&*pool, &test_uuid).await?;
+    if test.detection_result.as_deref() == Some(detection_result.as_str()) {
+        return Err(AppError::validation(
+            "The override matches the measured result; accept it instead",
+        ));
+    }
+    test.mark_overridden(
+        reviewer.username,
+        detection_result.as_str().to_string(),
+        reason,
+    );
+    Database::update_test(&*pool, &test).await?;
+    outbox::queue_result(&*pool, &test.uuid).await;
+
+    warn!(
+        "Test {} result overridden to {} by {}",
+        test.uuid,
+        detection_result.as_str(),
+        test.released_by.as_deref().unwrap_or_default()
+    );
+    Ok(test)
+}
+
+/// Reject a result and order a retest for the same patient; returns the retest
+#[tauri::command]
+pub async fn reject_test(
+    session: State<'_, SessionState>,
+    db_state: State<'_, DbState>,
+    test_uuid: String,
+    reason: String,
+) -> Result<Test, AppError> {
+    let reviewer = auth::require(&session, Permission::ReviewResults)?;
+    let reason = require_reason(&reason)?;
+    let pool = db_state.lock().await;
+
+    let mut test = test_awaiting_review(&*pool, &test_uuid).await?;
+    let mut retest = test.new_retest();
+    retest.id = Some(Database::insert_test(&*pool, &mut retest).await?);
+
+    test.mark_rejected(reviewer.username.clone(), reason, retest.uuid.clone());
+    Database::update_test(&*pool, &test).await?;
+
+    warn!(
+        "Test {} rejected by {}; retest {} ordered",
+        test.uuid, reviewer.username, retest.uuid
+    );
+    Ok(retest)
+}
+
+// ============================================================================
+// WORKLIST COMMANDS
+// ============================================================================
+
+/// Pending tests waiting to be run, oldest first
+///
+/// Orders placed here, imported from a file or downloaded from the LIS, and
+/// retests ordered by a reviewer all land on the same worklist.
+#[tauri::command]
+pub async fn get_worklist(
+    session: State<'_, SessionState>,
+    db_state: State<'_, DbState>,
+) -> Result<Vec<TestWithPatient>, AppError> {
+    auth::require_user(&session)?;
+    let db = db_state.lock().await;
+    Database::get_tests_with_patients_by_status(&*db, TestStatus::Pending).await
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateOrderRequest {
+    pub patient_uuid: String,
+    pub test_type: String,
+    pub specimen_id: Option<String>,
+}
+
+/// Put a test on the worklist for a registered patient
+#[tauri::command]
+pub async fn create_order(
+    app: AppHandle,
+    session: State<'_, SessionState>,
+    db_state: State<'_, DbState>,
+    order: CreateOrderRequest,
+) -> Result<Test, AppError> {
+    let user = auth::require(&session, Permission::RunTests)?;
+    let test_type = worklist::parse_test_type(&order.test_type).map_err(AppError::validation)?;
+    let specimen_id =
+        worklist::normalize_specimen_id(order.specimen_id).map_err(AppError::validation)?;
+    let db = db_state.lock().await;
+
+    let patient = Database::get_patient_by_uuid(&*db, &order.patient_uuid)
+        .await?
+        .ok_or_else(|| AppError::not_found("Patient", &order.patient_uuid))?;
+    let patient_id = patient
+        .id
+        .ok_or_else(|| AppError::internal("Patient has no ID"))?;
+
+    if let Some(duplicate) =
+        worklist::find_duplicate_order(&*db, patient_id, &test_type, specimen_id.as_deref()).await?
+    {
+        return Err(AppError::conflict(format!(
+            "The order repeats the {}",
+            duplicate
+        )));
+    }
+
+    let mut test = Test::new_pending(patient_id, test_type.as_str().to_string(), None, None);
+    test.specimen_id = specimen_id;
+    test.id = Some(Database::insert_test(&*db, &mut test).await?);
+
+    // Pre-registered tubes need their label before they reach a device
+    label_printer::print_new_test(&app, &*db, &test.uuid).await;
+
+    info!(
+        "{} ordered {} for patient {} as test {}",
+        user.username, test.test_type, patient.uuid, test.uuid
+    );
+    Ok(test)
+}
+
+/// Add the orders in a CSV file to the worklist
+///
+/// With `dry_run` nothing is written and the report lists each row's errors
+/// and duplicates. A bare file name is read from the user's documents folder.
+#[tauri::command]
+pub async fn import_orders(
+    app: AppHandle,
+    session: State<'_, SessionState>,
+    db_state: State<'_, DbState>,
+    path: String,
+    dry_run: bool,
+) -> Result<OrderImportReport, AppError> {
+    let user = auth::require(&session, Permission::RunTests)?;
+    let path = input_path(&app, &path)?;
+    let db = db_state.lock().await;
+    let report = worklist::import_orders(&*db, &path, dry_run).await?;
+    if !report.dry_run {
+        info!(
+            "{} imported {} orders from {}",
+            user.username,
+            report.imported,
+            path.display()
+        );
+    }
+    for test_uuid in &report.created_tests {
+        label_printer::print_new_test(&app, &*db, test_uuid).await;
+    }
+    Ok(report)
+}
+
+/// Take an order off the worklist and run it on a device
+///
+/// The test stays pending until the device reports, like one created at the
+/// bench; an order that is never run can be picked again.
+#[tauri::command]
+pub async fn start_order(
+    session: State<'_, SessionState>,
+    db_state: State<'_, DbState>,
+    registry: State<'_, DeviceRegistry>,
+    test_uuid: String,
+    device_id: String,
+) -> Result<Test, AppError> {
+    let operator = auth::require(&session, Permission::RunTests)?;
+    let db = db_state.lock().await;
+
+    let mut test = Database::get_test_by_uuid(&*db, &test_uuid)
+        .await?
+        .ok_or_else(|| AppError::not_found("Test", &test_uuid))?;
+    if test.status != TestStatus::Pending.as_str() {
+        return Err(AppError::conflict(format!(
+            "Test is {}, not waiting on the worklist",
+            test.status
+        )));
+    }
+    let device = Database::get_device(&*db, &device_id)
+        .await?
+        .ok_or_else(|| AppError::not_found("Device", &device_id))?;
+    if !device.enabled {
+        return Err(AppError::validation(format!(
+            "Device {} is disabled",
+            device.label
+        )));
+    }
+    qc::check_device(&*db, &device_id, &test.test_type).await?;
+
+    // Picking the order again moves it off the device it was on, unless the
+    // new device is busy with another test
+    let previous = registry.release_test(&test.uuid);
+    if let Err(e) = registry.assign_test(&device_id, &test.uuid) {
+        if let Some(previous) = previous {
+            let _ = registry.assign_test(&previous.device_id, &test.uuid);
+        }
+        return Err(e);
+    }
+
+    test.device_id = Some(device_id);
+    test.operated_by = Some(operator.username);
+    test.touch();
+    Database::update_test(&*db, &test).await?;
+
+    info!(
+        "Order {} started on {} by {}",
+        test.uuid,
+        device.label,
+        test.operated_by.as_deref().unwrap_or_default()
+    );
+    Ok(test)
+}
+
+// ============================================================================
+// QUALITY CONTROL COMMANDS
+// ============================================================================
+
+#[tauri::command]
+pub async fn list_control_materials(
+    session: State<'_, SessionState>,
+    db_state: State<'_, DbState>,
+) -> Result<Vec<ControlMaterial>, AppError> {
+    auth::require_user(&session)?;
+    let db = db_state.lock().await;
+    Database::get_all_control_materials(&*db).await
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SaveControlMaterialRequest {
+    /// Material to change; a new one is added without it
+    pub id: Option<i64>,
+    pub name: String,
+    pub level: ControlLevel,
+    pub test_type: String,
+    pub lot_number: String,
+    pub expires_on: String,
+    pub expected_min: f64,
+    pub expected_max: f64,
+    pub active: bool,
+}
+
+/// Add a lot of control material or change one
+///
+/// A new lot number starts a new Levey-Jennings chart; runs of the old lot
+/// are kept with it.
+#[tauri::command]
+pub async fn save_control_material(
+    session: State<'_, SessionState>,
+    db_state: State<'_, DbState>,
+    material: SaveControlMaterialRequest,
+) -> Result<ControlMaterial, AppError> {
+    let user = auth::require(&session, Permission::ManageSettings)?;
+    let now = chrono::Utc::now().to_rfc3339();
+    let db = db_state.lock().await;
+
+    let created_at = match material.id {
+        Some(id) => {
+            Database::get_control_material(&*db, id)
+                .await?
+                .ok_or_else(|| AppError::not_found("Control material", &id.to_string()))?
+                .created_at
+        }
+        None => now.clone(),
+    };
+    let mut saved = ControlMaterial {
+        id: material.id.unwrap_or_default(),
+        name: material.name,
+        level: material.level,
+        test_type: material.test_type,
+        lot_number: material.lot_number,
+        expires_on: material.expires_on,
+        expected_min: material.expected_min,
+        expected_max: material.expected_max,
+        active: material.active,
+        created_at,
+        updated_at: now,
+    }
+    .normalized()?;
+
+    if material.id.is_some() {
+        Database::update_control_material(&*db, &saved).await?;
+    } else {
+        saved.id = Database::insert_control_material(&*db, &saved).await?;
+    }
+    info!(
+        "{} saved control material {} ({} lot {}, {:.3}-{:.3})",
+        user.username,
+        saved.name,
+        saved.level.as_str(),
+        saved.lot_number,
+        saved.expected_min,
+        saved.expected_max
+    );
+    Ok(saved)
+}
+
+#[tauri::command]
+pub async fn get_qc_settings(
+    session: State<'_, SessionState>,
+    db_state: State<'_, DbState>,
+) -> Result<QcSettings, AppError> {
+    auth::require_user(&session)?;
+    let db = db_state.lock().await;
+    qc::load_settings(&*db).await
+}
+
+#[tauri::command]
+pub async fn save_qc_settings(
+    session: State<'_, SessionState>,
+    db_state: State<'_, DbState>,
+    settings: QcSettings,
+) -> Result<QcSettings, AppError> {
+    auth::require(&session, Permission::ManageSettings)?;
+    let settings = settings.normalized()?;
+    let db = db_state.lock().await;
+    qc::save_settings(&*db, &settings).await?;
+    info!(
+        "QC settings saved: required={}, valid_hours={}",
+        settings.required, settings.valid_hours
+    );
+    Ok(settings)
+}
+
+/// Route a control to a device; the next dataset the device produces is the
+/// QC result
+#[tauri::command]
+pub async fn start_qc_run(
+    session: State<'_, SessionState>,
+    db_state: State<'_, DbState>,
+    registry: State<'_, DeviceRegistry>,
+    device_id: String,
+    material_id: i64,
+) -> Result<QcRun, AppError> {
+    let operator = auth::require(&session, Permission::RunTests)?;
+    let db = db_state.lock().await;
+
+    let material = Database::get_control_material(&*db, material_id)
+        .await?
+        .ok_or_else(|| AppError::not_found("Control material", &material_id.to_string()))?;
+    if !material.active {
+        return Err(AppError::validation(format!(
+            "{} is no longer in use",
+            material.name
+        )));
+    }
+    if material.is_expired(chrono::Utc::now().date_naive()) {
+        return Err(AppError::validation(format!(
+            "Lot {} of {} expired on {}",
+            material.lot_number, material.name, material.expires_on
+        )));
+    }
+    let device = Database::get_device(&*db, &device_id)
+        .await?
+        .ok_or_else(|| AppError::not_found("Device", &device_id))?;
+    if !device.enabled {
+        return Err(AppError::validation(format!(
+            "Device {} is disabled",
+            device.label
+        )));
+    }
+
+    // The control is read like a patient test: the device's next dataset is its value
+    let mut run = QcRun::new_pending(&material, device_id, operator.username);
+    registry.assign_test(&run.device_id, &run.uuid)?;
+    run.id = match Database::insert_qc_run(&*db, &run).await {
+        Ok(id) => id,
+        Err(e) => {
+            registry.release_test(&run.uuid);
+            return Err(e);
+        }
+    };
+
+    info!(
+        "QC run {} of {} lot {} started on {}",
+        run.uuid, material.name, run.lot_number, device.label
+    );
+    Ok(run)
+}
+
+/// A QC run with its outcome once the device's reading has been checked
+#[tauri::command]
+pub async fn get_qc_run(
+    session: State<'_, SessionState>,
+    db_state: State<'_, DbState>,
+    run_uuid: String,
+) -> Result<QcRun, AppError> {
+    auth::require_user(&session)?;
+    let db = db_state.lock().await;
+    Database::get_qc_run_by_uuid(&*db, &run_uuid)
+        .await?
+        .ok_or_else(|| AppError::not_found("QC run", &run_uuid))
+}
+
+/// Give up on a QC run whose device never reported
+#[tauri::command]
+pub async fn cancel_qc_run(
+    session: State<'_, SessionState>,
+    db_state: State<'_, DbState>,
+    registry: State<'_, DeviceRegistry>,
+    run_uuid: String,
+) -> Result<(), AppError> {
+    auth::require(&session, Permission::RunTests)?;
+    let db = db_state.lock().await;
+
+    let mut run = Database::get_qc_run_by_uuid(&*db, &run_uuid)
+        .await?
+        .ok_or_else(|| AppError::not_found("QC run", &run_uuid))?;
+    if !run.is_pending() {
+        return Err(AppError::conflict(format!(
+            "QC run is already {}",
+            run.status
+        )));
+    }
+    run.status = qc::QcStatus::Cancelled.as_str().to_string();
+    registry.release_test(&run.uuid);
+    Database::update_qc_run(&*db, &run).await?;
+    info!("QC run {} cancelled", run.uuid);
+    Ok(())
+}
+
+/// QC standing of every enabled device for each test type that has control
+/// material, or for every test type when QC is required
+#[tauri::command]
+pub async fn get_qc_status(
+    session: State<'_, SessionState>,
+    db_state: State<'_, DbState>,
+) -> Result<Vec<DeviceQc>, AppError> {
+    auth::require_user(&session)?;
+    let db = db_state.lock().await;
+    let settings = qc::load_settings(&*db).await?;
+
+    let mut test_types: Vec<String> = if settings.required {
+        TestType::all()
+            .iter()
+            .map(|t| t.as_str().to_string())
+            .collect()
+    } else {
+        Database::get_all_control_materials(&*db)
+            .await?
+            .into_iter()
+            .map(|m| m.test_type)
+            .collect()
+    };
+    test_types.sort();
+    test_types.dedup();
+
+    let now = chrono::Utc::now();
+    let mut statuses = Vec::new();
+    for device in Database::get_all_devices(&*db).await? {
+        if !device.enabled {
+            continue;
+        }
+        for test_type in &test_types {
+            statuses
+                .push(qc::device_status(&*db, &settings, &device.device_id, test_type, now).await?);
+        }
+    }
+    Ok(statuses)
+}
+
+/// Runs of a control material's current lot on a device, with the chart limits
+#[tauri::command]
+pub async fn get_levey_jennings(
+    session: State<'_, SessionState>,
+    db_state: State<'_, DbState>,
+    material_id: i64,
+    device_id: String,
+) -> Result<LeveyJennings, AppError> {
+    auth::require_user(&session)?;
+    let db = db_state.lock().await;
+    qc::levey_jennings(&*db, material_id, &device_id).await
+}
+
+// ============================================================================
+// REPORT COMMANDS
+// ============================================================================
+
+#[tauri::command]
+pub async fn get_report_settings(
+    session: State<'_, SessionState>,
+    db_state: State<'_, DbState>,
+) -> Result<ReportSettings, AppError> {
+    auth::require_user(&session)?;
+    let db = db_state.lock().await;
+    report::load_settings(&*db).await
+}
+
+#[tauri::command]
+pub async fn save_report_settings(
+    session: State<'_, SessionState>,
+    db_state: State<'_, DbState>,
+    settings: ReportSettings,
+) -> Result<ReportSettings, AppError> {
+    auth::require(&session, Permission::ManageSettings)?;
+    let settings = settings.normalized()?;
+    let db = db_state.lock().await;
+    report::save_settings(&*db, &settings).await?;
+    info!("Report header saved for {}", settings.clinic_name);
+    Ok(settings)
+}
+
+/// Render a released test as a PDF and save it; returns the full path written
+///
+/// A bare file name is saved in the user's documents folder.
+#[tauri::command]
+pub async fn generate_test_report(
+    app: AppHandle,
+    session: State<'_, SessionState>,
+    db_state: State<'_, DbState>,
+    test_uuid: String,
+    path: String,
+) -> Result<String, AppError> {
+    let user = auth::require_user(&session)?;
+    let path = output_path(&app, &path, "pdf")?;
+    let db = db_state.lock().await;
+
+    let data = Database::get_test_with_patient(&*db, &test_uuid)
+        .await?
+        .ok_or_else(|| AppError::not_found("Test", &test_uuid))?;
+    if !data.test.is_released() {
+        return Err(AppError::validation(
+            "Only released results can be reported",
+        ));
+    }
+    let settings = report::load_settings(&*db).await?;
+    drop(db);
+
+    let bytes = report::render(&data, &settings, &user)?;
+    report::save(&bytes, &path)?;
+    info!(
+        "Report for test {} saved to {} by {}",
+        test_uuid,
+        path.display(),
+        user.username
+    );
+    Ok(path.display().to_string())
+}
+
+/// Resolve a file chosen by the user, adding `extension` if the name lacks it
+fn output_path(app: &AppHandle, path: &str, extension: &str) -> Result<PathBuf, AppError> {
+    let path = path.trim();
+    if path.is_empty() {
+        return Err(AppError::validation("Choose where to save the file"));
+    }
+    let mut path = in_documents(app, path)?;
+    if !path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case(extension))
+    {
+        path.set_extension(extension);
+    }
+    Ok(path)
+}
+
+/// Resolve a file the user chose to read; its name is taken as given
+fn input_path(app: &AppHandle, path: &str) -> Result<PathBuf, AppError> {
+    let path = path.trim();
+    if path.is_empty() {
+        return Err(AppError::validation("Choose the file to import"));
+    }
+    in_documents(app, path)
+}
+
+/// Bare file names and relative paths live in the user's documents folder
+fn in_documents(app: &AppHandle, path: &str) -> Result<PathBuf, AppError> {
+    let path = PathBuf::from(path);
+    if path.is_absolute() {
+        return Ok(path);
+    }
+    let documents = app
+        .path()
+        .document_dir()
+        .map_err(|e| AppError::io("Failed to get documents folder", e))?;
+    Ok(documents.join(path))
+}
+
+// ============================================================================
+// LABEL COMMANDS
+// ============================================================================
+
+/// Find the test or patient a scanned barcode belongs to
+#[tauri::command]
+pub async fn lookup_barcode(
+    session: State<'_, SessionState>,
+    db_state: State<'_, DbState>,
+    code: String,
+) -> Result<ScanMatch, AppError> {
+    auth::require_user(&session)?;
+    let db = db_state.lock().await;
+    labels::lookup(&*db, &code).await
+}
+
+/// Print the labels of a test, or save them as a PDF when no label printer is
+/// set up; returns where they went
+#[tauri::command]
+pub async fn print_test_label(
+    app: AppHandle,
+    session: State<'_, SessionState>,
+    db_state: State<'_, DbState>,
+    test_uuid: String,
+) -> Result<String, AppError> {
+    let user = auth::require_user(&session)?;
+    let db = db_state.lock().await;
+    let data = Database::get_test_with_patient(&*db, &test_uuid)
+        .await?
+        .ok_or_else(|| AppError::not_found("Test", &test_uuid))?;
+    let settings = label_printer::load_settings(&*db).await?;
+    drop(db);
+
+    let destination = if settings.enabled {
+        label_printer::print(&app, &settings, &data).await?
+    } else {
+        let path = output_path(
+            &app,
+            &format!("labels/{}", labels::barcode_value(&data)),
+            "pdf",
+        )?;
+        let bytes = labels::render(&data, settings.copies)?;
+        report::save(&bytes, &path)?;
+        path.display().to_string()
+    };
+    info!(
+        "{} label(s) for test {} sent to {} by {}",
+        settings.copies, test_uuid, destination, user.username
+    );
+    Ok(destination)
+}
+
+#[tauri::command]
+pub async fn get_label_printer_settings(
+    session: State<'_, SessionState>,
+    db_state: State<'_, DbState>,
+) -> Result<LabelPrinterSettings, AppError> {
+    auth::require_user(&session)?;
+    let db = db_state.lock().await;
+    label_printer::load_settings(&*db).await
+}
+
+#[tauri::command]
+pub async fn save_label_printer_settings(
+    session: State<'_, SessionState>,
+    db_state: State<'_, DbState>,
+    settings: LabelPrinterSettings,
+) -> Result<LabelPrinterSettings, AppError> {
+    auth::require(&session, Permission::ManageSettings)?;
+    let settings = settings.normalized()?;
+    let db = db_state.lock().await;
+    label_printer::save_settings(&*db, &settings).await?;
+    info!(
+        "Label printer saved: enabled={}, language={:?}, transport={:?}",
+        settings.enabled, settings.language, settings.transport
+    );
+    Ok(settings)
+}
+
+/// Print a label with made-up patient details to check the printer settings
+/// before they are saved; returns where it went
+#[tauri::command]
+pub async fn print_sample_label(
+    app: AppHandle,
+    session: State<'_, SessionState>,
+    settings: LabelPrinterSettings,
+) -> Result<String, AppError> {
+    auth::require(&session, Permission::ManageSettings)?;
+    let settings = LabelPrinterSettings {
+        enabled: true,
+        copies: 1,
+        ..settings
+    }
+    .normalized()?;
+    let patient = Patient::new(
+        "Sample".to_string(),
+        "Patient".to_string(),
+        Some("1980-01-31".to_string()),
+        None,
+        None,
+        None,
+        None,
+    );
+    let mut test = Test::new_pending(0, TestType::Covid19.as_str().to_string(), None, None);
+    test.accession_number = Some("0000000000".to_string());
+    label_printer::print(&app, &settings, &TestWithPatient { test, patient }).await
+}
+
+// ============================================================================
+// EXPORT COMMANDS
+// ============================================================================
+
+/// Export released tests matching the history filter to CSV or NDJSON
+///
+/// A bare file name is saved in the user's documents folder.
+#[tauri::command]
+pub async fn export_tests(
+    app: AppHandle,
+    session: State<'_, SessionState>,
+    db_state: State<'_, DbState>,
+    options: ExportOptions,
+    path: String,
+) -> Result<ExportSummary, AppError> {
+    let user = auth::require(&session, Permission::ReviewResults)?;
+    let path = output_path(&app, &path, options.format.extension())?;
+    let db = db_state.lock().await;
+    let summary = export::export_tests(&*db, &options, &path).await?;
+    info!(
+        "{} exported {} tests (pseudonymised: {}, raw signal: {})",
+        user.username, summary.exported, options.pseudonymize, options.include_raw_signal
+    );
+    Ok(summary)
+}
+
+/// Write a released test as a FHIR R4 transaction Bundle
+///
+/// A bare file name is saved in the user's documents folder.
+#[tauri::command]
+pub async fn export_fhir_bundle(
+    app: AppHandle,
+    session: State<'_, SessionState>,
+    db_state: State<'_, DbState>,
+    test_uuid: String,
+    path: String,
+) -> Result<String, AppError> {
+    let user = auth::require_user(&session)?;
+    let path = output_path(&app, &path, "json")?;
+    let db = db_state.lock().await;
+
+    let data = Database::get_test_with_patient(&*db, &test_uuid)
+        .await?
+        .ok_or_else(|| AppError::not_found("Test", &test_uuid))?;
+    if !data.test.is_released() {
+        return Err(AppError::validation(
+            "Only released results can be exported",
+        ));
+    }
+    let settings = fhir::load_settings(&*db).await?;
+    drop(db);
+
+    let bundle_id = uuid::Uuid::new_v4().to_string();
+    let bundle = fhir::transaction_bundle(&data, &settings, &bundle_id, chrono::Utc::now());
+    let json = serde_json::to_vec_pretty(&bundle)
+        .map_err(|e| AppError::internal("Failed to encode FHIR bundle").with_details(e))?;
+    report::save(&json, &path)?;
+    info!(
+        "FHIR bundle for test {} saved to {} by {}",
+        test_uuid,
+        path.display(),
+        user.username
+    );
+    Ok(path.display().to_string())
+}
+
+// ============================================================================
+// RESULT DELIVERY COMMANDS
+// ============================================================================
+
+/// Messages shown in the delivery log
+const OUTBOUND_MESSAGE_LIMIT: u64 = 100;
+
+#[tauri::command]
+pub async fn get_hl7_settings(
+    session: State<'_, SessionState>,
+    db_state: State<'_, DbState>,
+) -> Result<Hl7Settings, AppError> {
+    auth::require_user(&session)?;
+    let db = db_state.lock().await;
+    hl7::load_settings(&*db).await
+}
+
+#[tauri::command]
+pub async fn save_hl7_settings(
+    session: State<'_, SessionState>,
+    db_state: State<'_, DbState>,
+    settings: Hl7Settings,
+) -> Result<Hl7Settings, AppError> {
+    auth::require(&session, Permission::ManageSettings)?;
+    let settings = settings.normalized()?;
+    let db = db_state.lock().await;
+    hl7::save_settings(&*db, &settings).await?;
+    info!(
+        "HL7 delivery saved: enabled={}, {}:{}",
+        settings.enabled, settings.host, settings.port
+    );
+    Ok(settings)
+}
+
+#[tauri::command]
+pub async fn get_fhir_settings(
+    session: State<'_, SessionState>,
+    db_state: State<'_, DbState>,
+) -> Result<FhirSettings, AppError> {
+    // The settings carry the endpoint's bearer token
+    auth::require(&session, Permission::ManageSettings)?;
+    let db = db_state.lock().await;
+    fhir::load_settings(&*db).await
+}
+
+#[tauri::command]
+pub async fn save_fhir_settings(
+    session: State<'_, SessionState>,
+    db_state: State<'_, DbState>,
+    settings: FhirSettings,
+) -> Result<FhirSettings, AppError> {
+    auth::require(&session, Permission::ManageSettings)?;
+    let settings = settings.normalized()?;
+    let db = db_state.lock().await;
+    fhir::save_settings(&*db, &settings).await?;
+    info!(
+        "FHIR delivery saved: enabled={}, {}",
+        settings.enabled, settings.base_url
+    );
+    Ok(settings)
+}
+
+#[tauri::command]
+pub async fn get_astm_settings(
+    session: State<'_, SessionState>,
+    db_state: State<'_, DbState>,
+) -> Result<AstmSettings, AppError> {
+    auth::require_user(&session)?;
+    let db = db_state.lock().await;
+    astm::load_settings(&*db).await
+}
+
+#[tauri::command]
+pub async fn save_astm_settings(
+    session: State<'_, SessionState>,
+    db_state: State<'_, DbState>,
+    settings: AstmSettings,
+) -> Result<AstmSettings, AppError> {
+    auth::require(&session, Permission::ManageSettings)?;
+    let settings = settings.normalized()?;
+    let db = db_state.lock().await;
+    astm::save_settings(&*db, &settings).await?;
+    info!(
+        "ASTM delivery saved: enabled={}, transport={:?}, orders={}",
+        settings.enabled, settings.transport, settings.accept_orders
+    );
+    Ok(settings)
+}
+
+/// Recent result messages with their delivery state, newest first
+#[tauri::command]
+pub async fn list_outbound_messages(
+    session: State<'_, SessionState>,
+    db_state: State<'_, DbState>,
+) -> Result<Vec<OutboundMessage>, AppError> {
+    auth::require(&session, Permission::ManageSettings)?;
+    let db = db_state.lock().await;
+    Database::list_outbound_messages(&*db, OUTBOUND_MESSAGE_LIMIT).await
+}
+
+/// Send a message again on the next delivery run
+#[tauri::command]
+pub async fn retry_outbound_message(
+    session: State<'_, SessionState>,
+    db_state: State<'_, DbState>,
+    id: i64,
+) -> Result<OutboundMessage, AppError> {
+    let user = auth::require(&session, Permission::ManageSettings)?;
+    let db = db_state.lock().await;
+    let mut message = Database::get_outbound_message(&*db, id)
+        .await?
+        .ok_or_else(|| AppError::not_found("Message", &id.to_string()))?;
+    if message.status == outbox::MessageStatus::Acked.as_str() {
+        return Err(AppError::validation("The message was already acknowledged"));
+    }
+    message.reset();
+    Database::update_outbound_message(&*db, &message).await?;
+    info!(
+        "Message {} queued again by {}",
+        message.control_id, user.username
+    );
+    Ok(message)
 }
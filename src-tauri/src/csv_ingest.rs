@@ -0,0 +1,384 @@
+use crate::models::{ColumnRole, ColumnSchema, DataQualityReport, QualityThresholds, RejectedRow};
+
+/// Maximum number of rejected rows kept in the report
+const MAX_REPORTED_REJECTIONS: usize = 20;
+/// Consecutive samples pinned at a channel limit before they count as saturated
+const SATURATION_RUN: usize = 3;
+
+/// Header aliases accepted for the fixed columns and a single-channel value
+const TIMESTAMP_ALIASES: &[&str] = &["timestamp", "time", "ts", "t"];
+const INDEX_ALIASES: &[&str] = &["index", "idx", "sample", "n", "i"];
+const VALUE_ALIASES: &[&str] = &["value", "val", "signal", "reading", "v"];
+
+/// One row of device output, with a value per schema channel
+#[derive(Debug, Clone)]
+pub struct Sample {
+    pub index: u32,
+    pub timestamp: f64,
+    pub values: Vec<f64>,
+}
+
+/// Parsed dataset together with its quality report
+pub struct ParsedCsv {
+    pub samples: Vec<Sample>,
+    pub report: DataQualityReport,
+}
+
+/// Positions of the schema's fields within a row
+struct ColumnMap {
+    timestamp: usize,
+    index: usize,
+    channels: Vec<(usize, String)>,
+    width: usize,
+}
+
+impl ColumnMap {
+    /// Positions as declared by the schema, used when there is no header
+    fn from_schema(schema: &ColumnSchema) -> Self {
+        let position = |role: ColumnRole| {
+            schema
+                .columns
+                .iter()
+                .position(|c| c.role == role)
+                .unwrap_or_default()
+        };
+
+        Self {
+            timestamp: position(ColumnRole::Timestamp),
+            index: position(ColumnRole::Index),
+            channels: schema
+                .columns
+                .iter()
+                .enumerate()
+                .filter(|(_, c)| c.role == ColumnRole::Channel)
+                .map(|(i, c)| (i, c.name.clone()))
+                .collect(),
+            width: schema.columns.len(),
+        }
+    }
+
+    /// Build a column map from a header row, if it names every schema field
+    fn from_header(fields: &[String], schema: &ColumnSchema) -> Option<Self> {
+        let position = |name: &str, aliases: &[&str]| {
+            fields.iter().position(|f| {
+                let f = f.to_ascii_lowercase();
+                f == name.to_ascii_lowercase() || aliases.contains(&f.as_str())
+            })
+        };
+
+        let single_channel = !schema.is_multi_channel();
+        let mut timestamp = None;
+        let mut index = None;
+        let mut channels = Vec::new();
+        for column in &schema.columns {
+            match column.role {
+                ColumnRole::Timestamp => timestamp = position(&column.name, TIMESTAMP_ALIASES),
+                ColumnRole::Index => index = position(&column.name, INDEX_ALIASES),
+                ColumnRole::Channel => {
+                    let aliases = if single_channel { VALUE_ALIASES } else { &[] };
+                    channels.push((position(&column.name, aliases)?, column.name.clone()));
+                }
+                ColumnRole::Ignore => {}
+            }
+        }
+
+        Some(Self {
+            timestamp: timestamp?,
+            index: index?,
+            channels,
+            width: fields.len(),
+        })
+    }
+}
+
+/// A row that parsed successfully, with the order it arrived in
+struct AcceptedRow {
+    sample: Sample,
+    repaired: bool,
+}
+
+/// Parse a raw CSV buffer, skipping or repairing bad rows
+///
+/// Never fails outright: rows that cannot be used are recorded in the report,
+/// and the report's `passed` flag says whether the dataset is fit for detection.
+pub fn parse_csv_data(
+    csv_buffer: &str,
+    schema: &ColumnSchema,
+    thresholds: &QualityThresholds,
+) -> ParsedCsv {
+    let mut report = DataQualityReport::default();
+    let mut columns = ColumnMap::from_schema(schema);
+    let mut rows: Vec<AcceptedRow> = Vec::new();
+
+    for (line_num, raw_line) in csv_buffer.lines().enumerate() {
+        let line_num = line_num + 1;
+
+        // Drop stray control bytes left by chunk boundaries or line noise
+        let cleaned: String = raw_line
+            .chars()
+            .filter(|c| !c.is_control() || *c == '\t')
+            .collect();
+        let mut repaired = cleaned.len() != raw_line.len();
+
+        let line = cleaned.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let (fields, delimiter_repaired) = split_fields(line);
+        repaired |= delimiter_repaired;
+
+        // A header is only accepted before any data has been seen
+        if rows.is_empty() && !report.header_detected && is_header(&fields) {
+            if let Some(map) = ColumnMap::from_header(&fields, schema) {
+                columns = map;
+                report.header_detected = true;
+                continue;
+            }
+        }
+
+        report.total_rows += 1;
+        match parse_row(&fields, &columns) {
+            Ok((sample, value_repaired)) => rows.push(AcceptedRow {
+                sample,
+                repaired: repaired || value_repaired,
+            }),
+            Err(reason) => {
+                report.rejected_rows += 1;
+                if report.rejected.len() < MAX_REPORTED_REJECTIONS {
+                    report.rejected.push(RejectedRow {
+                        line: line_num,
+                        reason,
+                    });
+                }
+            }
+        }
+    }
+
+    report.repaired_rows = rows.iter().filter(|r| r.repaired).count();
+
+    // Count rows that arrived behind an index already seen
+    let mut max_index: Option<u32> = None;
+    for row in &rows {
+        if max_index.is_some_and(|max| row.sample.index < max) {
+            report.out_of_order_rows += 1;
+        }
+        max_index = max_index.max(Some(row.sample.index));
+    }
+
+    // Restore index order, keeping the first sample seen for each index
+    let mut samples: Vec<Sample> = rows.into_iter().map(|r| r.sample).collect();
+    samples.sort_by_key(|s| s.index);
+    let before_dedup = samples.len();
+    samples.dedup_by_key(|s| s.index);
+    report.duplicate_indices = before_dedup - samples.len();
+    report.accepted_rows = samples.len();
+
+    for pair in samples.windows(2) {
+        let step = pair[1].index - pair[0].index;
+        if step > 1 {
+            report.index_gaps += 1;
+            report.missing_indices += (step - 1) as usize;
+        }
+        if pair[1].timestamp < pair[0].timestamp {
+            report.non_monotonic_timestamps += 1;
+        }
+    }
+
+    // Report the worst channel so one clipped sensor is not averaged away
+    report.saturated_samples = (0..columns.channels.len())
+        .map(|channel| {
+            let values: Vec<f64> = samples.iter().map(|s| s.values[channel]).collect();
+            count_saturated(
+                &values,
+                channel_limits(schema, &columns.channels[channel].1),
+            )
+        })
+        .max()
+        .unwrap_or(0);
+    report.saturation_ratio = ratio(report.saturated_samples, samples.len());
+    report.issues = evaluate(&report, thresholds);
+    report.passed = report.issues.is_empty();
+    report.thresholds = Some(thresholds.clone());
+
+    ParsedCsv { samples, report }
+}
+
+/// Split a line on its delimiter, normalising `;` and tab separated rows
+fn split_fields(line: &str) -> (Vec<String>, bool) {
+    let delimiter = [',', ';', '\t']
+        .into_iter()
+        .find(|d| line.contains(*d))
+        .unwrap_or(',');
+    let mut repaired = delimiter != ',';
+
+    let mut fields: Vec<String> = line
+        .split(delimiter)
+        .map(|f| f.trim().to_string())
+        .collect();
+
+    // A trailing delimiter leaves an empty last field
+    while fields.len() > 1 && fields.last().is_some_and(|f| f.is_empty()) {
+        fields.pop();
+        repaired = true;
+    }
+
+    (fields, repaired)
+}
+
+/// Whether a row looks like column names rather than data
+fn is_header(fields: &[String]) -> bool {
+    fields.iter().all(|f| f.parse::<f64>().is_err())
+        && fields
+            .iter()
+            .any(|f| f.chars().any(|c| c.is_ascii_alphabetic()))
+}
+
+/// Parse one row into a sample, returning whether a value was repaired
+fn parse_row(fields: &[String], columns: &ColumnMap) -> Result<(Sample, bool), String> {
+    if fields.len() != columns.width {
+        return Err(format!(
+            "expected {} columns, got {}",
+            columns.width,
+            fields.len()
+        ));
+    }
+
+    let timestamp = parse_finite(&fields[columns.timestamp])
+        .ok_or_else(|| format!("invalid timestamp '{}'", fields[columns.timestamp]))?;
+
+    let values = columns
+        .channels
+        .iter()
+        .map(|(position, name)| {
+            parse_finite(&fields[*position])
+                .ok_or_else(|| format!("invalid {} '{}'", name, fields[*position]))
+        })
+        .collect::<Result<Vec<f64>, String>>()?;
+
+    // Some firmware prints the index as a float, e.g. `12.0`
+    let index_field = &fields[columns.index];
+    let (index, repaired) = match index_field.parse::<u32>() {
+        Ok(index) => (index, false),
+        Err(_) => match parse_finite(index_field) {
+            Some(f) if f >= 0.0 && f.fract() == 0.0 && f <= u32::MAX as f64 => (f as u32, true),
+            _ => return Err(format!("invalid index '{}'", index_field)),
+        },
+    };
+
+    Ok((
+        Sample {
+            index,
+            timestamp,
+            values,
+        },
+        repaired,
+    ))
+}
+
+fn parse_finite(field: &str) -> Option<f64> {
+    field.parse::<f64>().ok().filter(|v| v.is_finite())
+}
+
+/// Output limits the schema declares for a channel
+fn channel_limits(schema: &ColumnSchema, name: &str) -> Option<(f64, f64)> {
+    schema
+        .channels()
+        .find(|c| c.name == name)
+        .and_then(|c| c.limits)
+}
+
+/// Count samples sitting in runs pinned at the channel's output limits
+///
+/// A flat signal inside the limits is valid data, so nothing counts as
+/// saturated for a channel whose limits are unknown.
+fn count_saturated(values: &[f64], limits: Option<(f64, f64)>) -> usize {
+    let Some((low, high)) = limits else {
+        return 0;
+    };
+
+    let mut saturated = 0;
+    let mut run = 0;
+    for (i, &value) in values.iter().enumerate() {
+        let pinned = value <= low || value >= high;
+        let continues = i > 0 && pinned && value == values[i - 1];
+        run = if continues {
+            run + 1
+        } else if pinned {
+            1
+        } else {
+            0
+        };
+
+        if run == SATURATION_RUN {
+            saturated += SATURATION_RUN;
+        } else if run > SATURATION_RUN {
+            saturated += 1;
+        }
+    }
+    saturated
+}
+
+fn ratio(part: usize, whole: usize) -> f64 {
+    if whole == 0 {
+        0.0
+    } else {
+        part as f64 / whole as f64
+    }
+}
+
+/// Compare the report against the thresholds and describe each violation
+fn evaluate(report: &DataQualityReport, thresholds: &QualityThresholds) -> Vec<String> {
+    let mut issues = Vec::new();
+
+    if report.accepted_rows < thresholds.min_accepted_rows {
+        issues.push(format!(
+            "Only {} usable rows (minimum {})",
+            report.accepted_rows, thresholds.min_accepted_rows
+        ));
+    }
+
+    let rejected_ratio = ratio(report.rejected_rows, report.total_rows);
+    if rejected_ratio > thresholds.max_rejected_ratio {
+        issues.push(format!(
+            "{:.1}% of rows rejected (maximum {:.1}%)",
+            rejected_ratio * 100.0,
+            thresholds.max_rejected_ratio * 100.0
+        ));
+    }
+
+    let missing_ratio = ratio(
+        report.missing_indices,
+        report.accepted_rows + report.missing_indices,
+    );
+    if missing_ratio > thresholds.max_missing_ratio {
+        issues.push(format!(
+            "{:.1}% of samples missing across {} gaps (maximum {:.1}%)",
+            missing_ratio * 100.0,
+            report.index_gaps,
+            thresholds.max_missing_ratio * 100.0
+        ));
+    }
+
+    if report.saturation_ratio > thresholds.max_saturation_ratio {
+        issues.push(format!(
+            "{:.1}% of samples saturated (maximum {:.1}%)",
+            report.saturation_ratio * 100.0,
+            thresholds.max_saturation_ratio * 100.0
+        ));
+    }
+
+    let non_monotonic_ratio = ratio(
+        report.non_monotonic_timestamps,
+        report.accepted_rows.saturating_sub(1),
+    );
+    if non_monotonic_ratio > thresholds.max_non_monotonic_ratio {
+        issues.push(format!(
+            "{} timestamps run backwards (maximum {:.1}%)",
+            report.non_monotonic_timestamps,
+            thresholds.max_non_monotonic_ratio * 100.0
+        ));
+    }
+
+    issues
+}
@@ -1,14 +1,106 @@
-use crate::entities::{patient, setting, test};
-use crate::models::{Patient, Test, TestWithPatient};
+use crate::auth::Credentials;
+use crate::encryption::DatabaseKey;
+use crate::entities::{
+    control_material, device, outbound_message, patient, qc_run, setting, test, user,
+};
+use crate::migrations::{self, SchemaVersion};
+use crate::models::{
+    AppError, ColumnSchema, DataQualityReport, Device, FlowControl, Parity, Patient,
+    PreprocessingPipeline, Role, SerialProfile, Test, TestStatus, TestType, TestWithPatient, User,
+};
+use crate::outbox::{MessageStatus, OutboundMessage};
+use crate::qc::{ControlLevel, ControlMaterial, QcRun, QcStatus};
 use sea_orm::*;
+use std::path::Path;
 
 // ============================================================================
 // DATABASE ORM OPERATIONS (SeaORM)
 // ============================================================================
 
+/// Database file name inside the app data dir
+pub const DATABASE_FILE: &str = "ebers.db";
+
 pub struct Database;
 
 impl Database {
+    // ------------------------------------------------------------------------
+    // CONNECTION & SCHEMA
+    // ------------------------------------------------------------------------
+
+    /// Connect to the SQLite database at `path`, creating it if missing
+    ///
+    /// With a `key` the file is opened through SQLCipher; a wrong key or an
+    /// encrypted file opened without one is reported as a validation error.
+    pub async fn connect(
+        path: &Path,
+        key: Option<&DatabaseKey>,
+    ) -> Result<DatabaseConnection, AppError> {
+        let mut opt = ConnectOptions::new(format!("sqlite://{}?mode=rwc", path.display()));
+        opt.max_connections(5)
+            .min_connections(1)
+            .sqlx_logging(false);
+        if let Some(key) = key {
+            // `key` must be the first pragma run on every pooled connection
+            let value = key.pragma_value();
+            opt.map_sqlx_sqlite_opts(move |o| o.pragma("key", value.clone()));
+        }
+
+        // SQLCipher only notices a wrong key when the first page is read
+        let connect_error = |e: DbErr| {
+            if e.to_string().contains("file is not a database") {
+                match key {
+                    Some(_) => AppError::validation("Wrong passphrase or key for the database"),
+                    None => AppError::validation("The database is encrypted and needs a key"),
+                }
+            } else {
+                AppError::database("Failed to connect to database", e)
+            }
+        };
+        let db = sea_orm::Database::connect(opt)
+            .await
+            .map_err(connect_error)?;
+        db.query_one(Statement::from_string(
+            DbBackend::Sqlite,
+            "SELECT count(*) FROM sqlite_master",
+        ))
+        .await
+        .map_err(connect_error)?;
+        Ok(db)
+    }
+
+    /// Open (creating if needed) the SQLite database at `path` and migrate it
+    pub async fn open(
+        path: &Path,
+        key: Option<&DatabaseKey>,
+    ) -> Result<DatabaseConnection, AppError> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| AppError::io("Failed to create database directory", e))?;
+        }
+
+        let db = Self::connect(path, key).await?;
+
+        migrations::run(&db)
+            .await
+            .map_err(|e| AppError::database("Failed to migrate database", e))?;
+
+        Ok(db)
+    }
+
+    /// Empty in-memory stand-in managed until an encrypted database is unlocked
+    pub async fn locked_placeholder() -> Result<DatabaseConnection, AppError> {
+        sea_orm::Database::connect("sqlite::memory:")
+            .await
+            .map_err(|e| AppError::database("Failed to create placeholder database", e))
+    }
+
+    /// Applied and pending migrations plus any entity/schema drift
+    pub async fn schema_version(db: &DatabaseConnection) -> Result<SchemaVersion, AppError> {
+        migrations::schema_version(db)
+            .await
+            .map_err(|e| AppError::database("Failed to read schema version", e))
+    }
+
     // ------------------------------------------------------------------------
     // SETTINGS OPERATIONS
     // ------------------------------------------------------------------------
@@ -18,7 +110,7 @@ impl Database {
         db: &DatabaseConnection,
         key: String,
         value: String,
-    ) -> Result<(), String> {
+    ) -> Result<(), AppError> {
         let setting_model = setting::ActiveModel {
             key: Set(key.clone()),
             value: Set(value),
@@ -33,7 +125,7 @@ impl Database {
             )
             .exec(db)
             .await
-            .map_err(|e| format!("Failed to save setting: {}", e))?;
+            .map_err(|e| AppError::database("Failed to save setting", e))?;
 
         Ok(())
     }
@@ -42,21 +134,56 @@ impl Database {
     pub async fn get_setting(
         db: &DatabaseConnection,
         key: String,
-    ) -> Result<Option<String>, String> {
+    ) -> Result<Option<String>, AppError> {
         let result = setting::Entity::find_by_id(key)
             .one(db)
             .await
-            .map_err(|e| format!("Failed to get setting: {}", e))?;
+            .map_err(|e| AppError::database("Failed to get setting", e))?;
 
         Ok(result.map(|model| model.value))
     }
 
+    // ------------------------------------------------------------------------
+    // PREPROCESSING OPERATIONS
+    // ------------------------------------------------------------------------
+
+    /// Get the preprocessing pipeline for a test type; none configured means no steps
+    pub async fn get_preprocessing_pipeline(
+        db: &DatabaseConnection,
+        test_type: &TestType,
+    ) -> Result<PreprocessingPipeline, AppError> {
+        match Self::get_setting(db, Self::preprocessing_key(test_type)).await? {
+            Some(json) => serde_json::from_str(&json)
+                .map_err(|e| AppError::database("Failed to parse preprocessing pipeline", e)),
+            None => Ok(PreprocessingPipeline::default()),
+        }
+    }
+
+    /// Save the preprocessing pipeline for a test type
+    pub async fn save_preprocessing_pipeline(
+        db: &DatabaseConnection,
+        test_type: &TestType,
+        pipeline: &PreprocessingPipeline,
+    ) -> Result<(), AppError> {
+        let json = serde_json::to_string(pipeline).map_err(|e| {
+            AppError::internal("Failed to serialize preprocessing pipeline").with_details(e)
+        })?;
+        Self::save_setting(db, Self::preprocessing_key(test_type), json).await
+    }
+
+    fn preprocessing_key(test_type: &TestType) -> String {
+        format!("preprocessing.{}", test_type.as_str())
+    }
+
     // ------------------------------------------------------------------------
     // PATIENT OPERATIONS
     // ------------------------------------------------------------------------
 
     /// Insert a new patient record
-    pub async fn insert_patient(db: &DatabaseConnection, patient: &Patient) -> Result<i64, String> {
+    pub async fn insert_patient(
+        db: &DatabaseConnection,
+        patient: &Patient,
+    ) -> Result<i64, AppError> {
         let patient_model = patient::ActiveModel {
             uuid: Set(patient.uuid.clone()),
             first_name: Set(patient.first_name.clone()),
@@ -74,21 +201,82 @@ impl Database {
         let result = patient::Entity::insert(patient_model)
             .exec(db)
             .await
-            .map_err(|e| format!("Failed to insert patient: {}", e))?;
+            .map_err(|e| AppError::database("Failed to insert patient", e))?;
 
         Ok(result.last_insert_id)
     }
 
+    /// Insert many patients in one transaction; none are kept if any insert fails
+    pub async fn insert_patients(
+        db: &DatabaseConnection,
+        patients: &[Patient],
+    ) -> Result<(), AppError> {
+        let txn = db
+            .begin()
+            .await
+            .map_err(|e| AppError::database("Failed to start import", e))?;
+
+        for patient in patients {
+            let patient_model = patient::ActiveModel {
+                uuid: Set(patient.uuid.clone()),
+                first_name: Set(patient.first_name.clone()),
+                last_name: Set(patient.last_name.clone()),
+                date_of_birth: Set(patient.date_of_birth.clone()),
+                patient_id_number: Set(patient.patient_id_number.clone()),
+                email: Set(patient.email.clone()),
+                phone: Set(patient.phone.clone()),
+                notes: Set(patient.notes.clone()),
+                created_at: Set(patient.created_at.clone()),
+                updated_at: Set(patient.updated_at.clone()),
+                ..Default::default()
+            };
+            patient::Entity::insert(patient_model)
+                .exec(&txn)
+                .await
+                .map_err(|e| AppError::database("Failed to insert patient", e))?;
+        }
+
+        txn.commit()
+            .await
+            .map_err(|e| AppError::database("Failed to commit import", e))
+    }
+
     /// Get a patient by UUID
     pub async fn get_patient_by_uuid(
         db: &DatabaseConnection,
         uuid: &str,
-    ) -> Result<Option<Patient>, String> {
+    ) -> Result<Option<Patient>, AppError> {
         let result = patient::Entity::find()
             .filter(patient::Column::Uuid.eq(uuid))
             .one(db)
             .await
-            .map_err(|e| format!("Failed to fetch patient: {}", e))?;
+            .map_err(|e| AppError::database("Failed to fetch patient", e))?;
+
+        Ok(result.map(|model| Patient {
+            id: Some(model.id),
+            uuid: model.uuid,
+            first_name: model.first_name,
+            last_name: model.last_name,
+            date_of_birth: model.date_of_birth,
+            patient_id_number: model.patient_id_number,
+            email: model.email,
+            phone: model.phone,
+            notes: model.notes,
+            created_at: model.created_at,
+            updated_at: model.updated_at,
+        }))
+    }
+
+    /// Get a patient by their clinic-assigned ID number
+    pub async fn get_patient_by_id_number(
+        db: &DatabaseConnection,
+        patient_id_number: &str,
+    ) -> Result<Option<Patient>, AppError> {
+        let result = patient::Entity::find()
+            .filter(patient::Column::PatientIdNumber.eq(patient_id_number))
+            .one(db)
+            .await
+            .map_err(|e| AppError::database("Failed to fetch patient", e))?;
 
         Ok(result.map(|model| Patient {
             id: Some(model.id),
@@ -106,12 +294,12 @@ impl Database {
     }
 
     /// Get all patients
-    pub async fn get_all_patients(db: &DatabaseConnection) -> Result<Vec<Patient>, String> {
+    pub async fn get_all_patients(db: &DatabaseConnection) -> Result<Vec<Patient>, AppError> {
         let results = patient::Entity::find()
             .order_by_desc(patient::Column::CreatedAt)
             .all(db)
             .await
-            .map_err(|e| format!("Failed to fetch patients: {}", e))?;
+            .map_err(|e| AppError::database("Failed to fetch patients", e))?;
 
         Ok(results
             .into_iter()
@@ -135,9 +323,76 @@ impl Database {
     // TEST OPERATIONS
     // ------------------------------------------------------------------------
 
-    /// Insert a new test record
-    pub async fn insert_test(db: &DatabaseConnection, test: &Test) -> Result<i64, String> {
-        let test_model = test::ActiveModel {
+    /// Insert a new test record, giving it an accession number if it has none
+    pub async fn insert_test(db: &DatabaseConnection, test: &mut Test) -> Result<i64, AppError> {
+        if test.accession_number.is_none() {
+            let prefix = Self::accession_prefix();
+            let sequence = Self::next_accession_sequence(db, &prefix).await?;
+            test.accession_number = Some(format!("{}{:04}", prefix, sequence));
+        }
+        let test_model = Self::test_to_active_model(test)?;
+
+        let result = test::Entity::insert(test_model)
+            .exec(db)
+            .await
+            .map_err(|e| AppError::database("Failed to insert test", e))?;
+
+        Ok(result.last_insert_id)
+    }
+
+    /// Insert several tests in one transaction; none are kept if any fails
+    pub async fn insert_tests(db: &DatabaseConnection, tests: &mut [Test]) -> Result<(), AppError> {
+        let txn = db
+            .begin()
+            .await
+            .map_err(|e| AppError::database("Failed to start import", e))?;
+
+        let prefix = Self::accession_prefix();
+        let mut sequence = Self::next_accession_sequence(&txn, &prefix).await?;
+        for test in tests.iter_mut() {
+            if test.accession_number.is_none() {
+                test.accession_number = Some(format!("{}{:04}", prefix, sequence));
+                sequence += 1;
+            }
+            test::Entity::insert(Self::test_to_active_model(test)?)
+                .exec(&txn)
+                .await
+                .map_err(|e| AppError::database("Failed to insert test", e))?;
+        }
+
+        txn.commit()
+            .await
+            .map_err(|e| AppError::database("Failed to commit import", e))
+    }
+
+    /// Accession numbers are the local date and a daily sequence, e.g. 2610180007
+    fn accession_prefix() -> String {
+        chrono::Local::now().format("%y%m%d").to_string()
+    }
+
+    async fn next_accession_sequence<C: ConnectionTrait>(
+        db: &C,
+        prefix: &str,
+    ) -> Result<u32, AppError> {
+        let numbers: Vec<Option<String>> = test::Entity::find()
+            .select_only()
+            .column(test::Column::AccessionNumber)
+            .filter(test::Column::AccessionNumber.starts_with(prefix))
+            .into_tuple()
+            .all(db)
+            .await
+            .map_err(|e| AppError::database("Failed to fetch accession numbers", e))?;
+
+        Ok(numbers
+            .iter()
+            .flatten()
+            .filter_map(|number| number[prefix.len()..].parse::<u32>().ok())
+            .max()
+            .map_or(1, |last| last + 1))
+    }
+
+    fn test_to_active_model(test: &Test) -> Result<test::ActiveModel, AppError> {
+        Ok(test::ActiveModel {
             uuid: Set(test.uuid.clone()),
             patient_id: Set(test.patient_id),
             test_type: Set(test.test_type.clone()),
@@ -151,21 +406,25 @@ impl Database {
             created_at: Set(test.created_at.clone()),
             updated_at: Set(test.updated_at.clone()),
             completed_at: Set(test.completed_at.clone()),
+            quality_report: Set(Self::quality_report_to_json(test)?),
+            operated_by: Set(test.operated_by.clone()),
+            released_by: Set(test.released_by.clone()),
+            released_at: Set(test.released_at.clone()),
+            review_log: Set(Self::review_log_to_json(test)?),
+            retest_of: Set(test.retest_of.clone()),
+            signal_preview: Set(Self::signal_preview_to_json(test)?),
+            specimen_id: Set(test.specimen_id.clone()),
+            accession_number: Set(test.accession_number.clone()),
             ..Default::default()
-        };
-
-        let result = test::Entity::insert(test_model)
-            .exec(db)
-            .await
-            .map_err(|e| format!("Failed to insert test: {}", e))?;
-
-        Ok(result.last_insert_id)
+        })
     }
 
     /// Update an existing test record
-    pub async fn update_test(db: &DatabaseConnection, test: &Test) -> Result<(), String> {
+    pub async fn update_test(db: &DatabaseConnection, test: &Test) -> Result<(), AppError> {
         let test_model = test::ActiveModel {
-            id: Set(test.id.ok_or("Test has no ID")?),
+            id: Set(test
+                .id
+                .ok_or_else(|| AppError::internal("Test has no ID"))?),
             uuid: Set(test.uuid.clone()),
             patient_id: Set(test.patient_id),
             test_type: Set(test.test_type.clone()),
@@ -179,12 +438,26 @@ impl Database {
             created_at: Set(test.created_at.clone()),
             updated_at: Set(test.updated_at.clone()),
             completed_at: Set(test.completed_at.clone()),
+            quality_report: Set(Self::quality_report_to_json(test)?),
+            operated_by: Set(test.operated_by.clone()),
+            released_by: Set(test.released_by.clone()),
+            released_at: Set(test.released_at.clone()),
+            review_log: Set(Self::review_log_to_json(test)?),
+            retest_of: Set(test.retest_of.clone()),
+            signal_preview: Set(Self::signal_preview_to_json(test)?),
+            specimen_id: Set(test.specimen_id.clone()),
+            accession_number: Set(test.accession_number.clone()),
+            // Only written by `save_raw_signal`
+            raw_signal: NotSet,
         };
 
         test::Entity::update(test_model)
             .exec(db)
             .await
-            .map_err(|e| format!("Failed to update test: {}", e))?;
+            .map_err(|e| match e {
+                DbErr::RecordNotUpdated => AppError::not_found("Test", &test.uuid),
+                e => AppError::database("Failed to update test", e),
+            })?;
 
         Ok(())
     }
@@ -193,26 +466,188 @@ impl Database {
     pub async fn get_test_by_uuid(
         db: &DatabaseConnection,
         uuid: &str,
-    ) -> Result<Option<Test>, String> {
+    ) -> Result<Option<Test>, AppError> {
         let result = test::Entity::find()
             .filter(test::Column::Uuid.eq(uuid))
             .one(db)
             .await
-            .map_err(|e| format!("Failed to fetch test: {}", e))?;
+            .map_err(|e| AppError::database("Failed to fetch test", e))?;
 
         Ok(result.map(|model| Self::test_model_to_struct(model)))
     }
 
+    /// Find the test a scanned barcode belongs to
+    ///
+    /// Accession numbers and test UUIDs identify one test; a LIS specimen ID may
+    /// cover several, in which case a pending one is preferred, then the newest.
+    pub async fn find_test_by_barcode(
+        db: &DatabaseConnection,
+        code: &str,
+    ) -> Result<Option<TestWithPatient>, AppError> {
+        let results = test::Entity::find()
+            .filter(
+                Condition::any()
+                    .add(test::Column::AccessionNumber.eq(code))
+                    .add(test::Column::Uuid.eq(code.to_lowercase()))
+                    .add(test::Column::SpecimenId.eq(code)),
+            )
+            .find_also_related(patient::Entity)
+            .order_by_desc(test::Column::CreatedAt)
+            .all(db)
+            .await
+            .map_err(|e| AppError::database("Failed to look up barcode", e))?;
+
+        let mut matches: Vec<TestWithPatient> = results
+            .into_iter()
+            .filter_map(|(test_model, patient_model)| {
+                patient_model.map(|patient_model| TestWithPatient {
+                    test: Self::test_model_to_struct(test_model),
+                    patient: Self::patient_model_to_struct(patient_model),
+                })
+            })
+            .collect();
+        // Stable sort keeps the newest first within each rank
+        matches.sort_by_key(|m| {
+            if m.test.accession_number.as_deref() == Some(code) {
+                0
+            } else if m.test.uuid.eq_ignore_ascii_case(code) {
+                1
+            } else if m.test.status == TestStatus::Pending.as_str() {
+                2
+            } else {
+                3
+            }
+        });
+        Ok(matches.into_iter().next())
+    }
+
+    /// Get the newest test of a type ordered under a LIS specimen ID
+    pub async fn get_test_by_specimen_id(
+        db: &DatabaseConnection,
+        specimen_id: &str,
+        test_type: &str,
+    ) -> Result<Option<Test>, AppError> {
+        let result = test::Entity::find()
+            .filter(test::Column::SpecimenId.eq(specimen_id))
+            .filter(test::Column::TestType.eq(test_type))
+            .order_by_desc(test::Column::CreatedAt)
+            .one(db)
+            .await
+            .map_err(|e| AppError::database("Failed to fetch test", e))?;
+
+        Ok(result.map(Self::test_model_to_struct))
+    }
+
+    /// Attach the ingestion quality report to a test
+    pub async fn save_quality_report(
+        db: &DatabaseConnection,
+        uuid: &str,
+        report: &DataQualityReport,
+    ) -> Result<(), AppError> {
+        let json = serde_json::to_string(report).map_err(|e| {
+            AppError::internal("Failed to serialize quality report").with_details(e)
+        })?;
+
+        test::Entity::update_many()
+            .col_expr(test::Column::QualityReport, sea_query::Expr::value(json))
+            .col_expr(
+                test::Column::UpdatedAt,
+                sea_query::Expr::value(chrono::Utc::now().to_rfc3339()),
+            )
+            .filter(test::Column::Uuid.eq(uuid))
+            .exec(db)
+            .await
+            .map_err(|e| AppError::database("Failed to save quality report", e))?;
+
+        Ok(())
+    }
+
+    /// Attach the signal thumbnail to a test
+    pub async fn save_signal_preview(
+        db: &DatabaseConnection,
+        uuid: &str,
+        preview: &[f64],
+    ) -> Result<(), AppError> {
+        let json = serde_json::to_string(preview).map_err(|e| {
+            AppError::internal("Failed to serialize signal preview").with_details(e)
+        })?;
+
+        test::Entity::update_many()
+            .col_expr(test::Column::SignalPreview, sea_query::Expr::value(json))
+            .filter(test::Column::Uuid.eq(uuid))
+            .exec(db)
+            .await
+            .map_err(|e| AppError::database("Failed to save signal preview", e))?;
+
+        Ok(())
+    }
+
+    /// Keep the CSV a device sent for a test
+    pub async fn save_raw_signal(
+        db: &DatabaseConnection,
+        uuid: &str,
+        csv_data: &str,
+    ) -> Result<(), AppError> {
+        test::Entity::update_many()
+            .col_expr(
+                test::Column::RawSignal,
+                sea_query::Expr::value(csv_data.to_string()),
+            )
+            .filter(test::Column::Uuid.eq(uuid))
+            .exec(db)
+            .await
+            .map_err(|e| AppError::database("Failed to save raw signal", e))?;
+
+        Ok(())
+    }
+
+    /// The CSV a device sent for a test, if it was kept
+    pub async fn get_raw_signal(
+        db: &DatabaseConnection,
+        uuid: &str,
+    ) -> Result<Option<String>, AppError> {
+        let raw_signal = test::Entity::find()
+            .select_only()
+            .column(test::Column::RawSignal)
+            .filter(test::Column::Uuid.eq(uuid))
+            .into_tuple::<Option<String>>()
+            .one(db)
+            .await
+            .map_err(|e| AppError::database("Failed to fetch raw signal", e))?;
+
+        Ok(raw_signal.flatten())
+    }
+
+    /// Get a test together with its patient
+    pub async fn get_test_with_patient(
+        db: &DatabaseConnection,
+        uuid: &str,
+    ) -> Result<Option<TestWithPatient>, AppError> {
+        let result = test::Entity::find()
+            .filter(test::Column::Uuid.eq(uuid))
+            .find_also_related(patient::Entity)
+            .one(db)
+            .await
+            .map_err(|e| AppError::database("Failed to fetch test", e))?;
+
+        Ok(result.and_then(|(test_model, patient_model)| {
+            patient_model.map(|patient_model| TestWithPatient {
+                test: Self::test_model_to_struct(test_model),
+                patient: Self::patient_model_to_struct(patient_model),
+            })
+        }))
+    }
+
     /// Get all tests with patient information
     pub async fn get_all_tests_with_patients(
         db: &DatabaseConnection,
-    ) -> Result<Vec<TestWithPatient>, String> {
+    ) -> Result<Vec<TestWithPatient>, AppError> {
         let results = test::Entity::find()
             .find_also_related(patient::Entity)
             .order_by_desc(test::Column::CreatedAt)
             .all(db)
             .await
-            .map_err(|e| format!("Failed to fetch tests with patients: {}", e))?;
+            .map_err(|e| AppError::database("Failed to fetch tests with patients", e))?;
 
         let mut test_with_patients = Vec::new();
 
@@ -228,10 +663,778 @@ impl Database {
         Ok(test_with_patients)
     }
 
+    /// Tests in one status with their patients, oldest first
+    pub async fn get_tests_with_patients_by_status(
+        db: &DatabaseConnection,
+        status: TestStatus,
+    ) -> Result<Vec<TestWithPatient>, AppError> {
+        let results = test::Entity::find()
+            .filter(test::Column::Status.eq(status.as_str()))
+            .find_also_related(patient::Entity)
+            .order_by_asc(test::Column::CompletedAt)
+            .order_by_asc(test::Column::CreatedAt)
+            .all(db)
+            .await
+            .map_err(|e| AppError::database("Failed to fetch tests with patients", e))?;
+
+        Ok(results
+            .into_iter()
+            .filter_map(|(test_model, patient_model)| {
+                patient_model.map(|patient_model| TestWithPatient {
+                    test: Self::test_model_to_struct(test_model),
+                    patient: Self::patient_model_to_struct(patient_model),
+                })
+            })
+            .collect())
+    }
+
+    /// One page of released tests with their patients, newest first
+    ///
+    /// `status` narrows the page the same way the history filter does.
+    pub async fn get_released_tests_with_patients(
+        db: &DatabaseConnection,
+        status: Option<&TestStatus>,
+        page: u64,
+        page_size: u64,
+    ) -> Result<Vec<TestWithPatient>, AppError> {
+        let mut query = test::Entity::find().filter(test::Column::ReleasedAt.is_not_null());
+        if let Some(status) = status {
+            query = query.filter(test::Column::Status.eq(status.as_str()));
+        }
+        let results = query
+            .find_also_related(patient::Entity)
+            .order_by_desc(test::Column::CreatedAt)
+            .order_by_asc(test::Column::Id)
+            .paginate(db, page_size)
+            .fetch_page(page)
+            .await
+            .map_err(|e| AppError::database("Failed to fetch released tests", e))?;
+
+        Ok(results
+            .into_iter()
+            .filter_map(|(test_model, patient_model)| {
+                patient_model.map(|patient_model| TestWithPatient {
+                    test: Self::test_model_to_struct(test_model),
+                    patient: Self::patient_model_to_struct(patient_model),
+                })
+            })
+            .collect())
+    }
+
+    /// Count the tests matching `status` that have not been released
+    pub async fn count_unreleased_tests(
+        db: &DatabaseConnection,
+        status: Option<&TestStatus>,
+    ) -> Result<u64, AppError> {
+        let mut query = test::Entity::find().filter(test::Column::ReleasedAt.is_null());
+        if let Some(status) = status {
+            query = query.filter(test::Column::Status.eq(status.as_str()));
+        }
+        query
+            .count(db)
+            .await
+            .map_err(|e| AppError::database("Failed to count tests", e))
+    }
+
+    // ------------------------------------------------------------------------
+    // DEVICE OPERATIONS
+    // ------------------------------------------------------------------------
+
+    /// Insert or update a device keyed by its device_id
+    pub async fn save_device(db: &DatabaseConnection, device: &Device) -> Result<(), AppError> {
+        let column_schema = serde_json::to_string(&device.schema)
+            .map_err(|e| AppError::internal("Failed to serialize column schema").with_details(e))?;
+
+        let device_model = device::ActiveModel {
+            device_id: Set(device.device_id.clone()),
+            label: Set(device.label.clone()),
+            port: Set(device.port.clone()),
+            enabled: Set(device.enabled),
+            baud_rate: Set(device.profile.baud_rate as i64),
+            data_bits: Set(device.profile.data_bits as i32),
+            parity: Set(device.profile.parity.as_str().to_string()),
+            stop_bits: Set(device.profile.stop_bits as i32),
+            flow_control: Set(device.profile.flow_control.as_str().to_string()),
+            read_timeout_ms: Set(device.profile.read_timeout_ms as i64),
+            chunk_size: Set(device.profile.chunk_size as i64),
+            column_schema: Set(Some(column_schema)),
+            created_at: Set(device.created_at.clone()),
+            updated_at: Set(device.updated_at.clone()),
+            ..Default::default()
+        };
+
+        device::Entity::insert(device_model)
+            .on_conflict(
+                sea_query::OnConflict::column(device::Column::DeviceId)
+                    .update_columns([
+                        device::Column::Label,
+                        device::Column::Port,
+                        device::Column::Enabled,
+                        device::Column::BaudRate,
+                        device::Column::DataBits,
+                        device::Column::Parity,
+                        device::Column::StopBits,
+                        device::Column::FlowControl,
+                        device::Column::ReadTimeoutMs,
+                        device::Column::ChunkSize,
+                        device::Column::ColumnSchema,
+                        device::Column::UpdatedAt,
+                    ])
+                    .to_owned(),
+            )
+            .exec(db)
+            .await
+            .map_err(|e| AppError::database("Failed to save device", e))?;
+
+        Ok(())
+    }
+
+    /// Get a device by its device_id
+    pub async fn get_device(
+        db: &DatabaseConnection,
+        device_id: &str,
+    ) -> Result<Option<Device>, AppError> {
+        let result = device::Entity::find()
+            .filter(device::Column::DeviceId.eq(device_id))
+            .one(db)
+            .await
+            .map_err(|e| AppError::database("Failed to fetch device", e))?;
+
+        Ok(result.map(Self::device_model_to_struct))
+    }
+
+    /// Get all configured devices
+    pub async fn get_all_devices(db: &DatabaseConnection) -> Result<Vec<Device>, AppError> {
+        let results = device::Entity::find()
+            .order_by_asc(device::Column::CreatedAt)
+            .all(db)
+            .await
+            .map_err(|e| AppError::database("Failed to fetch devices", e))?;
+
+        Ok(results
+            .into_iter()
+            .map(Self::device_model_to_struct)
+            .collect())
+    }
+
+    /// Delete a device by its device_id
+    pub async fn delete_device(db: &DatabaseConnection, device_id: &str) -> Result<(), AppError> {
+        device::Entity::delete_many()
+            .filter(device::Column::DeviceId.eq(device_id))
+            .exec(db)
+            .await
+            .map_err(|e| AppError::database("Failed to delete device", e))?;
+
+        Ok(())
+    }
+
+    // ------------------------------------------------------------------------
+    // USER OPERATIONS
+    // ------------------------------------------------------------------------
+
+    /// Insert a new account with an already hashed password
+    pub async fn insert_user(
+        db: &DatabaseConnection,
+        user: &User,
+        password_hash: String,
+    ) -> Result<i64, AppError> {
+        let user_model = user::ActiveModel {
+            uuid: Set(user.uuid.clone()),
+            username: Set(user.username.clone()),
+            display_name: Set(user.display_name.clone()),
+            role: Set(user.role.as_str().to_string()),
+            password_hash: Set(password_hash),
+            active: Set(user.active),
+            created_at: Set(user.created_at.clone()),
+            updated_at: Set(user.updated_at.clone()),
+            last_login_at: Set(user.last_login_at.clone()),
+            pin_hash: Set(None),
+            ..Default::default()
+        };
+
+        let result = user::Entity::insert(user_model)
+            .exec(db)
+            .await
+            .map_err(|e| match e.sql_err() {
+                Some(SqlErr::UniqueConstraintViolation(_)) => {
+                    AppError::conflict(format!("Username {} is already taken", user.username))
+                }
+                _ => AppError::database("Failed to insert user", e),
+            })?;
+
+        Ok(result.last_insert_id)
+    }
+
+    /// Update an account's name, role and active flag
+    pub async fn update_user(db: &DatabaseConnection, user: &User) -> Result<(), AppError> {
+        let result = user::Entity::update_many()
+            .col_expr(
+                user::Column::DisplayName,
+                sea_query::Expr::value(user.display_name.clone()),
+            )
+            .col_expr(
+                user::Column::Role,
+                sea_query::Expr::value(user.role.as_str()),
+            )
+            .col_expr(user::Column::Active, sea_query::Expr::value(user.active))
+            .col_expr(
+                user::Column::UpdatedAt,
+                sea_query::Expr::value(user.updated_at.clone()),
+            )
+            .filter(user::Column::Uuid.eq(user.uuid.as_str()))
+            .exec(db)
+            .await
+            .map_err(|e| AppError::database("Failed to update user", e))?;
+
+        if result.rows_affected == 0 {
+            return Err(AppError::not_found("User", &user.uuid));
+        }
+        Ok(())
+    }
+
+    /// Replace an account's password hash
+    pub async fn set_password_hash(
+        db: &DatabaseConnection,
+        uuid: &str,
+        password_hash: String,
+    ) -> Result<(), AppError> {
+        let result = user::Entity::update_many()
+            .col_expr(
+                user::Column::PasswordHash,
+                sea_query::Expr::value(password_hash),
+            )
+            .col_expr(
+                user::Column::UpdatedAt,
+                sea_query::Expr::value(chrono::Utc::now().to_rfc3339()),
+            )
+            .filter(user::Column::Uuid.eq(uuid))
+            .exec(db)
+            .await
+            .map_err(|e| AppError::database("Failed to change password", e))?;
+
+        if result.rows_affected == 0 {
+            return Err(AppError::not_found("User", uuid));
+        }
+        Ok(())
+    }
+
+    /// Set or clear (`None`) an account's PIN hash
+    pub async fn set_pin_hash(
+        db: &DatabaseConnection,
+        uuid: &str,
+        pin_hash: Option<String>,
+    ) -> Result<(), AppError> {
+        let result = user::Entity::update_many()
+            .col_expr(user::Column::PinHash, sea_query::Expr::value(pin_hash))
+            .col_expr(
+                user::Column::UpdatedAt,
+                sea_query::Expr::value(chrono::Utc::now().to_rfc3339()),
+            )
+            .filter(user::Column::Uuid.eq(uuid))
+            .exec(db)
+            .await
+            .map_err(|e| AppError::database("Failed to change PIN", e))?;
+
+        if result.rows_affected == 0 {
+            return Err(AppError::not_found("User", uuid));
+        }
+        Ok(())
+    }
+
+    /// Stamp the time of a successful sign-in
+    pub async fn record_login(db: &DatabaseConnection, uuid: &str) -> Result<String, AppError> {
+        let now = chrono::Utc::now().to_rfc3339();
+        user::Entity::update_many()
+            .col_expr(
+                user::Column::LastLoginAt,
+                sea_query::Expr::value(now.clone()),
+            )
+            .filter(user::Column::Uuid.eq(uuid))
+            .exec(db)
+            .await
+            .map_err(|e| AppError::database("Failed to record sign-in", e))?;
+
+        Ok(now)
+    }
+
+    /// Get an account and its password and PIN hashes by username, ignoring case
+    pub async fn get_user_credentials(
+        db: &DatabaseConnection,
+        username: &str,
+    ) -> Result<Option<(User, Credentials)>, AppError> {
+        let result = user::Entity::find()
+            .filter(user::Column::Username.eq(username))
+            .one(db)
+            .await
+            .map_err(|e| AppError::database("Failed to fetch user", e))?;
+
+        Ok(result.map(|model| {
+            let credentials = Credentials {
+                password_hash: model.password_hash.clone(),
+                pin_hash: model.pin_hash.clone(),
+            };
+            (Self::user_model_to_struct(model), credentials)
+        }))
+    }
+
+    /// Get an account and its password and PIN hashes by UUID
+    pub async fn get_user_credentials_by_uuid(
+        db: &DatabaseConnection,
+        uuid: &str,
+    ) -> Result<Option<(User, Credentials)>, AppError> {
+        let result = user::Entity::find()
+            .filter(user::Column::Uuid.eq(uuid))
+            .one(db)
+            .await
+            .map_err(|e| AppError::database("Failed to fetch user", e))?;
+
+        Ok(result.map(|model| {
+            let credentials = Credentials {
+                password_hash: model.password_hash.clone(),
+                pin_hash: model.pin_hash.clone(),
+            };
+            (Self::user_model_to_struct(model), credentials)
+        }))
+    }
+
+    /// Get all accounts, ordered by username
+    pub async fn get_all_users(db: &DatabaseConnection) -> Result<Vec<User>, AppError> {
+        let results = user::Entity::find()
+            .order_by_asc(user::Column::Username)
+            .all(db)
+            .await
+            .map_err(|e| AppError::database("Failed to fetch users", e))?;
+
+        Ok(results
+            .into_iter()
+            .map(Self::user_model_to_struct)
+            .collect())
+    }
+
+    /// Number of accounts, active or not
+    pub async fn count_users(db: &DatabaseConnection) -> Result<u64, AppError> {
+        user::Entity::find()
+            .count(db)
+            .await
+            .map_err(|e| AppError::database("Failed to count users", e))
+    }
+
+    /// Number of active administrator accounts
+    pub async fn count_active_admins(db: &DatabaseConnection) -> Result<u64, AppError> {
+        user::Entity::find()
+            .filter(user::Column::Role.eq(Role::Admin.as_str()))
+            .filter(user::Column::Active.eq(true))
+            .count(db)
+            .await
+            .map_err(|e| AppError::database("Failed to count administrators", e))
+    }
+
+    // ------------------------------------------------------------------------
+    // OUTBOUND MESSAGE OPERATIONS
+    // ------------------------------------------------------------------------
+
+    /// Queue a message for delivery
+    pub async fn insert_outbound_message(
+        db: &DatabaseConnection,
+        message: &OutboundMessage,
+    ) -> Result<i64, AppError> {
+        let message_model = outbound_message::ActiveModel {
+            protocol: Set(message.protocol.clone()),
+            test_uuid: Set(message.test_uuid.clone()),
+            control_id: Set(message.control_id.clone()),
+            payload: Set(message.payload.clone()),
+            status: Set(message.status.clone()),
+            attempts: Set(message.attempts),
+            last_error: Set(message.last_error.clone()),
+            ack_code: Set(message.ack_code.clone()),
+            created_at: Set(message.created_at.clone()),
+            next_attempt_at: Set(message.next_attempt_at.clone()),
+            last_attempt_at: Set(message.last_attempt_at.clone()),
+            acked_at: Set(message.acked_at.clone()),
+            ..Default::default()
+        };
+
+        let result = outbound_message::Entity::insert(message_model)
+            .exec(db)
+            .await
+            .map_err(|e| AppError::database("Failed to queue message", e))?;
+
+        Ok(result.last_insert_id)
+    }
+
+    /// Record a delivery attempt or a manual retry
+    pub async fn update_outbound_message(
+        db: &DatabaseConnection,
+        message: &OutboundMessage,
+    ) -> Result<(), AppError> {
+        let message_model = outbound_message::ActiveModel {
+            id: Set(message.id),
+            status: Set(message.status.clone()),
+            attempts: Set(message.attempts),
+            last_error: Set(message.last_error.clone()),
+            ack_code: Set(message.ack_code.clone()),
+            next_attempt_at: Set(message.next_attempt_at.clone()),
+            last_attempt_at: Set(message.last_attempt_at.clone()),
+            acked_at: Set(message.acked_at.clone()),
+            ..Default::default()
+        };
+
+        outbound_message::Entity::update(message_model)
+            .exec(db)
+            .await
+            .map_err(|e| match e {
+                DbErr::RecordNotUpdated => AppError::not_found("Message", &message.id.to_string()),
+                e => AppError::database("Failed to update message", e),
+            })?;
+
+        Ok(())
+    }
+
+    pub async fn get_outbound_message(
+        db: &DatabaseConnection,
+        id: i64,
+    ) -> Result<Option<OutboundMessage>, AppError> {
+        let result = outbound_message::Entity::find_by_id(id)
+            .one(db)
+            .await
+            .map_err(|e| AppError::database("Failed to fetch message", e))?;
+
+        Ok(result.map(Self::outbound_message_model_to_struct))
+    }
+
+    /// Pending messages whose next attempt is due, oldest first
+    pub async fn get_due_outbound_messages(
+        db: &DatabaseConnection,
+        now: &str,
+        limit: u64,
+    ) -> Result<Vec<OutboundMessage>, AppError> {
+        let results = outbound_message::Entity::find()
+            .filter(outbound_message::Column::Status.eq(MessageStatus::Pending.as_str()))
+            .filter(outbound_message::Column::NextAttemptAt.lte(now))
+            .order_by_asc(outbound_message::Column::Id)
+            .limit(limit)
+            .all(db)
+            .await
+            .map_err(|e| AppError::database("Failed to fetch due messages", e))?;
+
+        Ok(results
+            .into_iter()
+            .map(Self::outbound_message_model_to_struct)
+            .collect())
+    }
+
+    /// The most recent messages in any state, newest first
+    pub async fn list_outbound_messages(
+        db: &DatabaseConnection,
+        limit: u64,
+    ) -> Result<Vec<OutboundMessage>, AppError> {
+        let results = outbound_message::Entity::find()
+            .order_by_desc(outbound_message::Column::Id)
+            .limit(limit)
+            .all(db)
+            .await
+            .map_err(|e| AppError::database("Failed to list messages", e))?;
+
+        Ok(results
+            .into_iter()
+            .map(Self::outbound_message_model_to_struct)
+            .collect())
+    }
+
+    // ------------------------------------------------------------------------
+    // QUALITY CONTROL OPERATIONS
+    // ------------------------------------------------------------------------
+
+    pub async fn insert_control_material(
+        db: &DatabaseConnection,
+        material: &ControlMaterial,
+    ) -> Result<i64, AppError> {
+        let material_model = control_material::ActiveModel {
+            name: Set(material.name.clone()),
+            level: Set(material.level.as_str().to_string()),
+            test_type: Set(material.test_type.clone()),
+            lot_number: Set(material.lot_number.clone()),
+            expires_on: Set(material.expires_on.clone()),
+            expected_min: Set(material.expected_min),
+            expected_max: Set(material.expected_max),
+            active: Set(material.active),
+            created_at: Set(material.created_at.clone()),
+            updated_at: Set(material.updated_at.clone()),
+            ..Default::default()
+        };
+
+        let result = control_material::Entity::insert(material_model)
+            .exec(db)
+            .await
+            .map_err(|e| AppError::database("Failed to add control material", e))?;
+
+        Ok(result.last_insert_id)
+    }
+
+    pub async fn update_control_material(
+        db: &DatabaseConnection,
+        material: &ControlMaterial,
+    ) -> Result<(), AppError> {
+        let material_model = control_material::ActiveModel {
+            id: Set(material.id),
+            name: Set(material.name.clone()),
+            level: Set(material.level.as_str().to_string()),
+            test_type: Set(material.test_type.clone()),
+            lot_number: Set(material.lot_number.clone()),
+            expires_on: Set(material.expires_on.clone()),
+            expected_min: Set(material.expected_min),
+            expected_max: Set(material.expected_max),
+            active: Set(material.active),
+            updated_at: Set(material.updated_at.clone()),
+            ..Default::default()
+        };
+
+        control_material::Entity::update(material_model)
+            .exec(db)
+            .await
+            .map_err(|e| match e {
+                DbErr::RecordNotUpdated => {
+                    AppError::not_found("Control material", &material.id.to_string())
+                }
+                e => AppError::database("Failed to update control material", e),
+            })?;
+
+        Ok(())
+    }
+
+    pub async fn get_control_material(
+        db: &DatabaseConnection,
+        id: i64,
+    ) -> Result<Option<ControlMaterial>, AppError> {
+        let result = control_material::Entity::find_by_id(id)
+            .one(db)
+            .await
+            .map_err(|e| AppError::database("Failed to fetch control material", e))?;
+
+        Ok(result.map(Self::control_material_model_to_struct))
+    }
+
+    /// All control materials, grouped by test type and level
+    pub async fn get_all_control_materials(
+        db: &DatabaseConnection,
+    ) -> Result<Vec<ControlMaterial>, AppError> {
+        let results = control_material::Entity::find()
+            .order_by_asc(control_material::Column::TestType)
+            .order_by_asc(control_material::Column::Level)
+            .order_by_asc(control_material::Column::Name)
+            .all(db)
+            .await
+            .map_err(|e| AppError::database("Failed to list control materials", e))?;
+
+        Ok(results
+            .into_iter()
+            .map(Self::control_material_model_to_struct)
+            .collect())
+    }
+
+    pub async fn insert_qc_run(db: &DatabaseConnection, run: &QcRun) -> Result<i64, AppError> {
+        let run_model = qc_run::ActiveModel {
+            uuid: Set(run.uuid.clone()),
+            material_id: Set(run.material_id),
+            device_id: Set(run.device_id.clone()),
+            test_type: Set(run.test_type.clone()),
+            level: Set(run.level.clone()),
+            lot_number: Set(run.lot_number.clone()),
+            status: Set(run.status.clone()),
+            value: Set(run.value),
+            detection_result: Set(run.detection_result.clone()),
+            violations: Set(Self::violations_to_json(run)?),
+            operated_by: Set(run.operated_by.clone()),
+            created_at: Set(run.created_at.clone()),
+            completed_at: Set(run.completed_at.clone()),
+            ..Default::default()
+        };
+
+        let result = qc_run::Entity::insert(run_model)
+            .exec(db)
+            .await
+            .map_err(|e| AppError::database("Failed to insert QC run", e))?;
+
+        Ok(result.last_insert_id)
+    }
+
+    /// Store the result or cancellation of a QC run
+    pub async fn update_qc_run(db: &DatabaseConnection, run: &QcRun) -> Result<(), AppError> {
+        let run_model = qc_run::ActiveModel {
+            id: Set(run.id),
+            status: Set(run.status.clone()),
+            value: Set(run.value),
+            detection_result: Set(run.detection_result.clone()),
+            violations: Set(Self::violations_to_json(run)?),
+            completed_at: Set(run.completed_at.clone()),
+            ..Default::default()
+        };
+
+        qc_run::Entity::update(run_model)
+            .exec(db)
+            .await
+            .map_err(|e| match e {
+                DbErr::RecordNotUpdated => AppError::not_found("QC run", &run.uuid),
+                e => AppError::database("Failed to update QC run", e),
+            })?;
+
+        Ok(())
+    }
+
+    pub async fn get_qc_run_by_uuid(
+        db: &DatabaseConnection,
+        uuid: &str,
+    ) -> Result<Option<QcRun>, AppError> {
+        let result = qc_run::Entity::find()
+            .filter(qc_run::Column::Uuid.eq(uuid))
+            .one(db)
+            .await
+            .map_err(|e| AppError::database("Failed to fetch QC run", e))?;
+
+        Ok(result.map(Self::qc_run_model_to_struct))
+    }
+
+    /// Completed runs of one lot of a material on a device, newest first
+    pub async fn get_qc_runs_for_lot(
+        db: &DatabaseConnection,
+        material_id: i64,
+        lot_number: &str,
+        device_id: &str,
+        limit: u64,
+    ) -> Result<Vec<QcRun>, AppError> {
+        let results = qc_run::Entity::find()
+            .filter(qc_run::Column::MaterialId.eq(material_id))
+            .filter(qc_run::Column::LotNumber.eq(lot_number))
+            .filter(qc_run::Column::DeviceId.eq(device_id))
+            .filter(qc_run::Column::Status.is_in(QcStatus::completed().map(|s| s.as_str())))
+            .order_by_desc(qc_run::Column::CompletedAt)
+            .order_by_desc(qc_run::Column::Id)
+            .limit(limit)
+            .all(db)
+            .await
+            .map_err(|e| AppError::database("Failed to fetch QC runs", e))?;
+
+        Ok(results
+            .into_iter()
+            .map(Self::qc_run_model_to_struct)
+            .collect())
+    }
+
+    /// The last completed run of a control level for a test type on a device
+    pub async fn get_latest_qc_run(
+        db: &DatabaseConnection,
+        device_id: &str,
+        test_type: &str,
+        level: ControlLevel,
+    ) -> Result<Option<QcRun>, AppError> {
+        let result = qc_run::Entity::find()
+            .filter(qc_run::Column::DeviceId.eq(device_id))
+            .filter(qc_run::Column::TestType.eq(test_type))
+            .filter(qc_run::Column::Level.eq(level.as_str()))
+            .filter(qc_run::Column::Status.is_in(QcStatus::completed().map(|s| s.as_str())))
+            .order_by_desc(qc_run::Column::CompletedAt)
+            .order_by_desc(qc_run::Column::Id)
+            .one(db)
+            .await
+            .map_err(|e| AppError::database("Failed to fetch latest QC run", e))?;
+
+        Ok(result.map(Self::qc_run_model_to_struct))
+    }
+
     // ------------------------------------------------------------------------
     // HELPER FUNCTIONS
     // ------------------------------------------------------------------------
 
+    fn outbound_message_model_to_struct(model: outbound_message::Model) -> OutboundMessage {
+        OutboundMessage {
+            id: model.id,
+            protocol: model.protocol,
+            test_uuid: model.test_uuid,
+            control_id: model.control_id,
+            payload: model.payload,
+            status: model.status,
+            attempts: model.attempts,
+            last_error: model.last_error,
+            ack_code: model.ack_code,
+            created_at: model.created_at,
+            next_attempt_at: model.next_attempt_at,
+            last_attempt_at: model.last_attempt_at,
+            acked_at: model.acked_at,
+        }
+    }
+
+    fn control_material_model_to_struct(model: control_material::Model) -> ControlMaterial {
+        ControlMaterial {
+            id: model.id,
+            name: model.name,
+            // Materials are only stored through `normalized`; fall back to the safer reading
+            level: ControlLevel::from_str(&model.level).unwrap_or(ControlLevel::Negative),
+            test_type: model.test_type,
+            lot_number: model.lot_number,
+            expires_on: model.expires_on,
+            expected_min: model.expected_min,
+            expected_max: model.expected_max,
+            active: model.active,
+            created_at: model.created_at,
+            updated_at: model.updated_at,
+        }
+    }
+
+    fn qc_run_model_to_struct(model: qc_run::Model) -> QcRun {
+        QcRun {
+            id: model.id,
+            uuid: model.uuid,
+            material_id: model.material_id,
+            device_id: model.device_id,
+            test_type: model.test_type,
+            level: model.level,
+            lot_number: model.lot_number,
+            status: model.status,
+            value: model.value,
+            detection_result: model.detection_result,
+            violations: model
+                .violations
+                .and_then(|json| serde_json::from_str(&json).ok())
+                .unwrap_or_default(),
+            operated_by: model.operated_by,
+            created_at: model.created_at,
+            completed_at: model.completed_at,
+        }
+    }
+
+    fn device_model_to_struct(model: device::Model) -> Device {
+        // Fall back to defaults for values the driver can't represent
+        let defaults = SerialProfile::default();
+        let profile = SerialProfile {
+            baud_rate: u32::try_from(model.baud_rate).unwrap_or(defaults.baud_rate),
+            data_bits: u8::try_from(model.data_bits).unwrap_or(defaults.data_bits),
+            parity: Parity::from_str(&model.parity).unwrap_or(defaults.parity),
+            stop_bits: u8::try_from(model.stop_bits).unwrap_or(defaults.stop_bits),
+            flow_control: FlowControl::from_str(&model.flow_control)
+                .unwrap_or(defaults.flow_control),
+            read_timeout_ms: u64::try_from(model.read_timeout_ms)
+                .unwrap_or(defaults.read_timeout_ms),
+            chunk_size: usize::try_from(model.chunk_size).unwrap_or(defaults.chunk_size),
+        };
+        let schema = model
+            .column_schema
+            .and_then(|json| serde_json::from_str::<ColumnSchema>(&json).ok())
+            .filter(|schema| schema.validate().is_ok())
+            .unwrap_or_default();
+
+        Device {
+            id: Some(model.id),
+            device_id: model.device_id,
+            label: model.label,
+            port: model.port,
+            enabled: model.enabled,
+            profile,
+            schema,
+            created_at: model.created_at,
+            updated_at: model.updated_at,
+        }
+    }
+
     fn patient_model_to_struct(model: patient::Model) -> Patient {
         Patient {
             id: Some(model.id),
@@ -264,6 +1467,74 @@ impl Database {
             created_at: model.created_at,
             updated_at: model.updated_at,
             completed_at: model.completed_at,
+            quality_report: model
+                .quality_report
+                .and_then(|json| serde_json::from_str(&json).ok()),
+            operated_by: model.operated_by,
+            released_by: model.released_by,
+            released_at: model.released_at,
+            review_log: model
+                .review_log
+                .and_then(|json| serde_json::from_str(&json).ok())
+                .unwrap_or_default(),
+            retest_of: model.retest_of,
+            specimen_id: model.specimen_id,
+            accession_number: model.accession_number,
+            signal_preview: model
+                .signal_preview
+                .and_then(|json| serde_json::from_str(&json).ok())
+                .unwrap_or_default(),
+        }
+    }
+
+    fn user_model_to_struct(model: user::Model) -> User {
+        User {
+            id: Some(model.id),
+            uuid: model.uuid,
+            username: model.username,
+            display_name: model.display_name,
+            // Roles are constrained by the roles table; anything else gets least privilege
+            role: Role::from_str(&model.role).unwrap_or(Role::Operator),
+            active: model.active,
+            created_at: model.created_at,
+            updated_at: model.updated_at,
+            last_login_at: model.last_login_at,
+            has_pin: model.pin_hash.is_some(),
+        }
+    }
+
+    fn quality_report_to_json(test: &Test) -> Result<Option<String>, AppError> {
+        test.quality_report
+            .as_ref()
+            .map(serde_json::to_string)
+            .transpose()
+            .map_err(|e| AppError::internal("Failed to serialize quality report").with_details(e))
+    }
+
+    fn signal_preview_to_json(test: &Test) -> Result<Option<String>, AppError> {
+        if test.signal_preview.is_empty() {
+            return Ok(None);
+        }
+        serde_json::to_string(&test.signal_preview)
+            .map(Some)
+            .map_err(|e| AppError::internal("Failed to serialize signal preview").with_details(e))
+    }
+
+    fn review_log_to_json(test: &Test) -> Result<Option<String>, AppError> {
+        if test.review_log.is_empty() {
+            return Ok(None);
+        }
+        serde_json::to_string(&test.review_log)
+            .map(Some)
+            .map_err(|e| AppError::internal("Failed to serialize review log").with_details(e))
+    }
+
+    fn violations_to_json(run: &QcRun) -> Result<Option<String>, AppError> {
+        if run.violations.is_empty() {
+            return Ok(None);
         }
+        serde_json::to_string(&run.violations)
+            .map(Some)
+            .map_err(|e| AppError::internal("Failed to serialize QC violations").with_details(e))
     }
 }
@@ -1,7 +1,10 @@
+use crate::csv_ingest::Sample;
+use crate::models::{AppError, ColumnSchema, PreprocessStep};
 use serde::{Deserialize, Serialize};
 use std::time::Duration;
+use tracing::{debug, warn};
 
-/// Represents a single data point from the CSV
+/// Represents a single data point from the CSV (the first data channel)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DataPoint {
     pub index: u32,
@@ -9,12 +12,31 @@ pub struct DataPoint {
     pub value: f64,
 }
 
+/// Full series for one named data channel
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChannelSeries {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unit: Option<String>,
+    pub values: Vec<f64>,
+}
+
+/// Preprocessing applied to the dataset before it was sent
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PreprocessingMetadata {
+    pub test_type: Option<String>,
+    pub steps: Vec<PreprocessStep>,
+    pub input_samples: usize,
+    pub output_samples: usize,
+}
+
 /// Metadata about the data collection
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DatasetMetadata {
     pub port: String,
     pub baud_rate: u32,
     pub collection_duration_ms: u64,
+    pub preprocessing: PreprocessingMetadata,
 }
 
 /// Request payload sent to the detection API
@@ -24,6 +46,9 @@ pub struct DetectionRequest {
     pub timestamp: String,
     pub row_count: usize,
     pub data: Vec<DataPoint>,
+    /// Every channel for multi-channel devices; omitted for the legacy format
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub channels: Vec<ChannelSeries>,
     pub metadata: DatasetMetadata,
 }
 
@@ -61,7 +86,7 @@ pub struct DetectionErrorResponse {
 }
 
 /// Result type for detection operations
-pub type DetectionResult = Result<DetectionResponse, String>;
+pub type DetectionResult = Result<DetectionResponse, AppError>;
 
 /// API client for making detection requests
 #[derive(Clone)]
@@ -88,40 +113,40 @@ impl DetectionApiClient {
 
     /// Make a detection request with retry logic
     pub async fn detect(&self, request: DetectionRequest) -> DetectionResult {
-        let mut last_error = String::new();
+        let mut last_error = AppError::detection_api("Detection API was not called");
 
         for attempt in 1..=self.max_retries {
-            println!(
-                "[api_client] Attempt {}/{} to call detection API",
+            debug!(
+                "Attempt {}/{} to call detection API",
                 attempt, self.max_retries
             );
 
             match self.make_request(&request).await {
                 Ok(response) => {
-                    println!(
-                        "[api_client] Detection successful: probability={}",
-                        response.probability
-                    );
+                    debug!("Detection successful: probability={}", response.probability);
                     return Ok(response);
                 }
                 Err(err) => {
-                    last_error = err.clone();
-                    println!("[api_client] Attempt {} failed: {}", attempt, err);
+                    warn!("Attempt {} failed: {}", attempt, err);
+                    last_error = err;
 
                     if attempt < self.max_retries {
                         // Exponential backoff: 1s, 2s, 4s
                         let backoff_ms = 1000 * (1 << (attempt - 1));
-                        println!("[api_client] Retrying in {}ms...", backoff_ms);
+                        debug!("Retrying in {}ms...", backoff_ms);
                         tokio::time::sleep(Duration::from_millis(backoff_ms)).await;
                     }
                 }
             }
         }
 
-        Err(format!(
-            "Failed after {} attempts. Last error: {}",
-            self.max_retries, last_error
-        ))
+        Err(AppError {
+            message: format!(
+                "Detection failed after {} attempts. {}",
+                self.max_retries, last_error.message
+            ),
+            ..last_error
+        })
     }
 
     /// Make a single HTTP request to the API
@@ -134,111 +159,100 @@ impl DetectionApiClient {
             .send()
             .await
             .map_err(|e| {
-                if e.is_timeout() {
-                    "Request timeout - API took too long to respond".to_string()
+                let message = if e.is_timeout() {
+                    "Request timeout - API took too long to respond"
                 } else if e.is_connect() {
-                    "Cannot connect to API - check if the service is running".to_string()
+                    "Cannot connect to API - check if the service is running"
                 } else {
-                    format!("Network error: {}", e)
-                }
+                    "Network error while calling the detection API"
+                };
+                AppError::detection_api(message).with_details(e)
             })?;
 
         let status = response.status();
 
         // Handle successful response (200-299)
         if status.is_success() {
-            let detection = response
-                .json::<DetectionResponse>()
-                .await
-                .map_err(|e| format!("Failed to parse API response: {}", e))?;
+            let detection = response.json::<DetectionResponse>().await.map_err(|e| {
+                AppError::detection_api("Detection API returned an unreadable response")
+                    .with_details(e)
+            })?;
 
             return Ok(detection);
         }
 
         // Handle error responses (4xx, 5xx)
         if let Ok(error_response) = response.json::<DetectionErrorResponse>().await {
-            return Err(format!(
-                "API error [{}]: {}",
-                error_response.error.code, error_response.error.message
-            ));
+            let error = error_response.error;
+            let details = match error.details {
+                Some(details) => format!("[{}] {}", error.code, details),
+                None => format!("[{}]", error.code),
+            };
+            return Err(
+                AppError::detection_api(format!("Detection API error: {}", error.message))
+                    .with_details(details),
+            );
         }
 
         // Fallback for unexpected responses
-        Err(format!("API returned error status: {}", status))
+        Err(AppError::detection_api(format!(
+            "Detection API returned error status {}",
+            status
+        )))
     }
 }
 
-/// Parse CSV buffer into structured data points
-pub fn parse_csv_data(csv_buffer: &str) -> Result<Vec<DataPoint>, String> {
-    let mut data_points = Vec::new();
-
-    for (line_num, line) in csv_buffer.lines().enumerate() {
-        let line = line.trim();
-        if line.is_empty() {
-            continue;
-        }
-
-        let parts: Vec<&str> = line.split(',').collect();
-        if parts.len() != 3 {
-            return Err(format!(
-                "Invalid CSV format at line {}: expected 3 columns, got {}",
-                line_num + 1,
-                parts.len()
-            ));
-        }
-
-        // Parse based on actual data format: timestamp,index,value
-        let timestamp = parts[0]
-            .trim()
-            .parse::<f64>()
-            .map_err(|e| format!("Invalid timestamp at line {}: {}", line_num + 1, e))?;
-
-        let index = parts[1]
-            .trim()
-            .parse::<u32>()
-            .map_err(|e| format!("Invalid index at line {}: {}", line_num + 1, e))?;
-
-        let value = parts[2]
-            .trim()
-            .parse::<f64>()
-            .map_err(|e| format!("Invalid value at line {}: {}", line_num + 1, e))?;
-
-        data_points.push(DataPoint {
-            index,
-            timestamp,
-            value,
-        });
-    }
-
-    if data_points.is_empty() {
-        return Err("No valid data points found in CSV".to_string());
-    }
-
-    Ok(data_points)
-}
-
-/// Create a detection request from CSV data
+/// Create a detection request from parsed samples
+///
+/// `data` always carries the first channel so single-value consumers keep
+/// working; multi-channel devices additionally get one series per channel.
 pub fn create_detection_request(
-    csv_buffer: &str,
+    samples: Vec<Sample>,
+    schema: &ColumnSchema,
     port: String,
     baud_rate: u32,
     collection_duration_ms: u64,
-) -> Result<DetectionRequest, String> {
-    let data = parse_csv_data(csv_buffer)?;
-    let row_count = data.len();
+    preprocessing: PreprocessingMetadata,
+) -> DetectionRequest {
+    let row_count = samples.len();
+
+    let channels = if schema.is_multi_channel() {
+        schema
+            .channels()
+            .enumerate()
+            .map(|(i, column)| ChannelSeries {
+                name: column.name.clone(),
+                unit: column.unit.clone(),
+                values: samples.iter().map(|s| s.values[i]).collect(),
+            })
+            .collect()
+    } else {
+        Vec::new()
+    };
+
+    let data = samples
+        .into_iter()
+        .map(|s| DataPoint {
+            index: s.index,
+            timestamp: s.timestamp,
+            value: s.values[0],
+        })
+        .collect();
 
     let dataset_id = uuid::Uuid::new_v4().to_string();
     let timestamp = chrono::Utc::now().to_rfc3339();
 
-    Ok(DetectionRequest {
+    DetectionRequest {
         dataset_id,
         timestamp,
         row_count,
         data,
+        channels,
         metadata: DatasetMetadata {
             port,
             baud_rate,
             collection_duration_ms,
+            preprocessing,
         },
-    })
+    }
 }
@@ -0,0 +1,175 @@
+use crate::models::{AppError, DeviceStatus};
+use crate::serial_reader::{ReaderHandle, ReaderStats};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tauri::async_runtime::JoinHandle;
+
+/// Runtime state for a single monitored device
+struct DeviceSlot {
+    status: DeviceStatus,
+    task: Option<JoinHandle<()>>,
+    reader: Option<ReaderHandle>,
+}
+
+/// Registry of serial devices being monitored concurrently
+///
+/// Each device gets its own monitor task and status. Tests are routed to a
+/// device by assigning the test UUID, so that the dataset the device produces
+/// next is attributed to that test.
+#[derive(Default)]
+pub struct DeviceRegistry {
+    slots: Mutex<HashMap<String, DeviceSlot>>,
+}
+
+impl DeviceRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a device, stopping any monitor task already running for it
+    pub fn register(&self, status: DeviceStatus) {
+        let mut slots = self.slots.lock().unwrap();
+        let previous = slots.insert(
+            status.device_id.clone(),
+            DeviceSlot {
+                status,
+                task: None,
+                reader: None,
+            },
+        );
+        // Dropping the previous reader handle signals its thread to stop
+        if let Some(task) = previous.and_then(|slot| slot.task) {
+            task.abort();
+        }
+    }
+
+    /// Attach the monitor task and reader thread for a registered device
+    pub fn attach_task(&self, device_id: &str, task: JoinHandle<()>, reader: ReaderHandle) {
+        let mut slots = self.slots.lock().unwrap();
+        match slots.get_mut(device_id) {
+            Some(slot) => {
+                slot.task = Some(task);
+                slot.reader = Some(reader);
+            }
+            None => task.abort(),
+        }
+    }
+
+    /// Remove a device and stop its monitor task, returning its reader so the
+    /// caller can wait for the port to be released
+    pub fn unregister(&self, device_id: &str) -> Option<ReaderHandle> {
+        let slot = self.slots.lock().unwrap().remove(device_id)?;
+        if let Some(task) = slot.task {
+            task.abort();
+        }
+        slot.reader
+    }
+
+    /// Whether a monitor task is running for the device
+    pub fn is_running(&self, device_id: &str) -> bool {
+        self.slots
+            .lock()
+            .unwrap()
+            .get(device_id)
+            .is_some_and(|slot| slot.task.is_some())
+    }
+
+    /// Apply a change to a device status and return the updated snapshot
+    pub fn update_status<F>(&self, device_id: &str, f: F) -> Option<DeviceStatus>
+    where
+        F: FnOnce(&mut DeviceStatus),
+    {
+        let mut slots = self.slots.lock().unwrap();
+        let slot = slots.get_mut(device_id)?;
+        f(&mut slot.status);
+        Some(slot.status.clone())
+    }
+
+    /// Snapshot of a single device status
+    pub fn status(&self, device_id: &str) -> Option<DeviceStatus> {
+        self.slots
+            .lock()
+            .unwrap()
+            .get(device_id)
+            .map(|slot| slot.status.clone())
+    }
+
+    /// Snapshot of all device statuses, ordered by device_id
+    pub fn statuses(&self) -> Vec<DeviceStatus> {
+        let mut statuses: Vec<DeviceStatus> = self
+            .slots
+            .lock()
+            .unwrap()
+            .values()
+            .map(|slot| slot.status.clone())
+            .collect();
+        statuses.sort_by(|a, b| a.device_id.cmp(&b.device_id));
+        statuses
+    }
+
+    /// Activity counters of every running reader thread, ordered by device_id
+    pub fn reader_stats(&self) -> Vec<ReaderStats> {
+        let mut stats: Vec<ReaderStats> = self
+            .slots
+            .lock()
+            .unwrap()
+            .values()
+            .filter_map(|slot| slot.reader.as_ref().map(|reader| reader.stats()))
+            .collect();
+        stats.sort_by(|a, b| a.device_id.cmp(&b.device_id));
+        stats
+    }
+
+    /// Route a test to a device; the next dataset from the device belongs to it
+    ///
+    /// A device runs one test at a time: the test it holds has to be released
+    /// before another can be assigned.
+    pub fn assign_test(&self, device_id: &str, test_uuid: &str) -> Result<DeviceStatus, AppError> {
+        let mut slots = self.slots.lock().unwrap();
+
+        if let Some((other, _)) = slots.iter().find(|(id, slot)| {
+            id.as_str() != device_id && slot.status.active_test_uuid.as_deref() == Some(test_uuid)
+        }) {
+            return Err(AppError::conflict(format!(
+                "Test {} is already assigned to {}",
+                test_uuid, other
+            )));
+        }
+
+        let slot = slots
+            .get_mut(device_id)
+            .ok_or_else(|| AppError::not_found("Device", device_id))?;
+        if let Some(active) = slot
+            .status
+            .active_test_uuid
+            .as_deref()
+            .filter(|active| *active != test_uuid)
+        {
+            return Err(AppError::conflict(format!(
+                "Device {} is running test {}",
+                device_id, active
+            )));
+        }
+        slot.status.active_test_uuid = Some(test_uuid.to_string());
+        Ok(slot.status.clone())
+    }
+
+    /// Test currently routed to the device, if any
+    pub fn active_test(&self, device_id: &str) -> Option<String> {
+        self.slots
+            .lock()
+            .unwrap()
+            .get(device_id)
+            .and_then(|slot| slot.status.active_test_uuid.clone())
+    }
+
+    /// Clear the assignment of a test from whichever device holds it
+    pub fn release_test(&self, test_uuid: &str) -> Option<DeviceStatus> {
+        let mut slots = self.slots.lock().unwrap();
+        let slot = slots
+            .values_mut()
+            .find(|slot| slot.status.active_test_uuid.as_deref() == Some(test_uuid))?;
+        slot.status.active_test_uuid = None;
+        Some(slot.status.clone())
+    }
+}
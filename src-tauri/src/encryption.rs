@@ -0,0 +1,388 @@
+use crate::backup::{remove_database_files, sibling};
+use crate::db_orm::Database;
+use crate::models::AppError;
+use rand::RngCore;
+use sea_orm::DatabaseConnection;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use tracing::{error, info, warn};
+use zeroize::Zeroizing;
+
+/// Encryption settings, kept beside the database since they are needed to open it
+pub const DATABASE_CONFIG_FILE: &str = "database.json";
+/// Holds the generated key when the OS keyring is unavailable (e.g. headless Linux CI)
+pub const KEY_FILE: &str = "ebers.key";
+/// Environment variable supplying the passphrase at startup instead of the unlock screen
+pub const PASSPHRASE_ENV: &str = "EBERS_DB_PASSPHRASE";
+
+const KEYRING_SERVICE: &str = "ebers";
+const KEYRING_USER: &str = "database-key";
+/// Generated keys are 256-bit raw SQLCipher keys
+const KEY_BYTES: usize = 32;
+const MIN_PASSPHRASE_CHARS: usize = 12;
+
+/// How the database file is protected at rest
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EncryptionMode {
+    /// Plain SQLite
+    #[default]
+    None,
+    /// Random key held in the OS keyring (or `KEY_FILE` as a fallback)
+    Keyring,
+    /// Key derived by SQLCipher from a passphrase the operator enters
+    Passphrase,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DatabaseConfig {
+    pub encryption: EncryptionMode,
+}
+
+/// Secret handed to SQLCipher, wiped from memory when dropped
+///
+/// Either a passphrase (run through SQLCipher's PBKDF2) or a raw key in
+/// SQLCipher's `x'<hex>'` form, which skips key derivation.
+#[derive(Clone)]
+pub struct DatabaseKey(Zeroizing<String>);
+
+impl DatabaseKey {
+    pub fn from_passphrase(passphrase: &str) -> Self {
+        Self(Zeroizing::new(passphrase.to_string()))
+    }
+
+    fn from_raw(bytes: &[u8]) -> Self {
+        Self(Zeroizing::new(format!("x'{}'", hex::encode(bytes))))
+    }
+
+    fn generate() -> Self {
+        let mut bytes = Zeroizing::new([0u8; KEY_BYTES]);
+        rand::rngs::OsRng.fill_bytes(bytes.as_mut());
+        Self::from_raw(bytes.as_ref())
+    }
+
+    /// Parse a key as stored in the keyring or key file
+    fn from_stored(stored: &str) -> Result<Self, AppError> {
+        let bytes =
+            Zeroizing::new(hex::decode(stored.trim()).map_err(|e| {
+                AppError::internal("Stored database key is corrupt").with_details(e)
+            })?);
+        if bytes.len() != KEY_BYTES {
+            return Err(AppError::internal(
+                "Stored database key has the wrong length",
+            ));
+        }
+        Ok(Self::from_raw(&bytes))
+    }
+
+    /// Hex form written to the keyring or key file
+    fn to_stored(&self) -> Zeroizing<String> {
+        Zeroizing::new(
+            self.0
+                .trim_start_matches("x'")
+                .trim_end_matches('\'')
+                .to_string(),
+        )
+    }
+
+    /// The secret as a quoted SQL string for `PRAGMA key`
+    pub fn pragma_value(&self) -> String {
+        format!("'{}'", self.0.replace('\'', "''"))
+    }
+
+    /// The secret as a bind parameter, e.g. for `ATTACH ... KEY ?`
+    fn secret(&self) -> &str {
+        &self.0
+    }
+}
+
+/// Reject passphrases too short to resist guessing
+pub fn validate_passphrase(passphrase: &str) -> Result<(), AppError> {
+    if passphrase.chars().count() < MIN_PASSPHRASE_CHARS {
+        return Err(AppError::validation(format!(
+            "Passphrase must be at least {} characters",
+            MIN_PASSPHRASE_CHARS
+        )));
+    }
+    Ok(())
+}
+
+/// Protection of the open database, managed as `EncryptionState`
+pub struct Encryption {
+    pub mode: EncryptionMode,
+    /// Key the open database was opened with; `None` when plain or still locked
+    pub key: Option<DatabaseKey>,
+    /// False until the passphrase has been entered
+    pub unlocked: bool,
+}
+
+pub type EncryptionState = std::sync::Mutex<Encryption>;
+
+/// Encryption state reported to the UI
+#[derive(Debug, Clone, Serialize)]
+pub struct EncryptionStatus {
+    pub mode: EncryptionMode,
+    pub unlocked: bool,
+    /// Set when the keyring key lives in a file because no OS keyring was available
+    pub key_file: Option<String>,
+}
+
+impl Encryption {
+    pub fn status(&self, data_dir: &Path) -> EncryptionStatus {
+        let key_file = data_dir.join(KEY_FILE);
+        EncryptionStatus {
+            mode: self.mode,
+            unlocked: self.unlocked,
+            key_file: (self.mode == EncryptionMode::Keyring && key_file.exists())
+                .then(|| key_file.to_string_lossy().to_string()),
+        }
+    }
+}
+
+// ----------------------------------------------------------------------------
+// CONFIG
+// ----------------------------------------------------------------------------
+
+pub fn load_config(data_dir: &Path) -> Result<DatabaseConfig, AppError> {
+    match fs::read_to_string(data_dir.join(DATABASE_CONFIG_FILE)) {
+        Ok(json) => serde_json::from_str(&json)
+            .map_err(|e| AppError::internal("Failed to parse database config").with_details(e)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(DatabaseConfig::default()),
+        Err(e) => Err(AppError::io("Failed to read database config", e)),
+    }
+}
+
+pub fn save_config(data_dir: &Path, config: &DatabaseConfig) -> Result<(), AppError> {
+    let json = serde_json::to_string_pretty(config)
+        .map_err(|e| AppError::internal("Failed to encode database config").with_details(e))?;
+    let path = data_dir.join(DATABASE_CONFIG_FILE);
+    let tmp = sibling(&path, ".tmp");
+    fs::write(&tmp, json).map_err(|e| AppError::io("Failed to write database config", e))?;
+    fs::rename(&tmp, &path).map_err(|e| AppError::io("Failed to write database config", e))
+}
+
+// ----------------------------------------------------------------------------
+// KEYRING
+// ----------------------------------------------------------------------------
+
+fn keyring_entry() -> keyring::Result<keyring::Entry> {
+    keyring::Entry::new(KEYRING_SERVICE, KEYRING_USER)
+}
+
+/// Load the key for `EncryptionMode::Keyring`
+///
+/// A key file left by the fallback takes precedence, so a keyring that
+/// appears later does not hide the key the database was encrypted with.
+pub fn load_keyring_key(data_dir: &Path) -> Result<DatabaseKey, AppError> {
+    let key_file = data_dir.join(KEY_FILE);
+    if key_file.exists() {
+        let stored = Zeroizing::new(
+            fs::read_to_string(&key_file)
+                .map_err(|e| AppError::io("Failed to read database key file", e))?,
+        );
+        return DatabaseKey::from_stored(&stored);
+    }
+
+    let stored = Zeroizing::new(
+        keyring_entry()
+            .and_then(|entry| entry.get_password())
+            .map_err(|e| {
+                AppError::internal("Database key not found in the OS keyring").with_details(e)
+            })?,
+    );
+    DatabaseKey::from_stored(&stored)
+}
+
+/// Generate a new key and store it in the OS keyring, or `KEY_FILE` if there is none
+pub fn create_keyring_key(data_dir: &Path) -> Result<DatabaseKey, AppError> {
+    let key = DatabaseKey::generate();
+    let stored = key.to_stored();
+
+    match keyring_entry().and_then(|entry| entry.set_password(&stored)) {
+        Ok(()) => {
+            // A stale fallback file would otherwise take precedence
+            let _ = fs::remove_file(data_dir.join(KEY_FILE));
+            info!("Database key stored in the OS keyring");
+        }
+        Err(e) => {
+            warn!(
+                "OS keyring unavailable ({}); storing database key in a file",
+                e
+            );
+            write_key_file(&data_dir.join(KEY_FILE), &stored)?;
+        }
+    }
+    Ok(key)
+}
+
+/// Forget the keyring key once the database no longer uses it
+pub fn delete_keyring_key(data_dir: &Path) {
+    if let Err(e) = keyring_entry().and_then(|entry| entry.delete_credential()) {
+        if !matches!(e, keyring::Error::NoEntry) {
+            warn!("Failed to remove database key from the OS keyring: {}", e);
+        }
+    }
+    let _ = fs::remove_file(data_dir.join(KEY_FILE));
+}
+
+fn write_key_file(path: &Path, stored: &str) -> Result<(), AppError> {
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options
+        .open(path)
+        .map_err(|e| AppError::io("Failed to create database key file", e))?;
+    std::io::Write::write_all(&mut file, stored.as_bytes())
+        .map_err(|e| AppError::io("Failed to write database key file", e))
+}
+
+// ----------------------------------------------------------------------------
+// CONVERSION
+// ----------------------------------------------------------------------------
+
+/// Copy the whole database into a new file protected by `key` (plain if `None`)
+///
+/// Uses SQLCipher's `sqlcipher_export`, which also handles plain to
+/// encrypted and back. `dest` must not exist yet.
+pub async fn export(
+    db: &DatabaseConnection,
+    dest: &Path,
+    key: Option<&DatabaseKey>,
+) -> Result<(), AppError> {
+    use sea_orm::sqlx;
+
+    // ATTACH only affects one connection, so run everything on the same one
+    let mut conn = db
+        .get_sqlite_connection_pool()
+        .acquire()
+        .await
+        .map_err(|e| AppError::database("Failed to acquire database connection", e))?;
+
+    sqlx::query("ATTACH DATABASE ? AS export KEY ?")
+        .bind(dest.to_string_lossy().to_string())
+        .bind(key.map(|k| k.secret()).unwrap_or(""))
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| AppError::database("Failed to create export database", e))?;
+
+    let exported = sqlx::query("SELECT sqlcipher_export('export')")
+        .execute(&mut *conn)
+        .await;
+    let detached = sqlx::query("DETACH DATABASE export")
+        .execute(&mut *conn)
+        .await;
+
+    exported.map_err(|e| AppError::database("Failed to export database", e))?;
+    detached.map_err(|e| AppError::database("Failed to detach export database", e))?;
+    Ok(())
+}
+
+/// Re-protect the live database with `new_key`, swapping the file in place
+///
+/// The database is exported to a sibling file, that copy is opened and
+/// checked, and only then replaces the original. `persist` records the new
+/// settings once the swapped file is open and before the original is deleted.
+/// On any failure the original is put back and reopened with `old_key`.
+/// Callers must hold the database lock.
+pub async fn rekey(
+    current: &mut DatabaseConnection,
+    db_path: &Path,
+    old_key: Option<&DatabaseKey>,
+    new_key: Option<&DatabaseKey>,
+    persist: impl FnOnce() -> Result<(), AppError>,
+) -> Result<(), AppError> {
+    let staging = sibling(db_path, ".rekey");
+    remove_database_files(&staging)?;
+    if let Err(e) = export(current, &staging, new_key).await {
+        let _ = remove_database_files(&staging);
+        return Err(e);
+    }
+
+    // Make sure the new file opens with the new key before relying on it
+    match Database::connect(&staging, new_key).await {
+        Ok(check) => {
+            let _ = check.close().await;
+        }
+        Err(e) => {
+            let _ = remove_database_files(&staging);
+            return Err(e);
+        }
+    }
+
+    let old = std::mem::replace(current, DatabaseConnection::Disconnected);
+    if let Err(e) = old.close().await {
+        warn!("Failed to close database cleanly before rekey: {}", e);
+    }
+
+    // Only undo the steps of the swap that actually ran
+    let previous = sibling(db_path, ".previous");
+    let mut moved_aside = false;
+    let mut swapped = remove_database_files(&previous).and_then(|_| {
+        fs::rename(db_path, &previous).map_err(|e| AppError::io("Failed to move database aside", e))
+    });
+    if swapped.is_ok() {
+        moved_aside = true;
+        swapped = fs::rename(&staging, db_path)
+            .map_err(|e| AppError::io("Failed to replace database", e));
+    }
+
+    let failed = match swapped {
+        Ok(()) => match Database::open(db_path, new_key).await {
+            // Without the new settings the file could not be opened next start
+            Ok(db) => match persist() {
+                Ok(()) => {
+                    *current = db;
+                    // The previous file is the unprotected (or old-key) copy; do not keep it
+                    if let Err(e) = remove_database_files(&previous) {
+                        warn!("Failed to remove previous database: {}", e);
+                    }
+                    return Ok(());
+                }
+                Err(e) => {
+                    let _ = db.close().await;
+                    e
+                }
+            },
+            Err(e) => e,
+        },
+        Err(e) => e,
+    };
+
+    warn!("Rekey failed, keeping the original database: {}", failed);
+    if moved_aside {
+        let restored = remove_database_files(db_path).and_then(|_| {
+            fs::rename(&previous, db_path)
+                .map_err(|e| AppError::io("Failed to put previous database back", e))
+        });
+        if let Err(e) = restored {
+            error!(
+                "{}; the original database is kept at {}",
+                e,
+                previous.display()
+            );
+        }
+    }
+    let _ = remove_database_files(&staging);
+    match Database::open(db_path, old_key).await {
+        Ok(db) => *current = db,
+        Err(e) => error!("Failed to reopen the original database: {}", e),
+    }
+    Err(failed)
+}
+
+/// Whether a file starts with the plain SQLite header (encrypted files look random)
+pub fn is_plaintext(path: &Path) -> Result<bool, AppError> {
+    let mut header = [0u8; 16];
+    let mut file = fs::File::open(path).map_err(|e| AppError::io("Failed to open database", e))?;
+    match std::io::Read::read_exact(&mut file, &mut header) {
+        Ok(()) => Ok(&header == b"SQLite format 3\0"),
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => Ok(false),
+        Err(e) => Err(AppError::io("Failed to read database", e)),
+    }
+}
+
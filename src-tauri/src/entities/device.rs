@@ -0,0 +1,28 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "devices")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i64,
+    pub device_id: String,
+    pub label: String,
+    pub port: String,
+    pub enabled: bool,
+    pub baud_rate: i64,
+    pub data_bits: i32,
+    pub parity: String,
+    pub stop_bits: i32,
+    pub flow_control: String,
+    pub read_timeout_ms: i64,
+    pub chunk_size: i64,
+    pub column_schema: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
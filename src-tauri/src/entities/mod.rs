@@ -1,3 +1,9 @@
+pub mod control_material;
+pub mod device;
+pub mod outbound_message;
 pub mod patient;
+pub mod qc_run;
+pub mod role;
 pub mod setting;
 pub mod test;
+pub mod user;
@@ -0,0 +1,27 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "qc_runs")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i64,
+    pub uuid: String,
+    pub material_id: i64,
+    pub device_id: String,
+    pub test_type: String,
+    pub level: String,
+    pub lot_number: String,
+    pub status: String,
+    pub value: Option<f64>,
+    pub detection_result: Option<String>,
+    pub violations: Option<String>,
+    pub operated_by: Option<String>,
+    pub created_at: String,
+    pub completed_at: Option<String>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
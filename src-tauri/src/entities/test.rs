@@ -19,6 +19,16 @@ pub struct Model {
     pub created_at: String,
     pub updated_at: String,
     pub completed_at: Option<String>,
+    pub quality_report: Option<String>,
+    pub operated_by: Option<String>,
+    pub released_by: Option<String>,
+    pub released_at: Option<String>,
+    pub review_log: Option<String>,
+    pub retest_of: Option<String>,
+    pub signal_preview: Option<String>,
+    pub raw_signal: Option<String>,
+    pub specimen_id: Option<String>,
+    pub accession_number: Option<String>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -0,0 +1,300 @@
+use crate::db_orm::Database;
+use crate::models::{AppError, TestStatus, TestWithPatient};
+use crate::report::probability;
+use hmac::{Hmac, Mac};
+use rand::RngCore;
+use sea_orm::DatabaseConnection;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use tracing::info;
+
+/// Setting holding the hex key behind pseudonymised patient identifiers
+///
+/// Created on first use and kept, so the same patient gets the same pseudonym
+/// in every export from this installation.
+pub const PSEUDONYM_KEY_SETTING: &str = "export_pseudonym_key";
+
+/// Tests fetched from the database per round trip while exporting
+const EXPORT_PAGE_SIZE: u64 = 200;
+/// Hex characters of the keyed hash kept in a pseudonym
+const PSEUDONYM_LENGTH: usize = 16;
+
+/// Column order of CSV exports; NDJSON uses the same field names
+const CSV_COLUMNS: [&str; 19] = [
+    "test_id",
+    "patient_ref",
+    "first_name",
+    "last_name",
+    "date_of_birth",
+    "test_type",
+    "device_id",
+    "firmware_version",
+    "detection_result",
+    "confidence",
+    "probability",
+    "quality_passed",
+    "operated_by",
+    "released_by",
+    "created_at",
+    "completed_at",
+    "released_at",
+    "retest_of",
+    "raw_signal",
+];
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Csv,
+    Ndjson,
+}
+
+impl ExportFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Ndjson => "ndjson",
+        }
+    }
+}
+
+/// What to export and how
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportOptions {
+    pub format: ExportFormat,
+    /// Same filter as the history page; `None` exports every status
+    #[serde(default)]
+    pub status: Option<TestStatus>,
+    /// Replace names and patient numbers with a stable keyed hash
+    #[serde(default)]
+    pub pseudonymize: bool,
+    /// Include the CSV each device sent, where it was kept
+    #[serde(default)]
+    pub include_raw_signal: bool,
+}
+
+/// Outcome of an export, shown to the user
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportSummary {
+    pub path: String,
+    pub exported: u64,
+    /// Tests matching the filter that were left out because they are not released
+    pub unreleased: u64,
+}
+
+/// One exported test; identifying fields are `None` when pseudonymised
+#[derive(Debug, Serialize)]
+struct ExportRecord {
+    test_id: String,
+    patient_ref: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    first_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    last_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    date_of_birth: Option<String>,
+    test_type: String,
+    device_id: Option<String>,
+    firmware_version: Option<String>,
+    detection_result: Option<String>,
+    confidence: Option<f64>,
+    probability: Option<f64>,
+    quality_passed: Option<bool>,
+    operated_by: Option<String>,
+    released_by: Option<String>,
+    created_at: String,
+    completed_at: Option<String>,
+    released_at: Option<String>,
+    retest_of: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    raw_signal: Option<String>,
+}
+
+impl ExportRecord {
+    fn new(data: TestWithPatient, pseudonym: Option<String>, raw_signal: Option<String>) -> Self {
+        let TestWithPatient { test, patient } = data;
+        let probability = probability(test.raw_response.as_deref());
+        let quality_passed = test.quality_report.as_ref().map(|report| report.passed);
+        let (patient_ref, first_name, last_name, date_of_birth) = match pseudonym {
+            Some(pseudonym) => (pseudonym, None, None, None),
+            None => (
+                patient.patient_id_number.unwrap_or(patient.uuid),
+                Some(patient.first_name),
+                Some(patient.last_name),
+                patient.date_of_birth,
+            ),
+        };
+        Self {
+            test_id: test.uuid,
+            patient_ref,
+            first_name,
+            last_name,
+            date_of_birth,
+            test_type: test.test_type,
+            device_id: test.device_id,
+            firmware_version: test.firmware_version,
+            detection_result: test.detection_result,
+            confidence: test.confidence,
+            probability,
+            quality_passed,
+            operated_by: test.operated_by,
+            released_by: test.released_by,
+            created_at: test.created_at,
+            completed_at: test.completed_at,
+            released_at: test.released_at,
+            retest_of: test.retest_of,
+            raw_signal,
+        }
+    }
+
+    /// Fields in `CSV_COLUMNS` order
+    fn csv_fields(&self) -> [String; 19] {
+        fn text(value: &Option<String>) -> String {
+            value.clone().unwrap_or_default()
+        }
+        fn number(value: Option<f64>) -> String {
+            value.map(|v| v.to_string()).unwrap_or_default()
+        }
+        [
+            self.test_id.clone(),
+            self.patient_ref.clone(),
+            text(&self.first_name),
+            text(&self.last_name),
+            text(&self.date_of_birth),
+            self.test_type.clone(),
+            text(&self.device_id),
+            text(&self.firmware_version),
+            text(&self.detection_result),
+            number(self.confidence),
+            number(self.probability),
+            self.quality_passed
+                .map(|passed| passed.to_string())
+                .unwrap_or_default(),
+            text(&self.operated_by),
+            text(&self.released_by),
+            self.created_at.clone(),
+            text(&self.completed_at),
+            text(&self.released_at),
+            text(&self.retest_of),
+            text(&self.raw_signal),
+        ]
+    }
+}
+
+/// Write every released test matching `options` to `path`, a page at a time
+pub async fn export_tests(
+    db: &DatabaseConnection,
+    options: &ExportOptions,
+    path: &Path,
+) -> Result<ExportSummary, AppError> {
+    let pseudonym_key = if options.pseudonymize {
+        Some(load_pseudonym_key(db).await?)
+    } else {
+        None
+    };
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| AppError::io(format!("Failed to create {}", parent.display()), e))?;
+    }
+    let file = File::create(path)
+        .map_err(|e| AppError::io(format!("Failed to create {}", path.display()), e))?;
+    let mut out = BufWriter::new(file);
+    let write_error = |e| AppError::io(format!("Failed to write {}", path.display()), e);
+
+    if options.format == ExportFormat::Csv {
+        write_csv_row(&mut out, CSV_COLUMNS.iter().copied()).map_err(write_error)?;
+    }
+
+    let status = options.status.as_ref();
+    let mut exported = 0;
+    let mut page = 0;
+    loop {
+        let tests =
+            Database::get_released_tests_with_patients(db, status, page, EXPORT_PAGE_SIZE).await?;
+        if tests.is_empty() {
+            break;
+        }
+        for data in tests {
+            let pseudonym = pseudonym_key
+                .as_deref()
+                .map(|key| pseudonym(key, &data.patient.uuid));
+            let raw_signal = if options.include_raw_signal {
+                Database::get_raw_signal(db, &data.test.uuid).await?
+            } else {
+                None
+            };
+            let record = ExportRecord::new(data, pseudonym, raw_signal);
+            match options.format {
+                ExportFormat::Csv => {
+                    let fields = record.csv_fields();
+                    write_csv_row(&mut out, fields.iter().map(String::as_str))
+                        .map_err(write_error)?;
+                }
+                ExportFormat::Ndjson => {
+                    serde_json::to_writer(&mut out, &record)
+                        .map_err(|e| AppError::internal("Failed to encode test").with_details(e))?;
+                    out.write_all(b"\n").map_err(write_error)?;
+                }
+            }
+            exported += 1;
+        }
+        page += 1;
+    }
+    out.flush().map_err(write_error)?;
+
+    let unreleased = Database::count_unreleased_tests(db, status).await?;
+    info!(
+        "Exported {} tests to {} ({} unreleased left out)",
+        exported,
+        path.display(),
+        unreleased
+    );
+    Ok(ExportSummary {
+        path: path.display().to_string(),
+        exported,
+        unreleased,
+    })
+}
+
+/// Load the pseudonym key, creating it the first time
+async fn load_pseudonym_key(db: &DatabaseConnection) -> Result<Vec<u8>, AppError> {
+    if let Some(key) = Database::get_setting(db, PSEUDONYM_KEY_SETTING.to_string()).await? {
+        return hex::decode(key)
+            .map_err(|e| AppError::internal("Pseudonym key is corrupt").with_details(e));
+    }
+    let mut key = vec![0u8; 32];
+    rand::thread_rng().fill_bytes(&mut key);
+    Database::save_setting(db, PSEUDONYM_KEY_SETTING.to_string(), hex::encode(&key)).await?;
+    info!("Created pseudonym key for exports");
+    Ok(key)
+}
+
+/// Stable identifier for a patient that cannot be reversed without the key
+fn pseudonym(key: &[u8], patient_uuid: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts any key length");
+    mac.update(patient_uuid.as_bytes());
+    let digest = hex::encode(mac.finalize().into_bytes());
+    format!("P-{}", &digest[..PSEUDONYM_LENGTH])
+}
+
+/// Write one RFC 4180 row, quoting fields that need it
+fn write_csv_row<'a>(
+    out: &mut impl Write,
+    fields: impl Iterator<Item = &'a str>,
+) -> std::io::Result<()> {
+    for (i, field) in fields.enumerate() {
+        if i > 0 {
+            out.write_all(b",")?;
+        }
+        if field.contains([',', '"', '\n', '\r']) {
+            write!(out, "\"{}\"", field.replace('"', "\"\""))?;
+        } else {
+            out.write_all(field.as_bytes())?;
+        }
+    }
+    out.write_all(b"\r\n")
+}
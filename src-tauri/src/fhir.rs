@@ -0,0 +1,405 @@
+use crate::db_orm::Database;
+use crate::models::{AppError, DetectionResult, ReviewAction, TestType, TestWithPatient};
+use crate::outbox::{Delivery, OutboundMessage};
+use crate::report::probability;
+use chrono::{DateTime, SecondsFormat, Utc};
+use sea_orm::DatabaseConnection;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::time::Duration;
+
+/// Setting holding the JSON-encoded `FhirSettings`
+pub const FHIR_SETTINGS_KEY: &str = "fhir_settings";
+/// Outbox protocol name for FHIR R4 transaction bundles
+pub const PROTOCOL: &str = "fhir-r4";
+
+const LOINC: &str = "http://loinc.org";
+const SNOMED: &str = "http://snomed.info/sct";
+const UCUM: &str = "http://unitsofmeasure.org";
+const INTERPRETATION: &str = "http://terminology.hl7.org/CodeSystem/v3-ObservationInterpretation";
+const OBSERVATION_CATEGORY: &str = "http://terminology.hl7.org/CodeSystem/observation-category";
+const DIAGNOSTIC_SERVICE: &str = "http://terminology.hl7.org/CodeSystem/v2-0074";
+/// Local code system for values that have no standard code
+const LOCAL_CODES: &str = "urn:ebers:code";
+/// Identifier systems for records this installation creates
+const TEST_IDENTIFIER_SYSTEM: &str = "urn:ebers:test";
+const DEVICE_IDENTIFIER_SYSTEM: &str = "urn:ebers:device";
+
+/// Which FHIR server receives released results
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FhirSettings {
+    pub enabled: bool,
+    /// Service base URL; bundles are POSTed here as transactions
+    pub base_url: String,
+    /// Sent as a bearer token when set
+    #[serde(default)]
+    pub auth_token: String,
+    /// Identifier system for patient numbers, e.g. the clinic's MRN namespace
+    pub patient_identifier_system: String,
+    pub timeout_secs: u64,
+}
+
+impl Default for FhirSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            base_url: String::new(),
+            auth_token: String::new(),
+            patient_identifier_system: "urn:ebers:patient".to_string(),
+            timeout_secs: 30,
+        }
+    }
+}
+
+impl FhirSettings {
+    /// Trim the text fields and check an enabled server is usable
+    pub fn normalized(mut self) -> Result<Self, AppError> {
+        self.base_url = self.base_url.trim().trim_end_matches('/').to_string();
+        self.auth_token = self.auth_token.trim().to_string();
+        self.patient_identifier_system = self.patient_identifier_system.trim().to_string();
+        if self.enabled && self.base_url.is_empty() {
+            return Err(AppError::validation("Enter the FHIR server base URL"));
+        }
+        if !self.base_url.is_empty()
+            && !self.base_url.starts_with("https://")
+            && !self.base_url.starts_with("http://")
+        {
+            return Err(AppError::validation(
+                "FHIR base URL must start with http:// or https://",
+            ));
+        }
+        if self.patient_identifier_system.is_empty() {
+            return Err(AppError::validation(
+                "Patient identifier system is required",
+            ));
+        }
+        if !(1..=300).contains(&self.timeout_secs) {
+            return Err(AppError::validation(
+                "Timeout must be between 1 and 300 seconds",
+            ));
+        }
+        Ok(self)
+    }
+}
+
+pub async fn load_settings(db: &DatabaseConnection) -> Result<FhirSettings, AppError> {
+    match Database::get_setting(db, FHIR_SETTINGS_KEY.to_string()).await? {
+        Some(json) => serde_json::from_str(&json)
+            .map_err(|e| AppError::internal("Failed to parse FHIR settings").with_details(e)),
+        None => Ok(FhirSettings::default()),
+    }
+}
+
+pub async fn save_settings(
+    db: &DatabaseConnection,
+    settings: &FhirSettings,
+) -> Result<(), AppError> {
+    let json = serde_json::to_string(settings)
+        .map_err(|e| AppError::internal("Failed to encode FHIR settings").with_details(e))?;
+    Database::save_setting(db, FHIR_SETTINGS_KEY.to_string(), json).await
+}
+
+/// LOINC code and display for each test type, where one exists
+fn loinc(test_type: &TestType) -> Option<(&'static str, &'static str)> {
+    match test_type {
+        TestType::Covid19 => Some((
+            "94500-6",
+            "SARS-CoV-2 (COVID-19) RNA [Presence] in Respiratory system specimen by NAA with probe detection",
+        )),
+        TestType::Flu => Some((
+            "92142-9",
+            "Influenza virus A RNA [Presence] in Respiratory system specimen by NAA with probe detection",
+        )),
+        TestType::Strep => Some((
+            "78012-2",
+            "Streptococcus pyogenes Ag [Presence] in Throat by Rapid immunoassay",
+        )),
+        TestType::InfectiousDisease => None,
+    }
+}
+
+/// Coded result and interpretation for a detection result
+fn result_codes(result: &DetectionResult) -> (Value, Value) {
+    let (snomed, interpretation, display) = match result {
+        DetectionResult::Positive => ("10828004", "POS", "Positive"),
+        DetectionResult::Negative => ("260385009", "NEG", "Negative"),
+        DetectionResult::Inconclusive => ("419984006", "IND", "Indeterminate"),
+    };
+    (
+        json!({
+            "coding": [{ "system": SNOMED, "code": snomed, "display": result.label() }],
+            "text": result.label(),
+        }),
+        json!({
+            "coding": [{ "system": INTERPRETATION, "code": interpretation, "display": display }],
+        }),
+    )
+}
+
+/// Build a transaction Bundle for a released result
+///
+/// Patient and Device are created only if the server has no record with the
+/// same identifier, and the Observation and DiagnosticReport carry the test
+/// UUID, so sending the same bundle twice does not duplicate anything.
+pub fn transaction_bundle(
+    data: &TestWithPatient,
+    settings: &FhirSettings,
+    bundle_id: &str,
+    now: DateTime<Utc>,
+) -> Value {
+    let test = &data.test;
+    let patient = &data.patient;
+    let patient_ref = format!("urn:uuid:{}", patient.uuid);
+    let observation_ref = format!("urn:uuid:{}", uuid::Uuid::new_v4());
+    let report_ref = format!("urn:uuid:{}", uuid::Uuid::new_v4());
+    let device_ref = test
+        .device_id
+        .as_ref()
+        .map(|_| format!("urn:uuid:{}", uuid::Uuid::new_v4()));
+
+    let (patient_system, patient_value) = match &patient.patient_id_number {
+        Some(number) => (settings.patient_identifier_system.as_str(), number.as_str()),
+        None => ("urn:ietf:rfc:3986", patient_ref.as_str()),
+    };
+    let mut patient_resource = json!({
+        "resourceType": "Patient",
+        "identifier": [{ "system": patient_system, "value": patient_value }],
+        "name": [{ "family": patient.last_name, "given": [patient.first_name] }],
+    });
+    if let Some(dob) = &patient.date_of_birth {
+        patient_resource["birthDate"] = json!(dob);
+    }
+    let mut telecom = Vec::new();
+    if let Some(phone) = &patient.phone {
+        telecom.push(json!({ "system": "phone", "value": phone }));
+    }
+    if let Some(email) = &patient.email {
+        telecom.push(json!({ "system": "email", "value": email }));
+    }
+    if !telecom.is_empty() {
+        patient_resource["telecom"] = json!(telecom);
+    }
+
+    let mut entries = vec![entry(
+        &patient_ref,
+        patient_resource,
+        "Patient",
+        &format!("identifier={}|{}", patient_system, patient_value),
+    )];
+
+    if let (Some(device_id), Some(device_ref)) = (&test.device_id, &device_ref) {
+        let mut device = json!({
+            "resourceType": "Device",
+            "identifier": [{ "system": DEVICE_IDENTIFIER_SYSTEM, "value": device_id }],
+            "status": "active",
+        });
+        if let Some(firmware) = &test.firmware_version {
+            device["version"] = json!([{ "type": { "text": "firmware" }, "value": firmware }]);
+        }
+        entries.push(entry(
+            device_ref,
+            device,
+            "Device",
+            &format!("identifier={}|{}", DEVICE_IDENTIFIER_SYSTEM, device_id),
+        ));
+    }
+
+    let code = test_code(&test.test_type);
+    let test_identifier = json!([{ "system": TEST_IDENTIFIER_SYSTEM, "value": test.uuid }]);
+    let effective = test.completed_at.as_deref().unwrap_or(&test.created_at);
+    let issued = test
+        .released_at
+        .clone()
+        .unwrap_or_else(|| now.to_rfc3339_opts(SecondsFormat::Secs, true));
+
+    let mut observation = json!({
+        "resourceType": "Observation",
+        "identifier": test_identifier,
+        "status": "final",
+        "category": [{
+            "coding": [{ "system": OBSERVATION_CATEGORY, "code": "laboratory", "display": "Laboratory" }],
+        }],
+        "code": code,
+        "subject": { "reference": patient_ref },
+        "effectiveDateTime": effective,
+        "issued": issued,
+    });
+    if let Some(result) = test
+        .detection_result
+        .as_deref()
+        .and_then(|r| DetectionResult::from_str(r).ok())
+    {
+        let (value, interpretation) = result_codes(&result);
+        observation["valueCodeableConcept"] = value;
+        observation["interpretation"] = json!([interpretation]);
+    }
+    if let Some(device_ref) = &device_ref {
+        observation["device"] = json!({ "reference": device_ref });
+    }
+    if let Some(by) = &test.released_by {
+        observation["performer"] = json!([{ "display": by }]);
+    }
+    if let Some(event) = test
+        .review_log
+        .iter()
+        .rev()
+        .find(|e| e.action == ReviewAction::Overridden)
+    {
+        observation["note"] = json!([{
+            "text": format!(
+                "Measured result {} overridden by {}: {}",
+                event.previous_result.as_deref().unwrap_or("-"),
+                event.by,
+                event.reason.as_deref().unwrap_or("-")
+            ),
+        }]);
+    }
+    let components: Vec<Value> = [
+        ("confidence", "Model confidence", test.confidence),
+        (
+            "probability",
+            "Detection probability",
+            probability(test.raw_response.as_deref()),
+        ),
+    ]
+    .into_iter()
+    .filter_map(|(code, display, value)| {
+        value.map(|value| {
+            json!({
+                "code": { "coding": [{ "system": LOCAL_CODES, "code": code, "display": display }] },
+                "valueQuantity": {
+                    "value": (value * 1000.0).round() / 10.0,
+                    "unit": "%",
+                    "system": UCUM,
+                    "code": "%",
+                },
+            })
+        })
+    })
+    .collect();
+    if !components.is_empty() {
+        observation["component"] = json!(components);
+    }
+    entries.push(entry(
+        &observation_ref,
+        observation,
+        "Observation",
+        &format!("identifier={}|{}", TEST_IDENTIFIER_SYSTEM, test.uuid),
+    ));
+
+    let report = json!({
+        "resourceType": "DiagnosticReport",
+        "identifier": test_identifier,
+        "status": "final",
+        "category": [{
+            "coding": [{ "system": DIAGNOSTIC_SERVICE, "code": "LAB", "display": "Laboratory" }],
+        }],
+        "code": code,
+        "subject": { "reference": patient_ref },
+        "effectiveDateTime": effective,
+        "issued": issued,
+        "result": [{ "reference": observation_ref }],
+    });
+    entries.push(entry(
+        &report_ref,
+        report,
+        "DiagnosticReport",
+        &format!("identifier={}|{}", TEST_IDENTIFIER_SYSTEM, test.uuid),
+    ));
+
+    json!({
+        "resourceType": "Bundle",
+        "id": bundle_id,
+        "type": "transaction",
+        "timestamp": now.to_rfc3339_opts(SecondsFormat::Secs, true),
+        "entry": entries,
+    })
+}
+
+/// CodeableConcept for the test, LOINC where available
+fn test_code(test_type: &str) -> Value {
+    let parsed = TestType::from_str(test_type).ok();
+    match parsed.as_ref().and_then(loinc) {
+        Some((code, display)) => json!({
+            "coding": [{ "system": LOINC, "code": code, "display": display }],
+            "text": display,
+        }),
+        None => json!({
+            "coding": [{ "system": LOCAL_CODES, "code": test_type }],
+            "text": test_type,
+        }),
+    }
+}
+
+/// Transaction entry that creates `resource` unless `if_none_exist` matches
+fn entry(full_url: &str, resource: Value, resource_type: &str, if_none_exist: &str) -> Value {
+    json!({
+        "fullUrl": full_url,
+        "resource": resource,
+        "request": {
+            "method": "POST",
+            "url": resource_type,
+            "ifNoneExist": if_none_exist,
+        },
+    })
+}
+
+/// POST a queued bundle to the server and interpret the response
+pub async fn deliver(settings: &FhirSettings, message: &OutboundMessage) -> Delivery {
+    let client = match reqwest::Client::builder()
+        .timeout(Duration::from_secs(settings.timeout_secs))
+        .build()
+    {
+        Ok(client) => client,
+        Err(e) => return Delivery::Retry(format!("Failed to create HTTP client: {}", e)),
+    };
+
+    let mut request = client
+        .post(&settings.base_url)
+        .header("Content-Type", "application/fhir+json")
+        .header("Accept", "application/fhir+json")
+        .body(message.payload.clone());
+    if !settings.auth_token.is_empty() {
+        request = request.bearer_auth(&settings.auth_token);
+    }
+
+    let response = match request.send().await {
+        Ok(response) => response,
+        Err(e) if e.is_timeout() => {
+            return Delivery::Retry(format!("No response from {}", settings.base_url))
+        }
+        Err(e) => return Delivery::Retry(format!("Cannot reach {}: {}", settings.base_url, e)),
+    };
+
+    let status = response.status();
+    let code = status.as_u16().to_string();
+    if status.is_success() {
+        return Delivery::Accepted { ack_code: code };
+    }
+    let reason = response
+        .text()
+        .await
+        .ok()
+        .and_then(|body| operation_outcome(&body))
+        .unwrap_or_else(|| format!("Server returned {}", status));
+    // The server refused the bundle itself, unless it is busy or rate limiting
+    if status.is_client_error() && status.as_u16() != 408 && status.as_u16() != 429 {
+        Delivery::Rejected {
+            ack_code: code,
+            reason,
+        }
+    } else {
+        Delivery::Retry(reason)
+    }
+}
+
+/// First diagnostics text of an OperationOutcome response
+fn operation_outcome(body: &str) -> Option<String> {
+    let outcome: Value = serde_json::from_str(body).ok()?;
+    outcome["issue"].as_array()?.iter().find_map(|issue| {
+        issue["diagnostics"]
+            .as_str()
+            .or_else(|| issue["details"]["text"].as_str())
+            .map(str::to_string)
+    })
+}
@@ -0,0 +1,465 @@
+use crate::db_orm::Database;
+use crate::models::{AppError, DetectionResult, ReviewAction, TestWithPatient};
+use crate::outbox::{Delivery, OutboundMessage};
+use crate::report::probability;
+use chrono::{DateTime, NaiveDate, Utc};
+use sea_orm::DatabaseConnection;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+/// Setting holding the JSON-encoded `Hl7Settings`
+pub const HL7_SETTINGS_KEY: &str = "hl7_settings";
+/// Outbox protocol name for HL7 v2 over MLLP
+pub const PROTOCOL: &str = "hl7v2";
+
+const VERSION: &str = "2.5.1";
+/// MLLP frame: <VT> message <FS><CR>
+const START_BLOCK: u8 = 0x0b;
+const END_BLOCK: u8 = 0x1c;
+const CARRIAGE_RETURN: u8 = 0x0d;
+/// Longest acknowledgement accepted before the connection is dropped
+const MAX_ACK_BYTES: usize = 64 * 1024;
+/// MSH-10 is limited to 20 characters by many receivers
+const CONTROL_ID_LENGTH: usize = 20;
+
+/// Where and as whom result messages are sent
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Hl7Settings {
+    pub enabled: bool,
+    pub host: String,
+    pub port: u16,
+    pub sending_application: String,
+    #[serde(default)]
+    pub sending_facility: String,
+    #[serde(default)]
+    pub receiving_application: String,
+    #[serde(default)]
+    pub receiving_facility: String,
+    /// How long to wait for the connection and for the ACK
+    pub ack_timeout_secs: u64,
+}
+
+impl Default for Hl7Settings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            host: String::new(),
+            port: 2575,
+            sending_application: "EBERS".to_string(),
+            sending_facility: String::new(),
+            receiving_application: String::new(),
+            receiving_facility: String::new(),
+            ack_timeout_secs: 30,
+        }
+    }
+}
+
+impl Hl7Settings {
+    /// Trim the text fields and check an enabled destination is usable
+    pub fn normalized(mut self) -> Result<Self, AppError> {
+        self.host = self.host.trim().to_string();
+        self.sending_application = self.sending_application.trim().to_string();
+        self.sending_facility = self.sending_facility.trim().to_string();
+        self.receiving_application = self.receiving_application.trim().to_string();
+        self.receiving_facility = self.receiving_facility.trim().to_string();
+        if self.enabled && self.host.is_empty() {
+            return Err(AppError::validation("Enter the LIS host name or address"));
+        }
+        if self.port == 0 {
+            return Err(AppError::validation("Port must be between 1 and 65535"));
+        }
+        if self.sending_application.is_empty() {
+            return Err(AppError::validation("Sending application is required"));
+        }
+        if !(1..=300).contains(&self.ack_timeout_secs) {
+            return Err(AppError::validation(
+                "ACK timeout must be between 1 and 300 seconds",
+            ));
+        }
+        Ok(self)
+    }
+}
+
+pub async fn load_settings(db: &DatabaseConnection) -> Result<Hl7Settings, AppError> {
+    match Database::get_setting(db, HL7_SETTINGS_KEY.to_string()).await? {
+        Some(json) => serde_json::from_str(&json)
+            .map_err(|e| AppError::internal("Failed to parse HL7 settings").with_details(e)),
+        None => Ok(Hl7Settings::default()),
+    }
+}
+
+pub async fn save_settings(
+    db: &DatabaseConnection,
+    settings: &Hl7Settings,
+) -> Result<(), AppError> {
+    let json = serde_json::to_string(settings)
+        .map_err(|e| AppError::internal("Failed to encode HL7 settings").with_details(e))?;
+    Database::save_setting(db, HL7_SETTINGS_KEY.to_string(), json).await
+}
+
+/// New message control ID (MSH-10)
+pub fn control_id() -> String {
+    let mut id = uuid::Uuid::new_v4().simple().to_string();
+    id.truncate(CONTROL_ID_LENGTH);
+    id
+}
+
+/// Build an ORU^R01 message for a released result
+///
+/// One OBR for the test, then OBX segments for the result, the model
+/// confidence and the detection probability. A reviewer's override is
+/// carried in an NTE after the result.
+pub fn oru_r01(
+    data: &TestWithPatient,
+    settings: &Hl7Settings,
+    control_id: &str,
+    now: DateTime<Utc>,
+) -> String {
+    let test = &data.test;
+    let patient = &data.patient;
+    let result = test
+        .detection_result
+        .as_deref()
+        .and_then(|r| DetectionResult::from_str(r).ok());
+    let assigning_authority = if settings.sending_facility.is_empty() {
+        &settings.sending_application
+    } else {
+        &settings.sending_facility
+    };
+    let test_type = escape(&test.test_type);
+
+    let mut segments = vec![
+        segment(&[
+            "MSH",
+            "^~\\&",
+            &escape(&settings.sending_application),
+            &escape(&settings.sending_facility),
+            &escape(&settings.receiving_application),
+            &escape(&settings.receiving_facility),
+            &now.format("%Y%m%d%H%M%S+0000").to_string(),
+            "",
+            "ORU^R01^ORU_R01",
+            &escape(control_id),
+            "P",
+            VERSION,
+        ]),
+        segment(&[
+            "PID",
+            "1",
+            "",
+            &format!(
+                "{}^^^{}^MR",
+                escape(
+                    patient
+                        .patient_id_number
+                        .as_deref()
+                        .unwrap_or(&patient.uuid)
+                ),
+                escape(assigning_authority)
+            ),
+            "",
+            &format!(
+                "{}^{}",
+                escape(&patient.last_name),
+                escape(&patient.first_name)
+            ),
+            "",
+            &patient
+                .date_of_birth
+                .as_deref()
+                .map(hl7_date)
+                .unwrap_or_default(),
+            "",
+            "",
+            "",
+            "",
+            "",
+            &escape(patient.phone.as_deref().unwrap_or_default()),
+        ]),
+        segment(&[
+            "OBR",
+            "1",
+            &escape(test.specimen_id.as_deref().unwrap_or_default()),
+            &format!(
+                "{}^{}",
+                escape(&test.uuid),
+                escape(&settings.sending_application)
+            ),
+            &format!("{}^{}^L", test_type, test_type),
+            "",
+            "",
+            &hl7_time(&test.created_at),
+            "",
+            "",
+            "",
+            "",
+            "",
+            "",
+            "",
+            "",
+            "",
+            "",
+            "",
+            "",
+            "",
+            "",
+            &test
+                .released_at
+                .as_deref()
+                .map(hl7_time)
+                .unwrap_or_default(),
+            "",
+            "",
+            "F",
+        ]),
+        observation(
+            1,
+            "CWE",
+            &format!("{}^{} result^L", test_type, test_type),
+            &result
+                .as_ref()
+                .map(|r| format!("{}^{}^L", r.as_str(), r.label()))
+                .unwrap_or_default(),
+            "",
+            match result {
+                Some(DetectionResult::Positive) => "A",
+                Some(DetectionResult::Negative) => "N",
+                _ => "",
+            },
+            data,
+        ),
+    ];
+
+    if let Some(event) = test
+        .review_log
+        .iter()
+        .rev()
+        .find(|e| e.action == ReviewAction::Overridden)
+    {
+        segments.push(segment(&[
+            "NTE",
+            "1",
+            "L",
+            &escape(&format!(
+                "Measured result {} overridden by {}: {}",
+                event.previous_result.as_deref().unwrap_or("-"),
+                event.by,
+                event.reason.as_deref().unwrap_or("-")
+            )),
+        ]));
+    }
+
+    let mut set_id = 1;
+    for (identifier, value) in [
+        ("CONFIDENCE^Model confidence^L", test.confidence),
+        (
+            "PROBABILITY^Detection probability^L",
+            probability(test.raw_response.as_deref()),
+        ),
+    ] {
+        if let Some(value) = value {
+            set_id += 1;
+            segments.push(observation(
+                set_id,
+                "NM",
+                identifier,
+                &format!("{:.1}", value * 100.0),
+                "%^percent^UCUM",
+                "",
+                data,
+            ));
+        }
+    }
+
+    let mut message = segments.join("\r");
+    message.push('\r');
+    message
+}
+
+/// One OBX segment; status, time, observer and device come from the test
+fn observation(
+    set_id: usize,
+    value_type: &str,
+    identifier: &str,
+    value: &str,
+    units: &str,
+    abnormal_flag: &str,
+    data: &TestWithPatient,
+) -> String {
+    let test = &data.test;
+    segment(&[
+        "OBX",
+        &set_id.to_string(),
+        value_type,
+        identifier,
+        "",
+        value,
+        units,
+        "",
+        abnormal_flag,
+        "",
+        "",
+        "F",
+        "",
+        "",
+        &test
+            .completed_at
+            .as_deref()
+            .map(hl7_time)
+            .unwrap_or_default(),
+        "",
+        &escape(test.released_by.as_deref().unwrap_or_default()),
+        "",
+        &escape(test.device_id.as_deref().unwrap_or_default()),
+    ])
+}
+
+/// Send a queued message over MLLP and interpret the reply
+pub async fn deliver(settings: &Hl7Settings, message: &OutboundMessage) -> Delivery {
+    match exchange(settings, &message.payload).await {
+        Ok(ack) => interpret_ack(&ack, &message.control_id),
+        Err(e) => Delivery::Retry(e),
+    }
+}
+
+async fn exchange(settings: &Hl7Settings, payload: &str) -> Result<String, String> {
+    let timeout = Duration::from_secs(settings.ack_timeout_secs);
+    let address = format!("{}:{}", settings.host, settings.port);
+
+    let mut stream = tokio::time::timeout(timeout, TcpStream::connect(&address))
+        .await
+        .map_err(|_| format!("Timed out connecting to {}", address))?
+        .map_err(|e| format!("Cannot connect to {}: {}", address, e))?;
+
+    let mut frame = Vec::with_capacity(payload.len() + 3);
+    frame.push(START_BLOCK);
+    frame.extend_from_slice(payload.as_bytes());
+    frame.extend_from_slice(&[END_BLOCK, CARRIAGE_RETURN]);
+    stream
+        .write_all(&frame)
+        .await
+        .map_err(|e| format!("Failed to send to {}: {}", address, e))?;
+
+    tokio::time::timeout(timeout, read_frame(&mut stream))
+        .await
+        .map_err(|_| format!("No ACK from {} within {}s", address, timeout.as_secs()))?
+}
+
+/// Read one MLLP frame and return the message inside it
+async fn read_frame(stream: &mut TcpStream) -> Result<String, String> {
+    let mut buffer = Vec::new();
+    let mut chunk = [0u8; 1024];
+    loop {
+        let read = stream
+            .read(&mut chunk)
+            .await
+            .map_err(|e| format!("Failed to read ACK: {}", e))?;
+        if read == 0 {
+            return Err("Connection closed before the ACK arrived".to_string());
+        }
+        buffer.extend_from_slice(&chunk[..read]);
+
+        if let Some(end) = buffer
+            .windows(2)
+            .position(|w| w == [END_BLOCK, CARRIAGE_RETURN])
+        {
+            let start = buffer
+                .iter()
+                .position(|&b| b == START_BLOCK)
+                .map_or(0, |i| i + 1);
+            return Ok(String::from_utf8_lossy(&buffer[start.min(end)..end]).into_owned());
+        }
+        if buffer.len() > MAX_ACK_BYTES {
+            return Err("ACK is too large".to_string());
+        }
+    }
+}
+
+/// Map the MSA segment of an ACK onto a delivery outcome
+pub fn interpret_ack(ack: &str, control_id: &str) -> Delivery {
+    let Some(msa) = ack
+        .split(['\r', '\n'])
+        .find(|segment| segment.starts_with("MSA|"))
+    else {
+        return Delivery::Retry("Reply has no MSA segment".to_string());
+    };
+    let fields: Vec<&str> = msa.split('|').collect();
+    let code = fields.get(1).copied().unwrap_or_default();
+    let acked_id = fields.get(2).copied().unwrap_or_default();
+    let text = fields.get(3).copied().unwrap_or_default();
+
+    if acked_id != control_id {
+        return Delivery::Retry(format!(
+            "ACK is for message {}, expected {}",
+            acked_id, control_id
+        ));
+    }
+    let reason = |default: &str| {
+        if text.is_empty() {
+            default.to_string()
+        } else {
+            text.to_string()
+        }
+    };
+    match code {
+        "AA" | "CA" => Delivery::Accepted {
+            ack_code: code.to_string(),
+        },
+        "AE" | "CE" => Delivery::Rejected {
+            ack_code: code.to_string(),
+            reason: reason("Receiver reported an error in the message"),
+        },
+        "AR" | "CR" => Delivery::Retry(format!(
+            "Receiver refused the message ({}): {}",
+            code,
+            reason("no reason given")
+        )),
+        other => Delivery::Retry(format!("Unknown acknowledgement code '{}'", other)),
+    }
+}
+
+/// Join fields into a segment, dropping empty trailing fields
+fn segment(fields: &[&str]) -> String {
+    let used = fields
+        .iter()
+        .rposition(|field| !field.is_empty())
+        .map_or(0, |i| i + 1);
+    fields[..used].join("|")
+}
+
+/// Escape HL7 delimiters in free text; line breaks become spaces
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => escaped.push_str("\\E\\"),
+            '|' => escaped.push_str("\\F\\"),
+            '^' => escaped.push_str("\\S\\"),
+            '&' => escaped.push_str("\\T\\"),
+            '~' => escaped.push_str("\\R\\"),
+            '\r' | '\n' => escaped.push(' '),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// RFC 3339 timestamp as an HL7 DTM in UTC
+fn hl7_time(timestamp: &str) -> String {
+    DateTime::parse_from_rfc3339(timestamp)
+        .map(|t| {
+            t.with_timezone(&Utc)
+                .format("%Y%m%d%H%M%S+0000")
+                .to_string()
+        })
+        .unwrap_or_default()
+}
+
+/// `YYYY-MM-DD` as an HL7 DT; anything else is left out
+fn hl7_date(date: &str) -> String {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .map(|d| d.format("%Y%m%d").to_string())
+        .unwrap_or_default()
+}
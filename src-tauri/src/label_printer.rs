@@ -0,0 +1,415 @@
+use crate::db_orm::Database;
+use crate::labels;
+use crate::models::{AppError, TestWithPatient};
+use sea_orm::DatabaseConnection;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tokio::io::AsyncWriteExt;
+use tokio::net::TcpStream;
+use tracing::{error, info};
+
+#[cfg(desktop)]
+use tauri_plugin_serialplugin::{commands, desktop_api, state as serial_state};
+
+/// Setting holding the JSON-encoded `LabelPrinterSettings`
+pub const LABEL_PRINTER_SETTINGS_KEY: &str = "label_printer_settings";
+
+/// How long to wait for a network printer to accept the connection and the job
+const PRINTER_TIMEOUT: Duration = Duration::from_secs(10);
+/// Folder under Documents that print-to-file jobs go to when none is set
+const DEFAULT_FOLDER: &str = "labels";
+
+// Layout of a 50 x 25 mm label at 203 dpi, in dots
+const LABEL_WIDTH_DOTS: u32 = 400;
+const LABEL_HEIGHT_DOTS: u32 = 200;
+const LEFT: u32 = 16;
+/// Text stops short of the QR code in the top right corner
+const MAX_NAME_CHARS: usize = 18;
+const MAX_DETAIL_CHARS: usize = 24;
+
+/// Command language the printer understands
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum LabelLanguage {
+    /// Zebra Programming Language, for Zebra and most compatible printers
+    Zpl,
+    /// Eltron Programming Language, for older desktop printers
+    Epl,
+}
+
+impl LabelLanguage {
+    fn extension(self) -> &'static str {
+        match self {
+            LabelLanguage::Zpl => "zpl",
+            LabelLanguage::Epl => "epl",
+        }
+    }
+}
+
+/// Where print jobs are sent
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum PrinterTransport {
+    /// Raw TCP, usually port 9100
+    Tcp,
+    Serial,
+    /// Write each job to a file instead of printing, for setting up without a printer
+    File,
+}
+
+/// The label printer tube labels are sent to
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LabelPrinterSettings {
+    /// Print a label whenever a test or order is created; registering a patient
+    /// alone prints nothing, since every label carries a test's accession
+    pub enabled: bool,
+    pub language: LabelLanguage,
+    pub transport: PrinterTransport,
+    #[serde(default)]
+    pub host: String,
+    pub port: u16,
+    /// Port name, e.g. COM5 or /dev/ttyUSB2; must not be a detection device's port
+    #[serde(default)]
+    pub serial_port: String,
+    pub baud_rate: u32,
+    /// Folder print-to-file jobs are written to; relative to Documents
+    #[serde(default)]
+    pub folder: String,
+    /// Labels per test, e.g. one for the tube and one for the cartridge
+    pub copies: u32,
+}
+
+impl Default for LabelPrinterSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            language: LabelLanguage::Zpl,
+            transport: PrinterTransport::Tcp,
+            host: String::new(),
+            port: 9100,
+            serial_port: String::new(),
+            baud_rate: 9600,
+            folder: String::new(),
+            copies: 2,
+        }
+    }
+}
+
+impl LabelPrinterSettings {
+    /// Trim the text fields and check the chosen transport is usable
+    pub fn normalized(mut self) -> Result<Self, AppError> {
+        self.host = self.host.trim().to_string();
+        self.serial_port = self.serial_port.trim().to_string();
+        self.folder = self.folder.trim().to_string();
+        if self.enabled {
+            match self.transport {
+                PrinterTransport::Tcp if self.host.is_empty() => {
+                    return Err(AppError::validation(
+                        "Enter the label printer's host name or address",
+                    ))
+                }
+                PrinterTransport::Serial if self.serial_port.is_empty() => {
+                    return Err(AppError::validation(
+                        "Choose the serial port of the label printer",
+                    ))
+                }
+                _ => {}
+            }
+        }
+        if self.port == 0 {
+            return Err(AppError::validation("Port must be between 1 and 65535"));
+        }
+        if self.baud_rate == 0 {
+            return Err(AppError::validation("Baud rate is required"));
+        }
+        if !(1..=labels::MAX_COPIES).contains(&self.copies) {
+            return Err(AppError::validation(format!(
+                "Copies must be between 1 and {}",
+                labels::MAX_COPIES
+            )));
+        }
+        Ok(self)
+    }
+}
+
+pub async fn load_settings(db: &DatabaseConnection) -> Result<LabelPrinterSettings, AppError> {
+    match Database::get_setting(db, LABEL_PRINTER_SETTINGS_KEY.to_string()).await? {
+        Some(json) => serde_json::from_str(&json).map_err(|e| {
+            AppError::internal("Failed to parse label printer settings").with_details(e)
+        }),
+        None => Ok(LabelPrinterSettings::default()),
+    }
+}
+
+pub async fn save_settings(
+    db: &DatabaseConnection,
+    settings: &LabelPrinterSettings,
+) -> Result<(), AppError> {
+    let json = serde_json::to_string(settings).map_err(|e| {
+        AppError::internal("Failed to encode label printer settings").with_details(e)
+    })?;
+    Database::save_setting(db, LABEL_PRINTER_SETTINGS_KEY.to_string(), json).await
+}
+
+// ----------------------------------------------------------------------------
+// Label languages
+// ----------------------------------------------------------------------------
+
+/// The label job for a test in the printer's language
+///
+/// The label carries the patient's name and date of birth, the test type and
+/// a Code 128 barcode of the accession number. ZPL labels add a QR code of
+/// the test UUID, as on the PDF labels. Tests stored before accession numbers
+/// existed have no linear barcode, since a UUID is too long for the label.
+pub fn render(language: LabelLanguage, data: &TestWithPatient, copies: u32) -> String {
+    let name = truncate(&data.patient.full_name(), MAX_NAME_CHARS);
+    let mut details = Vec::new();
+    if let Some(dob) = &data.patient.date_of_birth {
+        details.push(format!("DOB {}", dob));
+    }
+    details.push(truncate(&data.test.test_type, MAX_DETAIL_CHARS));
+    // Detail lines sit under the name, one after another
+    let detail_y = |i: usize| 50 + 26 * i;
+    let accession = data.test.accession_number.as_deref();
+    let copies = copies.clamp(1, labels::MAX_COPIES);
+
+    match language {
+        LabelLanguage::Zpl => {
+            let mut job = format!(
+                "^XA\n^CI28\n^PW{}\n^LL{}\n^LH0,0\n",
+                LABEL_WIDTH_DOTS, LABEL_HEIGHT_DOTS
+            );
+            job.push_str(&format!(
+                "^FO{},16^A0N,28,24^FH^FD{}^FS\n",
+                LEFT,
+                zpl_field(&name)
+            ));
+            for (i, line) in details.iter().enumerate() {
+                job.push_str(&format!(
+                    "^FO{},{}^A0N,22,20^FH^FD{}^FS\n",
+                    LEFT,
+                    detail_y(i),
+                    zpl_field(line)
+                ));
+            }
+            job.push_str(&format!("^FO296,8^BQN,2,3^FDMA,{}^FS\n", data.test.uuid));
+            if let Some(accession) = accession {
+                // Automatic mode packs digit pairs into code set C
+                job.push_str(&format!(
+                    "^FO{},112^BY2^BCN,50,Y,N,N,A^FH^FD{}^FS\n",
+                    LEFT,
+                    zpl_field(accession)
+                ));
+            }
+            job.push_str(&format!("^PQ{}\n^XZ\n", copies));
+            job
+        }
+        LabelLanguage::Epl => {
+            // The leading line feed ends anything left over from an earlier job
+            let mut job = format!("\nN\nq{}\nQ{},24\n", LABEL_WIDTH_DOTS, LABEL_HEIGHT_DOTS);
+            job.push_str(&format!(
+                "A{},16,0,3,1,1,N,\"{}\"\n",
+                LEFT,
+                epl_field(&name)
+            ));
+            for (i, line) in details.iter().enumerate() {
+                job.push_str(&format!(
+                    "A{},{},0,2,1,1,N,\"{}\"\n",
+                    LEFT,
+                    detail_y(i),
+                    epl_field(line)
+                ));
+            }
+            if let Some(accession) = accession {
+                // Barcode type 1 is Code 128 with automatic code set selection
+                job.push_str(&format!(
+                    "B{},112,0,1,2,2,50,B,\"{}\"\n",
+                    LEFT,
+                    epl_field(accession)
+                ));
+            }
+            job.push_str(&format!("P{}\n", copies));
+            job
+        }
+    }
+}
+
+/// Field data with `^`, `~` and `_` hex-escaped for `^FH`
+fn zpl_field(text: &str) -> String {
+    let mut field = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '^' => field.push_str("_5E"),
+            '~' => field.push_str("_7E"),
+            '_' => field.push_str("_5F"),
+            c if c.is_control() => field.push(' '),
+            c => field.push(c),
+        }
+    }
+    field
+}
+
+/// Quoted EPL data; the printer's code page is ASCII only
+fn epl_field(text: &str) -> String {
+    let mut field = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '"' => field.push_str("\\\""),
+            '\\' => field.push_str("\\\\"),
+            c if c.is_ascii() && !c.is_ascii_control() => field.push(c),
+            _ => field.push('?'),
+        }
+    }
+    field
+}
+
+fn truncate(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+    let mut short: String = text.chars().take(max_chars - 1).collect();
+    short.push('.');
+    short
+}
+
+// ----------------------------------------------------------------------------
+// Printing
+// ----------------------------------------------------------------------------
+
+/// Send the labels of a test to the printer; returns where they went
+pub async fn print(
+    app: &AppHandle,
+    settings: &LabelPrinterSettings,
+    data: &TestWithPatient,
+) -> Result<String, AppError> {
+    let job = render(settings.language, data, settings.copies);
+    match settings.transport {
+        PrinterTransport::Tcp => {
+            let address = format!("{}:{}", settings.host, settings.port);
+            send_tcp(&address, job.as_bytes()).await?;
+            Ok(address)
+        }
+        PrinterTransport::Serial => {
+            send_serial(app, settings, job.into_bytes()).await?;
+            Ok(settings.serial_port.clone())
+        }
+        PrinterTransport::File => {
+            let path = job_path(app, settings, labels::barcode_value(data))?;
+            crate::report::save(job.as_bytes(), &path)?;
+            Ok(path.display().to_string())
+        }
+    }
+}
+
+/// Print the labels of a newly created test when a printer is set up
+///
+/// Errors are logged rather than returned: the test has already been saved,
+/// and its labels can be printed again from the worklist or the reading page.
+/// The job is sent in the background so a slow printer doesn't hold up the test.
+pub async fn print_new_test(app: &AppHandle, db: &DatabaseConnection, test_uuid: &str) {
+    let job = async {
+        let settings = load_settings(db).await?;
+        if !settings.enabled {
+            return Ok(None);
+        }
+        let data = Database::get_test_with_patient(db, test_uuid)
+            .await?
+            .ok_or_else(|| AppError::not_found("Test", test_uuid))?;
+        Ok::<_, AppError>(Some((settings, data)))
+    };
+    match job.await {
+        Ok(Some((settings, data))) => {
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                match print(&app, &settings, &data).await {
+                    Ok(destination) => {
+                        info!("Labels for test {} sent to {}", data.test.uuid, destination)
+                    }
+                    Err(e) => error!("Failed to print labels for test {}: {}", data.test.uuid, e),
+                }
+            });
+        }
+        Ok(None) => {}
+        Err(e) => error!("Failed to print labels for test {}: {}", test_uuid, e),
+    }
+}
+
+async fn send_tcp(address: &str, job: &[u8]) -> Result<(), AppError> {
+    let send = async {
+        let mut stream = TcpStream::connect(address).await?;
+        stream.write_all(job).await?;
+        stream.shutdown().await
+    };
+    tokio::time::timeout(PRINTER_TIMEOUT, send)
+        .await
+        .map_err(|_| AppError::io(format!("Cannot print to {}", address), "timed out"))?
+        .map_err(|e| AppError::io(format!("Cannot print to {}", address), e))
+}
+
+#[cfg(desktop)]
+async fn send_serial(
+    app: &AppHandle,
+    settings: &LabelPrinterSettings,
+    job: Vec<u8>,
+) -> Result<(), AppError> {
+    let (app, port, baud_rate) = (
+        app.clone(),
+        settings.serial_port.clone(),
+        settings.baud_rate,
+    );
+    tokio::task::spawn_blocking(move || {
+        let serial = app.state::<desktop_api::SerialPort<tauri::Wry>>();
+        commands::open(
+            app.clone(),
+            serial.clone(),
+            port.clone(),
+            baud_rate,
+            Some(serial_state::DataBits::Eight),
+            Some(serial_state::FlowControl::None),
+            Some(serial_state::Parity::None),
+            Some(serial_state::StopBits::One),
+            None,
+        )
+        .map_err(|e| AppError::serial(format!("Cannot open {}: {}", port, e)))?;
+        let written = commands::write_binary(app.clone(), serial.clone(), port.clone(), job)
+            .map_err(|e| AppError::serial(format!("Failed to print on {}: {}", port, e)));
+        let _ = commands::close(app.clone(), serial.clone(), port);
+        written.map(|_| ())
+    })
+    .await
+    .map_err(|e| AppError::internal("Label print task failed").with_details(e))?
+}
+
+#[cfg(not(desktop))]
+async fn send_serial(
+    _app: &AppHandle,
+    _settings: &LabelPrinterSettings,
+    _job: Vec<u8>,
+) -> Result<(), AppError> {
+    Err(AppError::serial(
+        "Serial ports are not available on this platform",
+    ))
+}
+
+/// File a print-to-file job is written to, named after the label's barcode
+fn job_path(
+    app: &AppHandle,
+    settings: &LabelPrinterSettings,
+    barcode: &str,
+) -> Result<PathBuf, AppError> {
+    let mut folder = PathBuf::from(if settings.folder.is_empty() {
+        DEFAULT_FOLDER
+    } else {
+        &settings.folder
+    });
+    if folder.is_relative() {
+        let documents = app
+            .path()
+            .document_dir()
+            .map_err(|e| AppError::io("Failed to get documents folder", e))?;
+        folder = documents.join(folder);
+    }
+    Ok(folder.join(format!("{}.{}", barcode, settings.language.extension())))
+}
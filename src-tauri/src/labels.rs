@@ -0,0 +1,280 @@
+use crate::db_orm::Database;
+use crate::models::{AppError, Patient, TestWithPatient};
+use printpdf::path::PaintMode;
+use printpdf::{BuiltinFont, Color, Mm, PdfDocument, PdfLayerReference, Rect, Rgb};
+use qrcode::{EcLevel, QrCode};
+use sea_orm::DatabaseConnection;
+use serde::Serialize;
+
+/// Label size in mm, the common 50 x 25 mm thermal roll
+const LABEL_WIDTH: f32 = 50.0;
+const LABEL_HEIGHT: f32 = 25.0;
+const MARGIN: f32 = 2.0;
+/// Widest Code 128 module, 3 dots on a 203 dpi printer
+const MAX_MODULE_WIDTH: f32 = 0.375;
+/// Clear space either side of a Code 128 symbol, in modules
+const QUIET_ZONE_MODULES: usize = 10;
+const BARCODE_HEIGHT: f32 = 6.0;
+const QR_SIZE: f32 = 12.0;
+/// Labels per request, e.g. one for the tube and one for the cartridge
+pub const MAX_COPIES: u32 = 10;
+
+/// Bar and space widths of each Code 128 symbol value, bar first
+const CODE128_PATTERNS: [&str; 107] = [
+    "212222", "222122", "222221", "121223", "121322", "131222", "122213", "122312", "132212",
+    "221213", "221312", "231212", "112232", "122132", "122231", "113222", "123122", "123221",
+    "223211", "221132", "221231", "213212", "223112", "312131", "311222", "321122", "321221",
+    "312212", "322112", "322211", "212123", "212321", "232121", "111323", "131123", "131321",
+    "112313", "132113", "132311", "211313", "231113", "231311", "112133", "112331", "132131",
+    "113123", "113321", "133121", "313121", "211331", "231131", "213113", "213311", "213131",
+    "311123", "311321", "331121", "312113", "312311", "332111", "314111", "221411", "431111",
+    "111224", "111422", "121124", "121421", "141122", "141221", "112214", "112412", "122114",
+    "122411", "142112", "142211", "241211", "221114", "413111", "241112", "134111", "111242",
+    "121142", "121241", "114212", "124112", "124211", "411212", "421112", "421211", "212141",
+    "214121", "412121", "111143", "111341", "131141", "114113", "114311", "411113", "411311",
+    "113141", "114131", "311141", "411131", "211412", "211214", "211232", "2331112",
+];
+const CODE128_START_B: usize = 104;
+const CODE128_START_C: usize = 105;
+const CODE128_STOP: usize = 106;
+
+/// Encode printable ASCII as Code 128 modules, `true` for a bar
+///
+/// Code set C packs digit pairs, so accession numbers, which are all digits
+/// and of even length, stay short enough to scan from a small label.
+pub fn code128(data: &str) -> Result<Vec<bool>, AppError> {
+    if data.is_empty() || !data.bytes().all(|b| (32..127).contains(&b)) {
+        return Err(AppError::validation(format!(
+            "'{}' cannot be printed as a Code 128 barcode",
+            data
+        )));
+    }
+    let mut values = Vec::new();
+    if data.len().is_multiple_of(2) && data.bytes().all(|b| b.is_ascii_digit()) {
+        values.push(CODE128_START_C);
+        values.extend(
+            data.as_bytes()
+                .chunks(2)
+                .map(|pair| usize::from((pair[0] - b'0') * 10 + (pair[1] - b'0'))),
+        );
+    } else {
+        values.push(CODE128_START_B);
+        values.extend(data.bytes().map(|b| usize::from(b - 32)));
+    }
+    let checksum = values
+        .iter()
+        .enumerate()
+        .map(|(position, value)| position.max(1) * value)
+        .sum::<usize>()
+        % 103;
+    values.push(checksum);
+    values.push(CODE128_STOP);
+
+    let mut modules = Vec::new();
+    for value in values {
+        for (i, width) in CODE128_PATTERNS[value].bytes().enumerate() {
+            let bar = i % 2 == 0;
+            modules.extend(std::iter::repeat_n(bar, usize::from(width - b'0')));
+        }
+    }
+    Ok(modules)
+}
+
+/// What the linear barcode on a label carries: the accession number, or the
+/// test UUID for tests stored before accession numbers existed
+pub fn barcode_value(data: &TestWithPatient) -> &str {
+    data.test
+        .accession_number
+        .as_deref()
+        .unwrap_or(&data.test.uuid)
+}
+
+/// Render `copies` identical labels for a test, one per page
+///
+/// Each label has the patient's name and date of birth, the test type, a
+/// Code 128 barcode of the accession number and a QR code of the test UUID.
+pub fn render(data: &TestWithPatient, copies: u32) -> Result<Vec<u8>, AppError> {
+    let test = &data.test;
+    let patient = &data.patient;
+    let barcode = code128(barcode_value(data))?;
+    let qr = QrCode::with_error_correction_level(test.uuid.as_bytes(), EcLevel::M)
+        .map_err(|e| AppError::internal("Failed to encode QR code").with_details(e.to_string()))?;
+
+    let (doc, page, layer) = PdfDocument::new(
+        format!("Label {}", barcode_value(data)),
+        Mm(LABEL_WIDTH),
+        Mm(LABEL_HEIGHT),
+        "Label",
+    );
+    let regular = doc
+        .add_builtin_font(BuiltinFont::Helvetica)
+        .map_err(render_error)?;
+    let bold = doc
+        .add_builtin_font(BuiltinFont::HelveticaBold)
+        .map_err(render_error)?;
+
+    let mut layers = vec![doc.get_page(page).get_layer(layer)];
+    for _ in 1..copies.clamp(1, MAX_COPIES) {
+        let (page, layer) = doc.add_page(Mm(LABEL_WIDTH), Mm(LABEL_HEIGHT), "Label");
+        layers.push(doc.get_page(page).get_layer(layer));
+    }
+
+    let date_of_birth = patient
+        .date_of_birth
+        .as_deref()
+        .map(|dob| format!("DOB {}", dob))
+        .unwrap_or_default();
+    let mut details = vec![date_of_birth, test.test_type.clone()];
+    if let Some(specimen_id) = &test.specimen_id {
+        details.push(format!("Specimen {}", specimen_id));
+    }
+
+    for layer in &layers {
+        layer.set_fill_color(black());
+        let name_width = LABEL_WIDTH - QR_SIZE - 3.0 * MARGIN;
+        layer.use_text(
+            fit(&patient.full_name(), name_width, 7.0),
+            7.0,
+            Mm(MARGIN),
+            Mm(LABEL_HEIGHT - MARGIN - 2.5),
+            &bold,
+        );
+        for (i, line) in details.iter().filter(|l| !l.is_empty()).enumerate() {
+            layer.use_text(
+                fit(line, name_width, 6.0),
+                6.0,
+                Mm(MARGIN),
+                Mm(LABEL_HEIGHT - MARGIN - 5.5 - 2.6 * i as f32),
+                &regular,
+            );
+        }
+
+        draw_qr(
+            layer,
+            &qr,
+            LABEL_WIDTH - MARGIN - QR_SIZE,
+            LABEL_HEIGHT - MARGIN - QR_SIZE,
+        );
+        draw_code128(layer, &barcode, MARGIN + 2.5);
+
+        let text = barcode_value(data);
+        let text_width = text.len() as f32 * 6.0 * 0.5 * 0.3528;
+        layer.use_text(
+            text,
+            6.0,
+            Mm((LABEL_WIDTH - text_width) / 2.0),
+            Mm(MARGIN),
+            &regular,
+        );
+    }
+
+    doc.save_to_bytes().map_err(render_error)
+}
+
+/// Draw the bars centred across the label with their bottom edge at `bottom`
+fn draw_code128(layer: &PdfLayerReference, modules: &[bool], bottom: f32) {
+    let module = ((LABEL_WIDTH - 2.0 * MARGIN) / (modules.len() + 2 * QUIET_ZONE_MODULES) as f32)
+        .min(MAX_MODULE_WIDTH);
+    let mut x = (LABEL_WIDTH - module * modules.len() as f32) / 2.0;
+    // Adjacent bar modules are merged into one rectangle
+    for (bar, run) in runs(modules) {
+        let width = module * run as f32;
+        if bar {
+            layer.add_rect(
+                Rect::new(
+                    Mm(x),
+                    Mm(bottom),
+                    Mm(x + width),
+                    Mm(bottom + BARCODE_HEIGHT),
+                )
+                .with_mode(PaintMode::Fill),
+            );
+        }
+        x += width;
+    }
+}
+
+/// Draw the QR code in a `QR_SIZE` square, quiet zone included, from its lower left corner
+fn draw_qr(layer: &PdfLayerReference, qr: &QrCode, left: f32, bottom: f32) {
+    let width = qr.width();
+    let module = QR_SIZE / (width + 4) as f32;
+    let colors = qr.to_colors();
+    for (row, line) in colors.chunks(width).enumerate() {
+        let y = bottom + QR_SIZE - module * (row + 3) as f32;
+        let mut x = left + module * 2.0;
+        for (dark, run) in runs(
+            &line
+                .iter()
+                .map(|c| *c == qrcode::Color::Dark)
+                .collect::<Vec<_>>(),
+        ) {
+            let w = module * run as f32;
+            if dark {
+                layer.add_rect(
+                    Rect::new(Mm(x), Mm(y), Mm(x + w), Mm(y + module)).with_mode(PaintMode::Fill),
+                );
+            }
+            x += w;
+        }
+    }
+}
+
+/// Lengths of consecutive equal values
+fn runs(values: &[bool]) -> Vec<(bool, usize)> {
+    let mut runs: Vec<(bool, usize)> = Vec::new();
+    for &value in values {
+        match runs.last_mut() {
+            Some((last, count)) if *last == value => *count += 1,
+            _ => runs.push((value, 1)),
+        }
+    }
+    runs
+}
+
+/// Shorten text to roughly fit `width` mm of Helvetica at `size` points
+fn fit(text: &str, width: f32, size: f32) -> String {
+    // Average Helvetica glyph is about half the font size wide
+    let max_chars = (width / (size * 0.5 * 0.3528)) as usize;
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+    let mut short: String = text.chars().take(max_chars.saturating_sub(1)).collect();
+    short.push('…');
+    short
+}
+
+fn black() -> Color {
+    Color::Rgb(Rgb::new(0.0, 0.0, 0.0, None))
+}
+
+fn render_error(e: printpdf::Error) -> AppError {
+    AppError::internal("Failed to render label").with_details(e.to_string())
+}
+
+/// What a scanned barcode identifies
+#[derive(Debug, Clone, Serialize)]
+pub struct ScanMatch {
+    pub code: String,
+    /// Test with that accession number, UUID or LIS specimen ID
+    pub test: Option<TestWithPatient>,
+    /// The test's patient, or the patient with that ID number
+    pub patient: Option<Patient>,
+}
+
+/// Look up a scanned code as a test first, then as a patient ID number
+pub async fn lookup(db: &DatabaseConnection, code: &str) -> Result<ScanMatch, AppError> {
+    let code = code.trim();
+    if code.is_empty() {
+        return Err(AppError::validation("Nothing was scanned"));
+    }
+    let test = Database::find_test_by_barcode(db, code).await?;
+    let patient = match &test {
+        Some(found) => Some(found.patient.clone()),
+        None => Database::get_patient_by_id_number(db, code).await?,
+    };
+    Ok(ScanMatch {
+        code: code.to_string(),
+        test,
+        patient,
+    })
+}
@@ -1,10 +1,30 @@
+mod astm;
+mod auth;
+mod backup;
+mod capture;
 mod commands;
+mod csv_ingest;
 mod db_orm;
 mod detection_client;
+mod device_registry;
+mod encryption;
 mod entities;
+mod export;
+mod fhir;
+mod hl7;
+mod label_printer;
+mod labels;
+mod logging;
 mod migrations;
 mod models;
+mod outbox;
+mod patient_import;
+mod preprocessing;
+mod qc;
+mod report;
 mod serial_handler;
+mod serial_reader;
+mod worklist;
 
 use tauri::Manager;
 
@@ -40,59 +60,170 @@ pub fn run() {
     {
         builder = builder
             .plugin(tauri_plugin_serialplugin::init())
-            .plugin(
-                tauri_plugin_sql::Builder::default()
-                    .add_migrations("sqlite:ebers.db", migrations::get_migrations())
-                    .build(),
-            )
             .setup(|app| {
-                // Connect to the database that tauri-plugin-sql created and migrated
+                let app_data_dir = app
+                    .path()
+                    .app_data_dir()
+                    .expect("Failed to get app data dir");
+
+                // Logging first, so everything after startup is captured
+                let log_state = logging::init(&app_data_dir.join("logs"))
+                    .expect("Failed to initialise logging");
+
+                // An encrypted database opens with its keyring key or a passphrase from the
+                // environment; otherwise it stays locked until the unlock screen supplies one
+                let config =
+                    encryption::load_config(&app_data_dir).expect("Failed to read database config");
+                let (key, unlocked) = match config.encryption {
+                    encryption::EncryptionMode::None => (None, true),
+                    encryption::EncryptionMode::Keyring => (
+                        Some(
+                            encryption::load_keyring_key(&app_data_dir)
+                                .expect("Failed to load database key"),
+                        ),
+                        true,
+                    ),
+                    encryption::EncryptionMode::Passphrase => {
+                        match std::env::var(encryption::PASSPHRASE_ENV) {
+                            Ok(passphrase) => (
+                                Some(encryption::DatabaseKey::from_passphrase(&passphrase)),
+                                true,
+                            ),
+                            Err(_) => (None, false),
+                        }
+                    }
+                };
+
+                // Open and migrate the database before anything can query it
                 tauri::async_runtime::block_on(async {
-                    use sea_orm::{ConnectOptions, Database as SeaDatabase};
+                    let db_path = app_data_dir.join(db_orm::DATABASE_FILE);
+
+                    tracing::info!("Database path: {}", db_path.display());
 
-                    let app_data_dir = app
-                        .path()
-                        .app_data_dir()
-                        .expect("Failed to get app data dir");
+                    let db = if unlocked {
+                        let db = db_orm::Database::open(&db_path, key.as_ref())
+                            .await
+                            .expect("Failed to open database");
+                        tracing::info!("Connected to database via SeaORM");
+                        commands::apply_saved_log_filter(&db, &log_state).await;
+                        db
+                    } else {
+                        tracing::info!("Database is locked until the passphrase is entered");
+                        db_orm::Database::locked_placeholder()
+                            .await
+                            .expect("Failed to create placeholder database")
+                    };
 
-                    let db_path = app_data_dir.join("ebers.db");
+                    app.manage(tokio::sync::Mutex::new(db));
+                });
 
-                    println!("📁 Database path: {}", db_path.display());
+                app.manage(std::sync::Mutex::new(encryption::Encryption {
+                    mode: config.encryption,
+                    key,
+                    unlocked,
+                }));
 
-                    // Connect to the same database that tauri-plugin-sql manages
-                    let database_url = format!("sqlite://{}?mode=rwc", db_path.display());
+                app.manage(log_state);
 
-                    let mut opt = ConnectOptions::new(database_url);
-                    opt.max_connections(5)
-                        .min_connections(1)
-                        .sqlx_logging(false);
+                // Nobody is signed in until the login screen succeeds
+                app.manage(std::sync::Mutex::new(auth::Session::default()));
 
-                    let db = SeaDatabase::connect(opt)
-                        .await
-                        .expect("Failed to connect to database");
+                backup::spawn_scheduler(
+                    app.handle().clone(),
+                    app_data_dir.join(backup::BACKUP_DIR),
+                );
 
-                    println!("✅ Connected to database via SeaORM");
+                outbox::spawn_sender(app.handle().clone());
+                astm::spawn_order_listener(app.handle().clone());
 
-                    app.manage(tokio::sync::Mutex::new(db));
-                });
+                app.manage(device_registry::DeviceRegistry::new());
 
                 Ok(())
             })
             .invoke_handler(tauri::generate_handler![
                 serial_handler::start_serial,
                 serial_handler::list_serial_ports,
-                serial_handler::get_current_port,
-                serial_handler::set_serial_port,
+                serial_handler::list_devices,
+                serial_handler::get_device_statuses,
+                serial_handler::save_device,
+                serial_handler::remove_device,
+                serial_handler::get_reader_stats,
+                serial_handler::list_captures,
+                serial_handler::replay_capture,
                 commands::save_setting,
                 commands::get_setting,
+                commands::get_logs,
+                commands::set_log_filter,
+                commands::get_schema_version,
+                commands::check_database_integrity,
+                commands::backup_database,
+                commands::list_backups,
+                commands::restore_database,
+                commands::get_backup_schedule,
+                commands::save_backup_schedule,
+                commands::get_encryption_status,
+                commands::unlock_database,
+                commands::set_database_encryption,
+                commands::get_auth_status,
+                commands::login,
+                commands::logout,
+                commands::create_initial_admin,
+                commands::list_users,
+                commands::create_user,
+                commands::update_user,
+                commands::reset_user_password,
+                commands::change_password,
+                commands::set_pin,
+                commands::touch_session,
+                commands::lock_session,
+                commands::unlock_session,
+                commands::set_auto_lock_minutes,
+                commands::get_preprocessing_pipelines,
+                commands::save_preprocessing_pipeline,
                 commands::create_patient,
                 commands::get_patient_by_uuid,
                 commands::get_all_patients,
+                commands::import_patients,
                 commands::create_test,
                 commands::get_test_by_uuid,
                 commands::get_all_tests,
                 commands::update_test_status,
-                commands::complete_test
+                commands::complete_test,
+                commands::get_tests_awaiting_review,
+                commands::release_test,
+                commands::override_test_result,
+                commands::reject_test,
+                commands::get_worklist,
+                commands::create_order,
+                commands::import_orders,
+                commands::start_order,
+                commands::list_control_materials,
+                commands::save_control_material,
+                commands::get_qc_settings,
+                commands::save_qc_settings,
+                commands::start_qc_run,
+                commands::get_qc_run,
+                commands::cancel_qc_run,
+                commands::get_qc_status,
+                commands::get_levey_jennings,
+                commands::get_report_settings,
+                commands::save_report_settings,
+                commands::generate_test_report,
+                commands::lookup_barcode,
+                commands::print_test_label,
+                commands::get_label_printer_settings,
+                commands::save_label_printer_settings,
+                commands::print_sample_label,
+                commands::export_tests,
+                commands::export_fhir_bundle,
+                commands::get_hl7_settings,
+                commands::save_hl7_settings,
+                commands::get_fhir_settings,
+                commands::save_fhir_settings,
+                commands::get_astm_settings,
+                commands::save_astm_settings,
+                commands::list_outbound_messages,
+                commands::retry_outbound_message
             ]);
     }
 
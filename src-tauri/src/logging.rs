@@ -0,0 +1,233 @@
+use regex::{Captures, Regex};
+use serde::Serialize;
+use std::borrow::Cow;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::fmt::MakeWriter;
+use tracing_subscriber::prelude::*;
+use tracing_subscriber::{reload, EnvFilter, Registry};
+
+/// Filter used when neither `EBERS_LOG` nor the `log_filter` setting is set
+pub const DEFAULT_FILTER: &str = "info,sqlx=warn,sea_orm=warn,tao=warn,wry=warn";
+/// Environment variable overriding the startup filter, in `RUST_LOG` syntax
+pub const FILTER_ENV: &str = "EBERS_LOG";
+/// Log files are named `ebers.<date>.log`, one per day
+const LOG_FILE_PREFIX: &str = "ebers";
+const LOG_FILE_SUFFIX: &str = "log";
+/// Daily files kept before the oldest is deleted
+const MAX_LOG_FILES: usize = 14;
+/// Upper bound on lines returned by `get_logs`
+pub const MAX_LOG_LINES: usize = 5000;
+
+const REDACTED: &str = "[REDACTED]";
+
+/// Keeps the file writer alive and lets the filter change at runtime
+pub struct LogState {
+    dir: PathBuf,
+    filter: reload::Handle<EnvFilter, Registry>,
+    _guard: WorkerGuard,
+}
+
+/// Recent log output returned to the UI for support
+#[derive(Debug, Clone, Serialize)]
+pub struct LogSnapshot {
+    pub log_dir: String,
+    pub filter: String,
+    pub lines: Vec<String>,
+    pub truncated: bool,
+}
+
+/// Install the global subscriber writing to stdout and to daily files in `log_dir`
+///
+/// Everything written passes through `redact`, so patient details logged by
+/// mistake never reach disk. Colour is off so field names stay matchable.
+pub fn init(log_dir: &Path) -> Result<LogState, String> {
+    fs::create_dir_all(log_dir).map_err(|e| format!("Failed to create log directory: {}", e))?;
+
+    let appender = RollingFileAppender::builder()
+        .rotation(Rotation::DAILY)
+        .filename_prefix(LOG_FILE_PREFIX)
+        .filename_suffix(LOG_FILE_SUFFIX)
+        .max_log_files(MAX_LOG_FILES)
+        .build(log_dir)
+        .map_err(|e| format!("Failed to open log file: {}", e))?;
+    let (file_writer, guard) = tracing_appender::non_blocking(appender);
+
+    let initial = std::env::var(FILTER_ENV).unwrap_or_else(|_| DEFAULT_FILTER.to_string());
+    let filter = parse_filter(&initial).unwrap_or_else(|_| EnvFilter::new(DEFAULT_FILTER));
+    let (filter, handle) = reload::Layer::new(filter);
+
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(
+            tracing_subscriber::fmt::layer()
+                .with_ansi(false)
+                .with_writer(Redacting(file_writer)),
+        )
+        .with(
+            tracing_subscriber::fmt::layer()
+                .with_ansi(false)
+                .with_writer(Redacting(io::stdout)),
+        )
+        .try_init()
+        .map_err(|e| format!("Failed to install logger: {}", e))?;
+
+    Ok(LogState {
+        dir: log_dir.to_path_buf(),
+        filter: handle,
+        _guard: guard,
+    })
+}
+
+/// Parse a filter such as `info,ebers_lib::serial_handler=debug`
+pub fn parse_filter(spec: &str) -> Result<EnvFilter, String> {
+    EnvFilter::try_new(spec).map_err(|e| format!("Invalid log filter '{}': {}", spec, e))
+}
+
+impl LogState {
+    /// Replace the active filter; takes effect for the next event
+    pub fn set_filter(&self, spec: &str) -> Result<(), String> {
+        let filter = parse_filter(spec)?;
+        self.filter
+            .reload(filter)
+            .map_err(|e| format!("Failed to apply log filter: {}", e))
+    }
+
+    pub fn current_filter(&self) -> String {
+        self.filter
+            .with_current(|filter| filter.to_string())
+            .unwrap_or_default()
+    }
+
+    /// The last `max_lines` lines across the newest log files
+    pub fn tail(&self, max_lines: usize) -> Result<LogSnapshot, String> {
+        let max_lines = max_lines.clamp(1, MAX_LOG_LINES);
+
+        let mut files: Vec<PathBuf> = fs::read_dir(&self.dir)
+            .map_err(|e| format!("Failed to read log directory: {}", e))?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| {
+                path.file_name()
+                    .and_then(|name| name.to_str())
+                    .is_some_and(|name| {
+                        name.starts_with(LOG_FILE_PREFIX) && name.ends_with(LOG_FILE_SUFFIX)
+                    })
+            })
+            .collect();
+        // Names embed the date, so lexical order is chronological
+        files.sort();
+
+        let mut lines: Vec<String> = Vec::new();
+        let mut truncated = false;
+        for file in files.iter().rev() {
+            let content =
+                fs::read_to_string(file).map_err(|e| format!("Failed to read log file: {}", e))?;
+            let mut chunk: Vec<String> = content.lines().map(str::to_string).collect();
+            chunk.append(&mut lines);
+            lines = chunk;
+            if lines.len() >= max_lines {
+                truncated = lines.len() > max_lines;
+                lines.drain(..lines.len() - max_lines);
+                break;
+            }
+        }
+
+        Ok(LogSnapshot {
+            log_dir: self.dir.to_string_lossy().to_string(),
+            filter: self.current_filter(),
+            lines,
+            truncated,
+        })
+    }
+}
+
+/// Wraps a `MakeWriter` so every formatted event is redacted before output
+struct Redacting<M>(M);
+
+impl<'a, M: MakeWriter<'a>> MakeWriter<'a> for Redacting<M> {
+    type Writer = RedactingWriter<M::Writer>;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        RedactingWriter(self.0.make_writer())
+    }
+}
+
+/// The formatter writes one whole event per call, so each write is one line
+struct RedactingWriter<W>(W);
+
+impl<W: Write> Write for RedactingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let text = String::from_utf8_lossy(buf);
+        self.0.write_all(redact(&text).as_bytes())?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.flush()
+    }
+}
+
+struct Redactor {
+    fields: Regex,
+    email: Regex,
+    phone: Regex,
+    iso_date: Regex,
+    local_date: Regex,
+}
+
+fn redactor() -> &'static Redactor {
+    static REDACTOR: OnceLock<Redactor> = OnceLock::new();
+    REDACTOR.get_or_init(|| Redactor {
+        // `first_name = "Ada"`, `"email":"a@b.c"`, `phone: Some("555…")`
+        fields: Regex::new(
+            r#"(?i)\b(first_name|last_name|full_name|patient_name|date_of_birth|dob|email|phone)\b("?\s*[:=]\s*)(Some\()?("[^"]*"|'[^']*'|[^\s,;)}\]]+)"#,
+        )
+        .unwrap(),
+        email: Regex::new(r"[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}").unwrap(),
+        // Separated digit groups only, so UUIDs and sample values are left alone
+        phone: Regex::new(
+            r"(?:\+\d{1,3}[\s.-]?)?(?:\(\d{3}\)\s?|\b\d{3}[\s.-])\d{3}[\s.-]\d{4}\b|\+\d{1,3}(?:[\s-]\d{2,5}){2,4}\b",
+        )
+        .unwrap(),
+        // Bare dates are possible birth dates; `\b` leaves `2024-01-02T…` timestamps intact
+        iso_date: Regex::new(r"\b\d{4}-\d{2}-\d{2}\b").unwrap(),
+        local_date: Regex::new(r"\b\d{1,2}[/.]\d{1,2}[/.]\d{4}\b").unwrap(),
+    })
+}
+
+/// Remove patient names, birth dates, email addresses and phone numbers
+pub fn redact(text: &str) -> Cow<'_, str> {
+    let r = redactor();
+    let mut out = Cow::Borrowed(text);
+
+    if r.fields.is_match(&out) {
+        out = Cow::Owned(
+            r.fields
+                .replace_all(&out, |caps: &Captures| {
+                    format!(
+                        "{}{}{}{}",
+                        &caps[1],
+                        &caps[2],
+                        caps.get(3).map_or("", |m| m.as_str()),
+                        REDACTED
+                    )
+                })
+                .into_owned(),
+        );
+    }
+    for (pattern, replacement) in [
+        (&r.email, "[EMAIL]"),
+        (&r.phone, "[PHONE]"),
+        (&r.iso_date, "[DATE]"),
+        (&r.local_date, "[DATE]"),
+    ] {
+        if pattern.is_match(&out) {
+            out = Cow::Owned(pattern.replace_all(&out, replacement).into_owned());
+        }
+    }
+    out
+}
@@ -1,26 +1,63 @@
-use tauri_plugin_sql::{Migration, MigrationKind};
+use crate::entities::{
+    control_material, device, outbound_message, patient, qc_run, role, setting, test, user,
+};
+use sea_orm::{
+    ConnectionTrait, DatabaseConnection, DbBackend, DbErr, EntityTrait, IdenStatic, Iterable,
+    Statement,
+};
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::{seaql_migrations, MigrationStatus};
+use serde::Serialize;
+use std::collections::HashSet;
+use tracing::{error, info};
 
-/// Get all database migrations
-pub fn get_migrations() -> Vec<Migration> {
+/// One schema change, applied as raw SQL
+///
+/// `version` is the number the legacy `tauri_plugin_sql` migrator recorded,
+/// used to carry existing databases over without re-running anything.
+struct SqlMigration {
+    version: i64,
+    name: &'static str,
+    sql: &'static str,
+}
+
+impl MigrationName for SqlMigration {
+    fn name(&self) -> &str {
+        self.name
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for SqlMigration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .get_connection()
+            .execute_unprepared(self.sql)
+            .await
+            .map(|_| ())
+    }
+}
+
+/// All schema migrations, oldest first
+fn sql_migrations() -> Vec<SqlMigration> {
     vec![
         // Migration 1: Initial schema with settings table
-        Migration {
+        SqlMigration {
             version: 1,
-            description: "create_settings_table",
+            name: "m0001_create_settings_table",
             sql: "CREATE TABLE IF NOT EXISTS settings (
                     key TEXT PRIMARY KEY,
                     value TEXT NOT NULL
                   );",
-            kind: MigrationKind::Up,
         },
         // Migration 2: Patient and test schema
-        Migration {
+        SqlMigration {
             version: 2,
-            description: "create_patient_test_schema",
+            name: "m0002_create_patient_test_schema",
             sql: "
                 -- Drop old detections table if it exists
                 DROP TABLE IF EXISTS detections;
-                
+
                 -- Create patients table
                 CREATE TABLE IF NOT EXISTS patients (
                     id INTEGER PRIMARY KEY AUTOINCREMENT,
@@ -65,8 +102,392 @@ pub fn get_migrations() -> Vec<Migration> {
                 CREATE INDEX IF NOT EXISTS idx_tests_status ON tests(status);
                 CREATE INDEX IF NOT EXISTS idx_tests_created_at ON tests(created_at);
             ",
-            kind: MigrationKind::Up,
+        },
+        // Migration 3: Device registry for concurrent acquisition
+        SqlMigration {
+            version: 3,
+            name: "m0003_create_devices_table",
+            sql: "
+                CREATE TABLE IF NOT EXISTS devices (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    device_id TEXT NOT NULL UNIQUE,
+                    label TEXT NOT NULL,
+                    port TEXT NOT NULL UNIQUE,
+                    enabled INTEGER NOT NULL DEFAULT 1,
+                    created_at TEXT NOT NULL,
+                    updated_at TEXT NOT NULL
+                );
+
+                CREATE INDEX IF NOT EXISTS idx_tests_device_id ON tests(device_id);
+            ",
+        },
+        // Migration 4: Per-device serial line profile
+        SqlMigration {
+            version: 4,
+            name: "m0004_add_device_serial_profile",
+            sql: "
+                ALTER TABLE devices ADD COLUMN baud_rate INTEGER NOT NULL DEFAULT 115200;
+                ALTER TABLE devices ADD COLUMN data_bits INTEGER NOT NULL DEFAULT 8;
+                ALTER TABLE devices ADD COLUMN parity TEXT NOT NULL DEFAULT 'none';
+                ALTER TABLE devices ADD COLUMN stop_bits INTEGER NOT NULL DEFAULT 1;
+                ALTER TABLE devices ADD COLUMN flow_control TEXT NOT NULL DEFAULT 'none';
+                ALTER TABLE devices ADD COLUMN read_timeout_ms INTEGER NOT NULL DEFAULT 50;
+                ALTER TABLE devices ADD COLUMN chunk_size INTEGER NOT NULL DEFAULT 1024;
+            ",
+        },
+        // Migration 5: Data quality report captured at ingestion
+        SqlMigration {
+            version: 5,
+            name: "m0005_add_test_quality_report",
+            sql: "
+                ALTER TABLE tests ADD COLUMN quality_report TEXT;
+            ",
+        },
+        // Migration 6: Per-device CSV column schema (JSON, NULL = legacy layout)
+        SqlMigration {
+            version: 6,
+            name: "m0006_add_device_column_schema",
+            sql: "
+                ALTER TABLE devices ADD COLUMN column_schema TEXT;
+            ",
+        },
+        // Migration 7: Operator accounts with roles, and who ran and released each test
+        SqlMigration {
+            version: 7,
+            name: "m0007_create_users_and_roles",
+            sql: "
+                CREATE TABLE IF NOT EXISTS roles (
+                    name TEXT PRIMARY KEY,
+                    description TEXT NOT NULL
+                );
+
+                INSERT OR IGNORE INTO roles (name, description) VALUES
+                    ('operator', 'Registers patients and runs tests'),
+                    ('reviewer', 'Runs tests and releases results'),
+                    ('admin', 'Manages users, devices and settings');
+
+                CREATE TABLE IF NOT EXISTS users (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    uuid TEXT NOT NULL UNIQUE,
+                    username TEXT NOT NULL UNIQUE COLLATE NOCASE,
+                    display_name TEXT NOT NULL,
+                    role TEXT NOT NULL REFERENCES roles(name),
+                    password_hash TEXT NOT NULL,
+                    active INTEGER NOT NULL DEFAULT 1,
+                    created_at TEXT NOT NULL,
+                    updated_at TEXT NOT NULL,
+                    last_login_at TEXT
+                );
+
+                -- Usernames of the operator who ran a test and the reviewer who released it
+                ALTER TABLE tests ADD COLUMN operated_by TEXT;
+                ALTER TABLE tests ADD COLUMN released_by TEXT;
+                ALTER TABLE tests ADD COLUMN released_at TEXT;
+            ",
+        },
+        // Migration 8: Optional PIN for unlocking an idle session
+        SqlMigration {
+            version: 8,
+            name: "m0008_add_user_pin",
+            sql: "
+                ALTER TABLE users ADD COLUMN pin_hash TEXT;
+            ",
+        },
+        // Migration 9: Result review log and retest links; results completed
+        // before review existed count as released
+        SqlMigration {
+            version: 9,
+            name: "m0009_add_test_review",
+            sql: "
+                ALTER TABLE tests ADD COLUMN review_log TEXT;
+                ALTER TABLE tests ADD COLUMN retest_of TEXT;
+                UPDATE tests SET released_at = COALESCE(completed_at, updated_at)
+                    WHERE status = 'completed' AND released_at IS NULL;
+            ",
+        },
+        // Migration 10: Downsampled signal kept with each test for reports
+        SqlMigration {
+            version: 10,
+            name: "m0010_add_test_signal_preview",
+            sql: "
+                ALTER TABLE tests ADD COLUMN signal_preview TEXT;
+            ",
+        },
+        // Migration 11: Device data as received, for research exports
+        SqlMigration {
+            version: 11,
+            name: "m0011_add_test_raw_signal",
+            sql: "
+                ALTER TABLE tests ADD COLUMN raw_signal TEXT;
+            ",
+        },
+        // Migration 12: Results waiting to be delivered to other systems
+        SqlMigration {
+            version: 12,
+            name: "m0012_create_outbound_messages",
+            sql: "
+                CREATE TABLE IF NOT EXISTS outbound_messages (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    protocol TEXT NOT NULL,
+                    test_uuid TEXT NOT NULL,
+                    control_id TEXT NOT NULL,
+                    payload TEXT NOT NULL,
+                    status TEXT NOT NULL DEFAULT 'pending',
+                    attempts INTEGER NOT NULL DEFAULT 0,
+                    last_error TEXT,
+                    ack_code TEXT,
+                    created_at TEXT NOT NULL,
+                    next_attempt_at TEXT NOT NULL,
+                    last_attempt_at TEXT,
+                    acked_at TEXT
+                );
+
+                CREATE INDEX IF NOT EXISTS idx_outbound_messages_due
+                    ON outbound_messages(status, next_attempt_at);
+                CREATE INDEX IF NOT EXISTS idx_outbound_messages_test_uuid
+                    ON outbound_messages(test_uuid);
+            ",
+        },
+        // Migration 13: Sample identifiers of orders downloaded from the LIS
+        SqlMigration {
+            version: 13,
+            name: "m0013_add_test_specimen_id",
+            sql: "
+                ALTER TABLE tests ADD COLUMN specimen_id TEXT;
+                CREATE INDEX IF NOT EXISTS idx_tests_specimen_id ON tests(specimen_id);
+            ",
+        },
+        // Migration 14: Short numeric sample identifiers for barcode labels
+        SqlMigration {
+            version: 14,
+            name: "m0014_add_test_accession_number",
+            sql: "
+                ALTER TABLE tests ADD COLUMN accession_number TEXT;
+                CREATE UNIQUE INDEX IF NOT EXISTS idx_tests_accession_number ON tests(accession_number);
+            ",
+        },
+        // Migration 15: Control materials and the QC runs made with them
+        SqlMigration {
+            version: 15,
+            name: "m0015_create_quality_control",
+            sql: "
+                CREATE TABLE IF NOT EXISTS control_materials (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    name TEXT NOT NULL,
+                    level TEXT NOT NULL,
+                    test_type TEXT NOT NULL,
+                    lot_number TEXT NOT NULL,
+                    expires_on TEXT NOT NULL,
+                    expected_min REAL NOT NULL,
+                    expected_max REAL NOT NULL,
+                    active INTEGER NOT NULL DEFAULT 1,
+                    created_at TEXT NOT NULL,
+                    updated_at TEXT NOT NULL
+                );
+
+                CREATE TABLE IF NOT EXISTS qc_runs (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    uuid TEXT NOT NULL UNIQUE,
+                    material_id INTEGER NOT NULL,
+                    device_id TEXT NOT NULL,
+                    test_type TEXT NOT NULL,
+                    level TEXT NOT NULL,
+                    lot_number TEXT NOT NULL,
+                    status TEXT NOT NULL,
+                    value REAL,
+                    detection_result TEXT,
+                    violations TEXT,
+                    operated_by TEXT,
+                    created_at TEXT NOT NULL,
+                    completed_at TEXT,
+                    FOREIGN KEY (material_id) REFERENCES control_materials(id)
+                );
+
+                CREATE INDEX IF NOT EXISTS idx_qc_runs_device ON qc_runs(device_id, test_type);
+                CREATE INDEX IF NOT EXISTS idx_qc_runs_material ON qc_runs(material_id, lot_number);
+            ",
         },
     ]
 }
 
+pub struct Migrator;
+
+#[async_trait::async_trait]
+impl MigratorTrait for Migrator {
+    fn migrations() -> Vec<Box<dyn MigrationTrait>> {
+        sql_migrations()
+            .into_iter()
+            .map(|m| Box::new(m) as Box<dyn MigrationTrait>)
+            .collect()
+    }
+}
+
+/// Columns an entity expects that the table lacks, or the table lacks entirely
+#[derive(Debug, Clone, Serialize)]
+pub struct TableDrift {
+    pub table: String,
+    pub missing_table: bool,
+    pub missing_columns: Vec<String>,
+}
+
+/// Schema state reported by `get_schema_version`
+#[derive(Debug, Clone, Serialize)]
+pub struct SchemaVersion {
+    /// Name of the newest applied migration, if any
+    pub version: Option<String>,
+    pub applied: usize,
+    pub known: usize,
+    pub pending: Vec<String>,
+    pub drift: Vec<TableDrift>,
+}
+
+/// Bring a database up to date and verify the entities match it
+///
+/// Safe to run on any SQLite database: fresh, already current, or created by
+/// the legacy `tauri_plugin_sql` migrator.
+pub async fn run(db: &DatabaseConnection) -> Result<SchemaVersion, DbErr> {
+    adopt_legacy_migrations(db).await?;
+    Migrator::up(db, None).await?;
+
+    let status = schema_version(db).await?;
+    match &status.version {
+        Some(version) => info!("Database schema at {}", version),
+        None => info!("Database schema is empty"),
+    }
+    for drift in &status.drift {
+        error!(
+            "Schema drift in {}: missing table={}, missing columns={:?}",
+            drift.table, drift.missing_table, drift.missing_columns
+        );
+    }
+    Ok(status)
+}
+
+/// Current migration state and any entity/schema drift
+pub async fn schema_version(db: &DatabaseConnection) -> Result<SchemaVersion, DbErr> {
+    let migrations = Migrator::get_migration_with_status(db).await?;
+    let applied: Vec<String> = migrations
+        .iter()
+        .filter(|m| m.status() == MigrationStatus::Applied)
+        .map(|m| m.name().to_string())
+        .collect();
+    let pending = migrations
+        .iter()
+        .filter(|m| m.status() == MigrationStatus::Pending)
+        .map(|m| m.name().to_string())
+        .collect();
+
+    Ok(SchemaVersion {
+        version: applied.last().cloned(),
+        applied: applied.len(),
+        known: migrations.len(),
+        pending,
+        drift: check_drift(db).await?,
+    })
+}
+
+/// Record migrations already applied by `tauri_plugin_sql` so they are not re-run
+async fn adopt_legacy_migrations(db: &DatabaseConnection) -> Result<(), DbErr> {
+    if !table_exists(db, "_sqlx_migrations").await? {
+        return Ok(());
+    }
+    if !Migrator::get_migration_models(db).await?.is_empty() {
+        return Ok(());
+    }
+
+    let rows = db
+        .query_all(Statement::from_string(
+            DbBackend::Sqlite,
+            "SELECT version FROM _sqlx_migrations WHERE success = 1",
+        ))
+        .await?;
+    let legacy: HashSet<i64> = rows
+        .iter()
+        .filter_map(|row| row.try_get::<i64>("", "version").ok())
+        .collect();
+
+    let now = chrono::Utc::now().timestamp();
+    for migration in sql_migrations()
+        .into_iter()
+        .filter(|m| legacy.contains(&m.version))
+    {
+        seaql_migrations::Entity::insert(seaql_migrations::ActiveModel {
+            version: sea_orm::Set(migration.name.to_string()),
+            applied_at: sea_orm::Set(now),
+        })
+        .exec(db)
+        .await?;
+    }
+
+    info!(
+        "Adopted {} migrations from the legacy migrator",
+        legacy.len()
+    );
+    Ok(())
+}
+
+async fn table_exists(db: &DatabaseConnection, table: &str) -> Result<bool, DbErr> {
+    let row = db
+        .query_one(Statement::from_sql_and_values(
+            DbBackend::Sqlite,
+            "SELECT COUNT(*) AS n FROM sqlite_master WHERE type = 'table' AND name = ?",
+            [table.into()],
+        ))
+        .await?;
+    Ok(row
+        .and_then(|row| row.try_get::<i64>("", "n").ok())
+        .unwrap_or(0)
+        > 0)
+}
+
+/// Column names of a table, empty if it does not exist
+async fn table_columns(db: &DatabaseConnection, table: &str) -> Result<HashSet<String>, DbErr> {
+    let rows = db
+        .query_all(Statement::from_string(
+            DbBackend::Sqlite,
+            format!("PRAGMA table_info(\"{}\")", table),
+        ))
+        .await?;
+    Ok(rows
+        .iter()
+        .filter_map(|row| row.try_get::<String>("", "name").ok())
+        .collect())
+}
+
+/// Compare one entity's columns with its table
+async fn entity_drift<E>(db: &DatabaseConnection, entity: E) -> Result<Option<TableDrift>, DbErr>
+where
+    E: EntityTrait,
+{
+    let table = entity.table_name().to_string();
+    let columns = table_columns(db, &table).await?;
+    let missing_columns: Vec<String> = E::Column::iter()
+        .map(|column| column.as_str().to_string())
+        .filter(|column| !columns.contains(column))
+        .collect();
+
+    if missing_columns.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(TableDrift {
+        table,
+        missing_table: columns.is_empty(),
+        missing_columns,
+    }))
+}
+
+/// Check every entity against the live schema
+async fn check_drift(db: &DatabaseConnection) -> Result<Vec<TableDrift>, DbErr> {
+    let drift = [
+        entity_drift(db, setting::Entity).await?,
+        entity_drift(db, patient::Entity).await?,
+        entity_drift(db, test::Entity).await?,
+        entity_drift(db, device::Entity).await?,
+        entity_drift(db, role::Entity).await?,
+        entity_drift(db, user::Entity).await?,
+        entity_drift(db, outbound_message::Entity).await?,
+        entity_drift(db, control_material::Entity).await?,
+        entity_drift(db, qc_run::Entity).await?,
+    ];
+    Ok(drift.into_iter().flatten().collect())
+}
@@ -1,8 +1,14 @@
-﻿use sea_orm::DatabaseConnection;
+use sea_orm::DatabaseConnection;
 use tokio::sync::Mutex;
 
 // Re-export shared types for convenience
-pub use shared_types::{DetectionResult, Patient, Test, TestStatus, TestWithPatient};
+pub use shared_types::{
+    AppError, ColumnRole, ColumnSchema, DataQualityReport, DetectionResult, Device, DeviceStatus,
+    ErrorCode, FlowControl, NormalizeMethod, Parity, Patient, Permission, PreprocessStep,
+    PreprocessingPipeline, QualityThresholds, RejectedRow, ReviewAction, Role, SerialProfile, Test,
+    TestStatus, TestType, TestTypePipeline, TestWithPatient, User, INCONCLUSIVE_THRESHOLD,
+    POSITIVE_THRESHOLD,
+};
 
 /// Type alias for the database pool state
 pub type DbState = Mutex<DatabaseConnection>;
@@ -0,0 +1,285 @@
+use crate::astm;
+use crate::db_orm::Database;
+use crate::encryption::EncryptionState;
+use crate::fhir;
+use crate::hl7;
+use crate::models::{AppError, DbState};
+use chrono::{DateTime, SecondsFormat, Utc};
+use sea_orm::DatabaseConnection;
+use serde::Serialize;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tracing::{error, info, warn};
+
+/// How often the sender looks for messages that are due
+const SENDER_TICK: Duration = Duration::from_secs(15);
+/// Messages sent per tick, so a long backlog doesn't starve new results
+const DELIVERY_BATCH: u64 = 20;
+/// Failed attempts before a message is given up on until retried by hand
+const MAX_ATTEMPTS: i32 = 10;
+/// Delay after the first failure; doubled per attempt up to `RETRY_MAX_SECS`
+const RETRY_BASE_SECS: i64 = 30;
+const RETRY_MAX_SECS: i64 = 3600;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MessageStatus {
+    /// Waiting for its first or next attempt
+    Pending,
+    /// The receiver acknowledged it
+    Acked,
+    /// Rejected by the receiver or out of attempts
+    Failed,
+}
+
+impl MessageStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MessageStatus::Pending => "pending",
+            MessageStatus::Acked => "acked",
+            MessageStatus::Failed => "failed",
+        }
+    }
+}
+
+/// A result message queued for another system, with its delivery history
+#[derive(Debug, Clone, Serialize)]
+pub struct OutboundMessage {
+    pub id: i64,
+    /// Which destination and format, e.g. `hl7v2`
+    pub protocol: String,
+    pub test_uuid: String,
+    /// Identifier the receiver echoes back in its acknowledgement
+    pub control_id: String,
+    pub payload: String,
+    pub status: String,
+    pub attempts: i32,
+    pub last_error: Option<String>,
+    pub ack_code: Option<String>,
+    pub created_at: String,
+    pub next_attempt_at: String,
+    pub last_attempt_at: Option<String>,
+    pub acked_at: Option<String>,
+}
+
+/// What happened when a message was sent
+#[derive(Debug, Clone, PartialEq)]
+pub enum Delivery {
+    Accepted {
+        ack_code: String,
+    },
+    /// The receiver refused the message itself; sending it again won't help
+    Rejected {
+        ack_code: String,
+        reason: String,
+    },
+    /// Connection trouble or a temporary refusal
+    Retry(String),
+}
+
+impl OutboundMessage {
+    pub fn new(protocol: &str, test_uuid: &str, control_id: String, payload: String) -> Self {
+        let now = timestamp(Utc::now());
+        Self {
+            id: 0,
+            protocol: protocol.to_string(),
+            test_uuid: test_uuid.to_string(),
+            control_id,
+            payload,
+            status: MessageStatus::Pending.as_str().to_string(),
+            attempts: 0,
+            last_error: None,
+            ack_code: None,
+            created_at: now.clone(),
+            next_attempt_at: now,
+            last_attempt_at: None,
+            acked_at: None,
+        }
+    }
+
+    /// Update status and schedule from the outcome of one attempt
+    pub fn record_attempt(&mut self, delivery: Delivery, now: DateTime<Utc>) {
+        self.attempts += 1;
+        self.last_attempt_at = Some(timestamp(now));
+        match delivery {
+            Delivery::Accepted { ack_code } => {
+                self.status = MessageStatus::Acked.as_str().to_string();
+                self.ack_code = Some(ack_code);
+                self.acked_at = Some(timestamp(now));
+                self.last_error = None;
+            }
+            Delivery::Rejected { ack_code, reason } => {
+                self.status = MessageStatus::Failed.as_str().to_string();
+                self.ack_code = Some(ack_code);
+                self.last_error = Some(reason);
+            }
+            Delivery::Retry(reason) => {
+                self.last_error = Some(reason);
+                if self.attempts >= MAX_ATTEMPTS {
+                    self.status = MessageStatus::Failed.as_str().to_string();
+                } else {
+                    let delay = retry_delay(self.attempts);
+                    self.next_attempt_at = timestamp(now + chrono::Duration::seconds(delay));
+                }
+            }
+        }
+    }
+
+    /// Queue the message again with a fresh set of attempts
+    pub fn reset(&mut self) {
+        self.status = MessageStatus::Pending.as_str().to_string();
+        self.attempts = 0;
+        self.next_attempt_at = timestamp(Utc::now());
+    }
+}
+
+/// Queue a newly released result for every enabled destination
+///
+/// Errors are logged rather than returned: the release has already been saved
+/// and must not be undone because a destination is misconfigured.
+pub async fn queue_result(db: &DatabaseConnection, test_uuid: &str) {
+    if let Err(e) = try_queue_result(db, test_uuid).await {
+        error!("Failed to queue result of test {}: {}", test_uuid, e);
+    }
+}
+
+async fn try_queue_result(db: &DatabaseConnection, test_uuid: &str) -> Result<(), AppError> {
+    let hl7_settings = hl7::load_settings(db).await?;
+    let fhir_settings = fhir::load_settings(db).await?;
+    let astm_settings = astm::load_settings(db).await?;
+    if !hl7_settings.enabled && !fhir_settings.enabled && !astm_settings.enabled {
+        return Ok(());
+    }
+
+    let data = Database::get_test_with_patient(db, test_uuid)
+        .await?
+        .ok_or_else(|| AppError::not_found("Test", test_uuid))?;
+    if !data.test.is_released() {
+        return Err(AppError::validation("Only released results can be sent"));
+    }
+
+    let now = Utc::now();
+    let mut messages = Vec::new();
+    if hl7_settings.enabled {
+        let control_id = hl7::control_id();
+        let payload = hl7::oru_r01(&data, &hl7_settings, &control_id, now);
+        messages.push(OutboundMessage::new(
+            hl7::PROTOCOL,
+            test_uuid,
+            control_id,
+            payload,
+        ));
+    }
+    if fhir_settings.enabled {
+        let bundle_id = uuid::Uuid::new_v4().to_string();
+        let bundle = fhir::transaction_bundle(&data, &fhir_settings, &bundle_id, now);
+        messages.push(OutboundMessage::new(
+            fhir::PROTOCOL,
+            test_uuid,
+            bundle_id,
+            bundle.to_string(),
+        ));
+    }
+    if astm_settings.enabled {
+        let control_id = hl7::control_id();
+        let payload = astm::result_message(&data, &astm_settings, &control_id, now);
+        messages.push(OutboundMessage::new(
+            astm::PROTOCOL,
+            test_uuid,
+            control_id,
+            payload,
+        ));
+    }
+
+    for message in messages {
+        Database::insert_outbound_message(db, &message).await?;
+        info!(
+            "Queued {} result message {} for test {}",
+            message.protocol, message.control_id, test_uuid
+        );
+    }
+    Ok(())
+}
+
+/// Deliver queued messages in the background for the life of the app
+pub fn spawn_sender(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            if let Err(e) = deliver_due(&app).await {
+                error!("Result delivery failed: {}", e);
+            }
+            tokio::time::sleep(SENDER_TICK).await;
+        }
+    });
+}
+
+/// Send every due message once; the database is not held while waiting on the network
+async fn deliver_due(app: &AppHandle) -> Result<(), AppError> {
+    // Nothing can be read until an encrypted database has been unlocked
+    if !app.state::<EncryptionState>().lock().unwrap().unlocked {
+        return Ok(());
+    }
+
+    let db_state = app.state::<DbState>();
+    let (due, hl7_settings, fhir_settings, astm_settings) = {
+        let db = db_state.lock().await;
+        let now = timestamp(Utc::now());
+        (
+            Database::get_due_outbound_messages(&*db, &now, DELIVERY_BATCH).await?,
+            hl7::load_settings(&*db).await?,
+            fhir::load_settings(&*db).await?,
+            astm::load_settings(&*db).await?,
+        )
+    };
+
+    for mut message in due {
+        let delivery = match message.protocol.as_str() {
+            hl7::PROTOCOL if hl7_settings.enabled => hl7::deliver(&hl7_settings, &message).await,
+            fhir::PROTOCOL if fhir_settings.enabled => {
+                fhir::deliver(&fhir_settings, &message).await
+            }
+            astm::PROTOCOL if astm_settings.enabled => {
+                astm::deliver(app, &astm_settings, &message).await
+            }
+            // Held until the destination is switched back on
+            hl7::PROTOCOL | fhir::PROTOCOL | astm::PROTOCOL => continue,
+            other => Delivery::Rejected {
+                ack_code: String::new(),
+                reason: format!("Unknown protocol {}", other),
+            },
+        };
+
+        match &delivery {
+            Delivery::Accepted { ack_code } => info!(
+                "Message {} for test {} acknowledged ({})",
+                message.control_id, message.test_uuid, ack_code
+            ),
+            Delivery::Rejected { ack_code, reason } => error!(
+                "Message {} for test {} rejected ({}): {}",
+                message.control_id, message.test_uuid, ack_code, reason
+            ),
+            Delivery::Retry(reason) => warn!(
+                "Message {} for test {} not delivered (attempt {}): {}",
+                message.control_id,
+                message.test_uuid,
+                message.attempts + 1,
+                reason
+            ),
+        }
+        message.record_attempt(delivery, Utc::now());
+
+        let db = db_state.lock().await;
+        Database::update_outbound_message(&*db, &message).await?;
+    }
+    Ok(())
+}
+
+/// Seconds to wait before the next attempt after `attempts` failures
+fn retry_delay(attempts: i32) -> i64 {
+    let doublings = attempts.saturating_sub(1).clamp(0, 16) as u32;
+    (RETRY_BASE_SECS << doublings).min(RETRY_MAX_SECS)
+}
+
+/// Second-precision UTC, so stored times compare correctly as text
+fn timestamp(time: DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
@@ -0,0 +1,527 @@
+use crate::commands::CreatePatientRequest;
+use crate::db_orm::Database;
+use crate::models::{AppError, Patient};
+use chrono::NaiveDate;
+use sea_orm::DatabaseConnection;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use tracing::info;
+
+/// Largest file accepted, so a wrong pick can't exhaust memory
+const MAX_FILE_BYTES: u64 = 20 * 1024 * 1024;
+/// New rows listed in the report; invalid and duplicate rows are always listed
+pub const PREVIEW_ROWS: usize = 20;
+
+/// Header names recognised for each patient field when suggesting a mapping
+const FIRST_NAME_ALIASES: &[&str] = &["first name", "firstname", "given name", "given", "forename"];
+const LAST_NAME_ALIASES: &[&str] = &["last name", "lastname", "surname", "family name", "family"];
+const DATE_OF_BIRTH_ALIASES: &[&str] = &["date of birth", "dob", "birth date", "birthdate", "born"];
+pub const PATIENT_ID_ALIASES: &[&str] = &[
+    "patient id number",
+    "patient id",
+    "patient number",
+    "mrn",
+    "id number",
+    "id",
+];
+const EMAIL_ALIASES: &[&str] = &["email", "e mail", "email address"];
+const PHONE_ALIASES: &[&str] = &["phone", "phone number", "telephone", "mobile", "tel"];
+const NOTES_ALIASES: &[&str] = &["notes", "note", "comments", "comment"];
+
+/// How dates of birth are written in the file
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum DateFormat {
+    /// 1990-01-31
+    #[default]
+    Iso,
+    /// 31/01/1990 or 31.01.1990
+    DayFirst,
+    /// 01/31/1990
+    MonthFirst,
+}
+
+impl DateFormat {
+    /// Parse a date in this format into `YYYY-MM-DD`
+    fn to_iso(self, text: &str) -> Option<String> {
+        let text = text.trim().replace('.', "/");
+        let pattern = match self {
+            DateFormat::Iso => "%Y-%m-%d",
+            DateFormat::DayFirst => "%d/%m/%Y",
+            DateFormat::MonthFirst => "%m/%d/%Y",
+        };
+        NaiveDate::parse_from_str(&text, pattern)
+            .ok()
+            .map(|d| d.format("%Y-%m-%d").to_string())
+    }
+}
+
+/// Which CSV column holds each patient field; `None` leaves the field empty
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PatientColumnMapping {
+    pub first_name: Option<String>,
+    pub last_name: Option<String>,
+    #[serde(default)]
+    pub date_of_birth: Option<String>,
+    #[serde(default)]
+    pub patient_id_number: Option<String>,
+    #[serde(default)]
+    pub email: Option<String>,
+    #[serde(default)]
+    pub phone: Option<String>,
+    #[serde(default)]
+    pub notes: Option<String>,
+    #[serde(default)]
+    pub date_format: DateFormat,
+}
+
+impl PatientColumnMapping {
+    /// Guess the mapping from the header names
+    pub fn suggest(headers: &[String]) -> Self {
+        let find = |aliases: &[&str]| {
+            aliases.iter().find_map(|alias| {
+                headers
+                    .iter()
+                    .find(|header| normalize_header(header) == *alias)
+                    .cloned()
+            })
+        };
+        Self {
+            first_name: find(FIRST_NAME_ALIASES),
+            last_name: find(LAST_NAME_ALIASES),
+            date_of_birth: find(DATE_OF_BIRTH_ALIASES),
+            patient_id_number: find(PATIENT_ID_ALIASES),
+            email: find(EMAIL_ALIASES),
+            phone: find(PHONE_ALIASES),
+            notes: find(NOTES_ALIASES),
+            date_format: DateFormat::Iso,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum RowStatus {
+    /// Valid and not on file; imported unless this is a dry run
+    New,
+    /// Matches a patient on file or an earlier row; never imported
+    Duplicate,
+    /// Fails validation; never imported
+    Invalid,
+}
+
+/// Outcome for one data row
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportRow {
+    /// Line in the file where the row starts; the header is line 1
+    pub line: usize,
+    pub status: RowStatus,
+    pub name: String,
+    #[serde(default)]
+    pub errors: Vec<String>,
+    /// What the row duplicates, e.g. an existing patient or an earlier line
+    #[serde(default)]
+    pub duplicate_of: Option<String>,
+}
+
+/// Summary of a preview or import
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportReport {
+    pub dry_run: bool,
+    pub headers: Vec<String>,
+    /// The mapping used, including the suggested one when none was given
+    pub mapping: PatientColumnMapping,
+    pub total_rows: usize,
+    pub new_patients: usize,
+    pub duplicates: usize,
+    pub invalid: usize,
+    pub imported: usize,
+    pub rows: Vec<ImportRow>,
+}
+
+/// Validate every row of a patient CSV and, unless `dry_run`, import the new ones
+///
+/// Without a mapping nothing is imported: the report carries the headers and
+/// a suggested mapping for the user to confirm. The new patients are written
+/// in one transaction, so a failure leaves the database untouched.
+pub async fn import_patients(
+    db: &DatabaseConnection,
+    path: &Path,
+    mapping: Option<PatientColumnMapping>,
+    dry_run: bool,
+) -> Result<ImportReport, AppError> {
+    let text = read_file(path)?;
+    let mut records = parse_csv(&text).into_iter();
+    let (_, headers) = records
+        .next()
+        .ok_or_else(|| AppError::validation("The file is empty"))?;
+    let headers: Vec<String> = headers.into_iter().map(|h| h.trim().to_string()).collect();
+
+    let suggested = mapping.is_none();
+    let (mapping, dry_run) = match mapping {
+        Some(mapping) => (mapping, dry_run),
+        None => (PatientColumnMapping::suggest(&headers), true),
+    };
+    let columns = match ColumnIndex::new(&headers, &mapping) {
+        Ok(columns) => columns,
+        // The names could not be guessed; let the user choose them
+        Err(_) if suggested => {
+            return Ok(ImportReport {
+                dry_run,
+                headers,
+                mapping,
+                total_rows: 0,
+                new_patients: 0,
+                duplicates: 0,
+                invalid: 0,
+                imported: 0,
+                rows: Vec::new(),
+            })
+        }
+        Err(e) => return Err(e),
+    };
+
+    let existing = Database::get_all_patients(db).await?;
+    let mut known = DuplicateIndex::default();
+    for patient in &existing {
+        known.add(
+            patient.patient_id_number.as_deref(),
+            &patient.first_name,
+            &patient.last_name,
+            patient.date_of_birth.as_deref(),
+            describe_patient(patient),
+        );
+    }
+
+    let mut report = ImportReport {
+        dry_run,
+        headers,
+        mapping,
+        total_rows: 0,
+        new_patients: 0,
+        duplicates: 0,
+        invalid: 0,
+        imported: 0,
+        rows: Vec::new(),
+    };
+    let mut patients = Vec::new();
+
+    for (line, fields) in records {
+        if fields.iter().all(|f| f.trim().is_empty()) {
+            continue;
+        }
+        report.total_rows += 1;
+
+        let (request, mut errors) = columns.request(&fields, report.mapping.date_format);
+        let request = match request.normalized() {
+            Ok(request) => Some(request),
+            Err(field_errors) => {
+                errors.extend(field_errors);
+                None
+            }
+        };
+        let request = request.filter(|_| errors.is_empty());
+
+        let name = columns.name(&fields);
+        let row = match request {
+            None => {
+                report.invalid += 1;
+                ImportRow {
+                    line,
+                    status: RowStatus::Invalid,
+                    name,
+                    errors,
+                    duplicate_of: None,
+                }
+            }
+            Some(request) => {
+                let duplicate = known.find(
+                    request.patient_id_number.as_deref(),
+                    &request.first_name,
+                    &request.last_name,
+                    request.date_of_birth.as_deref(),
+                );
+                match duplicate {
+                    Some(of) => {
+                        report.duplicates += 1;
+                        ImportRow {
+                            line,
+                            status: RowStatus::Duplicate,
+                            name,
+                            errors: Vec::new(),
+                            duplicate_of: Some(of),
+                        }
+                    }
+                    None => {
+                        known.add(
+                            request.patient_id_number.as_deref(),
+                            &request.first_name,
+                            &request.last_name,
+                            request.date_of_birth.as_deref(),
+                            format!("line {}", line),
+                        );
+                        report.new_patients += 1;
+                        patients.push(Patient::new(
+                            request.first_name,
+                            request.last_name,
+                            request.date_of_birth,
+                            request.patient_id_number,
+                            request.email,
+                            request.phone,
+                            request.notes,
+                        ));
+                        ImportRow {
+                            line,
+                            status: RowStatus::New,
+                            name,
+                            errors: Vec::new(),
+                            duplicate_of: None,
+                        }
+                    }
+                }
+            }
+        };
+
+        if row.status != RowStatus::New || report.new_patients <= PREVIEW_ROWS {
+            report.rows.push(row);
+        }
+    }
+
+    if !report.dry_run && !patients.is_empty() {
+        Database::insert_patients(db, &patients).await?;
+        report.imported = patients.len();
+        info!(
+            "Imported {} patients from {} ({} duplicates, {} invalid rows skipped)",
+            report.imported,
+            path.display(),
+            report.duplicates,
+            report.invalid
+        );
+    }
+    Ok(report)
+}
+
+pub fn read_file(path: &Path) -> Result<String, AppError> {
+    let size = fs::metadata(path)
+        .map_err(|e| AppError::io(format!("Failed to read {}", path.display()), e))?
+        .len();
+    if size > MAX_FILE_BYTES {
+        return Err(AppError::validation(format!(
+            "The file is larger than {} MB",
+            MAX_FILE_BYTES / (1024 * 1024)
+        )));
+    }
+    let bytes = fs::read(path)
+        .map_err(|e| AppError::io(format!("Failed to read {}", path.display()), e))?;
+    let text = String::from_utf8_lossy(&bytes);
+    Ok(text.trim_start_matches('\u{feff}').to_string())
+}
+
+/// Column positions for the mapped fields
+struct ColumnIndex {
+    first_name: usize,
+    last_name: usize,
+    date_of_birth: Option<usize>,
+    patient_id_number: Option<usize>,
+    email: Option<usize>,
+    phone: Option<usize>,
+    notes: Option<usize>,
+}
+
+impl ColumnIndex {
+    fn new(headers: &[String], mapping: &PatientColumnMapping) -> Result<Self, AppError> {
+        let position = |column: &Option<String>| -> Result<Option<usize>, AppError> {
+            match column.as_deref().map(str::trim).filter(|c| !c.is_empty()) {
+                None => Ok(None),
+                Some(column) => headers
+                    .iter()
+                    .position(|h| h == column)
+                    .map(Some)
+                    .ok_or_else(|| {
+                        AppError::validation(format!("The file has no column '{}'", column))
+                    }),
+            }
+        };
+        Ok(Self {
+            first_name: position(&mapping.first_name)?
+                .ok_or_else(|| AppError::validation("Choose the column with first names"))?,
+            last_name: position(&mapping.last_name)?
+                .ok_or_else(|| AppError::validation("Choose the column with last names"))?,
+            date_of_birth: position(&mapping.date_of_birth)?,
+            patient_id_number: position(&mapping.patient_id_number)?,
+            email: position(&mapping.email)?,
+            phone: position(&mapping.phone)?,
+            notes: position(&mapping.notes)?,
+        })
+    }
+
+    /// The row as a create request, with errors for dates that don't parse
+    fn request(
+        &self,
+        fields: &[String],
+        date_format: DateFormat,
+    ) -> (CreatePatientRequest, Vec<String>) {
+        let get = |i: usize| fields.get(i).cloned().unwrap_or_default();
+        let optional = |i: Option<usize>| i.map(get).filter(|v| !v.trim().is_empty());
+
+        let mut errors = Vec::new();
+        let date_of_birth = optional(self.date_of_birth).and_then(|text| {
+            let parsed = date_format.to_iso(&text);
+            if parsed.is_none() {
+                errors.push(format!(
+                    "Date of birth '{}' is not a valid date",
+                    text.trim()
+                ));
+            }
+            parsed
+        });
+
+        let request = CreatePatientRequest {
+            first_name: get(self.first_name),
+            last_name: get(self.last_name),
+            date_of_birth,
+            patient_id_number: optional(self.patient_id_number),
+            email: optional(self.email),
+            phone: optional(self.phone),
+            notes: optional(self.notes),
+        };
+        (request, errors)
+    }
+
+    fn name(&self, fields: &[String]) -> String {
+        let get = |i: usize| fields.get(i).map(|f| f.trim()).unwrap_or_default();
+        format!("{} {}", get(self.first_name), get(self.last_name))
+            .trim()
+            .to_string()
+    }
+}
+
+/// Patients seen so far, keyed by ID number and by name with date of birth
+#[derive(Default)]
+struct DuplicateIndex {
+    by_id_number: HashMap<String, String>,
+    by_name_and_birth: HashMap<(String, String, String), String>,
+}
+
+impl DuplicateIndex {
+    fn add(
+        &mut self,
+        id_number: Option<&str>,
+        first_name: &str,
+        last_name: &str,
+        date_of_birth: Option<&str>,
+        description: String,
+    ) {
+        if let Some(id) = id_number {
+            self.by_id_number
+                .entry(id.trim().to_lowercase())
+                .or_insert_with(|| description.clone());
+        }
+        if let Some(key) = name_key(first_name, last_name, date_of_birth) {
+            self.by_name_and_birth.entry(key).or_insert(description);
+        }
+    }
+
+    fn find(
+        &self,
+        id_number: Option<&str>,
+        first_name: &str,
+        last_name: &str,
+        date_of_birth: Option<&str>,
+    ) -> Option<String> {
+        id_number
+            .and_then(|id| self.by_id_number.get(&id.trim().to_lowercase()))
+            .or_else(|| {
+                name_key(first_name, last_name, date_of_birth)
+                    .and_then(|key| self.by_name_and_birth.get(&key))
+            })
+            .cloned()
+    }
+}
+
+/// Names only identify a patient together with a date of birth
+fn name_key(
+    first_name: &str,
+    last_name: &str,
+    date_of_birth: Option<&str>,
+) -> Option<(String, String, String)> {
+    date_of_birth.map(|dob| {
+        (
+            first_name.trim().to_lowercase(),
+            last_name.trim().to_lowercase(),
+            dob.to_string(),
+        )
+    })
+}
+
+fn describe_patient(patient: &Patient) -> String {
+    match &patient.patient_id_number {
+        Some(id) => format!("patient {} ({})", patient.full_name(), id),
+        None => format!("patient {}", patient.full_name()),
+    }
+}
+
+/// Lower-case a header and treat `_` and `-` as spaces
+pub fn normalize_header(header: &str) -> String {
+    header
+        .trim()
+        .to_lowercase()
+        .replace(['_', '-'], " ")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Split RFC 4180 text into records with the line each starts on
+///
+/// The delimiter is whichever of comma, semicolon or tab appears most in the
+/// header, since spreadsheets in many locales export with semicolons.
+pub fn parse_csv(text: &str) -> Vec<(usize, Vec<String>)> {
+    let header = text.lines().next().unwrap_or_default();
+    let delimiter = [',', ';', '\t']
+        .into_iter()
+        .max_by_key(|d| header.matches(*d).count())
+        .unwrap_or(',');
+
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut line = 1;
+    let mut start_line = 1;
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes => {
+                if chars.peek() == Some(&'"') {
+                    chars.next();
+                    field.push('"');
+                } else {
+                    in_quotes = false;
+                }
+            }
+            '"' if field.is_empty() => in_quotes = true,
+            '\n' if in_quotes => {
+                line += 1;
+                field.push('\n');
+            }
+            '\r' if !in_quotes => {}
+            '\n' => {
+                record.push(std::mem::take(&mut field));
+                records.push((start_line, std::mem::take(&mut record)));
+                line += 1;
+                start_line = line;
+            }
+            c if c == delimiter && !in_quotes => record.push(std::mem::take(&mut field)),
+            c => field.push(c),
+        }
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push((start_line, record));
+    }
+    records
+}
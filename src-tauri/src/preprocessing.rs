@@ -0,0 +1,287 @@
+use crate::csv_ingest::Sample;
+use crate::models::{NormalizeMethod, PreprocessStep, PreprocessingPipeline};
+
+/// Upper bound on samples produced by resampling, to catch bad intervals
+const MAX_RESAMPLED_POINTS: usize = 1_000_000;
+/// Scale factor turning a median absolute deviation into a standard deviation
+const MAD_TO_SIGMA: f64 = 1.4826;
+
+/// Run every step of the pipeline over all channels, in order
+pub fn apply_pipeline(
+    samples: Vec<Sample>,
+    pipeline: &PreprocessingPipeline,
+) -> Result<Vec<Sample>, String> {
+    pipeline.steps.iter().try_fold(samples, apply_step)
+}
+
+fn apply_step(samples: Vec<Sample>, step: &PreprocessStep) -> Result<Vec<Sample>, String> {
+    step.validate()?;
+    if samples.is_empty() {
+        return Ok(samples);
+    }
+
+    let samples = match step {
+        PreprocessStep::Baseline { window } => map_channels(samples, |v| baseline(v, *window)),
+        PreprocessStep::MovingAverage { window } => {
+            map_channels(samples, |v| moving_average(v, *window))
+        }
+        PreprocessStep::SavitzkyGolay { window, order } => {
+            map_channels(samples, |v| savitzky_golay(v, *window, *order))
+        }
+        PreprocessStep::Outliers { z } => map_channels(samples, |v| replace_outliers(v, *z)),
+        PreprocessStep::Resample { interval } => resample(samples, *interval)?,
+        PreprocessStep::Normalize { method } => map_channels(samples, |v| normalize(v, *method)),
+    };
+    Ok(samples)
+}
+
+/// Apply a per-channel transform that keeps the number of samples
+fn map_channels<F>(mut samples: Vec<Sample>, transform: F) -> Vec<Sample>
+where
+    F: Fn(&[f64]) -> Vec<f64>,
+{
+    let channel_count = samples.first().map(|s| s.values.len()).unwrap_or(0);
+    for channel in 0..channel_count {
+        let values: Vec<f64> = samples.iter().map(|s| s.values[channel]).collect();
+        for (sample, value) in samples.iter_mut().zip(transform(&values)) {
+            sample.values[channel] = value;
+        }
+    }
+    samples
+}
+
+/// First channel reduced to at most `points` bucket means, for thumbnails
+pub fn signal_preview(samples: &[Sample], points: usize) -> Vec<f64> {
+    let values: Vec<f64> = samples
+        .iter()
+        .filter_map(|s| s.values.first().copied())
+        .collect();
+    if values.is_empty() || points == 0 {
+        return Vec::new();
+    }
+    let bucket = values.len().div_ceil(points);
+    values.chunks(bucket).map(mean).collect()
+}
+
+fn mean(values: &[f64]) -> f64 {
+    values.iter().sum::<f64>() / values.len() as f64
+}
+
+fn median(values: &[f64]) -> f64 {
+    let mut sorted = values.to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    let mid = sorted.len() / 2;
+    if sorted.len().is_multiple_of(2) {
+        (sorted[mid - 1] + sorted[mid]) / 2.0
+    } else {
+        sorted[mid]
+    }
+}
+
+fn baseline(values: &[f64], window: usize) -> Vec<f64> {
+    let offset = mean(&values[..window.min(values.len())]);
+    values.iter().map(|v| v - offset).collect()
+}
+
+fn moving_average(values: &[f64], window: usize) -> Vec<f64> {
+    let half = window / 2;
+    (0..values.len())
+        .map(|i| {
+            let lo = i.saturating_sub(half);
+            let hi = (i + window - half).min(values.len());
+            mean(&values[lo..hi])
+        })
+        .collect()
+}
+
+/// Savitzky-Golay smoothing; edges use the nearest full window's fit
+fn savitzky_golay(values: &[f64], window: usize, order: usize) -> Vec<f64> {
+    if values.len() < window {
+        return values.to_vec();
+    }
+
+    let half = window / 2;
+    let centre = savgol_weights(window, order, 0.0);
+    let apply = |start: usize, weights: &[f64]| {
+        weights
+            .iter()
+            .zip(&values[start..start + window])
+            .map(|(w, v)| w * v)
+            .sum::<f64>()
+    };
+
+    (0..values.len())
+        .map(|i| {
+            if i < half {
+                apply(0, &savgol_weights(window, order, i as f64 - half as f64))
+            } else if i + half >= values.len() {
+                let start = values.len() - window;
+                apply(
+                    start,
+                    &savgol_weights(window, order, (i - start) as f64 - half as f64),
+                )
+            } else {
+                apply(i - half, &centre)
+            }
+        })
+        .collect()
+}
+
+/// Weights that evaluate a least-squares polynomial fit at offset `x`
+///
+/// Solves `(AᵀA) c = p(x)` for the window's Vandermonde matrix `A`, so the
+/// weights are `A c`.
+fn savgol_weights(window: usize, order: usize, x: f64) -> Vec<f64> {
+    let half = (window / 2) as f64;
+    let terms = order + 1;
+    let positions: Vec<f64> = (0..window).map(|j| j as f64 - half).collect();
+
+    let mut normal = vec![vec![0.0; terms + 1]; terms];
+    for (row, normal_row) in normal.iter_mut().enumerate() {
+        for (col, cell) in normal_row.iter_mut().take(terms).enumerate() {
+            *cell = positions.iter().map(|p| p.powi((row + col) as i32)).sum();
+        }
+        normal_row[terms] = x.powi(row as i32);
+    }
+    let coefficients = solve(normal);
+
+    positions
+        .iter()
+        .map(|p| {
+            coefficients
+                .iter()
+                .enumerate()
+                .map(|(k, c)| c * p.powi(k as i32))
+                .sum()
+        })
+        .collect()
+}
+
+/// Gaussian elimination with partial pivoting on an augmented matrix
+fn solve(mut matrix: Vec<Vec<f64>>) -> Vec<f64> {
+    let n = matrix.len();
+    for col in 0..n {
+        let pivot = (col..n)
+            .max_by(|&a, &b| matrix[a][col].abs().total_cmp(&matrix[b][col].abs()))
+            .unwrap_or(col);
+        matrix.swap(col, pivot);
+
+        let (upper, lower) = matrix.split_at_mut(col + 1);
+        let pivot_row = &upper[col];
+        for row in lower.iter_mut() {
+            let factor = row[col] / pivot_row[col];
+            for (cell, pivot) in row.iter_mut().zip(pivot_row).skip(col) {
+                *cell -= factor * pivot;
+            }
+        }
+    }
+
+    let mut result = vec![0.0; n];
+    for row in (0..n).rev() {
+        let known: f64 = (row + 1..n).map(|k| matrix[row][k] * result[k]).sum();
+        result[row] = (matrix[row][n] - known) / matrix[row][row];
+    }
+    result
+}
+
+/// Replace outliers by interpolating between their nearest good neighbours
+fn replace_outliers(values: &[f64], z: f64) -> Vec<f64> {
+    let centre = median(values);
+    let deviations: Vec<f64> = values.iter().map(|v| (v - centre).abs()).collect();
+    let sigma = MAD_TO_SIGMA * median(&deviations);
+    if sigma == 0.0 {
+        return values.to_vec();
+    }
+
+    let good: Vec<bool> = deviations.iter().map(|d| d / sigma <= z).collect();
+    (0..values.len())
+        .map(|i| {
+            if good[i] {
+                return values[i];
+            }
+            let before = (0..i).rev().find(|&j| good[j]);
+            let after = (i + 1..values.len()).find(|&j| good[j]);
+            match (before, after) {
+                (Some(a), Some(b)) => {
+                    let t = (i - a) as f64 / (b - a) as f64;
+                    values[a] + t * (values[b] - values[a])
+                }
+                (Some(a), None) => values[a],
+                (None, Some(b)) => values[b],
+                (None, None) => values[i],
+            }
+        })
+        .collect()
+}
+
+/// Linearly interpolate all channels onto a fixed timestamp grid
+fn resample(samples: Vec<Sample>, interval: f64) -> Result<Vec<Sample>, String> {
+    let (first, last) = match (samples.first(), samples.last()) {
+        (Some(first), Some(last)) if last.timestamp > first.timestamp => {
+            (first.timestamp, last.timestamp)
+        }
+        _ => return Ok(samples),
+    };
+
+    let count = ((last - first) / interval).floor() as usize + 1;
+    if count > MAX_RESAMPLED_POINTS {
+        return Err(format!(
+            "Resampling at interval {} would produce {} samples",
+            interval, count
+        ));
+    }
+
+    let mut segment = 0;
+    let resampled = (0..count)
+        .map(|k| {
+            let t = first + k as f64 * interval;
+            while segment + 2 < samples.len() && samples[segment + 1].timestamp < t {
+                segment += 1;
+            }
+            let (a, b) = (&samples[segment], &samples[segment + 1]);
+            let span = b.timestamp - a.timestamp;
+            let weight = if span > 0.0 {
+                ((t - a.timestamp) / span).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+
+            Sample {
+                index: k as u32,
+                timestamp: t,
+                values: a
+                    .values
+                    .iter()
+                    .zip(&b.values)
+                    .map(|(va, vb)| va + weight * (vb - va))
+                    .collect(),
+            }
+        })
+        .collect();
+
+    Ok(resampled)
+}
+
+fn normalize(values: &[f64], method: NormalizeMethod) -> Vec<f64> {
+    match method {
+        NormalizeMethod::ZScore => {
+            let mu = mean(values);
+            let variance =
+                values.iter().map(|v| (v - mu).powi(2)).sum::<f64>() / values.len() as f64;
+            let sd = variance.sqrt();
+            values
+                .iter()
+                .map(|v| if sd > 0.0 { (v - mu) / sd } else { v - mu })
+                .collect()
+        }
+        NormalizeMethod::MinMax => {
+            let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+            let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+            let range = max - min;
+            values
+                .iter()
+                .map(|v| if range > 0.0 { (v - min) / range } else { 0.0 })
+                .collect()
+        }
+    }
+}
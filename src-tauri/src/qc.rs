@@ -0,0 +1,546 @@
+use crate::db_orm::Database;
+use crate::models::{AppError, DetectionResult, TestType};
+use chrono::{DateTime, NaiveDate, Utc};
+use sea_orm::DatabaseConnection;
+use serde::{Deserialize, Serialize};
+
+/// Setting holding the JSON-encoded `QcSettings`
+pub const QC_SETTINGS_KEY: &str = "qc_settings";
+
+/// Earlier runs of a lot the Westgard rules look back over; 10x needs ten
+pub const RULE_WINDOW: u64 = 9;
+/// Runs plotted on a Levey-Jennings chart
+pub const CHART_RUNS: u64 = 60;
+/// Format of control material expiry dates
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// Which result a control material is made to give
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ControlLevel {
+    Positive,
+    Negative,
+}
+
+impl ControlLevel {
+    pub fn all() -> [ControlLevel; 2] {
+        [ControlLevel::Positive, ControlLevel::Negative]
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ControlLevel::Positive => "positive",
+            ControlLevel::Negative => "negative",
+        }
+    }
+
+    pub fn from_str(s: &str) -> Result<Self, String> {
+        match s {
+            "positive" => Ok(ControlLevel::Positive),
+            "negative" => Ok(ControlLevel::Negative),
+            _ => Err(format!("Unknown control level: {}", s)),
+        }
+    }
+
+    /// The detection result the control has to give to pass
+    pub fn expected_result(&self) -> DetectionResult {
+        match self {
+            ControlLevel::Positive => DetectionResult::Positive,
+            ControlLevel::Negative => DetectionResult::Negative,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum QcStatus {
+    /// Routed to a device, waiting for its dataset
+    Pending,
+    /// Within 2SD and no rule broken
+    Passed,
+    /// Broke only the 1-2s warning rule; the run is still accepted
+    Warning,
+    /// Broke a rejection rule or gave the wrong result
+    Failed,
+    /// Abandoned before the device reported
+    Cancelled,
+}
+
+impl QcStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            QcStatus::Pending => "pending",
+            QcStatus::Passed => "passed",
+            QcStatus::Warning => "warning",
+            QcStatus::Failed => "failed",
+            QcStatus::Cancelled => "cancelled",
+        }
+    }
+
+    /// States of a run that has a result
+    pub fn completed() -> [QcStatus; 3] {
+        [QcStatus::Passed, QcStatus::Warning, QcStatus::Failed]
+    }
+}
+
+/// A QC rule broken by a run; all but `WrongResult` are Westgard rules
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum QcRule {
+    /// One value beyond 2SD; a warning to look at the earlier runs
+    #[serde(rename = "1-2s")]
+    OneTwoS,
+    /// One value beyond 3SD, i.e. outside the expected range
+    #[serde(rename = "1-3s")]
+    OneThreeS,
+    /// Two consecutive values beyond 2SD on the same side
+    #[serde(rename = "2-2s")]
+    TwoTwoS,
+    /// Two consecutive values more than 4SD apart, one either side
+    #[serde(rename = "R-4s")]
+    RFourS,
+    /// Four consecutive values beyond 1SD on the same side
+    #[serde(rename = "4-1s")]
+    FourOneS,
+    /// Ten consecutive values on the same side of the mean
+    #[serde(rename = "10x")]
+    TenX,
+    /// The control read as something other than its level
+    #[serde(rename = "result")]
+    WrongResult,
+}
+
+impl QcRule {
+    /// Whether breaking the rule fails the run
+    pub fn rejects(&self) -> bool {
+        !matches!(self, QcRule::OneTwoS)
+    }
+}
+
+/// A lot of positive or negative control material
+#[derive(Debug, Clone, Serialize)]
+pub struct ControlMaterial {
+    pub id: i64,
+    pub name: String,
+    pub level: ControlLevel,
+    pub test_type: String,
+    pub lot_number: String,
+    /// Last day the lot can be used, `YYYY-MM-DD`
+    pub expires_on: String,
+    /// Range of detection probability the manufacturer gives for the lot
+    pub expected_min: f64,
+    pub expected_max: f64,
+    /// Offered for new QC runs
+    pub active: bool,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+impl ControlMaterial {
+    /// Target value, the middle of the expected range
+    pub fn mean(&self) -> f64 {
+        (self.expected_min + self.expected_max) / 2.0
+    }
+
+    /// The expected range is taken as the mean ± 3SD
+    pub fn sd(&self) -> f64 {
+        (self.expected_max - self.expected_min) / 6.0
+    }
+
+    /// Whether the lot is past its expiry date on `today`
+    pub fn is_expired(&self, today: NaiveDate) -> bool {
+        NaiveDate::parse_from_str(&self.expires_on, DATE_FORMAT).map_or(true, |day| day < today)
+    }
+
+    /// Trim the text fields and check the range and dates make sense
+    pub fn normalized(mut self) -> Result<Self, AppError> {
+        self.name = self.name.trim().to_string();
+        self.lot_number = self.lot_number.trim().to_string();
+        self.expires_on = self.expires_on.trim().to_string();
+        if self.name.is_empty() {
+            return Err(AppError::validation("Control material name is required"));
+        }
+        if self.lot_number.is_empty() {
+            return Err(AppError::validation("Lot number is required"));
+        }
+        TestType::from_str(&self.test_type).map_err(AppError::validation)?;
+        NaiveDate::parse_from_str(&self.expires_on, DATE_FORMAT).map_err(|_| {
+            AppError::validation(format!(
+                "Expiry date '{}' must be YYYY-MM-DD",
+                self.expires_on
+            ))
+        })?;
+        let in_range = |v: f64| (0.0..=1.0).contains(&v);
+        if !in_range(self.expected_min) || !in_range(self.expected_max) {
+            return Err(AppError::validation(
+                "Expected range must be a probability between 0 and 1",
+            ));
+        }
+        if self.expected_min >= self.expected_max {
+            return Err(AppError::validation(
+                "Expected minimum must be below the maximum",
+            ));
+        }
+        Ok(self)
+    }
+}
+
+/// One measurement of control material on a device
+#[derive(Debug, Clone, Serialize)]
+pub struct QcRun {
+    pub id: i64,
+    pub uuid: String,
+    pub material_id: i64,
+    pub device_id: String,
+    /// Test type, level and lot of the material when the run was made
+    pub test_type: String,
+    pub level: String,
+    pub lot_number: String,
+    pub status: String,
+    /// Detection probability the device gave for the control
+    pub value: Option<f64>,
+    pub detection_result: Option<String>,
+    pub violations: Vec<QcRule>,
+    pub operated_by: Option<String>,
+    pub created_at: String,
+    pub completed_at: Option<String>,
+}
+
+impl QcRun {
+    pub fn new_pending(material: &ControlMaterial, device_id: String, operator: String) -> Self {
+        Self {
+            id: 0,
+            uuid: uuid::Uuid::new_v4().to_string(),
+            material_id: material.id,
+            device_id,
+            test_type: material.test_type.clone(),
+            level: material.level.as_str().to_string(),
+            lot_number: material.lot_number.clone(),
+            status: QcStatus::Pending.as_str().to_string(),
+            value: None,
+            detection_result: None,
+            violations: Vec::new(),
+            operated_by: Some(operator),
+            created_at: Utc::now().to_rfc3339(),
+            completed_at: None,
+        }
+    }
+
+    /// Record the device's value and the rules it broke
+    pub fn record_result(&mut self, value: f64, violations: Vec<QcRule>) {
+        let status = if violations.iter().any(QcRule::rejects) {
+            QcStatus::Failed
+        } else if violations.is_empty() {
+            QcStatus::Passed
+        } else {
+            QcStatus::Warning
+        };
+        self.status = status.as_str().to_string();
+        self.value = Some(value);
+        self.detection_result = Some(
+            DetectionResult::from_probability(value)
+                .as_str()
+                .to_string(),
+        );
+        self.violations = violations;
+        self.completed_at = Some(Utc::now().to_rfc3339());
+    }
+
+    pub fn is_pending(&self) -> bool {
+        self.status == QcStatus::Pending.as_str()
+    }
+}
+
+/// Rules broken by a new value of a control material
+///
+/// `previous` holds the values of earlier runs of the same lot on the same
+/// device, newest first. The expected range is ±3SD, so a value outside it
+/// breaks 1-3s; 1-2s only warns when no rejection rule is broken.
+pub fn evaluate(material: &ControlMaterial, value: f64, previous: &[f64]) -> Vec<QcRule> {
+    let mean = material.mean();
+    let sd = material.sd();
+    let z: Vec<f64> = std::iter::once(value)
+        .chain(previous.iter().copied())
+        .map(|v| (v - mean) / sd)
+        .collect();
+    let all_beyond = |n: usize, limit: f64| {
+        z.len() >= n && (z[..n].iter().all(|v| *v > limit) || z[..n].iter().all(|v| *v < -limit))
+    };
+
+    let mut violations = Vec::new();
+    if z[0].abs() > 3.0 {
+        violations.push(QcRule::OneThreeS);
+    }
+    if all_beyond(2, 2.0) {
+        violations.push(QcRule::TwoTwoS);
+    }
+    if z.len() >= 2 && z[0].abs() > 2.0 && z[1].abs() > 2.0 && z[0].signum() != z[1].signum() {
+        violations.push(QcRule::RFourS);
+    }
+    if all_beyond(4, 1.0) {
+        violations.push(QcRule::FourOneS);
+    }
+    if all_beyond(10, 0.0) {
+        violations.push(QcRule::TenX);
+    }
+    if violations.is_empty() && z[0].abs() > 2.0 {
+        violations.push(QcRule::OneTwoS);
+    }
+    if DetectionResult::from_probability(value) != material.level.expected_result() {
+        violations.push(QcRule::WrongResult);
+    }
+    violations
+}
+
+/// Run a control that has been read on its device through the rules
+pub async fn complete_run(
+    db: &DatabaseConnection,
+    run: &mut QcRun,
+    value: f64,
+) -> Result<(), AppError> {
+    let material = Database::get_control_material(db, run.material_id)
+        .await?
+        .ok_or_else(|| AppError::not_found("Control material", &run.material_id.to_string()))?;
+    let previous: Vec<f64> = Database::get_qc_runs_for_lot(
+        db,
+        run.material_id,
+        &run.lot_number,
+        &run.device_id,
+        RULE_WINDOW,
+    )
+    .await?
+    .into_iter()
+    .filter(|earlier| earlier.uuid != run.uuid)
+    .filter_map(|earlier| earlier.value)
+    .collect();
+    let violations = evaluate(&material, value, &previous);
+    run.record_result(value, violations);
+    Database::update_qc_run(db, run).await
+}
+
+/// Record the detection probability a device reported for a pending QC run
+///
+/// Returns `None` when the dataset belonged to something other than a QC run.
+pub async fn complete_detection(
+    db: &DatabaseConnection,
+    run_uuid: &str,
+    probability: f64,
+) -> Result<Option<QcRun>, AppError> {
+    let Some(mut run) = Database::get_qc_run_by_uuid(db, run_uuid).await? else {
+        return Ok(None);
+    };
+    if !run.is_pending() {
+        return Err(AppError::conflict(format!(
+            "QC run is already {}",
+            run.status
+        )));
+    }
+    complete_run(db, &mut run, probability).await?;
+    Ok(Some(run))
+}
+
+// ----------------------------------------------------------------------------
+// Device lockout
+// ----------------------------------------------------------------------------
+
+/// When patient tests need QC on their device
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QcSettings {
+    /// Block devices that have never run QC for a test type, not just those
+    /// whose last QC failed or expired
+    pub required: bool,
+    /// How long a passed QC run covers its device
+    pub valid_hours: u32,
+}
+
+impl Default for QcSettings {
+    fn default() -> Self {
+        Self {
+            required: false,
+            valid_hours: 24,
+        }
+    }
+}
+
+impl QcSettings {
+    pub fn normalized(self) -> Result<Self, AppError> {
+        if !(1..=24 * 31).contains(&self.valid_hours) {
+            return Err(AppError::validation(
+                "QC must stay valid for between 1 hour and 31 days",
+            ));
+        }
+        Ok(self)
+    }
+}
+
+pub async fn load_settings(db: &DatabaseConnection) -> Result<QcSettings, AppError> {
+    match Database::get_setting(db, QC_SETTINGS_KEY.to_string()).await? {
+        Some(json) => serde_json::from_str(&json)
+            .map_err(|e| AppError::internal("Failed to parse QC settings").with_details(e)),
+        None => Ok(QcSettings::default()),
+    }
+}
+
+pub async fn save_settings(db: &DatabaseConnection, settings: &QcSettings) -> Result<(), AppError> {
+    let json = serde_json::to_string(settings)
+        .map_err(|e| AppError::internal("Failed to encode QC settings").with_details(e))?;
+    Database::save_setting(db, QC_SETTINGS_KEY.to_string(), json).await
+}
+
+/// Whether a device may run patient tests of a type
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum QcState {
+    /// The last QC of each level passed and is still valid
+    Valid,
+    /// No QC has been run; only blocks when QC is required
+    Missing,
+    /// The last QC of a level passed too long ago or on an expired lot
+    Expired,
+    /// The last QC of a level failed
+    Failed,
+}
+
+/// QC standing of one device for one test type
+#[derive(Debug, Clone, Serialize)]
+pub struct DeviceQc {
+    pub device_id: String,
+    pub test_type: String,
+    pub state: QcState,
+    /// Patient tests are refused on the device
+    pub blocked: bool,
+    /// Why the device is not valid, for the operator
+    pub reason: Option<String>,
+    /// Latest completed run of each level
+    pub last_runs: Vec<QcRun>,
+}
+
+/// Work out whether QC allows patient tests of `test_type` on a device
+pub async fn device_status(
+    db: &DatabaseConnection,
+    settings: &QcSettings,
+    device_id: &str,
+    test_type: &str,
+    now: DateTime<Utc>,
+) -> Result<DeviceQc, AppError> {
+    let mut state = QcState::Valid;
+    let mut reasons = Vec::new();
+    let mut last_runs = Vec::new();
+    let mut missing = Vec::new();
+
+    for level in ControlLevel::all() {
+        let Some(run) = Database::get_latest_qc_run(db, device_id, test_type, level).await? else {
+            missing.push(level.as_str());
+            continue;
+        };
+        if run.status == QcStatus::Failed.as_str() {
+            state = QcState::Failed;
+            reasons.push(format!("the last {} control failed", level.as_str()));
+        } else if let Some(why) = expiry(db, settings, &run, now).await? {
+            if state != QcState::Failed {
+                state = QcState::Expired;
+            }
+            reasons.push(format!("the last {} control {}", level.as_str(), why));
+        }
+        last_runs.push(run);
+    }
+
+    if !missing.is_empty() && state == QcState::Valid {
+        state = QcState::Missing;
+        reasons.push(format!("no {} control has been run", missing.join(" or ")));
+    }
+    let blocked = match state {
+        QcState::Valid => false,
+        QcState::Missing => settings.required,
+        QcState::Expired | QcState::Failed => true,
+    };
+    Ok(DeviceQc {
+        device_id: device_id.to_string(),
+        test_type: test_type.to_string(),
+        state,
+        blocked,
+        reason: (!reasons.is_empty()).then(|| reasons.join("; ")),
+        last_runs,
+    })
+}
+
+/// Why a passed run no longer covers its device, if it doesn't
+async fn expiry(
+    db: &DatabaseConnection,
+    settings: &QcSettings,
+    run: &QcRun,
+    now: DateTime<Utc>,
+) -> Result<Option<String>, AppError> {
+    let age_hours = run
+        .completed_at
+        .as_deref()
+        .and_then(|at| DateTime::parse_from_rfc3339(at).ok())
+        .map(|at| (now - at.with_timezone(&Utc)).num_hours());
+    if age_hours.is_none_or(|hours| hours >= i64::from(settings.valid_hours)) {
+        return Ok(Some(format!(
+            "was run more than {} hours ago",
+            settings.valid_hours
+        )));
+    }
+    // A lot that has since been replaced on the material counts as expired
+    let lot_expired = Database::get_control_material(db, run.material_id)
+        .await?
+        .is_none_or(|material| {
+            material.lot_number != run.lot_number || material.is_expired(now.date_naive())
+        });
+    Ok(lot_expired.then(|| {
+        format!(
+            "used lot {}, which has expired or been replaced",
+            run.lot_number
+        )
+    }))
+}
+
+/// Refuse a patient test on a device that QC has not cleared
+pub async fn check_device(
+    db: &DatabaseConnection,
+    device_id: &str,
+    test_type: &str,
+) -> Result<(), AppError> {
+    let settings = load_settings(db).await?;
+    let status = device_status(db, &settings, device_id, test_type, Utc::now()).await?;
+    if !status.blocked {
+        return Ok(());
+    }
+    Err(AppError::validation(format!(
+        "Device {} is locked for {} tests: {}. Run QC on it first.",
+        device_id,
+        test_type,
+        status.reason.unwrap_or_default()
+    )))
+}
+
+/// Values and limits for the Levey-Jennings chart of a lot on a device
+#[derive(Debug, Clone, Serialize)]
+pub struct LeveyJennings {
+    pub material: ControlMaterial,
+    pub device_id: String,
+    pub mean: f64,
+    pub sd: f64,
+    /// Completed runs of the material's current lot, oldest first
+    pub runs: Vec<QcRun>,
+}
+
+pub async fn levey_jennings(
+    db: &DatabaseConnection,
+    material_id: i64,
+    device_id: &str,
+) -> Result<LeveyJennings, AppError> {
+    let material = Database::get_control_material(db, material_id)
+        .await?
+        .ok_or_else(|| AppError::not_found("Control material", &material_id.to_string()))?;
+    let mut runs =
+        Database::get_qc_runs_for_lot(db, material.id, &material.lot_number, device_id, CHART_RUNS)
+            .await?;
+    runs.reverse();
+    Ok(LeveyJennings {
+        mean: material.mean(),
+        sd: material.sd(),
+        device_id: device_id.to_string(),
+        material,
+        runs,
+    })
+}
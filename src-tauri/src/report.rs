@@ -0,0 +1,469 @@
+use crate::db_orm::Database;
+use crate::models::{
+    AppError, DetectionResult, ReviewAction, TestWithPatient, User, INCONCLUSIVE_THRESHOLD,
+    POSITIVE_THRESHOLD,
+};
+use printpdf::image_crate::{self, DynamicImage, GenericImageView, Rgb, RgbImage};
+use printpdf::path::PaintMode;
+use printpdf::{
+    BuiltinFont, Color, Image, ImageTransform, IndirectFontRef, Line, Mm, PdfDocument,
+    PdfLayerReference, Point, Rect,
+};
+use sea_orm::DatabaseConnection;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use tracing::info;
+
+/// Setting holding the JSON-encoded `ReportSettings`
+pub const REPORT_SETTINGS_KEY: &str = "report_settings";
+
+const PAGE_WIDTH: f32 = 210.0;
+const PAGE_HEIGHT: f32 = 297.0;
+const MARGIN: f32 = 20.0;
+/// Logos are scaled to fit this box in the header
+const LOGO_MAX_WIDTH: f32 = 50.0;
+const LOGO_MAX_HEIGHT: f32 = 20.0;
+/// Larger logos are downscaled before embedding to keep reports small
+const LOGO_MAX_PIXELS: u32 = 600;
+const THUMBNAIL_HEIGHT: f32 = 40.0;
+/// Rough line length for 9pt Helvetica across the page body
+const WRAP_CHARS: usize = 100;
+
+/// Clinic details printed at the top of every report
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReportSettings {
+    pub clinic_name: String,
+    #[serde(default)]
+    pub address: String,
+    #[serde(default)]
+    pub contact: String,
+    /// PNG or JPEG shown beside the clinic name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub logo_path: Option<String>,
+}
+
+impl ReportSettings {
+    /// Trim the text fields and check the logo can be read
+    pub fn normalized(mut self) -> Result<Self, AppError> {
+        self.clinic_name = self.clinic_name.trim().to_string();
+        self.address = self.address.trim().to_string();
+        self.contact = self.contact.trim().to_string();
+        self.logo_path = self
+            .logo_path
+            .map(|path| path.trim().to_string())
+            .filter(|path| !path.is_empty());
+        if let Some(path) = &self.logo_path {
+            load_logo(Path::new(path))?;
+        }
+        Ok(self)
+    }
+}
+
+pub async fn load_settings(db: &DatabaseConnection) -> Result<ReportSettings, AppError> {
+    match Database::get_setting(db, REPORT_SETTINGS_KEY.to_string()).await? {
+        Some(json) => serde_json::from_str(&json)
+            .map_err(|e| AppError::internal("Failed to parse report settings").with_details(e)),
+        None => Ok(ReportSettings::default()),
+    }
+}
+
+pub async fn save_settings(
+    db: &DatabaseConnection,
+    settings: &ReportSettings,
+) -> Result<(), AppError> {
+    let json = serde_json::to_string(settings)
+        .map_err(|e| AppError::internal("Failed to encode report settings").with_details(e))?;
+    Database::save_setting(db, REPORT_SETTINGS_KEY.to_string(), json).await
+}
+
+/// Render a released test as a one-page A4 PDF
+pub fn render(
+    data: &TestWithPatient,
+    settings: &ReportSettings,
+    generated_by: &User,
+) -> Result<Vec<u8>, AppError> {
+    let test = &data.test;
+    let patient = &data.patient;
+
+    let (doc, page, layer) = PdfDocument::new(
+        format!("Test report {}", test.uuid),
+        Mm(PAGE_WIDTH),
+        Mm(PAGE_HEIGHT),
+        "Report",
+    );
+    let regular = doc
+        .add_builtin_font(BuiltinFont::Helvetica)
+        .map_err(render_error)?;
+    let bold = doc
+        .add_builtin_font(BuiltinFont::HelveticaBold)
+        .map_err(render_error)?;
+    let mut pdf = PageWriter {
+        layer: doc.get_page(page).get_layer(layer),
+        regular,
+        bold,
+        y: PAGE_HEIGHT - MARGIN,
+    };
+
+    // Clinic header
+    let header_top = pdf.y;
+    let mut header_bottom = header_top;
+    let mut text_x = MARGIN;
+    if let Some(path) = &settings.logo_path {
+        let logo = load_logo(Path::new(path))?;
+        let (width_px, height_px) = logo.dimensions();
+        // Dots per inch that fit the logo inside the header box
+        let dpi = (height_px as f32 * 25.4 / LOGO_MAX_HEIGHT)
+            .max(width_px as f32 * 25.4 / LOGO_MAX_WIDTH);
+        let width = width_px as f32 * 25.4 / dpi;
+        let height = height_px as f32 * 25.4 / dpi;
+        Image::from_dynamic_image(&logo).add_to_layer(
+            pdf.layer.clone(),
+            ImageTransform {
+                translate_x: Some(Mm(MARGIN)),
+                translate_y: Some(Mm(pdf.y - height)),
+                dpi: Some(dpi),
+                ..Default::default()
+            },
+        );
+        text_x += width + 5.0;
+        header_bottom -= height;
+    }
+    pdf.text_at(&settings.clinic_name, 16.0, text_x, true);
+    pdf.y -= 6.0;
+    for line in settings.address.lines().chain(settings.contact.lines()) {
+        pdf.text_at(line, 9.0, text_x, false);
+        pdf.y -= 4.5;
+    }
+    pdf.y = pdf.y.min(header_bottom) - 3.0;
+    pdf.rule();
+
+    pdf.y -= 8.0;
+    pdf.text("Test Report", 14.0, true);
+    pdf.y -= 5.0;
+    pdf.text(&format!("Test ID: {}", test.uuid), 9.0, false);
+    pdf.y -= 8.0;
+
+    pdf.section("Patient");
+    pdf.fields(&[
+        (
+            "Name",
+            format!("{} {}", patient.first_name, patient.last_name),
+        ),
+        ("Patient ID", or_dash(patient.patient_id_number.as_deref())),
+        ("Date of birth", or_dash(patient.date_of_birth.as_deref())),
+        ("Phone", or_dash(patient.phone.as_deref())),
+    ]);
+
+    pdf.section("Test");
+    pdf.fields(&[
+        ("Test type", test.test_type.clone()),
+        ("Device", or_dash(test.device_id.as_deref())),
+        ("Firmware", or_dash(test.firmware_version.as_deref())),
+        ("Operator", or_dash(test.operated_by.as_deref())),
+        ("Started", format_timestamp(&test.created_at)),
+        (
+            "Completed",
+            test.completed_at
+                .as_deref()
+                .map(format_timestamp)
+                .unwrap_or_else(|| "-".to_string()),
+        ),
+        ("Released by", or_dash(test.released_by.as_deref())),
+        (
+            "Released",
+            test.released_at
+                .as_deref()
+                .map(format_timestamp)
+                .unwrap_or_else(|| "-".to_string()),
+        ),
+    ]);
+
+    pdf.section("Result");
+    let result = test
+        .detection_result
+        .as_deref()
+        .and_then(|r| DetectionResult::from_str(r).ok());
+    let (r, g, b) = match result {
+        Some(DetectionResult::Positive) => (0.6, 0.1, 0.1),
+        Some(DetectionResult::Inconclusive) => (0.57, 0.25, 0.05),
+        Some(DetectionResult::Negative) => (0.09, 0.4, 0.2),
+        None => (0.0, 0.0, 0.0),
+    };
+    pdf.layer.set_fill_color(rgb(r, g, b));
+    pdf.text(
+        &test
+            .detection_result
+            .as_deref()
+            .unwrap_or("No result")
+            .to_uppercase(),
+        18.0,
+        true,
+    );
+    pdf.layer.set_fill_color(rgb(0.0, 0.0, 0.0));
+    pdf.y -= 8.0;
+    pdf.fields(&[
+        (
+            "Confidence",
+            test.confidence
+                .map(|c| format!("{:.1}%", c * 100.0))
+                .unwrap_or_else(|| "-".to_string()),
+        ),
+        (
+            "Probability",
+            probability(test.raw_response.as_deref())
+                .map(|p| format!("{:.1}%", p * 100.0))
+                .unwrap_or_else(|| "-".to_string()),
+        ),
+    ]);
+    if let Some(event) = test
+        .review_log
+        .iter()
+        .rev()
+        .find(|e| e.action == ReviewAction::Overridden)
+    {
+        pdf.paragraph(&format!(
+            "Measured result {} was overridden by {} on {}: {}",
+            event.previous_result.as_deref().unwrap_or("-"),
+            event.by,
+            format_timestamp(&event.at),
+            event.reason.as_deref().unwrap_or("-")
+        ));
+    }
+
+    pdf.section("Thresholds");
+    pdf.paragraph(&format!(
+        "Positive at probability >= {:.0}%, inconclusive at >= {:.0}%, negative below.",
+        POSITIVE_THRESHOLD * 100.0,
+        INCONCLUSIVE_THRESHOLD * 100.0
+    ));
+    match test.quality_report.as_ref() {
+        Some(report) => {
+            if let Some(t) = &report.thresholds {
+                pdf.paragraph(&format!(
+                    "Data quality limits: at least {} rows, at most {:.0}% rejected, {:.0}% missing, {:.0}% saturated, {:.0}% non-monotonic.",
+                    t.min_accepted_rows,
+                    t.max_rejected_ratio * 100.0,
+                    t.max_missing_ratio * 100.0,
+                    t.max_saturation_ratio * 100.0,
+                    t.max_non_monotonic_ratio * 100.0
+                ));
+            }
+            pdf.paragraph(&format!("Data quality: {}", report.summary()));
+        }
+        None => pdf.paragraph("Data quality: not recorded"),
+    }
+
+    pdf.section("Signal");
+    pdf.thumbnail(&test.signal_preview);
+
+    // Footer
+    pdf.y = MARGIN;
+    pdf.rule();
+    pdf.y -= 5.0;
+    pdf.text(
+        &format!(
+            "Generated {} by {}",
+            format_timestamp(&chrono::Utc::now().to_rfc3339()),
+            generated_by.username
+        ),
+        8.0,
+        false,
+    );
+
+    let bytes = doc.save_to_bytes().map_err(render_error)?;
+    info!(
+        "Rendered report for test {} ({} bytes)",
+        test.uuid,
+        bytes.len()
+    );
+    Ok(bytes)
+}
+
+/// Write a rendered report, creating missing parent folders
+pub fn save(bytes: &[u8], path: &Path) -> Result<(), AppError> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| AppError::io(format!("Failed to create {}", parent.display()), e))?;
+    }
+    fs::write(path, bytes)
+        .map_err(|e| AppError::io(format!("Failed to write {}", path.display()), e))
+}
+
+/// Keeps the vertical position while laying out the page top to bottom
+struct PageWriter {
+    layer: PdfLayerReference,
+    regular: IndirectFontRef,
+    bold: IndirectFontRef,
+    /// Baseline of the next line, in mm from the bottom edge
+    y: f32,
+}
+
+impl PageWriter {
+    fn font(&self, bold: bool) -> &IndirectFontRef {
+        if bold {
+            &self.bold
+        } else {
+            &self.regular
+        }
+    }
+
+    fn text_at(&self, text: &str, size: f32, x: f32, bold: bool) {
+        self.layer
+            .use_text(text, size, Mm(x), Mm(self.y), self.font(bold));
+    }
+
+    fn text(&self, text: &str, size: f32, bold: bool) {
+        self.text_at(text, size, MARGIN, bold);
+    }
+
+    fn rule(&self) {
+        self.layer.set_outline_color(rgb(0.7, 0.7, 0.7));
+        self.layer.set_outline_thickness(0.5);
+        self.layer.add_line(Line {
+            points: vec![
+                (Point::new(Mm(MARGIN), Mm(self.y)), false),
+                (Point::new(Mm(PAGE_WIDTH - MARGIN), Mm(self.y)), false),
+            ],
+            is_closed: false,
+        });
+    }
+
+    fn section(&mut self, title: &str) {
+        self.y -= 4.0;
+        self.text(title, 11.0, true);
+        self.y -= 2.0;
+        self.rule();
+        self.y -= 5.5;
+    }
+
+    /// Label and value pairs laid out in two columns
+    fn fields(&mut self, fields: &[(&str, String)]) {
+        let column_width = (PAGE_WIDTH - 2.0 * MARGIN) / 2.0;
+        for row in fields.chunks(2) {
+            for (column, (label, value)) in row.iter().enumerate() {
+                let x = MARGIN + column as f32 * column_width;
+                self.layer.set_fill_color(rgb(0.4, 0.4, 0.4));
+                self.text_at(label, 8.0, x, false);
+                self.layer.set_fill_color(rgb(0.0, 0.0, 0.0));
+                self.text_at(value, 10.0, x + 28.0, false);
+            }
+            self.y -= 5.5;
+        }
+    }
+
+    fn paragraph(&mut self, text: &str) {
+        for line in wrap(text, WRAP_CHARS) {
+            self.text(&line, 9.0, false);
+            self.y -= 4.5;
+        }
+    }
+
+    /// Line plot of the signal preview, scaled to fill a framed box
+    fn thumbnail(&mut self, values: &[f64]) {
+        let top = self.y + 2.0;
+        let bottom = top - THUMBNAIL_HEIGHT;
+        let right = PAGE_WIDTH - MARGIN;
+
+        self.layer.set_outline_color(rgb(0.8, 0.8, 0.8));
+        self.layer.set_outline_thickness(0.5);
+        self.layer.add_rect(
+            Rect::new(Mm(MARGIN), Mm(bottom), Mm(right), Mm(top)).with_mode(PaintMode::Stroke),
+        );
+
+        if values.len() < 2 {
+            self.y = bottom + THUMBNAIL_HEIGHT / 2.0;
+            self.text_at("No signal recorded", 9.0, MARGIN + 5.0, false);
+        } else {
+            let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+            let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+            let span = if max > min { max - min } else { 1.0 };
+            let inset = 2.0;
+            let width = right - MARGIN - 2.0 * inset;
+            let height = THUMBNAIL_HEIGHT - 2.0 * inset;
+            let step = width / (values.len() - 1) as f32;
+
+            self.layer.set_outline_color(rgb(0.15, 0.3, 0.6));
+            self.layer.set_outline_thickness(0.6);
+            self.layer.add_line(Line {
+                points: values
+                    .iter()
+                    .enumerate()
+                    .map(|(i, value)| {
+                        let x = MARGIN + inset + i as f32 * step;
+                        let y = bottom + inset + ((value - min) / span) as f32 * height;
+                        (Point::new(Mm(x), Mm(y)), false)
+                    })
+                    .collect(),
+                is_closed: false,
+            });
+        }
+        self.y = bottom - 6.0;
+    }
+}
+
+/// Read a logo, downscaled and with transparency flattened onto white
+///
+/// Flattening avoids soft masks, which some PDF viewers render incorrectly.
+fn load_logo(path: &Path) -> Result<DynamicImage, AppError> {
+    let image = image_crate::open(path).map_err(|e| {
+        AppError::validation(format!("Cannot read logo {}", path.display())).with_details(e)
+    })?;
+    let image = if image.width() > LOGO_MAX_PIXELS || image.height() > LOGO_MAX_PIXELS {
+        image.thumbnail(LOGO_MAX_PIXELS, LOGO_MAX_PIXELS)
+    } else {
+        image
+    };
+
+    let rgba = image.to_rgba8();
+    let flattened = RgbImage::from_fn(rgba.width(), rgba.height(), |x, y| {
+        let pixel = rgba.get_pixel(x, y);
+        let alpha = u32::from(pixel[3]);
+        let blend = |c: u8| ((u32::from(c) * alpha + 255 * (255 - alpha)) / 255) as u8;
+        Rgb([blend(pixel[0]), blend(pixel[1]), blend(pixel[2])])
+    });
+    Ok(DynamicImage::ImageRgb8(flattened))
+}
+
+/// Detection probability from the stored API response
+pub fn probability(raw_response: Option<&str>) -> Option<f64> {
+    let json: serde_json::Value = serde_json::from_str(raw_response?).ok()?;
+    json.get("probability")?.as_f64()
+}
+
+fn format_timestamp(timestamp: &str) -> String {
+    chrono::DateTime::parse_from_rfc3339(timestamp)
+        .map(|t| t.format("%Y-%m-%d %H:%M UTC").to_string())
+        .unwrap_or_else(|_| timestamp.to_string())
+}
+
+fn or_dash(value: Option<&str>) -> String {
+    value.filter(|v| !v.is_empty()).unwrap_or("-").to_string()
+}
+
+/// Break text into lines of at most `width` characters at word boundaries
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        if !line.is_empty() && line.chars().count() + 1 + word.chars().count() > width {
+            lines.push(std::mem::take(&mut line));
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(word);
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+fn rgb(r: f32, g: f32, b: f32) -> Color {
+    Color::Rgb(printpdf::Rgb::new(r, g, b, None))
+}
+
+fn render_error(e: printpdf::Error) -> AppError {
+    AppError::internal("Failed to render report").with_details(e)
+}
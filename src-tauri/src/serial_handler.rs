@@ -1,37 +1,80 @@
+use crate::auth::{self, SessionState};
+use crate::capture::{
+    list_captures as list_capture_files, read_capture, CaptureInfo, CaptureRecord, CaptureWriter,
+};
+use crate::csv_ingest::parse_csv_data;
 use crate::db_orm::Database;
-use crate::detection_client::{create_detection_request, DetectionApiClient};
-use crate::models::DbState;
-use serde::Serialize;
-use std::thread::sleep;
+use crate::detection_client::{
+    create_detection_request, DetectionApiClient, DetectionResponse, PreprocessingMetadata,
+};
+use crate::device_registry::DeviceRegistry;
+use crate::models::{
+    AppError, ColumnSchema, DataQualityReport, DbState, Device, DeviceStatus, Permission,
+    PreprocessingPipeline, QualityThresholds, SerialProfile, TestType,
+};
+use crate::preprocessing::{apply_pipeline, signal_preview};
+use crate::qc;
+use crate::serial_reader::{spawn_reader, ReaderEvent, ReaderStats};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
 use std::time::{Duration, Instant};
-use tauri::{AppHandle, Emitter, Manager};
+use tauri::{AppHandle, Emitter, Manager, State};
+use tokio::sync::mpsc;
+use tracing::{debug, error, info, trace, warn};
 
 #[cfg(desktop)]
 use tauri_plugin_serialplugin::{commands, desktop_api};
 
+/// Points kept in the signal thumbnail stored with each test
+const SIGNAL_PREVIEW_POINTS: usize = 200;
+
 #[derive(Serialize, Clone)]
 pub struct SerialStatus {
+    pub device_id: String,
     pub connected: bool,
     pub port: Option<String>,
 }
 
+#[derive(Serialize, Clone)]
+pub struct SerialLine {
+    pub device_id: String,
+    pub line: String,
+}
+
 #[derive(Serialize, Clone)]
 pub struct DetectionLoading {
     pub loading: bool,
     pub dataset_id: Option<String>,
+    pub device_id: String,
+    pub test_uuid: Option<String>,
 }
 
 #[derive(Serialize, Clone)]
 pub struct DetectionError {
     pub error: String,
     pub dataset_id: Option<String>,
+    pub device_id: String,
+    pub test_uuid: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quality_report: Option<DataQualityReport>,
 }
 
-/// Configuration for serial connection
+#[derive(Serialize, Clone)]
+pub struct DetectionResultEvent {
+    #[serde(flatten)]
+    pub response: DetectionResponse,
+    pub device_id: String,
+    pub test_uuid: Option<String>,
+    pub quality_report: DataQualityReport,
+}
+
+/// Configuration for a single device's serial connection
 #[derive(Clone)]
 struct SerialConfig {
+    device_id: String,
     port: String,
-    baud_rate: u32,
+    profile: SerialProfile,
+    schema: ColumnSchema,
     api_endpoint: String,
 }
 
@@ -65,12 +108,13 @@ impl SerialDataState {
         self.reset_timing();
     }
 
-    fn is_idle(&self) -> bool {
-        if let Some(t) = self.last_data_at {
-            t.elapsed() >= self.idle_gap && !self.csv_buffer.is_empty()
-        } else {
-            false
+    /// When the pending dataset should be flushed if no more data arrives
+    fn idle_deadline(&self) -> Option<tokio::time::Instant> {
+        if self.csv_buffer.is_empty() {
+            return None;
         }
+        self.last_data_at
+            .map(|t| tokio::time::Instant::from_std(t + self.idle_gap))
     }
 
     fn get_collection_duration_ms(&self) -> u64 {
@@ -80,54 +124,76 @@ impl SerialDataState {
     }
 }
 
-/// Load configuration asynchronously (can check database)
-async fn load_serial_config_async(app: &AppHandle) -> SerialConfig {
-    crate::try_load_dotenv();
-
-    // Try to get port from database first
-    let port = if let Some(db_state) = app.try_state::<DbState>() {
-        let db = db_state.lock().await;
-        Database::get_setting(&*db, "serial_port".to_string())
-            .await
-            .ok()
-            .flatten()
-    } else {
-        None
-    };
-
+/// Build the serial configuration for a stored device
+fn serial_config_for(device: &Device) -> SerialConfig {
     SerialConfig {
-        port: port
-            .unwrap_or_else(|| std::env::var("SERIAL_PORT").unwrap_or_else(|_| "COM3".to_string())),
-        baud_rate: std::env::var("SERIAL_BAUD")
-            .ok()
-            .and_then(|s| s.parse().ok())
-            .unwrap_or(115_200),
+        device_id: device.device_id.clone(),
+        port: device.port.clone(),
+        profile: device.profile.clone(),
+        schema: device.schema.clone(),
         api_endpoint: std::env::var("DETECTION_API_ENDPOINT")
             .unwrap_or_else(|_| "http://localhost:8000/api/detect".to_string()),
     }
 }
 
-/// Attempt to open serial port connection
-fn try_open_serial_port(app: &AppHandle, port: &str, baud_rate: u32) -> Result<(), String> {
-    commands::open(
-        app.clone(),
-        app.state::<desktop_api::SerialPort<tauri::Wry>>().clone(),
-        port.to_string(),
-        baud_rate,
-        None,
-        None,
-        None,
-        None,
-        Some(50),
-    )
-    .map_err(|e| e.to_string())
+/// Load configured devices, seeding a default device from the legacy
+/// `serial_port` setting (or `SERIAL_PORT` env var) when none exist yet
+async fn load_devices_async(app: &AppHandle) -> Result<Vec<Device>, AppError> {
+    crate::try_load_dotenv();
+
+    let db_state = app.state::<DbState>();
+    let db = db_state.lock().await;
+
+    let devices = Database::get_all_devices(&*db).await?;
+    if !devices.is_empty() {
+        return Ok(devices);
+    }
+
+    let port = Database::get_setting(&*db, "serial_port".to_string())
+        .await?
+        .unwrap_or_else(|| std::env::var("SERIAL_PORT").unwrap_or_else(|_| "COM3".to_string()));
+
+    let mut device = Device::new("reader-1".to_string(), "Reader 1".to_string(), port);
+    if let Some(baud_rate) = std::env::var("SERIAL_BAUD")
+        .ok()
+        .and_then(|s| s.parse().ok())
+    {
+        device.profile.baud_rate = baud_rate;
+    }
+    Database::save_device(&*db, &device).await?;
+
+    info!(device_id = %device.device_id, "Seeded default device on {}", device.port);
+    Ok(vec![device])
+}
+
+/// Apply a status change to a device and publish it to the frontend
+fn update_device_status<F>(app: &AppHandle, device_id: &str, f: F)
+where
+    F: FnOnce(&mut DeviceStatus),
+{
+    if let Some(status) = app.state::<DeviceRegistry>().update_status(device_id, f) {
+        publish_device_status(app, &status);
+    }
+}
+
+/// Emit a device status snapshot to the frontend status board
+fn publish_device_status(app: &AppHandle, status: &DeviceStatus) {
+    let _ = app.emit("serial:device_status", status);
 }
 
 /// Emit connection status to frontend
-fn emit_connection_status(app: &AppHandle, connected: bool, port: &str) {
+fn emit_connection_status(app: &AppHandle, device_id: &str, connected: bool, port: &str) {
+    update_device_status(app, device_id, |status| {
+        status.connected = connected;
+        if !connected {
+            status.collecting = false;
+        }
+    });
+
     let _ = app.emit(
         "serial:status",
         &SerialStatus {
+            device_id: device_id.to_string(),
             connected,
             port: Some(port.to_string()),
         },
@@ -137,20 +203,25 @@ fn emit_connection_status(app: &AppHandle, connected: bool, port: &str) {
 /// Handle successful serial port connection
 fn handle_connection_success(
     app: &AppHandle,
-    port: &str,
-    baud_rate: u32,
+    config: &SerialConfig,
     data_state: &mut SerialDataState,
 ) {
-    println!("[serial] opened {} @ {} baud", port, baud_rate);
+    info!(
+        device_id = %config.device_id,
+        "Opened {} @ {}",
+        config.port,
+        config.profile.summary()
+    );
     data_state.reset_timing();
-    emit_connection_status(app, true, port);
+    update_device_status(app, &config.device_id, |status| status.last_error = None);
+    emit_connection_status(app, &config.device_id, true, &config.port);
 }
 
 /// Process incoming serial data chunk
 fn process_serial_data_chunk(
     app: &AppHandle,
     chunk: &str,
-    port: &str,
+    device_id: &str,
     data_state: &mut SerialDataState,
 ) {
     if chunk.is_empty() {
@@ -161,10 +232,17 @@ fn process_serial_data_chunk(
     data_state.csv_buffer.push_str(chunk);
     let now = Instant::now();
     data_state.last_data_at = Some(now);
-    if data_state.data_start_at.is_none() {
+    let dataset_started = data_state.data_start_at.is_none();
+    if dataset_started {
         data_state.data_start_at = Some(now);
     }
 
+    let received_at = chrono::Utc::now().to_rfc3339();
+    update_device_status(app, device_id, |status| {
+        status.collecting = true;
+        status.last_data_at = Some(received_at);
+    });
+
     // Process complete lines for frontend emission
     data_state.line_buffer.push_str(chunk);
 
@@ -178,8 +256,14 @@ fn process_serial_data_chunk(
                 line.pop();
             }
             // Log and emit full line (prevents chunk boundary artifacts)
-            println!("[serial {}] {}", port, line);
-            let _ = app.emit("serial:data", &line);
+            trace!(device_id = %device_id, "{}", line);
+            let _ = app.emit(
+                "serial:data",
+                &SerialLine {
+                    device_id: device_id.to_string(),
+                    line,
+                },
+            );
         } else {
             break;
         }
@@ -190,75 +274,237 @@ fn process_serial_data_chunk(
 async fn handle_detection_api_call(
     app: AppHandle,
     csv_data: String,
-    port: String,
-    baud_rate: u32,
+    config: SerialConfig,
+    test_uuid: Option<String>,
     collection_duration_ms: u64,
     api_client: DetectionApiClient,
 ) {
+    let device_id = config.device_id.clone();
+
     // Emit loading state
     let _ = app.emit(
         "serial:detection_loading",
         &DetectionLoading {
             loading: true,
             dataset_id: None,
+            device_id: device_id.clone(),
+            test_uuid: test_uuid.clone(),
         },
     );
 
-    // Create detection request
-    match create_detection_request(&csv_data, port.clone(), baud_rate, collection_duration_ms) {
-        Ok(request) => {
-            let dataset_id = request.dataset_id.clone();
-            println!(
-                "[api_client] Calling detection API for dataset {}",
-                dataset_id
+    // Parse tolerantly and check the data is fit for detection
+    let thresholds = load_quality_thresholds(&app).await;
+    let parsed = parse_csv_data(&csv_data, &config.schema, &thresholds);
+    let quality_report = parsed.report;
+    info!(
+        device_id = %device_id,
+        "Data quality: {}",
+        quality_report.summary()
+    );
+
+    if let Some(test_uuid) = &test_uuid {
+        let db_state = app.state::<DbState>();
+        let db = db_state.lock().await;
+        if let Err(e) = Database::save_quality_report(&*db, test_uuid, &quality_report).await {
+            error!(device_id = %device_id, "{}", e);
+        }
+        if let Err(e) = Database::save_raw_signal(&*db, test_uuid, &csv_data).await {
+            error!(device_id = %device_id, "{}", e);
+        }
+    }
+
+    if !quality_report.passed {
+        warn!(
+            device_id = %device_id,
+            "Detection blocked by data quality: {}",
+            quality_report.issues.join("; ")
+        );
+        let _ = app.emit(
+            "serial:detection_error",
+            &DetectionError {
+                error: format!("Data quality too low: {}", quality_report.issues.join("; ")),
+                dataset_id: None,
+                device_id,
+                test_uuid,
+                quality_report: Some(quality_report),
+            },
+        );
+        return;
+    }
+
+    // Apply the test type's preprocessing so the model sees consistent input
+    let (test_type, pipeline) = load_preprocessing(&app, test_uuid.as_deref()).await;
+    let input_samples = parsed.samples.len();
+    let samples = match apply_pipeline(parsed.samples, &pipeline) {
+        Ok(samples) => samples,
+        Err(e) => {
+            error!(device_id = %device_id, "Preprocessing failed: {}", e);
+            let _ = app.emit(
+                "serial:detection_error",
+                &DetectionError {
+                    error: format!("Preprocessing failed: {}", e),
+                    dataset_id: None,
+                    device_id,
+                    test_uuid,
+                    quality_report: Some(quality_report),
+                },
             );
+            return;
+        }
+    };
+    if let Some(test_uuid) = &test_uuid {
+        let preview = signal_preview(&samples, SIGNAL_PREVIEW_POINTS);
+        let db_state = app.state::<DbState>();
+        let db = db_state.lock().await;
+        if let Err(e) = Database::save_signal_preview(&*db, test_uuid, &preview).await {
+            error!(device_id = %device_id, "{}", e);
+        }
+    }
+    let preprocessing = PreprocessingMetadata {
+        test_type: test_type.map(|t| t.as_str().to_string()),
+        steps: pipeline.steps,
+        input_samples,
+        output_samples: samples.len(),
+    };
 
-            // Call API with retry logic
-            // Note: Results are emitted to frontend and saved by the TestResultsPage
-            match api_client.detect(request).await {
-                Ok(response) => {
-                    println!(
-                        "[api_client] Detection successful: {}",
-                        response.probability
-                    );
+    let request = create_detection_request(
+        samples,
+        &config.schema,
+        config.port.clone(),
+        config.profile.baud_rate,
+        collection_duration_ms,
+        preprocessing,
+    );
+    let dataset_id = request.dataset_id.clone();
+    info!(
+        device_id = %device_id,
+        dataset_id = %dataset_id,
+        "Calling detection API"
+    );
 
-                    // Emit detection result to frontend
-                    let _ = app.emit("serial:detection_result", &response);
-                }
-                Err(err) => {
-                    println!("[api_client] Detection failed: {}", err);
-
-                    // Emit error to frontend
-                    let _ = app.emit(
-                        "serial:detection_error",
-                        &DetectionError {
-                            error: err,
-                            dataset_id: Some(dataset_id),
-                        },
-                    );
-                }
+    // Call API with retry logic
+    // Note: Results are emitted to frontend and saved by the TestResultsPage;
+    // QC runs are checked here so a control's value never comes from the client
+    match api_client.detect(request).await {
+        Ok(response) => {
+            info!(
+                device_id = %device_id,
+                dataset_id = %dataset_id,
+                "Detection successful: probability={}",
+                response.probability
+            );
+            if let Some(test_uuid) = &test_uuid {
+                record_qc_result(&app, test_uuid, response.probability).await;
             }
+
+            // Emit detection result to frontend
+            let _ = app.emit(
+                "serial:detection_result",
+                &DetectionResultEvent {
+                    response,
+                    device_id,
+                    test_uuid,
+                    quality_report,
+                },
+            );
         }
         Err(err) => {
-            println!("[api_client] Failed to create detection request: {}", err);
+            error!(device_id = %device_id, dataset_id = %dataset_id, "Detection failed: {}", err);
+
+            // Emit error to frontend
             let _ = app.emit(
                 "serial:detection_error",
                 &DetectionError {
-                    error: format!("Failed to parse CSV data: {}", err),
-                    dataset_id: None,
+                    error: err.message,
+                    dataset_id: Some(dataset_id),
+                    device_id,
+                    test_uuid,
+                    quality_report: Some(quality_report),
                 },
             );
         }
     }
 }
 
+/// Complete the QC run a dataset was read for, if it was one
+async fn record_qc_result(app: &AppHandle, run_uuid: &str, probability: f64) {
+    let db_state = app.state::<DbState>();
+    let db = db_state.lock().await;
+    match qc::complete_detection(&*db, run_uuid, probability).await {
+        Ok(Some(run)) => {
+            app.state::<DeviceRegistry>().release_test(&run.uuid);
+            if run.status == qc::QcStatus::Failed.as_str() {
+                warn!(
+                    "QC run {} on {} failed: {:?}",
+                    run.uuid, run.device_id, run.violations
+                );
+            } else {
+                info!("QC run {} on {} {}", run.uuid, run.device_id, run.status);
+            }
+        }
+        Ok(None) => {}
+        Err(e) => error!("Failed to record QC run {}: {}", run_uuid, e),
+    }
+}
+
+/// Test type of the dataset's test or QC run and the preprocessing configured for it
+async fn load_preprocessing(
+    app: &AppHandle,
+    test_uuid: Option<&str>,
+) -> (Option<TestType>, PreprocessingPipeline) {
+    let Some(test_uuid) = test_uuid else {
+        return (None, PreprocessingPipeline::default());
+    };
+
+    let db_state = app.state::<DbState>();
+    let db = db_state.lock().await;
+
+    let test_type = match Database::get_test_by_uuid(&*db, test_uuid).await {
+        Ok(Some(test)) => Some(test.test_type),
+        // Control material runs through the same pipeline as patient samples
+        _ => Database::get_qc_run_by_uuid(&*db, test_uuid)
+            .await
+            .ok()
+            .flatten()
+            .map(|run| run.test_type),
+    }
+    .and_then(|test_type| TestType::from_str(&test_type).ok());
+    let pipeline = match &test_type {
+        Some(test_type) => Database::get_preprocessing_pipeline(&*db, test_type)
+            .await
+            .unwrap_or_else(|e| {
+                warn!("{}; using no preprocessing", e);
+                PreprocessingPipeline::default()
+            }),
+        None => PreprocessingPipeline::default(),
+    };
+
+    (test_type, pipeline)
+}
+
+/// Quality thresholds from the `quality_thresholds` setting, or the defaults
+async fn load_quality_thresholds(app: &AppHandle) -> QualityThresholds {
+    let db_state = app.state::<DbState>();
+    let db = db_state.lock().await;
+
+    Database::get_setting(&*db, "quality_thresholds".to_string())
+        .await
+        .ok()
+        .flatten()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
 /// Process completed dataset (either from idle timeout or disconnect)
+///
+/// `test_uuid` is the test the dataset belongs to; results without one are
+/// emitted but not stored against any test.
 fn process_completed_dataset(
     app: &AppHandle,
     data_state: &mut SerialDataState,
-    port: &str,
-    baud_rate: u32,
+    config: &SerialConfig,
     api_client: &DetectionApiClient,
+    test_uuid: Option<String>,
     reason: &str,
 ) {
     if data_state.csv_buffer.is_empty() {
@@ -266,25 +512,27 @@ fn process_completed_dataset(
     }
 
     let collection_duration_ms = data_state.get_collection_duration_ms();
-    println!(
-        "[serial {}] csv dataset complete {} ({} bytes)",
-        port,
+    info!(
+        device_id = %config.device_id,
+        "CSV dataset complete {} ({} bytes)",
         reason,
         data_state.csv_buffer.as_bytes().len()
     );
 
+    update_device_status(app, &config.device_id, |status| status.collecting = false);
+
     // Spawn API call task
     let csv_data = data_state.csv_buffer.clone();
     let app_clone = app.clone();
-    let port_clone = port.to_string();
+    let config_clone = config.clone();
     let api_client_clone = api_client.clone();
 
     tauri::async_runtime::spawn(async move {
         handle_detection_api_call(
             app_clone,
             csv_data,
-            port_clone,
-            baud_rate,
+            config_clone,
+            test_uuid,
             collection_duration_ms,
             api_client_clone,
         )
@@ -294,133 +542,267 @@ fn process_completed_dataset(
     data_state.clear_buffers();
 }
 
-/// Check if serial port is still available
-fn is_port_available(app: &AppHandle, port: &str) -> bool {
-    if let Ok(ports) = commands::available_ports(
-        app.clone(),
-        app.state::<desktop_api::SerialPort<tauri::Wry>>().clone(),
-    ) {
-        ports.contains_key(port)
-    } else {
-        false
-    }
-}
-
 /// Handle serial port disconnection
 fn handle_port_disconnection(
     app: &AppHandle,
     data_state: &mut SerialDataState,
-    port: &str,
-    baud_rate: u32,
+    config: &SerialConfig,
     api_client: &DetectionApiClient,
 ) {
     // Process any remaining data as completed dataset
+    let test_uuid = app.state::<DeviceRegistry>().active_test(&config.device_id);
     process_completed_dataset(
         app,
         data_state,
-        port,
-        baud_rate,
+        config,
         api_client,
+        test_uuid,
         "on disconnect",
     );
 
-    println!("[serial] device on {} disconnected", port);
-    emit_connection_status(app, false, port);
-}
-
-/// Read data from serial port
-fn read_serial_data(app: &AppHandle, port: &str) -> Result<String, String> {
-    commands::read(
-        app.clone(),
-        app.state::<desktop_api::SerialPort<tauri::Wry>>().clone(),
-        port.to_string(),
-        Some(50),
-        Some(1024),
-    )
-    .map_err(|e| e.to_string())
+    warn!(
+        device_id = %config.device_id,
+        "Device on {} disconnected",
+        config.port
+    );
+    emit_connection_status(app, &config.device_id, false, &config.port);
 }
 
-/// Main serial monitoring loop
+/// Main serial monitoring loop for one device
+///
+/// Consumes events from the device's reader thread. The task only wakes for
+/// incoming events or when a pending dataset reaches its idle deadline.
 async fn run_serial_monitor_loop(
     app: AppHandle,
     config: SerialConfig,
     api_client: DetectionApiClient,
+    mut events: mpsc::Receiver<ReaderEvent>,
 ) {
     let mut data_state = SerialDataState::new();
-    let mut is_open = false;
 
     loop {
-        if !is_open {
-            // Try to open the target port; keep retrying until connected
-            match try_open_serial_port(&app, &config.port, config.baud_rate) {
-                Ok(_) => {
-                    handle_connection_success(
+        let event = match data_state.idle_deadline() {
+            Some(deadline) => tokio::select! {
+                event = events.recv() => event,
+                _ = tokio::time::sleep_until(deadline) => {
+                    // Attribute the dataset to whichever test is routed to this device
+                    let test_uuid = app.state::<DeviceRegistry>().active_test(&config.device_id);
+                    process_completed_dataset(
                         &app,
-                        &config.port,
-                        config.baud_rate,
                         &mut data_state,
+                        &config,
+                        &api_client,
+                        test_uuid,
+                        "(idle timeout)",
                     );
-                    is_open = true;
-                }
-                Err(e) => {
-                    // Not connected yet; wait and retry
-                    println!("[serial] waiting for {}: {}", config.port, e);
-                    sleep(Duration::from_millis(500));
                     continue;
                 }
+            },
+            None => events.recv().await,
+        };
+
+        // The reader thread has exited; nothing more will arrive
+        let Some(event) = event else {
+            break;
+        };
+
+        match event {
+            ReaderEvent::Connected => {
+                handle_connection_success(&app, &config, &mut data_state);
+            }
+            ReaderEvent::OpenFailed(e) => {
+                debug!(
+                    device_id = %config.device_id,
+                    "Waiting for {}: {}",
+                    config.port,
+                    e
+                );
+                update_device_status(&app, &config.device_id, |status| {
+                    status.last_error = Some(e)
+                });
+            }
+            ReaderEvent::Data(chunk) => {
+                process_serial_data_chunk(&app, &chunk, &config.device_id, &mut data_state);
+            }
+            ReaderEvent::Disconnected => {
+                handle_port_disconnection(&app, &mut data_state, &config, &api_client);
             }
         }
+    }
+}
 
-        // When open, read with a short timeout and process data/idle flush
-        match read_serial_data(&app, &config.port) {
-            Ok(chunk) => {
-                process_serial_data_chunk(&app, &chunk, &config.port, &mut data_state);
-            }
-            Err(_) => {
-                // Timeout or no data; check for idle gap end-of-dataset
-                if data_state.is_idle() {
-                    process_completed_dataset(
-                        &app,
-                        &mut data_state,
-                        &config.port,
-                        config.baud_rate,
-                        &api_client,
-                        "(idle timeout)",
-                    );
-                }
+/// Directory holding per-device raw serial captures
+fn captures_dir(app: &AppHandle) -> Result<PathBuf, AppError> {
+    app.path()
+        .app_data_dir()
+        .map(|dir| dir.join("captures"))
+        .map_err(|e| AppError::io("Failed to get app data dir", e))
+}
 
-                // Also verify port is still present; if not, mark disconnected
-                if !is_port_available(&app, &config.port) {
-                    handle_port_disconnection(
-                        &app,
-                        &mut data_state,
-                        &config.port,
-                        config.baud_rate,
-                        &api_client,
-                    );
-                    is_open = false;
-                    // Back off briefly before attempting to reconnect
-                    sleep(Duration::from_millis(500));
-                }
+#[derive(Debug, Serialize)]
+pub struct ReplaySummary {
+    pub device_id: String,
+    pub chunks: usize,
+    pub bytes: usize,
+    pub datasets: usize,
+    pub capture_duration_ms: u64,
+}
+
+/// Wait until a capture timestamp is reached, scaled by the replay speed
+async fn pace_replay(position_us: &mut u64, t_us: u64, speed: f64) {
+    if t_us > *position_us {
+        let wait_us = ((t_us - *position_us) as f64 / speed) as u64;
+        tokio::time::sleep(Duration::from_micros(wait_us)).await;
+        *position_us = t_us;
+    }
+}
+
+/// Replay a capture through the same chunk and dataset handling as live data
+///
+/// Idle gaps are measured in capture time, so datasets split exactly where
+/// they did originally regardless of replay speed. Replayed datasets belong
+/// to no test, so the device's live test is left untouched.
+async fn run_replay(
+    app: &AppHandle,
+    events: Vec<CaptureRecord>,
+    config: SerialConfig,
+    speed: f64,
+) -> ReplaySummary {
+    let api_client = DetectionApiClient::new(config.api_endpoint.clone());
+    let mut data_state = SerialDataState::new();
+    let idle_gap_us = data_state.idle_gap.as_micros() as u64;
+    let mut summary = ReplaySummary {
+        device_id: config.device_id.clone(),
+        chunks: 0,
+        bytes: 0,
+        datasets: 0,
+        capture_duration_ms: 0,
+    };
+    let mut position_us = 0;
+    let mut last_data_us: Option<u64> = None;
+
+    let mut flush = |data_state: &mut SerialDataState, reason: &str| {
+        if !data_state.csv_buffer.is_empty() {
+            summary.datasets += 1;
+            process_completed_dataset(app, data_state, &config, &api_client, None, reason);
+        }
+    };
+
+    for event in events {
+        let t_us = event.t_us();
+
+        // Flush at the point the live loop would have hit its idle deadline
+        if let Some(last) = last_data_us {
+            let deadline = last + idle_gap_us;
+            if !data_state.csv_buffer.is_empty() && deadline <= t_us {
+                pace_replay(&mut position_us, deadline, speed).await;
+                flush(&mut data_state, "(idle timeout, replay)");
             }
         }
+        pace_replay(&mut position_us, t_us, speed).await;
 
-        // Yield to the async runtime to allow other tasks (like API calls) to run
-        // This prevents the serial loop from starving other async tasks
-        tokio::task::yield_now().await;
+        match event {
+            CaptureRecord::Data { data, .. } => {
+                summary.chunks += 1;
+                summary.bytes += data.len();
+                process_serial_data_chunk(app, &data, &config.device_id, &mut data_state);
+                last_data_us = Some(t_us);
+            }
+            CaptureRecord::Disconnected { .. } => {
+                flush(&mut data_state, "on disconnect (replay)");
+                last_data_us = None;
+            }
+            CaptureRecord::Connected { .. } | CaptureRecord::Header { .. } => {}
+        }
     }
+
+    flush(&mut data_state, "(end of replay)");
+    summary.capture_duration_ms = position_us / 1000;
+    summary
 }
 
+/// Register a device and spawn its reader thread and monitor task
+///
+/// `active_test` is the test the device was assigned before its monitor was
+/// stopped, so a restart keeps routing datasets to it.
 #[cfg(desktop)]
-#[tauri::command]
-pub async fn start_serial(app: AppHandle) -> Result<(), String> {
-    let config = load_serial_config_async(&app).await;
+fn spawn_device_monitor(
+    app: &AppHandle,
+    device: &Device,
+    active_test: Option<String>,
+) -> Result<(), AppError> {
+    let registry = app.state::<DeviceRegistry>();
+    let config = serial_config_for(device);
     let api_client = DetectionApiClient::new(config.api_endpoint.clone());
 
-    println!("[serial] Starting serial monitor on port: {}", config.port);
+    let mut status = DeviceStatus::offline(
+        device.device_id.clone(),
+        device.label.clone(),
+        device.port.clone(),
+    );
+    status.active_test_uuid = active_test;
+
+    registry.register(status.clone());
+    publish_device_status(app, &status);
+
+    info!(
+        device_id = %device.device_id,
+        "Starting serial monitor on port: {}",
+        device.port
+    );
+
+    // Record everything the port delivers so a session can be replayed later
+    let capture = captures_dir(app)
+        .and_then(|dir| {
+            CaptureWriter::new(&dir, &config.device_id, &config.port, &config.profile)
+                .map_err(|e| AppError::io("Failed to open capture", e))
+        })
+        .map_err(|e| warn!(device_id = %config.device_id, "Capture disabled: {}", e))
+        .ok();
+
+    // The reader thread owns the port; the task reacts to what it reads
+    let (reader, events) = spawn_reader(
+        app.clone(),
+        config.device_id.clone(),
+        config.port.clone(),
+        config.profile.clone(),
+        capture,
+    )
+    .map_err(|e| AppError::internal("Failed to start serial reader").with_details(e))?;
+    let task = tauri::async_runtime::spawn(run_serial_monitor_loop(
+        app.clone(),
+        config,
+        api_client,
+        events,
+    ));
+    registry.attach_task(&device.device_id, task, reader);
+    Ok(())
+}
+
+/// Stop a device's monitor and wait for its reader to release the port
+#[cfg(desktop)]
+async fn stop_device_monitor(app: &AppHandle, device_id: &str) {
+    if let Some(reader) = app.state::<DeviceRegistry>().unregister(device_id) {
+        let _ = tauri::async_runtime::spawn_blocking(move || reader.stop()).await;
+        info!(device_id = %device_id, "Stopped serial monitor");
+    }
+}
+
+#[cfg(desktop)]
+#[tauri::command]
+pub async fn start_serial(
+    app: AppHandle,
+    session: State<'_, SessionState>,
+) -> Result<(), AppError> {
+    auth::require_user(&session)?;
+    let devices = load_devices_async(&app).await?;
+    let registry = app.state::<DeviceRegistry>();
 
-    // Spawn a background task that will auto-connect, read, and handle hot-plug
-    tauri::async_runtime::spawn(run_serial_monitor_loop(app, config, api_client));
+    for device in devices.iter().filter(|d| d.enabled) {
+        if !registry.is_running(&device.device_id) {
+            spawn_device_monitor(&app, device, registry.active_test(&device.device_id))?;
+        }
+    }
 
     Ok(())
 }
@@ -428,55 +810,231 @@ pub async fn start_serial(app: AppHandle) -> Result<(), String> {
 /// List all available serial ports
 #[cfg(desktop)]
 #[tauri::command]
-pub async fn list_serial_ports(app: AppHandle) -> Result<Vec<String>, String> {
+pub async fn list_serial_ports(
+    app: AppHandle,
+    session: State<'_, SessionState>,
+) -> Result<Vec<String>, AppError> {
+    auth::require_user(&session)?;
     let ports = commands::available_ports(
         app.clone(),
         app.state::<desktop_api::SerialPort<tauri::Wry>>().clone(),
     )
-    .map_err(|e| format!("Failed to list ports: {}", e))?;
+    .map_err(|e| AppError::serial("Failed to list serial ports").with_details(e))?;
 
     let port_names: Vec<String> = ports.keys().cloned().collect();
     Ok(port_names)
 }
 
-/// Get the currently configured serial port
+/// List all configured devices
 #[cfg(desktop)]
 #[tauri::command]
-pub async fn get_current_port(app: AppHandle) -> Result<String, String> {
-    // Try to get from database first
-    let db_state = app.state::<DbState>();
+pub async fn list_devices(
+    session: State<'_, SessionState>,
+    db_state: State<'_, DbState>,
+) -> Result<Vec<Device>, AppError> {
+    auth::require_user(&session)?;
     let db = db_state.lock().await;
+    Database::get_all_devices(&*db).await
+}
 
-    let result = Database::get_setting(&*db, "serial_port".to_string()).await?;
+/// Get the live status of every monitored device
+#[cfg(desktop)]
+#[tauri::command]
+pub async fn get_device_statuses(
+    session: State<'_, SessionState>,
+    registry: State<'_, DeviceRegistry>,
+) -> Result<Vec<DeviceStatus>, AppError> {
+    auth::require_user(&session)?;
+    Ok(registry.statuses())
+}
+
+/// Get reader thread activity counters, used to measure idle overhead
+#[cfg(desktop)]
+#[tauri::command]
+pub async fn get_reader_stats(
+    session: State<'_, SessionState>,
+    registry: State<'_, DeviceRegistry>,
+) -> Result<Vec<ReaderStats>, AppError> {
+    auth::require_user(&session)?;
+    Ok(registry.reader_stats())
+}
 
-    if let Some(port) = result {
-        Ok(port)
-    } else {
-        // Fall back to env var or default
-        crate::try_load_dotenv();
-        Ok(std::env::var("SERIAL_PORT").unwrap_or_else(|_| "COM3".to_string()))
+/// List recorded serial captures, newest first
+#[cfg(desktop)]
+#[tauri::command]
+pub async fn list_captures(
+    app: AppHandle,
+    session: State<'_, SessionState>,
+) -> Result<Vec<CaptureInfo>, AppError> {
+    auth::require_user(&session)?;
+    Ok(list_capture_files(&captures_dir(&app)?))
+}
+
+/// Feed a recorded capture back through the serial pipeline
+///
+/// `speed` scales playback (1.0 = original timing, 10.0 = ten times faster).
+/// Datasets are parsed with the column schema of `device_id`, defaulting to the
+/// recording device. Everything the replay emits carries `replay:<device_id>`
+/// so it cannot be taken for live data, and the device's status is untouched.
+#[cfg(desktop)]
+#[tauri::command]
+pub async fn replay_capture(
+    app: AppHandle,
+    session: State<'_, SessionState>,
+    path: String,
+    speed: Option<f64>,
+    device_id: Option<String>,
+) -> Result<ReplaySummary, AppError> {
+    auth::require(&session, Permission::ManageDevices)?;
+    let speed = speed.unwrap_or(1.0);
+    if !speed.is_finite() || speed <= 0.0 {
+        return Err(AppError::validation(
+            "Replay speed must be greater than zero",
+        ));
     }
+
+    let capture =
+        read_capture(Path::new(&path)).map_err(|e| AppError::io("Failed to read capture", e))?;
+    let device_id = device_id.unwrap_or(capture.device_id);
+
+    let device = {
+        let db_state = app.state::<DbState>();
+        let db = db_state.lock().await;
+        Database::get_device(&*db, &device_id).await?
+    };
+    let device = device.unwrap_or_else(|| {
+        let mut device = Device::new(device_id.clone(), device_id.clone(), capture.port);
+        device.profile = capture.profile;
+        device
+    });
+
+    info!(
+        device_id = %device_id,
+        "Replaying capture {} (recorded {}) at {}x",
+        path,
+        capture.started_at,
+        speed
+    );
+
+    let mut config = serial_config_for(&device);
+    config.device_id = format!("replay:{}", device_id);
+    let summary = run_replay(&app, capture.events, config, speed).await;
+
+    info!(
+        device_id = %device_id,
+        "Replay finished: {} chunks, {} bytes, {} datasets",
+        summary.chunks,
+        summary.bytes,
+        summary.datasets
+    );
+    Ok(summary)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SaveDeviceRequest {
+    pub device_id: String,
+    pub label: String,
+    pub port: String,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    #[serde(default)]
+    pub profile: Option<SerialProfile>,
+    #[serde(default)]
+    pub schema: Option<ColumnSchema>,
 }
 
-/// Set the serial port and restart the connection
+fn default_enabled() -> bool {
+    true
+}
+
+/// Add or update a device and restart its monitor with the new settings
 #[cfg(desktop)]
 #[tauri::command]
-pub async fn set_serial_port(app: AppHandle, port: String) -> Result<(), String> {
-    println!("[serial] Changing port to: {}", port);
+pub async fn save_device(
+    app: AppHandle,
+    session: State<'_, SessionState>,
+    device_data: SaveDeviceRequest,
+) -> Result<Device, AppError> {
+    auth::require(&session, Permission::ManageDevices)?;
+    let device_id = device_data.device_id.trim().to_string();
+    let port = device_data.port.trim().to_string();
+    if device_id.is_empty() || port.is_empty() {
+        return Err(AppError::validation("Device ID and port are required"));
+    }
+    if let Some(profile) = &device_data.profile {
+        profile.validate().map_err(AppError::validation)?;
+    }
+    if let Some(schema) = &device_data.schema {
+        schema.validate().map_err(AppError::validation)?;
+    }
 
-    // Save to database
-    let db_state = app.state::<DbState>();
-    let db = db_state.lock().await;
+    let device = {
+        let db_state = app.state::<DbState>();
+        let db = db_state.lock().await;
+
+        if let Some(other) = Database::get_all_devices(&*db)
+            .await?
+            .into_iter()
+            .find(|d| d.port == port && d.device_id != device_id)
+        {
+            return Err(AppError::conflict(format!(
+                "Port {} is already used by {}",
+                port, other.device_id
+            )));
+        }
 
-    Database::save_setting(&*db, "serial_port".to_string(), port.clone()).await?;
+        let mut device = match Database::get_device(&*db, &device_id).await? {
+            Some(existing) => existing,
+            None => Device::new(device_id.clone(), device_data.label.clone(), port.clone()),
+        };
+        device.label = device_data.label;
+        device.port = port;
+        device.enabled = device_data.enabled;
+        if let Some(profile) = device_data.profile {
+            device.profile = profile;
+        }
+        if let Some(schema) = device_data.schema {
+            device.schema = schema;
+        }
+        device.touch();
+
+        Database::save_device(&*db, &device).await?;
+        device
+    };
 
-    println!("[serial] Port setting saved to database");
+    info!(
+        device_id = %device.device_id,
+        "Device saved on port {} ({})",
+        device.port,
+        device.profile.summary()
+    );
+
+    // Restart the monitor so the new port takes effect immediately; stopping
+    // drops the registry slot, so carry the test assignment over by hand
+    let active_test = app.state::<DeviceRegistry>().active_test(&device.device_id);
+    stop_device_monitor(&app, &device.device_id).await;
+    if device.enabled {
+        spawn_device_monitor(&app, &device, active_test)?;
+    }
 
-    // Note: The serial monitor loop will need to be restarted for this to take effect
-    // For now, we'll just save the setting. A full implementation would need to:
-    // 1. Stop the current serial monitor loop
-    // 2. Restart it with the new port
-    // This is complex because we'd need to manage the task handle
+    Ok(device)
+}
+
+/// Remove a device and stop monitoring its port
+#[cfg(desktop)]
+#[tauri::command]
+pub async fn remove_device(
+    app: AppHandle,
+    session: State<'_, SessionState>,
+    device_id: String,
+) -> Result<(), AppError> {
+    auth::require(&session, Permission::ManageDevices)?;
+    stop_device_monitor(&app, &device_id).await;
+
+    let db_state = app.state::<DbState>();
+    let db = db_state.lock().await;
+    Database::delete_device(&*db, &device_id).await?;
 
+    info!(device_id = %device_id, "Device removed");
     Ok(())
 }
@@ -0,0 +1,326 @@
+use crate::capture::CaptureWriter;
+use crate::models::{FlowControl, Parity, SerialProfile};
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+use tokio::sync::mpsc;
+use tracing::warn;
+
+#[cfg(desktop)]
+use tauri_plugin_serialplugin::{commands, desktop_api, state as serial_state};
+
+/// How often an idle reader checks whether its port is still present
+const PORT_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+/// First and maximum delay between attempts to open a missing port
+const OPEN_RETRY_MIN: Duration = Duration::from_millis(500);
+const OPEN_RETRY_MAX: Duration = Duration::from_secs(5);
+/// Granularity at which a sleeping reader notices a stop request
+const STOP_POLL_INTERVAL: Duration = Duration::from_millis(100);
+/// Chunks buffered between the reader thread and the monitor task
+const CHANNEL_CAPACITY: usize = 256;
+
+/// Events sent from a reader thread to its device's monitor task
+#[derive(Debug)]
+pub enum ReaderEvent {
+    Connected,
+    OpenFailed(String),
+    Data(String),
+    Disconnected,
+}
+
+/// Counters updated by a reader thread, used to measure its activity
+#[derive(Default)]
+pub struct ReaderCounters {
+    open_attempts: AtomicU64,
+    read_calls: AtomicU64,
+    empty_reads: AtomicU64,
+    bytes_received: AtomicU64,
+    port_checks: AtomicU64,
+}
+
+/// Snapshot of a reader thread's activity, returned by `get_reader_stats`
+#[derive(Debug, Clone, Serialize)]
+pub struct ReaderStats {
+    pub device_id: String,
+    pub uptime_secs: f64,
+    pub open_attempts: u64,
+    pub read_calls: u64,
+    pub empty_reads: u64,
+    pub bytes_received: u64,
+    pub port_checks: u64,
+    pub reads_per_sec: f64,
+    pub port_checks_per_sec: f64,
+}
+
+/// Handle to a device's dedicated reader thread
+pub struct ReaderHandle {
+    device_id: String,
+    stop: Arc<AtomicBool>,
+    thread: Option<thread::JoinHandle<()>>,
+    counters: Arc<ReaderCounters>,
+    started_at: Instant,
+}
+
+impl ReaderHandle {
+    /// Ask the thread to stop and wait for it to release the port
+    pub fn stop(mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+
+    /// Activity counters since the thread was started
+    pub fn stats(&self) -> ReaderStats {
+        let uptime_secs = self.started_at.elapsed().as_secs_f64();
+        let read_calls = self.counters.read_calls.load(Ordering::Relaxed);
+        let port_checks = self.counters.port_checks.load(Ordering::Relaxed);
+        let per_sec = |count: u64| {
+            if uptime_secs > 0.0 {
+                count as f64 / uptime_secs
+            } else {
+                0.0
+            }
+        };
+
+        ReaderStats {
+            device_id: self.device_id.clone(),
+            uptime_secs,
+            open_attempts: self.counters.open_attempts.load(Ordering::Relaxed),
+            read_calls,
+            empty_reads: self.counters.empty_reads.load(Ordering::Relaxed),
+            bytes_received: self.counters.bytes_received.load(Ordering::Relaxed),
+            port_checks,
+            reads_per_sec: per_sec(read_calls),
+            port_checks_per_sec: per_sec(port_checks),
+        }
+    }
+}
+
+impl Drop for ReaderHandle {
+    fn drop(&mut self) {
+        // A dropped handle never leaves a thread reading an unmonitored port
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Spawn a dedicated thread that owns the port and feeds chunks into a channel
+///
+/// The thread performs all blocking serial calls so the async runtime is never
+/// stalled. It exits when stopped or when the receiving side is dropped.
+pub fn spawn_reader(
+    app: AppHandle,
+    device_id: String,
+    port: String,
+    profile: SerialProfile,
+    capture: Option<CaptureWriter>,
+) -> Result<(ReaderHandle, mpsc::Receiver<ReaderEvent>), String> {
+    let (tx, rx) = mpsc::channel(CHANNEL_CAPACITY);
+    let stop = Arc::new(AtomicBool::new(false));
+    let counters = Arc::new(ReaderCounters::default());
+
+    let thread = {
+        let stop = stop.clone();
+        let counters = counters.clone();
+        thread::Builder::new()
+            .name(format!("serial-{}", device_id))
+            .spawn(move || run_reader(app, port, profile, capture, tx, stop, counters))
+            .map_err(|e| format!("Failed to start reader thread: {}", e))?
+    };
+
+    Ok((
+        ReaderHandle {
+            device_id,
+            stop,
+            thread: Some(thread),
+            counters,
+            started_at: Instant::now(),
+        },
+        rx,
+    ))
+}
+
+/// Reader thread body: open, read, detect unplug, and reconnect with backoff
+fn run_reader(
+    app: AppHandle,
+    port: String,
+    profile: SerialProfile,
+    mut capture: Option<CaptureWriter>,
+    tx: mpsc::Sender<ReaderEvent>,
+    stop: Arc<AtomicBool>,
+    counters: Arc<ReaderCounters>,
+) {
+    let mut is_open = false;
+    let mut retry_delay = OPEN_RETRY_MIN;
+    let mut last_port_check = Instant::now();
+
+    while !stop.load(Ordering::Relaxed) && !tx.is_closed() {
+        if !is_open {
+            counters.open_attempts.fetch_add(1, Ordering::Relaxed);
+            match open_port(&app, &port, &profile) {
+                Ok(_) => {
+                    is_open = true;
+                    retry_delay = OPEN_RETRY_MIN;
+                    last_port_check = Instant::now();
+                    record(&mut capture, &port, |c| c.connected());
+                    if tx.blocking_send(ReaderEvent::Connected).is_err() {
+                        break;
+                    }
+                }
+                Err(e) => {
+                    if tx.blocking_send(ReaderEvent::OpenFailed(e)).is_err() {
+                        break;
+                    }
+                    sleep_unless_stopped(&stop, retry_delay);
+                    retry_delay = (retry_delay * 2).min(OPEN_RETRY_MAX);
+                }
+            }
+            continue;
+        }
+
+        counters.read_calls.fetch_add(1, Ordering::Relaxed);
+        match read_port(&app, &port, &profile) {
+            Ok(chunk) if !chunk.is_empty() => {
+                counters
+                    .bytes_received
+                    .fetch_add(chunk.len() as u64, Ordering::Relaxed);
+                record(&mut capture, &port, |c| c.data(&chunk));
+                if tx.blocking_send(ReaderEvent::Data(chunk)).is_err() {
+                    break;
+                }
+            }
+            _ => {
+                counters.empty_reads.fetch_add(1, Ordering::Relaxed);
+
+                // Hot-plug detection is rate-limited rather than run on every empty read
+                if last_port_check.elapsed() >= PORT_CHECK_INTERVAL {
+                    last_port_check = Instant::now();
+                    counters.port_checks.fetch_add(1, Ordering::Relaxed);
+                    if !is_port_available(&app, &port) {
+                        close_port(&app, &port);
+                        is_open = false;
+                        record(&mut capture, &port, |c| c.disconnected());
+                        if tx.blocking_send(ReaderEvent::Disconnected).is_err() {
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    if is_open {
+        close_port(&app, &port);
+    }
+}
+
+/// Write to the capture, disabling it after the first failure
+fn record<F>(capture: &mut Option<CaptureWriter>, port: &str, f: F)
+where
+    F: FnOnce(&mut CaptureWriter) -> Result<(), String>,
+{
+    if let Some(writer) = capture.as_mut() {
+        if let Err(e) = f(writer) {
+            warn!(port = %port, "{}; capture disabled", e);
+            *capture = None;
+        }
+    }
+}
+
+/// Sleep for up to `duration`, returning early once a stop is requested
+fn sleep_unless_stopped(stop: &AtomicBool, duration: Duration) {
+    let deadline = Instant::now() + duration;
+    while !stop.load(Ordering::Relaxed) {
+        let now = Instant::now();
+        if now >= deadline {
+            break;
+        }
+        thread::sleep((deadline - now).min(STOP_POLL_INTERVAL));
+    }
+}
+
+/// Map a serial profile onto the line settings the serial plugin expects
+#[cfg(desktop)]
+fn plugin_line_settings(
+    profile: &SerialProfile,
+) -> (
+    serial_state::DataBits,
+    serial_state::FlowControl,
+    serial_state::Parity,
+    serial_state::StopBits,
+) {
+    let data_bits = match profile.data_bits {
+        5 => serial_state::DataBits::Five,
+        6 => serial_state::DataBits::Six,
+        7 => serial_state::DataBits::Seven,
+        _ => serial_state::DataBits::Eight,
+    };
+    let flow_control = match profile.flow_control {
+        FlowControl::None => serial_state::FlowControl::None,
+        FlowControl::Software => serial_state::FlowControl::Software,
+        FlowControl::Hardware => serial_state::FlowControl::Hardware,
+    };
+    let parity = match profile.parity {
+        Parity::None => serial_state::Parity::None,
+        Parity::Odd => serial_state::Parity::Odd,
+        Parity::Even => serial_state::Parity::Even,
+    };
+    let stop_bits = match profile.stop_bits {
+        2 => serial_state::StopBits::Two,
+        _ => serial_state::StopBits::One,
+    };
+    (data_bits, flow_control, parity, stop_bits)
+}
+
+/// Open the serial port with the device's serial profile
+fn open_port(app: &AppHandle, port: &str, profile: &SerialProfile) -> Result<(), String> {
+    let (data_bits, flow_control, parity, stop_bits) = plugin_line_settings(profile);
+    commands::open(
+        app.clone(),
+        app.state::<desktop_api::SerialPort<tauri::Wry>>().clone(),
+        port.to_string(),
+        profile.baud_rate,
+        Some(data_bits),
+        Some(flow_control),
+        Some(parity),
+        Some(stop_bits),
+        Some(profile.read_timeout_ms),
+    )
+    .map_err(|e| e.to_string())
+}
+
+/// Read one chunk, blocking for at most the profile's read timeout
+fn read_port(app: &AppHandle, port: &str, profile: &SerialProfile) -> Result<String, String> {
+    commands::read(
+        app.clone(),
+        app.state::<desktop_api::SerialPort<tauri::Wry>>().clone(),
+        port.to_string(),
+        Some(profile.read_timeout_ms),
+        Some(profile.chunk_size),
+    )
+    .map_err(|e| e.to_string())
+}
+
+/// Close the serial port, ignoring errors for ports that are already gone
+fn close_port(app: &AppHandle, port: &str) {
+    let _ = commands::close(
+        app.clone(),
+        app.state::<desktop_api::SerialPort<tauri::Wry>>().clone(),
+        port.to_string(),
+    );
+}
+
+/// Check if serial port is still available
+fn is_port_available(app: &AppHandle, port: &str) -> bool {
+    if let Ok(ports) = commands::available_ports(
+        app.clone(),
+        app.state::<desktop_api::SerialPort<tauri::Wry>>().clone(),
+    ) {
+        ports.contains_key(port)
+    } else {
+        false
+    }
+}
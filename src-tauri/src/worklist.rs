@@ -0,0 +1,259 @@
+use crate::db_orm::Database;
+use crate::models::{AppError, Patient, Test, TestStatus, TestType};
+use crate::patient_import::{self, RowStatus, PATIENT_ID_ALIASES};
+use sea_orm::DatabaseConnection;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use tracing::info;
+
+/// Header names recognised for the order columns
+const TEST_TYPE_ALIASES: &[&str] = &["test type", "test", "test code", "assay"];
+const SPECIMEN_ID_ALIASES: &[&str] = &[
+    "specimen id",
+    "specimen",
+    "sample id",
+    "sample",
+    "accession number",
+    "accession",
+];
+/// Longest specimen ID accepted, matching the patient ID limit
+const MAX_SPECIMEN_ID_CHARS: usize = 64;
+
+/// Parse a test type as typed by people, e.g. `COVID-19` or `covid19`
+pub fn parse_test_type(text: &str) -> Result<TestType, String> {
+    let code = text.trim().to_lowercase().replace([' ', '-'], "_");
+    TestType::from_str(&code)
+        .or_else(|_| TestType::from_str(&code.replace('_', "")))
+        .map_err(|_| format!("Unknown test type '{}'", text.trim()))
+}
+
+/// Check a specimen ID and drop it when blank
+pub fn normalize_specimen_id(specimen_id: Option<String>) -> Result<Option<String>, String> {
+    let Some(specimen_id) = specimen_id
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+    else {
+        return Ok(None);
+    };
+    if specimen_id.chars().count() > MAX_SPECIMEN_ID_CHARS {
+        return Err(format!(
+            "Specimen ID must be at most {} characters",
+            MAX_SPECIMEN_ID_CHARS
+        ));
+    }
+    Ok(Some(specimen_id))
+}
+
+/// Why a new order would repeat one already on the worklist, if it would
+///
+/// An order repeats another when the specimen ID and test type match, or when
+/// the patient already has a pending test of that type.
+pub async fn find_duplicate_order(
+    db: &DatabaseConnection,
+    patient_id: i64,
+    test_type: &TestType,
+    specimen_id: Option<&str>,
+) -> Result<Option<String>, AppError> {
+    if let Some(specimen_id) = specimen_id {
+        if let Some(test) =
+            Database::get_test_by_specimen_id(db, specimen_id, test_type.as_str()).await?
+        {
+            return Ok(Some(format!("specimen {} ({})", specimen_id, test.status)));
+        }
+    }
+    let pending = Database::get_tests_with_patients_by_status(db, TestStatus::Pending).await?;
+    Ok(pending
+        .iter()
+        .find(|t| t.test.patient_id == patient_id && t.test.test_type == test_type.as_str())
+        .map(|t| {
+            format!(
+                "pending {} order for {}",
+                t.test.test_type,
+                t.patient.full_name()
+            )
+        }))
+}
+
+/// Outcome for one data row of an order file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderImportRow {
+    /// Line in the file where the row starts; the header is line 1
+    pub line: usize,
+    pub status: RowStatus,
+    /// Patient ID number as written in the file
+    pub patient_id_number: String,
+    /// Name of the matching patient on file
+    #[serde(default)]
+    pub patient_name: Option<String>,
+    pub test_type: String,
+    #[serde(default)]
+    pub specimen_id: Option<String>,
+    #[serde(default)]
+    pub errors: Vec<String>,
+    #[serde(default)]
+    pub duplicate_of: Option<String>,
+}
+
+/// Summary of an order file preview or import
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderImportReport {
+    pub dry_run: bool,
+    pub total_rows: usize,
+    pub new_orders: usize,
+    pub duplicates: usize,
+    pub invalid: usize,
+    pub imported: usize,
+    pub rows: Vec<OrderImportRow>,
+    /// Tests the import created, so their labels can be printed
+    #[serde(skip)]
+    pub created_tests: Vec<String>,
+}
+
+/// Validate every row of an order CSV and, unless `dry_run`, add the new orders
+///
+/// Each row names a registered patient by ID number and a test type, with an
+/// optional specimen ID; patients are imported beforehand, not here. The new
+/// orders are written in one transaction as pending tests.
+pub async fn import_orders(
+    db: &DatabaseConnection,
+    path: &Path,
+    dry_run: bool,
+) -> Result<OrderImportReport, AppError> {
+    let text = patient_import::read_file(path)?;
+    let mut records = patient_import::parse_csv(&text).into_iter();
+    let (_, headers) = records
+        .next()
+        .ok_or_else(|| AppError::validation("The file is empty"))?;
+    let column = |aliases: &[&str]| {
+        aliases.iter().find_map(|alias| {
+            headers
+                .iter()
+                .position(|h| patient_import::normalize_header(h) == *alias)
+        })
+    };
+    let patient_column = column(PATIENT_ID_ALIASES).ok_or_else(|| {
+        AppError::validation("The file needs a patient ID column, e.g. 'Patient ID'")
+    })?;
+    let test_type_column = column(TEST_TYPE_ALIASES)
+        .ok_or_else(|| AppError::validation("The file needs a 'Test type' column"))?;
+    let specimen_column = column(SPECIMEN_ID_ALIASES);
+
+    let patients: HashMap<String, Patient> = Database::get_all_patients(db)
+        .await?
+        .into_iter()
+        .filter_map(|p| {
+            p.patient_id_number
+                .clone()
+                .map(|id| (id.trim().to_lowercase(), p))
+        })
+        .collect();
+
+    let mut report = OrderImportReport {
+        dry_run,
+        total_rows: 0,
+        new_orders: 0,
+        duplicates: 0,
+        invalid: 0,
+        imported: 0,
+        rows: Vec::new(),
+        created_tests: Vec::new(),
+    };
+    let mut tests = Vec::new();
+    // Orders earlier in the file, by patient and test type and by specimen
+    let mut seen = HashSet::new();
+
+    for (line, fields) in records {
+        if fields.iter().all(|f| f.trim().is_empty()) {
+            continue;
+        }
+        report.total_rows += 1;
+
+        let get = |i: usize| fields.get(i).map(|f| f.trim()).unwrap_or_default();
+        let mut row = OrderImportRow {
+            line,
+            status: RowStatus::Invalid,
+            patient_id_number: get(patient_column).to_string(),
+            patient_name: None,
+            test_type: get(test_type_column).to_string(),
+            specimen_id: None,
+            errors: Vec::new(),
+            duplicate_of: None,
+        };
+
+        let patient = if row.patient_id_number.is_empty() {
+            row.errors.push("Patient ID is required".to_string());
+            None
+        } else {
+            let found = patients.get(&row.patient_id_number.to_lowercase());
+            if found.is_none() {
+                row.errors.push(format!(
+                    "No patient with ID '{}' is registered",
+                    row.patient_id_number
+                ));
+            }
+            found
+        };
+        row.patient_name = patient.map(Patient::full_name);
+        let test_type = parse_test_type(&row.test_type)
+            .map_err(|e| row.errors.push(e))
+            .ok();
+        row.specimen_id = normalize_specimen_id(specimen_column.map(|i| get(i).to_string()))
+            .map_err(|e| row.errors.push(e))
+            .ok()
+            .flatten();
+
+        match (patient.and_then(|p| p.id), test_type) {
+            (Some(patient_id), Some(test_type)) if row.errors.is_empty() => {
+                row.test_type = test_type.as_str().to_string();
+                let in_file = format!("{}|{}", patient_id, test_type.as_str());
+                let specimen_in_file = row
+                    .specimen_id
+                    .as_ref()
+                    .map(|s| format!("specimen|{}|{}", s, test_type.as_str()));
+                let duplicate_of = if seen.contains(&in_file)
+                    || specimen_in_file.as_ref().is_some_and(|s| seen.contains(s))
+                {
+                    Some("an earlier line".to_string())
+                } else {
+                    find_duplicate_order(db, patient_id, &test_type, row.specimen_id.as_deref())
+                        .await?
+                };
+
+                if duplicate_of.is_some() {
+                    report.duplicates += 1;
+                    row.status = RowStatus::Duplicate;
+                    row.duplicate_of = duplicate_of;
+                } else {
+                    seen.insert(in_file);
+                    seen.extend(specimen_in_file);
+                    report.new_orders += 1;
+                    row.status = RowStatus::New;
+                    let mut test =
+                        Test::new_pending(patient_id, test_type.as_str().to_string(), None, None);
+                    test.specimen_id = row.specimen_id.clone();
+                    tests.push(test);
+                }
+            }
+            _ => report.invalid += 1,
+        }
+
+        if row.status != RowStatus::New || report.new_orders <= patient_import::PREVIEW_ROWS {
+            report.rows.push(row);
+        }
+    }
+
+    if !report.dry_run && !tests.is_empty() {
+        Database::insert_tests(db, &mut tests).await?;
+        report.imported = tests.len();
+        report.created_tests = tests.into_iter().map(|t| t.uuid).collect();
+        info!(
+            "Imported {} orders from {} ({} duplicates, {} invalid rows skipped)",
+            report.imported,
+            path.display(),
+            report.duplicates,
+            report.invalid
+        );
+    }
+    Ok(report)
+}
@@ -1,16 +1,46 @@
+use leptos::ev;
 use leptos::prelude::*;
 use leptos::task::spawn_local;
+use serde::Deserialize;
+use wasm_bindgen::prelude::*;
 
 mod components;
+mod errors;
 mod pages;
+mod scanner;
 mod serial;
 
 use components::ProfileMenu;
 use pages::{
-    DetectionsPage, LandingPage, PatientFormPage, ScientificViewPage, SettingsPage,
-    TestReadingPage, TestResultsPage,
+    DetectionsPage, LandingPage, LockPage, LoginPage, PatientFormPage, QualityControlPage,
+    ScientificViewPage, SettingsPage, TestReadingPage, TestResultsPage, UnlockPage, WorklistPage,
 };
 use serial::initialize_serial;
+use shared_types::{DeviceStatus, User};
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(catch, js_namespace = ["window", "__TAURI__", "core"])]
+    async fn invoke(cmd: &str, args: JsValue) -> Result<JsValue, JsValue>;
+}
+
+/// Whether the database still needs its passphrase
+#[derive(Deserialize)]
+struct EncryptionStatus {
+    unlocked: bool,
+}
+
+/// Who is signed in, or whether the first account still has to be created
+#[derive(Deserialize)]
+struct AuthStatus {
+    user: Option<User>,
+    needs_setup: bool,
+    locked: bool,
+    auto_lock_minutes: u32,
+}
+
+/// Activity is reported to the backend at most this often
+const TOUCH_INTERVAL_MS: f64 = 30_000.0;
 
 #[derive(Clone, PartialEq)]
 pub enum Page {
@@ -21,13 +51,15 @@ pub enum Page {
     History,
     Settings,
     ScientificView,
+    Worklist,
+    QualityControl,
 }
 
 #[component]
 pub fn App() -> impl IntoView {
     let (current_page, set_current_page) = signal(Page::Landing);
     let (_latest_serial, set_latest_serial) = signal(String::new());
-    let (connected, set_connected) = signal(false);
+    let (device_statuses, set_device_statuses) = signal(Vec::<DeviceStatus>::new());
 
     // Detection state
     let (detection_loading, set_detection_loading) = signal(false);
@@ -36,69 +68,277 @@ pub fn App() -> impl IntoView {
 
     // Current test UUID (set when creating a test, used to save results)
     let (current_test_uuid, set_current_test_uuid) = signal(None::<String>);
+    // Device the current test is routed to
+    let (current_device_id, set_current_device_id) = signal(None::<String>);
+
+    // Whether the device running the current test is connected
+    let connected = Signal::derive(move || {
+        let device_id = current_device_id.get();
+        device_statuses
+            .get()
+            .iter()
+            .any(|s| Some(&s.device_id) == device_id.as_ref() && s.connected)
+    });
+
+    // A new test starts with a clean detection state
+    Effect::new(move || {
+        current_test_uuid.track();
+        set_detection_loading.set(false);
+        set_detection_result.set(None);
+        set_detection_error.set(None);
+    });
+
+    // None until the backend says whether the database is unlocked
+    let (db_unlocked, set_db_unlocked) = signal(None::<bool>);
+    // None until the backend says whether anyone is signed in
+    let (needs_setup, set_needs_setup) = signal(None::<bool>);
+    let (current_user, set_current_user) = signal(None::<User>);
+    let signed_in = Memo::new(move |_| current_user.with(|user| user.is_some()));
+    let (serial_started, set_serial_started) = signal(false);
+
+    // Auto-lock: the session locks after a period without input
+    let (session_locked, set_session_locked) = signal(false);
+    let (auto_lock_minutes, set_auto_lock_minutes) = signal(0u32);
+    let last_activity = StoredValue::new(js_sys::Date::now());
+    let last_touch = StoredValue::new(0.0f64);
+
+    // Initialize serial communication once signed in; devices are read from the database
+    let start_serial = move || {
+        if serial_started.get_untracked() {
+            return;
+        }
+        set_serial_started.set(true);
+        spawn_local(async move {
+            initialize_serial(
+                set_latest_serial,
+                set_device_statuses,
+                current_test_uuid,
+                set_detection_loading,
+                set_detection_result,
+                set_detection_error,
+            )
+            .await;
+        });
+    };
+
+    let check_auth = move || {
+        spawn_local(async move {
+            match invoke("get_auth_status", JsValue::NULL).await {
+                Ok(value) => match serde_wasm_bindgen::from_value::<AuthStatus>(value) {
+                    Ok(status) => {
+                        if status.user.is_some() {
+                            start_serial();
+                        }
+                        last_activity.set_value(js_sys::Date::now());
+                        set_auto_lock_minutes.set(status.auto_lock_minutes);
+                        set_session_locked.set(status.locked);
+                        set_current_user.set(status.user);
+                        set_needs_setup.set(Some(status.needs_setup));
+                    }
+                    Err(e) => leptos::logging::log!("Failed to parse auth status: {:?}", e),
+                },
+                Err(e) => {
+                    errors::invoke_error("Failed to read sign-in status", e);
+                    set_needs_setup.set(Some(false));
+                }
+            }
+        });
+    };
 
-    // Initialize serial communication
     spawn_local(async move {
-        initialize_serial(
-            set_latest_serial,
-            set_connected,
-            set_detection_loading,
-            set_detection_result,
-            set_detection_error,
-        )
-        .await;
+        let unlocked = match invoke("get_encryption_status", JsValue::NULL).await {
+            Ok(value) => serde_wasm_bindgen::from_value::<EncryptionStatus>(value)
+                .map(|status| status.unlocked)
+                .unwrap_or(true),
+            Err(e) => {
+                errors::invoke_error("Failed to read encryption status", e);
+                true
+            }
+        };
+        set_db_unlocked.set(Some(unlocked));
+        if unlocked {
+            check_auth();
+        }
     });
 
+    let on_unlocked = move || {
+        set_db_unlocked.set(Some(true));
+        check_auth();
+    };
+
+    let on_signed_in = move |user: User| {
+        set_current_user.set(Some(user));
+        set_current_page.set(Page::Landing);
+        check_auth();
+    };
+
+    // Leave nothing of the previous operator's work on screen
+    let on_signed_out = move || {
+        set_session_locked.set(false);
+        set_current_user.set(None);
+        set_current_test_uuid.set(None);
+        set_current_device_id.set(None);
+        set_current_page.set(Page::Landing);
+    };
+
+    // Locking unmounts the current page, so no patient data stays on screen
+    let lock_now = move || {
+        set_session_locked.set(true);
+        spawn_local(async move {
+            if let Err(e) = invoke("lock_session", JsValue::NULL).await {
+                errors::invoke_error("Failed to lock session", e);
+            }
+        });
+    };
+
+    let on_session_unlocked = move |user: User| {
+        last_activity.set_value(js_sys::Date::now());
+        set_current_user.set(Some(user));
+        set_session_locked.set(false);
+    };
+
+    // Any input counts as activity; the backend hears about it periodically
+    let on_activity = move || {
+        if !signed_in.get_untracked() || session_locked.get_untracked() {
+            return;
+        }
+        let now = js_sys::Date::now();
+        last_activity.set_value(now);
+        if now - last_touch.get_value() < TOUCH_INTERVAL_MS {
+            return;
+        }
+        last_touch.set_value(now);
+        spawn_local(async move {
+            match invoke("touch_session", JsValue::NULL).await {
+                Ok(value) => {
+                    if let Some(minutes) = value.as_f64() {
+                        set_auto_lock_minutes.set(minutes as u32);
+                    }
+                }
+                // The backend locked first, e.g. after the machine slept
+                Err(_) => check_auth(),
+            }
+        });
+    };
+    window_event_listener(ev::mousemove, move |_| on_activity());
+    window_event_listener(ev::mousedown, move |_| on_activity());
+    window_event_listener(ev::keydown, move |_| on_activity());
+    window_event_listener(ev::touchstart, move |_| on_activity());
+    window_event_listener(ev::wheel, move |_| on_activity());
+
+    set_interval(
+        move || {
+            let minutes = auto_lock_minutes.get_untracked();
+            if minutes == 0 || !signed_in.get_untracked() || session_locked.get_untracked() {
+                return;
+            }
+            let idle_ms = js_sys::Date::now() - last_activity.get_value();
+            if idle_ms >= f64::from(minutes) * 60_000.0 {
+                lock_now();
+            }
+        },
+        std::time::Duration::from_secs(5),
+    );
+
     view! {
         <main class="container">
-            // Profile menu in top right corner
-            <div class="profile-menu-wrapper">
-                <ProfileMenu on_navigate=set_current_page />
-            </div>
-
-            {move || match current_page.get() {
-                Page::Landing => view! {
-                    <LandingPage
-                        on_navigate=set_current_page
-                    />
-                }.into_any(),
-                Page::PatientForm => view! {
-                    <PatientFormPage
-                        on_navigate=set_current_page
-                        set_current_test_uuid=set_current_test_uuid
-                    />
-                }.into_any(),
-                Page::TestReading => view! {
-                    <TestReadingPage
-                        connected=connected
-                        on_navigate=set_current_page
-                        detection_loading=detection_loading
-                        detection_result=detection_result
-                        detection_error=detection_error
-                    />
-                }.into_any(),
-                Page::TestResults => view! {
-                    <TestResultsPage
-                        on_navigate=set_current_page
-                        detection_result=detection_result
-                        current_test_uuid=current_test_uuid
-                    />
-                }.into_any(),
-                Page::History => view! {
-                    <DetectionsPage
-                        on_navigate_to_home=set_current_page
-                    />
-                }.into_any(),
-                Page::Settings => view! {
-                    <SettingsPage
-                        on_navigate_to_home=set_current_page
-                    />
-                }.into_any(),
-                Page::ScientificView => view! {
-                    <ScientificViewPage
-                        on_navigate=set_current_page
-                    />
-                }.into_any(),
+            {move || match db_unlocked.get() {
+                None => ().into_any(),
+                Some(false) => view! { <UnlockPage on_unlocked=on_unlocked /> }.into_any(),
+                Some(true) => match (needs_setup.get(), signed_in.get(), session_locked.get()) {
+                    (None, _, _) => ().into_any(),
+                    (Some(setup), false, _) => view! {
+                        <LoginPage needs_setup=setup on_signed_in=on_signed_in />
+                    }.into_any(),
+                    (Some(_), true, true) => view! {
+                        <LockPage
+                            current_user=current_user
+                            on_unlocked=on_session_unlocked
+                            on_signed_out=on_signed_out
+                        />
+                    }.into_any(),
+                    (Some(_), true, false) => view! {
+                        // Profile menu in top right corner
+                        <div class="profile-menu-wrapper">
+                            <ProfileMenu
+                                on_navigate=set_current_page
+                                current_user=current_user
+                                on_lock=lock_now
+                                on_signed_out=on_signed_out
+                            />
+                        </div>
+
+                        {move || match current_page.get() {
+                            Page::Landing => view! {
+                                <LandingPage
+                                    on_navigate=set_current_page
+                                    device_statuses=device_statuses
+                                />
+                            }.into_any(),
+                            Page::PatientForm => view! {
+                                <PatientFormPage
+                                    on_navigate=set_current_page
+                                    set_current_test_uuid=set_current_test_uuid
+                                    set_current_device_id=set_current_device_id
+                                />
+                            }.into_any(),
+                            Page::TestReading => view! {
+                                <TestReadingPage
+                                    connected=connected
+                                    current_test_uuid=current_test_uuid
+                                    on_navigate=set_current_page
+                                    detection_loading=detection_loading
+                                    detection_result=detection_result
+                                    detection_error=detection_error
+                                />
+                            }.into_any(),
+                            Page::TestResults => view! {
+                                <TestResultsPage
+                                    on_navigate=set_current_page
+                                    detection_result=detection_result
+                                    current_test_uuid=current_test_uuid
+                                />
+                            }.into_any(),
+                            Page::History => view! {
+                                <DetectionsPage
+                                    on_navigate_to_home=set_current_page
+                                    current_user=current_user
+                                />
+                            }.into_any(),
+                            Page::Settings => view! {
+                                <SettingsPage
+                                    on_navigate_to_home=set_current_page
+                                    current_user=current_user
+                                />
+                            }.into_any(),
+                            Page::ScientificView => view! {
+                                <ScientificViewPage
+                                    on_navigate=set_current_page
+                                />
+                            }.into_any(),
+                            Page::Worklist => view! {
+                                <WorklistPage
+                                    on_navigate=set_current_page
+                                    set_current_test_uuid=set_current_test_uuid
+                                    set_current_device_id=set_current_device_id
+                                />
+                            }.into_any(),
+                            Page::QualityControl => view! {
+                                <QualityControlPage
+                                    on_navigate=set_current_page
+                                    current_user=current_user
+                                    current_test_uuid=current_test_uuid
+                                    set_current_test_uuid=set_current_test_uuid
+                                    set_current_device_id=set_current_device_id
+                                    connected=connected
+                                    detection_loading=detection_loading
+                                    detection_result=detection_result
+                                    detection_error=detection_error
+                                />
+                            }.into_any(),
+                        }}
+                    }.into_any(),
+                },
             }}
         </main>
     }
@@ -0,0 +1,77 @@
+use leptos::prelude::*;
+
+// Use shared types
+use shared_types::DeviceStatus;
+
+#[component]
+pub fn DeviceStatusBoard(statuses: ReadSignal<Vec<DeviceStatus>>) -> impl IntoView {
+    view! {
+        <div class="card" style="margin-bottom: 2rem;">
+            <h2 style="font-size: 1.25rem; font-weight: 500; margin: 0 0 1.5rem 0; color: var(--color-text-secondary);">
+                "Devices"
+            </h2>
+            {move || {
+                let devices = statuses.get();
+                if devices.is_empty() {
+                    view! {
+                        <div style="text-align: center; padding: 2rem; color: var(--color-text-secondary);">
+                            <p style="margin: 0; font-size: 0.9375rem;">
+                                "No devices configured. Add a reader in Settings."
+                            </p>
+                        </div>
+                    }.into_any()
+                } else {
+                    view! {
+                        <div class="device-board">
+                            {devices.into_iter().map(|status| {
+                                view! { <DeviceStatusTile status=status /> }
+                            }).collect_view()}
+                        </div>
+                    }.into_any()
+                }
+            }}
+        </div>
+    }
+}
+
+#[component]
+fn DeviceStatusTile(status: DeviceStatus) -> impl IntoView {
+    let (state_color, state_text) = if !status.connected {
+        ("var(--color-text-tertiary)", "Disconnected")
+    } else if status.collecting {
+        ("var(--color-info)", "Collecting")
+    } else {
+        ("var(--color-success)", "Ready")
+    };
+
+    let assignment = status
+        .active_test_uuid
+        .as_ref()
+        .map(|uuid| format!("Test {}", uuid.chars().take(8).collect::<String>()))
+        .unwrap_or_else(|| "No test assigned".to_string());
+
+    view! {
+        <div class="device-tile">
+            <div style="display: flex; align-items: center; justify-content: space-between; margin-bottom: 0.5rem;">
+                <div style="font-weight: 500; color: var(--color-text-primary);">
+                    {status.label.clone()}
+                </div>
+                <div style="display: flex; align-items: center; gap: 0.375rem; font-size: 0.75rem;">
+                    <div style=format!("width: 8px; height: 8px; border-radius: 50%; background-color: {};", state_color)></div>
+                    <span style=format!("color: {}; font-weight: 500;", state_color)>{state_text}</span>
+                </div>
+            </div>
+            <div style="font-family: monospace; font-size: 0.75rem; color: var(--color-text-secondary); margin-bottom: 0.25rem;">
+                {format!("{} • {}", status.device_id, status.port)}
+            </div>
+            <div style="font-size: 0.75rem; color: var(--color-text-secondary);">
+                {assignment}
+            </div>
+            {status.last_error.clone().filter(|_| !status.connected).map(|err| view! {
+                <div style="font-size: 0.75rem; color: var(--color-error); margin-top: 0.25rem; word-break: break-word;">
+                    {err}
+                </div>
+            })}
+        </div>
+    }
+}
@@ -0,0 +1,68 @@
+use crate::app::errors::invoke_error;
+use leptos::prelude::*;
+use leptos::task::spawn_local;
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(catch, js_namespace = ["window", "__TAURI__", "core"])]
+    async fn invoke(cmd: &str, args: JsValue) -> Result<JsValue, JsValue>;
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PrintTestLabelArgs {
+    test_uuid: String,
+}
+
+/// Print the sample labels of a test on the label printer, or save them as a
+/// PDF in the Documents folder when none is set up
+#[component]
+pub fn LabelButton(test_uuid: String) -> impl IntoView {
+    let (printing, set_printing) = signal(false);
+    let (message, set_message) = signal(None::<Result<String, String>>);
+
+    let print_labels = move |_| {
+        let args = PrintTestLabelArgs {
+            test_uuid: test_uuid.clone(),
+        };
+        spawn_local(async move {
+            set_printing.set(true);
+            let result = match invoke(
+                "print_test_label",
+                serde_wasm_bindgen::to_value(&args).unwrap(),
+            )
+            .await
+            {
+                Ok(value) => serde_wasm_bindgen::from_value::<String>(value)
+                    .map(|destination| format!("Labels sent to {}", destination))
+                    .map_err(|e| format!("Failed to parse label destination: {:?}", e)),
+                Err(e) => Err(invoke_error("Failed to print labels", e)),
+            };
+            set_message.set(Some(result));
+            set_printing.set(false);
+        });
+    };
+
+    view! {
+        <div style="display: flex; flex-direction: column; align-items: flex-end; gap: 0.25rem;">
+            <button
+                class="button"
+                on:click=print_labels
+                disabled=move || printing.get()
+                style="padding: 0.5rem 0.75rem; font-size: 0.875rem;"
+            >
+                {move || if printing.get() { "Printing..." } else { "Labels" }}
+            </button>
+            {move || message.get().map(|result| match result {
+                Ok(msg) => view! {
+                    <div style="font-size: 0.75rem; color: var(--color-success); word-break: break-all;">{msg}</div>
+                }.into_any(),
+                Err(err) => view! {
+                    <div style="font-size: 0.75rem; color: var(--color-error);">{err}</div>
+                }.into_any(),
+            })}
+        </div>
+    }
+}
@@ -1,3 +1,7 @@
+pub mod device_status_board;
+pub mod label_button;
 pub mod profile_menu;
 
+pub use device_status_board::DeviceStatusBoard;
+pub use label_button::LabelButton;
 pub use profile_menu::ProfileMenu;
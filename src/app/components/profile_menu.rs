@@ -1,8 +1,23 @@
+use crate::app::errors::invoke_error;
 use crate::app::Page;
 use leptos::prelude::*;
+use leptos::task::spawn_local;
+use shared_types::User;
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(catch, js_namespace = ["window", "__TAURI__", "core"])]
+    async fn invoke(cmd: &str, args: JsValue) -> Result<JsValue, JsValue>;
+}
 
 #[component]
-pub fn ProfileMenu(on_navigate: WriteSignal<Page>) -> impl IntoView {
+pub fn ProfileMenu(
+    on_navigate: WriteSignal<Page>,
+    current_user: ReadSignal<Option<User>>,
+    on_lock: impl Fn() + Copy + Send + Sync + 'static,
+    on_signed_out: impl Fn() + Copy + Send + Sync + 'static,
+) -> impl IntoView {
     let (is_open, set_is_open) = signal(false);
 
     // Close dropdown when clicking outside
@@ -15,6 +30,21 @@ pub fn ProfileMenu(on_navigate: WriteSignal<Page>) -> impl IntoView {
         set_is_open.set(false);
     };
 
+    let lock = move |_| {
+        set_is_open.set(false);
+        on_lock();
+    };
+
+    let sign_out = move |_| {
+        set_is_open.set(false);
+        spawn_local(async move {
+            if let Err(e) = invoke("logout", JsValue::NULL).await {
+                invoke_error("Failed to sign out", e);
+            }
+            on_signed_out();
+        });
+    };
+
     view! {
         <div class="profile-menu-container">
             <button
@@ -55,12 +85,34 @@ pub fn ProfileMenu(on_navigate: WriteSignal<Page>) -> impl IntoView {
                 if is_open.get() {
                     view! {
                         <div class="profile-dropdown">
+                            {current_user.get().map(|user| view! {
+                                <div style="padding: 0.75rem 1rem; border-bottom: 1px solid var(--color-border-light);">
+                                    <div style="font-weight: 500; font-size: 0.875rem; color: var(--color-text-primary);">
+                                        {user.display_name}
+                                    </div>
+                                    <div style="font-size: 0.75rem; color: var(--color-text-secondary);">
+                                        {format!("{} · {}", user.username, user.role.label())}
+                                    </div>
+                                </div>
+                            })}
                             <button
                                 class="dropdown-item"
                                 on:click=navigate_to_settings
                             >
                                 <span>"Settings"</span>
                             </button>
+                            <button
+                                class="dropdown-item"
+                                on:click=lock
+                            >
+                                <span>"Lock"</span>
+                            </button>
+                            <button
+                                class="dropdown-item"
+                                on:click=sign_out
+                            >
+                                <span>"Sign Out"</span>
+                            </button>
                         </div>
                     }.into_any()
                 } else {
@@ -0,0 +1,36 @@
+use shared_types::{AppError, ErrorCode};
+use wasm_bindgen::JsValue;
+
+/// Decode a rejected `invoke` into the backend's error
+///
+/// Rejections that are not an `AppError` (e.g. an unknown command) are kept
+/// readable as internal errors.
+pub fn from_invoke(e: JsValue) -> AppError {
+    match serde_wasm_bindgen::from_value::<AppError>(e.clone()) {
+        Ok(error) => error,
+        Err(_) => AppError::internal(e.as_string().unwrap_or_else(|| format!("{:?}", e))),
+    }
+}
+
+/// What the operator can do about each kind of failure
+fn hint(code: ErrorCode) -> &'static str {
+    match code {
+        ErrorCode::NotFound => "It may have been removed; refresh and try again.",
+        ErrorCode::Validation => "Correct the input and try again.",
+        ErrorCode::Conflict => "Resolve the conflict and try again.",
+        ErrorCode::Database => "Try again; if it persists, restart the application.",
+        ErrorCode::Serial => "Check the reader's cable and port settings.",
+        ErrorCode::DetectionApi => "Check that the detection service is running and reachable.",
+        ErrorCode::Io => "Check free disk space and access to the application data folder.",
+        ErrorCode::Unauthorized => "Sign in or unlock the session and try again.",
+        ErrorCode::Forbidden => "Ask an administrator to do this for you.",
+        ErrorCode::Internal => "Try again; if it persists, contact support.",
+    }
+}
+
+/// Log a failed `invoke` with its details and return a message for the UI
+pub fn invoke_error(action: &str, e: JsValue) -> String {
+    let error = from_invoke(e);
+    leptos::logging::log!("{} [{}]: {}", action, error.code.as_str(), error);
+    format!("{}: {}. {}", action, error.message, hint(error.code))
+}
@@ -1,9 +1,14 @@
+use crate::app::errors::invoke_error;
 use crate::app::Page;
 use leptos::prelude::*;
+use leptos::task::spawn_local;
+use serde::{Deserialize, Serialize};
 use wasm_bindgen::prelude::*;
 
 // Use shared types
-use shared_types::TestWithPatient;
+use shared_types::{
+    DataQualityReport, DetectionResult, Permission, ReviewEvent, TestWithPatient, User,
+};
 
 #[wasm_bindgen]
 extern "C" {
@@ -11,8 +16,58 @@ extern "C" {
     async fn invoke(cmd: &str, args: JsValue) -> Result<JsValue, JsValue>;
 }
 
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ReleaseTestArgs {
+    test_uuid: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct OverrideTestResultArgs {
+    test_uuid: String,
+    detection_result: DetectionResult,
+    reason: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RejectTestArgs {
+    test_uuid: String,
+    reason: String,
+}
+
+/// Mirrors the backend's export options
+#[derive(Serialize)]
+struct ExportOptions {
+    format: String,
+    status: Option<String>,
+    pseudonymize: bool,
+    include_raw_signal: bool,
+}
+
+#[derive(Serialize)]
+struct ExportTestsArgs {
+    options: ExportOptions,
+    path: String,
+}
+
+#[derive(Deserialize)]
+struct ExportSummary {
+    path: String,
+    exported: u64,
+    unreleased: u64,
+}
+
 #[component]
-pub fn DetectionsPage(on_navigate_to_home: WriteSignal<Page>) -> impl IntoView {
+pub fn DetectionsPage(
+    on_navigate_to_home: WriteSignal<Page>,
+    current_user: ReadSignal<Option<User>>,
+) -> impl IntoView {
+    // Review controls are only offered to roles the backend lets review
+    let can_review = current_user
+        .get_untracked()
+        .is_some_and(|user| user.role.can(Permission::ReviewResults));
     let (tests, set_tests) = signal(Vec::<TestWithPatient>::new());
     let (loading, set_loading) = signal(true);
     let (error, set_error) = signal(None::<String>);
@@ -51,9 +106,7 @@ pub fn DetectionsPage(on_navigate_to_home: WriteSignal<Page>) -> impl IntoView {
                     }
                 }
                 Err(e) => {
-                    let error_msg = format!("Failed to load tests: {:?}", e);
-                    console::log_1(&JsValue::from_str(&error_msg));
-                    set_error.set(Some(error_msg));
+                    set_error.set(Some(invoke_error("Failed to load tests", e)));
                     set_loading.set(false);
                 }
             }
@@ -118,6 +171,18 @@ pub fn DetectionsPage(on_navigate_to_home: WriteSignal<Page>) -> impl IntoView {
                     >
                         "Completed"
                     </button>
+                    <button
+                        class=move || if filter_status.get() == "awaiting_review" { "button button-primary" } else { "button" }
+                        on:click=move |_| set_filter_status.set("awaiting_review".to_string())
+                    >
+                        "Awaiting Review"
+                    </button>
+                    <button
+                        class=move || if filter_status.get() == "rejected" { "button button-primary" } else { "button" }
+                        on:click=move |_| set_filter_status.set("rejected".to_string())
+                    >
+                        "Rejected"
+                    </button>
                     <button
                         class=move || if filter_status.get() == "in_progress" { "button button-primary" } else { "button" }
                         on:click=move |_| set_filter_status.set("in_progress".to_string())
@@ -137,6 +202,8 @@ pub fn DetectionsPage(on_navigate_to_home: WriteSignal<Page>) -> impl IntoView {
                         "Error"
                     </button>
                 </div>
+
+                {can_review.then(|| view! { <ExportPanel status=filter_status /> })}
             </header>
 
             // Loading state
@@ -173,7 +240,11 @@ pub fn DetectionsPage(on_navigate_to_home: WriteSignal<Page>) -> impl IntoView {
                             </div>
                             {test_list.into_iter().map(|test_with_patient| {
                                 view! {
-                                    <TestCard test_with_patient=test_with_patient />
+                                    <TestCard
+                                        test_with_patient=test_with_patient
+                                        can_review=can_review
+                                        on_reviewed=move || load_tests()
+                                    />
                                 }
                             }).collect_view()}
                         </div>
@@ -185,13 +256,19 @@ pub fn DetectionsPage(on_navigate_to_home: WriteSignal<Page>) -> impl IntoView {
 }
 
 #[component]
-fn TestCard(test_with_patient: TestWithPatient) -> impl IntoView {
+fn TestCard(
+    test_with_patient: TestWithPatient,
+    can_review: bool,
+    on_reviewed: impl Fn() + Copy + Send + Sync + 'static,
+) -> impl IntoView {
     let test = test_with_patient.test;
     let patient = test_with_patient.patient;
+    let awaiting_review = test.status == "awaiting_review";
 
     let status_color = match test.status.as_str() {
         "completed" => "var(--color-success)",
-        "error" => "var(--color-error)",
+        "awaiting_review" => "var(--color-warning)",
+        "error" | "rejected" => "var(--color-error)",
         "in_progress" => "var(--color-info)",
         "pending" => "var(--color-warning)",
         _ => "var(--color-text-secondary)",
@@ -199,7 +276,8 @@ fn TestCard(test_with_patient: TestWithPatient) -> impl IntoView {
 
     let status_bg = match test.status.as_str() {
         "completed" => "var(--color-success-bg)",
-        "error" => "var(--color-error-bg)",
+        "awaiting_review" => "var(--color-warning-bg)",
+        "error" | "rejected" => "var(--color-error-bg)",
         "in_progress" => "var(--color-info-bg)",
         "pending" => "var(--color-warning-bg)",
         _ => "var(--color-bg-secondary)",
@@ -237,6 +315,11 @@ fn TestCard(test_with_patient: TestWithPatient) -> impl IntoView {
                     <div style="font-family: monospace; font-size: 0.75rem; color: var(--color-text-secondary);">
                         {format!("Test ID: {}", test.uuid)}
                     </div>
+                    {test.retest_of.as_ref().map(|original| view! {
+                        <div style="font-family: monospace; font-size: 0.75rem; color: var(--color-text-secondary);">
+                            {format!("Retest of: {}", original)}
+                        </div>
+                    })}
                 </div>
                 <div style="display: flex; flex-direction: column; align-items: flex-end; gap: 0.5rem;">
                     <span
@@ -332,6 +415,19 @@ fn TestCard(test_with_patient: TestWithPatient) -> impl IntoView {
                 }
             })}
 
+            // Review trail, then the reviewer's decision if one is due
+            {(!test.review_log.is_empty()).then(|| view! {
+                <ReviewLogPanel events=test.review_log.clone() />
+            })}
+            {(awaiting_review && can_review).then(|| view! {
+                <ReviewPanel test_uuid=test.uuid.clone() on_reviewed=on_reviewed />
+            })}
+
+            // Data quality report (if available)
+            {test.quality_report.clone().map(|report| {
+                view! { <QualityReportPanel report=report /> }
+            })}
+
             // Raw response (if available)
             {test.raw_response.as_ref().map(|response| {
                 view! {
@@ -363,6 +459,334 @@ fn TestCard(test_with_patient: TestWithPatient) -> impl IntoView {
     }
 }
 
+#[component]
+fn ReviewLogPanel(events: Vec<ReviewEvent>) -> impl IntoView {
+    view! {
+        <div style="margin-bottom: 1rem;">
+            <div style="font-size: 0.75rem; color: var(--color-text-secondary); margin-bottom: 0.5rem;">
+                "Review"
+            </div>
+            <div style="display: flex; flex-direction: column; gap: 0.25rem; font-size: 0.875rem;">
+                {events.into_iter().map(|event| {
+                    let change = match (&event.previous_result, &event.new_result) {
+                        (Some(from), Some(to)) => format!(" ({} → {})", from, to),
+                        _ => String::new(),
+                    };
+                    view! {
+                        <div style="display: flex; gap: 0.75rem;">
+                            <span style="color: var(--color-text-secondary); white-space: nowrap;">
+                                {format_timestamp(&event.at)}
+                            </span>
+                            <span style="color: var(--color-text-primary);">
+                                {format!("{}{} by {}", event.action.label(), change, event.by)}
+                                {event.reason.map(|reason| view! {
+                                    <span style="color: var(--color-text-secondary);">
+                                        {format!(" — {}", reason)}
+                                    </span>
+                                })}
+                            </span>
+                        </div>
+                    }
+                }).collect_view()}
+            </div>
+        </div>
+    }
+}
+
+/// Accept, override or reject a result awaiting review
+#[component]
+fn ReviewPanel(
+    test_uuid: String,
+    on_reviewed: impl Fn() + Copy + Send + Sync + 'static,
+) -> impl IntoView {
+    let test_uuid = StoredValue::new(test_uuid);
+    let (reason, set_reason) = signal(String::new());
+    let (override_result, set_override_result) = signal(DetectionResult::Negative);
+    let (busy, set_busy) = signal(false);
+    let (error, set_error) = signal(None::<String>);
+
+    let submit = move |command: &'static str, args: JsValue, action: &'static str| {
+        spawn_local(async move {
+            set_busy.set(true);
+            set_error.set(None);
+            match invoke(command, args).await {
+                Ok(_) => on_reviewed(),
+                Err(e) => set_error.set(Some(invoke_error(action, e))),
+            }
+            set_busy.set(false);
+        });
+    };
+
+    let accept = move |_| {
+        let args = ReleaseTestArgs {
+            test_uuid: test_uuid.get_value(),
+        };
+        submit(
+            "release_test",
+            serde_wasm_bindgen::to_value(&args).unwrap(),
+            "Failed to release result",
+        );
+    };
+
+    let override_ = move |_| {
+        let args = OverrideTestResultArgs {
+            test_uuid: test_uuid.get_value(),
+            detection_result: override_result.get(),
+            reason: reason.get(),
+        };
+        submit(
+            "override_test_result",
+            serde_wasm_bindgen::to_value(&args).unwrap(),
+            "Failed to override result",
+        );
+    };
+
+    let reject = move |_| {
+        let args = RejectTestArgs {
+            test_uuid: test_uuid.get_value(),
+            reason: reason.get(),
+        };
+        submit(
+            "reject_test",
+            serde_wasm_bindgen::to_value(&args).unwrap(),
+            "Failed to reject result",
+        );
+    };
+
+    let no_reason = move || busy.get() || reason.get().trim().is_empty();
+
+    view! {
+        <div style="margin-bottom: 1rem; padding: 1rem; border: 1px solid var(--color-warning); border-radius: 8px; display: flex; flex-direction: column; gap: 0.75rem;">
+            <div style="font-size: 0.875rem; font-weight: 500; color: var(--color-text-primary);">
+                "Awaiting your review"
+            </div>
+            <input
+                type="text"
+                class="form-input"
+                placeholder="Reason (required to override or reject)"
+                prop:value=move || reason.get()
+                on:input=move |e| set_reason.set(event_target_value(&e))
+            />
+            <div style="display: flex; gap: 0.5rem; align-items: center; flex-wrap: wrap;">
+                <button class="button button-primary" on:click=accept disabled=move || busy.get()>
+                    "Accept & Release"
+                </button>
+                <select
+                    class="port-selector"
+                    on:change=move |ev| {
+                        if let Ok(selected) = DetectionResult::from_str(&event_target_value(&ev)) {
+                            set_override_result.set(selected);
+                        }
+                    }
+                    prop:value=move || override_result.get().as_str()
+                    style="padding: 0.5rem; border: 1px solid var(--color-border-medium); border-radius: 6px; background: var(--color-surface); font-family: inherit; font-size: 0.875rem;"
+                >
+                    <option value="positive">"Positive"</option>
+                    <option value="negative">"Negative"</option>
+                    <option value="inconclusive">"Inconclusive"</option>
+                </select>
+                <button class="button" on:click=override_ disabled=no_reason>
+                    "Override & Release"
+                </button>
+                <button class="button" on:click=reject disabled=no_reason>
+                    "Reject & Retest"
+                </button>
+            </div>
+            {move || error.get().map(|err| view! {
+                <div style="padding: 0.75rem; background: #f8d7da; border: 1px solid #f5c6cb; border-radius: 6px; color: #721c24; font-size: 0.875rem;">
+                    {err}
+                </div>
+            })}
+        </div>
+    }
+}
+
+/// Save the released tests matching the current filter for research use
+#[component]
+fn ExportPanel(status: ReadSignal<String>) -> impl IntoView {
+    let (format, set_format) = signal(String::from("csv"));
+    let (pseudonymize, set_pseudonymize) = signal(true);
+    let (include_raw_signal, set_include_raw_signal) = signal(false);
+    let (path, set_path) = signal(String::new());
+    let (busy, set_busy) = signal(false);
+    let (message, set_message) = signal(None::<Result<String, String>>);
+
+    let export = move |_| {
+        let status = status.get();
+        let args = ExportTestsArgs {
+            options: ExportOptions {
+                format: format.get(),
+                status: (status != "all").then_some(status),
+                pseudonymize: pseudonymize.get(),
+                include_raw_signal: include_raw_signal.get(),
+            },
+            path: path.get(),
+        };
+        spawn_local(async move {
+            set_busy.set(true);
+            set_message.set(None);
+            let result = match invoke("export_tests", serde_wasm_bindgen::to_value(&args).unwrap())
+                .await
+            {
+                Ok(value) => match serde_wasm_bindgen::from_value::<ExportSummary>(value) {
+                    Ok(summary) => {
+                        let mut msg =
+                            format!("Exported {} test(s) to {}", summary.exported, summary.path);
+                        if summary.unreleased > 0 {
+                            msg.push_str(&format!(
+                                "; {} unreleased test(s) left out",
+                                summary.unreleased
+                            ));
+                        }
+                        Ok(msg)
+                    }
+                    Err(e) => Err(format!("Failed to parse export summary: {:?}", e)),
+                },
+                Err(e) => Err(invoke_error("Failed to export tests", e)),
+            };
+            set_message.set(Some(result));
+            set_busy.set(false);
+        });
+    };
+
+    view! {
+        <details style="margin-top: 1rem; padding: 1rem; border: 1px solid var(--color-border-light); border-radius: 8px;">
+            <summary style="cursor: pointer; font-size: 0.875rem; font-weight: 500; color: var(--color-text-primary);">
+                "Export released results"
+            </summary>
+            <div style="display: flex; flex-direction: column; gap: 0.75rem; margin-top: 0.75rem;">
+                <div style="font-size: 0.875rem; color: var(--color-text-secondary);">
+                    "Uses the filter above. A file name alone is saved in your Documents folder."
+                </div>
+                <div style="display: flex; gap: 1rem; align-items: center; flex-wrap: wrap; font-size: 0.875rem;">
+                    <select
+                        class="port-selector"
+                        on:change=move |ev| set_format.set(event_target_value(&ev))
+                        prop:value=move || format.get()
+                        style="padding: 0.5rem; border: 1px solid var(--color-border-medium); border-radius: 6px; background: var(--color-surface); font-family: inherit; font-size: 0.875rem;"
+                    >
+                        <option value="csv">"CSV"</option>
+                        <option value="ndjson">"JSON (one test per line)"</option>
+                    </select>
+                    <label style="display: flex; gap: 0.5rem; align-items: center;">
+                        <input
+                            type="checkbox"
+                            prop:checked=move || pseudonymize.get()
+                            on:change=move |ev| set_pseudonymize.set(event_target_checked(&ev))
+                        />
+                        "Pseudonymise patients"
+                    </label>
+                    <label style="display: flex; gap: 0.5rem; align-items: center;">
+                        <input
+                            type="checkbox"
+                            prop:checked=move || include_raw_signal.get()
+                            on:change=move |ev| set_include_raw_signal.set(event_target_checked(&ev))
+                        />
+                        "Include raw signal"
+                    </label>
+                </div>
+                <div style="display: flex; gap: 0.5rem; align-items: center;">
+                    <input
+                        type="text"
+                        class="form-input"
+                        style="flex: 1;"
+                        placeholder="e.g. tests.csv"
+                        prop:value=move || path.get()
+                        on:input=move |e| set_path.set(event_target_value(&e))
+                    />
+                    <button
+                        class="button button-primary"
+                        on:click=export
+                        disabled=move || busy.get() || path.get().trim().is_empty()
+                    >
+                        {move || if busy.get() { "Exporting..." } else { "Export" }}
+                    </button>
+                </div>
+                {move || message.get().map(|result| match result {
+                    Ok(msg) => view! {
+                        <div style="padding: 0.75rem; background: #d4edda; border: 1px solid #c3e6cb; border-radius: 6px; color: #155724; font-size: 0.875rem;">
+                            {msg}
+                        </div>
+                    }.into_any(),
+                    Err(err) => view! {
+                        <div style="padding: 0.75rem; background: #f8d7da; border: 1px solid #f5c6cb; border-radius: 6px; color: #721c24; font-size: 0.875rem;">
+                            {err}
+                        </div>
+                    }.into_any(),
+                })}
+            </div>
+        </details>
+    }
+}
+
+#[component]
+fn QualityReportPanel(report: DataQualityReport) -> impl IntoView {
+    let (border, color) = if report.passed {
+        ("var(--color-border-light)", "var(--color-text-primary)")
+    } else {
+        ("var(--color-error)", "var(--color-error)")
+    };
+
+    let stats = vec![
+        (
+            "Rows Accepted",
+            format!("{} / {}", report.accepted_rows, report.total_rows),
+        ),
+        ("Rejected", report.rejected_rows.to_string()),
+        ("Repaired", report.repaired_rows.to_string()),
+        ("Duplicates", report.duplicate_indices.to_string()),
+        (
+            "Gaps",
+            format!("{} ({} missing)", report.index_gaps, report.missing_indices),
+        ),
+        (
+            "Saturation",
+            format!("{:.1}%", report.saturation_ratio * 100.0),
+        ),
+    ];
+
+    view! {
+        <div style="margin-bottom: 1rem;">
+            <div style="font-size: 0.75rem; color: var(--color-text-secondary); margin-bottom: 0.5rem;">
+                {if report.passed { "Data Quality" } else { "Data Quality (failed)" }}
+            </div>
+            <div style=format!(
+                "padding: 0.75rem; border: 1px solid {}; border-radius: 6px; font-size: 0.875rem;",
+                border
+            )>
+                <div style="display: grid; grid-template-columns: repeat(auto-fit, minmax(120px, 1fr)); gap: 0.5rem;">
+                    {stats
+                        .into_iter()
+                        .map(|(label, value)| {
+                            view! {
+                                <div>
+                                    <div style="font-size: 0.75rem; color: var(--color-text-secondary);">
+                                        {label}
+                                    </div>
+                                    <div style=format!("font-weight: 500; color: {};", color)>
+                                        {value}
+                                    </div>
+                                </div>
+                            }
+                        })
+                        .collect_view()}
+                </div>
+                {(!report.issues.is_empty()).then(|| {
+                    view! {
+                        <ul style="margin: 0.75rem 0 0 1rem; color: var(--color-error);">
+                            {report
+                                .issues
+                                .iter()
+                                .map(|issue| view! { <li>{issue.clone()}</li> })
+                                .collect_view()}
+                        </ul>
+                    }
+                })}
+            </div>
+        </div>
+    }
+}
+
 /// Format timestamp to a more readable format
 fn format_timestamp(timestamp: &str) -> String {
     // Try to parse and format the timestamp
@@ -1,10 +1,11 @@
+use crate::app::components::DeviceStatusBoard;
 use crate::app::Page;
 use leptos::prelude::*;
 use leptos::task::spawn_local;
 use wasm_bindgen::prelude::*;
 
 // Use shared types
-use shared_types::{Patient, Test, TestWithPatient};
+use shared_types::{DeviceStatus, Patient, Test, TestWithPatient};
 
 #[wasm_bindgen]
 extern "C" {
@@ -13,25 +14,46 @@ extern "C" {
 }
 
 #[component]
-pub fn LandingPage(on_navigate: WriteSignal<Page>) -> impl IntoView {
+pub fn LandingPage(
+    on_navigate: WriteSignal<Page>,
+    device_statuses: ReadSignal<Vec<DeviceStatus>>,
+) -> impl IntoView {
     let (recent_tests, set_recent_tests) = signal(Vec::<TestWithPatient>::new());
     let (loading_tests, set_loading_tests) = signal(true);
     let (total_tests, set_total_tests) = signal(0);
     let (today_tests, set_today_tests) = signal(0);
     let (total_patients, set_total_patients) = signal(0);
+    let (pending_orders, set_pending_orders) = signal(0);
 
     let on_begin_test = move |_| {
         on_navigate.set(Page::PatientForm);
     };
 
+    let on_open_worklist = move |_| {
+        on_navigate.set(Page::Worklist);
+    };
+
     let on_view_history = move |_| {
         on_navigate.set(Page::History);
     };
 
+    let on_quality_control = move |_| {
+        on_navigate.set(Page::QualityControl);
+    };
+
     let on_scientific_view = move |_: leptos::ev::MouseEvent| {
         on_navigate.set(Page::ScientificView);
     };
 
+    // Orders waiting on the worklist
+    spawn_local(async move {
+        if let Ok(result) = invoke("get_worklist", JsValue::NULL).await {
+            if let Ok(orders) = serde_wasm_bindgen::from_value::<Vec<TestWithPatient>>(result) {
+                set_pending_orders.set(orders.len());
+            }
+        }
+    });
+
     // Load recent tests and statistics on component mount
     spawn_local(async move {
         set_loading_tests.set(true);
@@ -116,6 +138,13 @@ pub fn LandingPage(on_navigate: WriteSignal<Page>) -> impl IntoView {
                     >
                         "Begin Test"
                     </button>
+                    <button
+                        class="button"
+                        on:click=on_open_worklist
+                        style="padding: 0.75rem 2rem; font-size: 1rem; font-weight: 400;"
+                    >
+                        {move || format!("Worklist ({})", pending_orders.get())}
+                    </button>
                     <button
                         class="button"
                         on:click=on_scientific_view
@@ -123,9 +152,18 @@ pub fn LandingPage(on_navigate: WriteSignal<Page>) -> impl IntoView {
                     >
                         "Scientific View"
                     </button>
+                    <button
+                        class="button"
+                        on:click=on_quality_control
+                        style="padding: 0.75rem 2rem; font-size: 1rem; font-weight: 400;"
+                    >
+                        "Quality Control"
+                    </button>
                 </div>
             </div>
 
+            <DeviceStatusBoard statuses=device_statuses />
+
             <div style="display: grid; grid-template-columns: repeat(auto-fit, minmax(250px, 1fr)); gap: 1.5rem; margin-bottom: 2rem;">
                 <div class="card stat-card">
                     <div style="text-align: center; padding: 1rem;">
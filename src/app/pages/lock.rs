@@ -0,0 +1,131 @@
+use crate::app::errors::{from_invoke, invoke_error};
+use leptos::prelude::*;
+use leptos::task::spawn_local;
+use serde::Serialize;
+use shared_types::{ErrorCode, User};
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(catch, js_namespace = ["window", "__TAURI__", "core"])]
+    async fn invoke(cmd: &str, args: JsValue) -> Result<JsValue, JsValue>;
+}
+
+#[derive(Serialize)]
+struct UnlockSessionArgs {
+    secret: String,
+}
+
+/// Shown in place of every page while the session is locked
+///
+/// Only the signed-in operator can unlock; anyone else signs them out first.
+#[component]
+pub fn LockPage(
+    current_user: ReadSignal<Option<User>>,
+    on_unlocked: impl Fn(User) + Copy + Send + Sync + 'static,
+    on_signed_out: impl Fn() + Copy + Send + Sync + 'static,
+) -> impl IntoView {
+    let (secret, set_secret) = signal(String::new());
+    let (busy, set_busy) = signal(false);
+    let (error, set_error) = signal(None::<String>);
+
+    let has_pin = move || current_user.with(|user| user.as_ref().is_some_and(|u| u.has_pin));
+
+    let on_submit = move || {
+        let args = UnlockSessionArgs {
+            secret: secret.get(),
+        };
+        spawn_local(async move {
+            set_busy.set(true);
+            set_error.set(None);
+            match invoke(
+                "unlock_session",
+                serde_wasm_bindgen::to_value(&args).unwrap(),
+            )
+            .await
+            {
+                Ok(value) => match serde_wasm_bindgen::from_value::<User>(value) {
+                    Ok(user) => {
+                        set_secret.set(String::new());
+                        on_unlocked(user);
+                    }
+                    Err(e) => set_error.set(Some(format!("Failed to parse account: {:?}", e))),
+                },
+                Err(e) => {
+                    set_secret.set(String::new());
+                    // Too many attempts, or the account was deactivated meanwhile
+                    if from_invoke(e.clone()).code == ErrorCode::Unauthorized {
+                        invoke_error("Session ended", e);
+                        on_signed_out();
+                    } else {
+                        set_error.set(Some(invoke_error("Failed to unlock", e)));
+                    }
+                }
+            }
+            set_busy.set(false);
+        });
+    };
+
+    let switch_user = move |_| {
+        spawn_local(async move {
+            if let Err(e) = invoke("logout", JsValue::NULL).await {
+                invoke_error("Failed to sign out", e);
+            }
+            on_signed_out();
+        });
+    };
+
+    view! {
+        <div class="animate-fade-in">
+            <header style="margin-bottom: 2rem; text-align: center;">
+                <h1 style="margin: 0; font-size: 2rem; font-weight: 300;">"Session Locked"</h1>
+                <p style="color: var(--color-text-secondary); margin: 0.5rem 0 0;">
+                    {move || current_user.with(|user| match user {
+                        Some(user) => format!("Locked by {} ({})", user.display_name, user.username),
+                        None => String::new(),
+                    })}
+                </p>
+            </header>
+
+            <div class="card" style="max-width: 480px; margin: 0 auto;">
+                <form on:submit=move |e| {
+                    e.prevent_default();
+                    on_submit();
+                }>
+                    <div class="form-group">
+                        <label class="form-label">
+                            {move || if has_pin() { "PIN or password" } else { "Password" }}
+                        </label>
+                        <input
+                            type="password"
+                            class="form-input"
+                            autofocus
+                            autocomplete="current-password"
+                            prop:value=move || secret.get()
+                            on:input=move |e| set_secret.set(event_target_value(&e))
+                        />
+                    </div>
+
+                    {move || error.get().map(|err| view! {
+                        <div style="padding: 1rem; background-color: rgba(239, 68, 68, 0.1); border: 1px solid rgba(239, 68, 68, 0.3); border-radius: 8px; color: rgb(239, 68, 68); margin-bottom: 1rem;">
+                            {err}
+                        </div>
+                    })}
+
+                    <div style="display: flex; justify-content: space-between; gap: 1rem;">
+                        <button type="button" class="button" on:click=switch_user>
+                            "Sign in as someone else"
+                        </button>
+                        <button
+                            type="submit"
+                            class="button"
+                            disabled=move || busy.get() || secret.get().is_empty()
+                        >
+                            {move || if busy.get() { "Please wait..." } else { "Unlock" }}
+                        </button>
+                    </div>
+                </form>
+            </div>
+        </div>
+    }
+}
@@ -0,0 +1,189 @@
+use crate::app::errors::invoke_error;
+use leptos::prelude::*;
+use leptos::task::spawn_local;
+use serde::Serialize;
+use shared_types::{Role, User};
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(catch, js_namespace = ["window", "__TAURI__", "core"])]
+    async fn invoke(cmd: &str, args: JsValue) -> Result<JsValue, JsValue>;
+}
+
+#[derive(Serialize)]
+struct LoginArgs {
+    username: String,
+    password: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CreateInitialAdminArgs {
+    user_data: CreateUserRequest,
+}
+
+#[derive(Serialize)]
+struct CreateUserRequest {
+    username: String,
+    display_name: String,
+    role: Role,
+    password: String,
+}
+
+/// Sign-in screen; on first run it creates the administrator account instead
+#[component]
+pub fn LoginPage(
+    needs_setup: bool,
+    on_signed_in: impl Fn(User) + Copy + Send + Sync + 'static,
+) -> impl IntoView {
+    let (username, set_username) = signal(String::new());
+    let (display_name, set_display_name) = signal(String::new());
+    let (password, set_password) = signal(String::new());
+    let (confirm, set_confirm) = signal(String::new());
+    let (busy, set_busy) = signal(false);
+    let (error, set_error) = signal(None::<String>);
+
+    let on_submit = move || {
+        if needs_setup && password.get() != confirm.get() {
+            set_error.set(Some("Passwords do not match".to_string()));
+            return;
+        }
+        let (command, args, action) = if needs_setup {
+            let args = CreateInitialAdminArgs {
+                user_data: CreateUserRequest {
+                    username: username.get(),
+                    display_name: display_name.get(),
+                    role: Role::Admin,
+                    password: password.get(),
+                },
+            };
+            (
+                "create_initial_admin",
+                serde_wasm_bindgen::to_value(&args).unwrap(),
+                "Failed to create administrator",
+            )
+        } else {
+            let args = LoginArgs {
+                username: username.get(),
+                password: password.get(),
+            };
+            (
+                "login",
+                serde_wasm_bindgen::to_value(&args).unwrap(),
+                "Failed to sign in",
+            )
+        };
+        spawn_local(async move {
+            set_busy.set(true);
+            set_error.set(None);
+            match invoke(command, args).await {
+                Ok(value) => match serde_wasm_bindgen::from_value::<User>(value) {
+                    Ok(user) => {
+                        set_password.set(String::new());
+                        set_confirm.set(String::new());
+                        on_signed_in(user);
+                    }
+                    Err(e) => set_error.set(Some(format!("Failed to parse account: {:?}", e))),
+                },
+                Err(e) => {
+                    set_password.set(String::new());
+                    set_error.set(Some(invoke_error(action, e)));
+                }
+            }
+            set_busy.set(false);
+        });
+    };
+
+    view! {
+        <div class="animate-fade-in">
+            <header style="margin-bottom: 2rem; text-align: center;">
+                <h1 style="margin: 0; font-size: 2rem; font-weight: 300;">
+                    {if needs_setup { "Create Administrator" } else { "Sign In" }}
+                </h1>
+                <p style="color: var(--color-text-secondary); margin: 0.5rem 0 0;">
+                    {if needs_setup {
+                        "No accounts exist yet; this account will manage users, devices and settings"
+                    } else {
+                        "Sign in with your operator account"
+                    }}
+                </p>
+            </header>
+
+            <div class="card" style="max-width: 480px; margin: 0 auto;">
+                <form on:submit=move |e| {
+                    e.prevent_default();
+                    on_submit();
+                }>
+                    <div class="form-group">
+                        <label class="form-label">"Username"</label>
+                        <input
+                            type="text"
+                            class="form-input"
+                            autofocus
+                            autocomplete="username"
+                            prop:value=move || username.get()
+                            on:input=move |e| set_username.set(event_target_value(&e))
+                        />
+                    </div>
+
+                    {needs_setup.then(|| view! {
+                        <div class="form-group">
+                            <label class="form-label">"Display name"</label>
+                            <input
+                                type="text"
+                                class="form-input"
+                                prop:value=move || display_name.get()
+                                on:input=move |e| set_display_name.set(event_target_value(&e))
+                            />
+                        </div>
+                    })}
+
+                    <div class="form-group">
+                        <label class="form-label">"Password"</label>
+                        <input
+                            type="password"
+                            class="form-input"
+                            autocomplete=if needs_setup { "new-password" } else { "current-password" }
+                            prop:value=move || password.get()
+                            on:input=move |e| set_password.set(event_target_value(&e))
+                        />
+                    </div>
+
+                    {needs_setup.then(|| view! {
+                        <div class="form-group">
+                            <label class="form-label">"Confirm password"</label>
+                            <input
+                                type="password"
+                                class="form-input"
+                                autocomplete="new-password"
+                                prop:value=move || confirm.get()
+                                on:input=move |e| set_confirm.set(event_target_value(&e))
+                            />
+                        </div>
+                    })}
+
+                    {move || error.get().map(|err| view! {
+                        <div style="padding: 1rem; background-color: rgba(239, 68, 68, 0.1); border: 1px solid rgba(239, 68, 68, 0.3); border-radius: 8px; color: rgb(239, 68, 68); margin-bottom: 1rem;">
+                            {err}
+                        </div>
+                    })}
+
+                    <div style="display: flex; justify-content: flex-end;">
+                        <button
+                            type="submit"
+                            class="button"
+                            disabled=move || busy.get() || username.get().trim().is_empty() || password.get().is_empty()
+                        >
+                            {move || match (busy.get(), needs_setup) {
+                                (true, _) => "Please wait...",
+                                (false, true) => "Create Account",
+                                (false, false) => "Sign In",
+                            }}
+                        </button>
+                    </div>
+                </form>
+            </div>
+        </div>
+    }
+}
@@ -1,15 +1,25 @@
 pub mod detections;
 pub mod landing;
+pub mod lock;
+pub mod login;
 pub mod patient_form;
+pub mod quality_control;
 pub mod scientific_view;
 pub mod settings;
 pub mod test_reading;
 pub mod test_results;
+pub mod unlock;
+pub mod worklist;
 
 pub use detections::DetectionsPage;
 pub use landing::LandingPage;
+pub use lock::LockPage;
+pub use login::LoginPage;
 pub use patient_form::PatientFormPage;
+pub use quality_control::QualityControlPage;
 pub use scientific_view::ScientificViewPage;
 pub use settings::SettingsPage;
 pub use test_reading::TestReadingPage;
 pub use test_results::TestResultsPage;
+pub use unlock::UnlockPage;
+pub use worklist::WorklistPage;
@@ -1,8 +1,13 @@
+use crate::app::errors::invoke_error;
+use crate::app::scanner::on_barcode_scan;
 use crate::app::Page;
 use leptos::prelude::*;
 use serde::{Deserialize, Serialize};
 use wasm_bindgen::prelude::*;
 
+// Use shared types
+use shared_types::{Device, TestStatus, TestWithPatient};
+
 #[wasm_bindgen]
 extern "C" {
     #[wasm_bindgen(catch, js_namespace = ["window", "__TAURI__", "core"])]
@@ -50,10 +55,40 @@ struct CreateTestRequest {
     firmware_version: Option<String>,
 }
 
+#[derive(Serialize)]
+struct LookupBarcodeArgs {
+    code: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct StartOrderArgs {
+    test_uuid: String,
+    device_id: String,
+}
+
+/// What a scanned barcode identifies
+#[derive(Deserialize, Clone)]
+struct ScanMatch {
+    code: String,
+    test: Option<TestWithPatient>,
+    patient: Option<shared_types::Patient>,
+}
+
+impl ScanMatch {
+    /// The scanned order, if it is still waiting to be run
+    fn pending_order(&self) -> Option<&TestWithPatient> {
+        self.test
+            .as_ref()
+            .filter(|t| t.test.status == TestStatus::Pending.as_str())
+    }
+}
+
 #[component]
 pub fn PatientFormPage(
     on_navigate: WriteSignal<Page>,
     set_current_test_uuid: WriteSignal<Option<String>>,
+    set_current_device_id: WriteSignal<Option<String>>,
 ) -> impl IntoView {
     // Form state
     let (first_name, set_first_name) = signal(String::new());
@@ -64,9 +99,82 @@ pub fn PatientFormPage(
     let (phone, set_phone) = signal(String::new());
     let (notes, set_notes) = signal(String::new());
     let (test_type, set_test_type) = signal(String::from("covid-19"));
+    let (devices, set_devices) = signal(Vec::<Device>::new());
+    let (device_id, set_device_id) = signal(String::new());
 
     let (submitting, set_submitting) = signal(false);
     let (error, set_error) = signal(None::<String>);
+    // Last barcode scanned; a matching patient or order is used instead of a new one
+    let (scanned, set_scanned) = signal(None::<ScanMatch>);
+    let patient_linked = move || scanned.with(|s| s.as_ref().is_some_and(|s| s.patient.is_some()));
+    let order_linked =
+        move || scanned.with(|s| s.as_ref().is_some_and(|s| s.pending_order().is_some()));
+
+    let fill_patient = move |patient: Option<&shared_types::Patient>| {
+        let text = |value: Option<&String>| value.cloned().unwrap_or_default();
+        set_first_name.set(text(patient.map(|p| &p.first_name)));
+        set_last_name.set(text(patient.map(|p| &p.last_name)));
+        set_date_of_birth.set(text(patient.and_then(|p| p.date_of_birth.as_ref())));
+        set_patient_id_number.set(text(patient.and_then(|p| p.patient_id_number.as_ref())));
+        set_email.set(text(patient.and_then(|p| p.email.as_ref())));
+        set_phone.set(text(patient.and_then(|p| p.phone.as_ref())));
+    };
+
+    on_barcode_scan(move |code| {
+        leptos::task::spawn_local(async move {
+            let args = LookupBarcodeArgs { code: code.clone() };
+            match invoke(
+                "lookup_barcode",
+                serde_wasm_bindgen::to_value(&args).unwrap(),
+            )
+            .await
+            {
+                Ok(value) => match serde_wasm_bindgen::from_value::<ScanMatch>(value) {
+                    Ok(found) => {
+                        set_error.set(None);
+                        if found.patient.is_some() {
+                            fill_patient(found.patient.as_ref());
+                        } else if patient_linked() {
+                            // Unknown codes must not stay attached to the previous patient
+                            fill_patient(None);
+                            set_patient_id_number.set(found.code.clone());
+                        } else if patient_id_number.get_untracked().is_empty() {
+                            set_patient_id_number.set(found.code.clone());
+                        }
+                        set_scanned.set(Some(found));
+                    }
+                    Err(e) => set_error.set(Some(format!("Failed to parse scan: {:?}", e))),
+                },
+                Err(e) => set_error.set(Some(invoke_error("Failed to look up barcode", e))),
+            }
+        });
+    });
+
+    let clear_scan = move |_| {
+        if patient_linked() {
+            fill_patient(None);
+        }
+        set_scanned.set(None);
+    };
+
+    // Load the readers a test can be routed to
+    leptos::task::spawn_local(async move {
+        match invoke("list_devices", JsValue::NULL).await {
+            Ok(value) => {
+                if let Ok(device_list) = serde_wasm_bindgen::from_value::<Vec<Device>>(value) {
+                    let enabled: Vec<Device> =
+                        device_list.into_iter().filter(|d| d.enabled).collect();
+                    if let Some(first) = enabled.first() {
+                        set_device_id.set(first.device_id.clone());
+                    }
+                    set_devices.set(enabled);
+                }
+            }
+            Err(e) => {
+                invoke_error("Failed to list devices", e);
+            }
+        }
+    });
 
     // Handle form submission
     let on_submit = move |_| {
@@ -78,130 +186,165 @@ pub fn PatientFormPage(
         let ph = phone.get();
         let nt = notes.get();
         let tt = test_type.get();
+        let device = device_id.get();
+        let scan = scanned.get();
+        let order_uuid = scan
+            .as_ref()
+            .and_then(|s| s.pending_order())
+            .map(|t| t.test.uuid.clone());
+        let known_patient_uuid = scan.and_then(|s| s.patient).map(|p| p.uuid);
 
         leptos::task::spawn_local(async move {
             use leptos::web_sys::console;
 
+            if device.is_empty() {
+                set_error.set(Some("Select a device to run the test on".to_string()));
+                return;
+            }
+
             set_submitting.set(true);
             set_error.set(None);
 
-            // Create patient
-            let patient_request = CreatePatientRequest {
-                first_name: first.clone(),
-                last_name: last.clone(),
-                date_of_birth: if dob.is_empty() {
-                    None
-                } else {
-                    Some(dob.clone())
-                },
-                patient_id_number: if pid.is_empty() {
-                    None
-                } else {
-                    Some(pid.clone())
-                },
-                email: if em.is_empty() {
-                    None
-                } else {
-                    Some(em.clone())
-                },
-                phone: if ph.is_empty() {
-                    None
-                } else {
-                    Some(ph.clone())
-                },
-                notes: if nt.is_empty() {
-                    None
-                } else {
-                    Some(nt.clone())
-                },
-            };
-
-            console::log_1(&JsValue::from_str("Creating patient..."));
-
-            let args = CreatePatientArgs {
-                patient_data: patient_request,
-            };
+            // A scanned order already has its patient and test; it only needs a device
+            if let Some(test_uuid) = order_uuid {
+                let args = StartOrderArgs {
+                    test_uuid: test_uuid.clone(),
+                    device_id: device.clone(),
+                };
+                match invoke("start_order", serde_wasm_bindgen::to_value(&args).unwrap()).await {
+                    Ok(_) => {
+                        set_current_device_id.set(Some(device));
+                        set_current_test_uuid.set(Some(test_uuid));
+                        set_submitting.set(false);
+                        on_navigate.set(Page::TestReading);
+                    }
+                    Err(e) => {
+                        set_error.set(Some(invoke_error("Failed to start order", e)));
+                        set_submitting.set(false);
+                    }
+                }
+                return;
+            }
 
-            match invoke(
-                "create_patient",
-                serde_wasm_bindgen::to_value(&args).unwrap(),
-            )
-            .await
-            {
-                Ok(patient_result) => {
+            let patient_uuid = match known_patient_uuid {
+                Some(uuid) => uuid,
+                None => {
+                    // Create patient
+                    let patient_request = CreatePatientRequest {
+                        first_name: first.clone(),
+                        last_name: last.clone(),
+                        date_of_birth: if dob.is_empty() {
+                            None
+                        } else {
+                            Some(dob.clone())
+                        },
+                        patient_id_number: if pid.is_empty() {
+                            None
+                        } else {
+                            Some(pid.clone())
+                        },
+                        email: if em.is_empty() {
+                            None
+                        } else {
+                            Some(em.clone())
+                        },
+                        phone: if ph.is_empty() {
+                            None
+                        } else {
+                            Some(ph.clone())
+                        },
+                        notes: if nt.is_empty() {
+                            None
+                        } else {
+                            Some(nt.clone())
+                        },
+                    };
+
+                    console::log_1(&JsValue::from_str("Creating patient..."));
+
+                    let args = CreatePatientArgs {
+                        patient_data: patient_request,
+                    };
+
+                    let patient_result = match invoke(
+                        "create_patient",
+                        serde_wasm_bindgen::to_value(&args).unwrap(),
+                    )
+                    .await
+                    {
+                        Ok(patient_result) => patient_result,
+                        Err(e) => {
+                            set_error.set(Some(invoke_error("Failed to create patient", e)));
+                            set_submitting.set(false);
+                            return;
+                        }
+                    };
                     console::log_1(&JsValue::from_str("Patient created successfully"));
 
                     // Parse patient to get UUID
                     match serde_wasm_bindgen::from_value::<Patient>(patient_result) {
-                        Ok(patient) => {
-                            console::log_1(&JsValue::from_str(&format!(
-                                "Patient UUID: {}",
-                                patient.uuid
-                            )));
-
-                            // Create test
-                            let test_request = CreateTestRequest {
-                                patient_uuid: patient.uuid,
-                                test_type: tt.clone(),
-                                device_id: None,
-                                firmware_version: None,
-                            };
-
-                            console::log_1(&JsValue::from_str("Creating test..."));
-
-                            let test_args = CreateTestArgs {
-                                test_data: test_request,
-                            };
-
-                            match invoke(
-                                "create_test",
-                                serde_wasm_bindgen::to_value(&test_args).unwrap(),
-                            )
-                            .await
-                            {
-                                Ok(result) => {
-                                    // Extract the test UUID from the result
-                                    if let Ok(test_uuid) =
-                                        js_sys::Reflect::get(&result, &JsValue::from_str("uuid"))
-                                    {
-                                        // Try as string first, fallback to JSON stringify for Uuid objects
-                                        let uuid_str = if let Some(s) = test_uuid.as_string() {
-                                            Some(s)
-                                        } else {
-                                            js_sys::JSON::stringify(&test_uuid)
-                                                .ok()
-                                                .and_then(|s| s.as_string())
-                                                .map(|s| s.trim_matches('"').to_string())
-                                        };
-
-                                        if let Some(uuid_str) = uuid_str {
-                                            set_current_test_uuid.set(Some(uuid_str));
-                                        }
-                                    }
-
-                                    set_submitting.set(false);
-                                    on_navigate.set(Page::TestReading);
-                                }
-                                Err(e) => {
-                                    let error_msg = format!("Failed to create test: {:?}", e);
-                                    console::log_1(&JsValue::from_str(&error_msg));
-                                    set_error.set(Some(error_msg));
-                                    set_submitting.set(false);
-                                }
-                            }
-                        }
+                        Ok(patient) => patient.uuid,
                         Err(e) => {
                             let error_msg = format!("Failed to parse patient: {:?}", e);
                             console::log_1(&JsValue::from_str(&error_msg));
                             set_error.set(Some(error_msg));
                             set_submitting.set(false);
+                            return;
+                        }
+                    }
+                }
+            };
+
+            console::log_1(&JsValue::from_str(&format!(
+                "Patient UUID: {}",
+                patient_uuid
+            )));
+
+            // Create test
+            let test_request = CreateTestRequest {
+                patient_uuid,
+                test_type: tt.clone(),
+                device_id: Some(device.clone()),
+                firmware_version: None,
+            };
+
+            console::log_1(&JsValue::from_str("Creating test..."));
+
+            let test_args = CreateTestArgs {
+                test_data: test_request,
+            };
+
+            match invoke(
+                "create_test",
+                serde_wasm_bindgen::to_value(&test_args).unwrap(),
+            )
+            .await
+            {
+                Ok(result) => {
+                    // Extract the test UUID from the result
+                    if let Ok(test_uuid) = js_sys::Reflect::get(&result, &JsValue::from_str("uuid"))
+                    {
+                        // Try as string first, fallback to JSON stringify for Uuid objects
+                        let uuid_str = if let Some(s) = test_uuid.as_string() {
+                            Some(s)
+                        } else {
+                            js_sys::JSON::stringify(&test_uuid)
+                                .ok()
+                                .and_then(|s| s.as_string())
+                                .map(|s| s.trim_matches('"').to_string())
+                        };
+
+                        if let Some(uuid_str) = uuid_str {
+                            set_current_device_id.set(Some(device.clone()));
+                            set_current_test_uuid.set(Some(uuid_str));
                         }
                     }
+
+                    set_submitting.set(false);
+                    on_navigate.set(Page::TestReading);
                 }
                 Err(e) => {
-                    let error_msg = format!("Failed to create patient: {:?}", e);
-                    console::log_1(&JsValue::from_str(&error_msg));
-                    set_error.set(Some(error_msg));
+                    set_error.set(Some(invoke_error("Failed to create test", e)));
                     set_submitting.set(false);
                 }
             }
@@ -230,7 +373,7 @@ pub fn PatientFormPage(
                     <div style="width: 100px;"></div> // Spacer for centering
                 </div>
                 <p style="text-align: center; color: var(--color-text-secondary); margin: 0;">
-                    "Enter patient details and select test type"
+                    "Enter patient details and select test type, or scan a sample or patient barcode"
                 </p>
             </header>
 
@@ -240,6 +383,41 @@ pub fn PatientFormPage(
                     e.prevent_default();
                     on_submit(());
                 }>
+                    // Scanned barcode
+                    {move || scanned.get().map(|found| {
+                        let text = match (found.pending_order(), &found.test, &found.patient) {
+                            (Some(order), _, _) => format!(
+                                "Scanned {}: pending {} order for {} {}. Starting runs this order on the selected device.",
+                                found.code, order.test.test_type, order.patient.first_name, order.patient.last_name
+                            ),
+                            (None, Some(test), Some(patient)) => format!(
+                                "Scanned {}: that {} test is {}, so a new test will be added for {} {}.",
+                                found.code, test.test.test_type, test.test.status, patient.first_name, patient.last_name
+                            ),
+                            (None, _, Some(patient)) => format!(
+                                "Scanned {}: registered patient {} {}. The new test will be added to their record.",
+                                found.code, patient.first_name, patient.last_name
+                            ),
+                            (None, _, None) => format!(
+                                "No test or patient matches {}; it was entered as the patient ID number.",
+                                found.code
+                            ),
+                        };
+                        view! {
+                            <div style="display: flex; justify-content: space-between; align-items: center; gap: 1rem; margin-bottom: 1.5rem; padding: 0.75rem; background: #d4edda; border: 1px solid #c3e6cb; border-radius: 6px; color: #155724; font-size: 0.875rem;">
+                                <span>{text}</span>
+                                <button
+                                    type="button"
+                                    class="button"
+                                    on:click=clear_scan
+                                    style="padding: 0.25rem 0.75rem; font-size: 0.875rem;"
+                                >
+                                    "Clear"
+                                </button>
+                            </div>
+                        }
+                    })}
+
                     // Patient Information Section
                     <div style="margin-bottom: 2rem;">
                         <h2 style="font-size: 1.25rem; font-weight: 500; margin-bottom: 1.5rem; color: var(--color-text-secondary); border-bottom: 1px solid var(--color-border-light); padding-bottom: 0.5rem;">
@@ -259,6 +437,7 @@ pub fn PatientFormPage(
                                     placeholder="Enter first name"
                                     required
                                     prop:value=move || first_name.get()
+                                    disabled=patient_linked
                                     on:input=move |e| set_first_name.set(event_target_value(&e))
                                 />
                             </div>
@@ -275,6 +454,7 @@ pub fn PatientFormPage(
                                     placeholder="Enter last name"
                                     required
                                     prop:value=move || last_name.get()
+                                    disabled=patient_linked
                                     on:input=move |e| set_last_name.set(event_target_value(&e))
                                 />
                             </div>
@@ -288,6 +468,7 @@ pub fn PatientFormPage(
                                     type="date"
                                     class="form-input"
                                     prop:value=move || date_of_birth.get()
+                                    disabled=patient_linked
                                     on:input=move |e| set_date_of_birth.set(event_target_value(&e))
                                 />
                             </div>
@@ -302,6 +483,7 @@ pub fn PatientFormPage(
                                     class="form-input"
                                     placeholder="Optional ID number"
                                     prop:value=move || patient_id_number.get()
+                                    disabled=patient_linked
                                     on:input=move |e| set_patient_id_number.set(event_target_value(&e))
                                 />
                             </div>
@@ -316,6 +498,7 @@ pub fn PatientFormPage(
                                     class="form-input"
                                     placeholder="patient@example.com"
                                     prop:value=move || email.get()
+                                    disabled=patient_linked
                                     on:input=move |e| set_email.set(event_target_value(&e))
                                 />
                             </div>
@@ -330,6 +513,7 @@ pub fn PatientFormPage(
                                     class="form-input"
                                     placeholder="(555) 123-4567"
                                     prop:value=move || phone.get()
+                                    disabled=patient_linked
                                     on:input=move |e| set_phone.set(event_target_value(&e))
                                 />
                             </div>
@@ -345,6 +529,7 @@ pub fn PatientFormPage(
                                 placeholder="Additional notes or observations..."
                                 rows="3"
                                 prop:value=move || notes.get()
+                                disabled=patient_linked
                                 on:input=move |e| set_notes.set(event_target_value(&e))
                                 style="resize: vertical; font-family: inherit;"
                             ></textarea>
@@ -366,6 +551,7 @@ pub fn PatientFormPage(
                                 class="form-input"
                                 required
                                 prop:value=move || test_type.get()
+                                disabled=order_linked
                                 on:change=move |e| set_test_type.set(event_target_value(&e))
                             >
                                 <option value="covid-19">"COVID-19"</option>
@@ -377,6 +563,35 @@ pub fn PatientFormPage(
                                 <option value="hepatitis">"Hepatitis"</option>
                             </select>
                         </div>
+
+                        <div class="form-group" style="margin-top: 1.5rem;">
+                            <label class="form-label">
+                                "Device "
+                                <span style="color: var(--color-error);">"*"</span>
+                            </label>
+                            <select
+                                class="form-input"
+                                required
+                                prop:value=move || device_id.get()
+                                on:change=move |e| set_device_id.set(event_target_value(&e))
+                            >
+                                {move || {
+                                    let device_list = devices.get();
+                                    if device_list.is_empty() {
+                                        vec![view! {
+                                            <option value="">"No devices configured"</option>
+                                        }.into_any()]
+                                    } else {
+                                        device_list.into_iter().map(|device| {
+                                            let text = format!("{} ({})", device.label, device.port);
+                                            view! {
+                                                <option value=device.device_id>{text}</option>
+                                            }.into_any()
+                                        }).collect::<Vec<_>>()
+                                    }
+                                }}
+                            </select>
+                        </div>
                     </div>
 
                     // Error Message
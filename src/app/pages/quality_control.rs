@@ -0,0 +1,1072 @@
+use crate::app::errors::invoke_error;
+use crate::app::serial::DetectionData;
+use crate::app::Page;
+use leptos::prelude::*;
+use leptos::task::spawn_local;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+// Use shared types
+use shared_types::{Device, Permission, Role, TestType, User};
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(catch, js_namespace = ["window", "__TAURI__", "core"])]
+    async fn invoke(cmd: &str, args: JsValue) -> Result<JsValue, JsValue>;
+}
+
+/// A lot of positive or negative control material
+#[derive(Deserialize, Clone)]
+struct ControlMaterial {
+    id: i64,
+    name: String,
+    level: String,
+    test_type: String,
+    lot_number: String,
+    expires_on: String,
+    expected_min: f64,
+    expected_max: f64,
+    active: bool,
+}
+
+/// One measurement of control material on a device
+#[derive(Deserialize, Clone)]
+struct QcRun {
+    uuid: String,
+    device_id: String,
+    level: String,
+    lot_number: String,
+    status: String,
+    value: Option<f64>,
+    violations: Vec<String>,
+    operated_by: Option<String>,
+    created_at: String,
+    completed_at: Option<String>,
+}
+
+/// QC standing of one device for one test type
+#[derive(Deserialize, Clone)]
+struct DeviceQc {
+    device_id: String,
+    test_type: String,
+    state: String,
+    blocked: bool,
+    reason: Option<String>,
+    last_runs: Vec<QcRun>,
+}
+
+/// Runs of a lot on a device with the chart limits
+#[derive(Deserialize, Clone)]
+struct LeveyJennings {
+    material: ControlMaterial,
+    mean: f64,
+    sd: f64,
+    runs: Vec<QcRun>,
+}
+
+#[derive(Serialize)]
+struct SaveControlMaterialRequest {
+    id: Option<i64>,
+    name: String,
+    level: String,
+    test_type: String,
+    lot_number: String,
+    expires_on: String,
+    expected_min: f64,
+    expected_max: f64,
+    active: bool,
+}
+
+#[derive(Serialize)]
+struct SaveControlMaterialArgs {
+    material: SaveControlMaterialRequest,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct StartQcRunArgs {
+    device_id: String,
+    material_id: i64,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct QcRunArgs {
+    run_uuid: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct LeveyJenningsArgs {
+    material_id: i64,
+    device_id: String,
+}
+
+/// Control material form, with the range kept as typed until it is saved
+#[derive(Clone)]
+struct MaterialForm {
+    id: Option<i64>,
+    name: String,
+    level: String,
+    test_type: String,
+    lot_number: String,
+    expires_on: String,
+    expected_min: String,
+    expected_max: String,
+    active: bool,
+}
+
+impl Default for MaterialForm {
+    fn default() -> Self {
+        Self {
+            id: None,
+            name: String::new(),
+            level: "positive".to_string(),
+            test_type: TestType::Covid19.as_str().to_string(),
+            lot_number: String::new(),
+            expires_on: String::new(),
+            expected_min: String::new(),
+            expected_max: String::new(),
+            active: true,
+        }
+    }
+}
+
+impl MaterialForm {
+    fn from_material(material: &ControlMaterial) -> Self {
+        Self {
+            id: Some(material.id),
+            name: material.name.clone(),
+            level: material.level.clone(),
+            test_type: material.test_type.clone(),
+            lot_number: material.lot_number.clone(),
+            expires_on: material.expires_on.clone(),
+            expected_min: material.expected_min.to_string(),
+            expected_max: material.expected_max.to_string(),
+            active: material.active,
+        }
+    }
+
+    fn to_request(&self) -> Result<SaveControlMaterialRequest, String> {
+        let parse = |label: &str, value: &str| {
+            value
+                .trim()
+                .parse::<f64>()
+                .map_err(|_| format!("{} must be a number", label))
+        };
+        Ok(SaveControlMaterialRequest {
+            id: self.id,
+            name: self.name.clone(),
+            level: self.level.clone(),
+            test_type: self.test_type.clone(),
+            lot_number: self.lot_number.clone(),
+            expires_on: self.expires_on.clone(),
+            expected_min: parse("Expected minimum", &self.expected_min)?,
+            expected_max: parse("Expected maximum", &self.expected_max)?,
+            active: self.active,
+        })
+    }
+}
+
+async fn fetch_materials() -> Result<Vec<ControlMaterial>, String> {
+    let value = invoke("list_control_materials", JsValue::NULL)
+        .await
+        .map_err(|e| invoke_error("Failed to load control materials", e))?;
+    serde_wasm_bindgen::from_value::<Vec<ControlMaterial>>(value)
+        .map_err(|e| format!("Failed to parse control materials: {:?}", e))
+}
+
+async fn fetch_qc_status() -> Result<Vec<DeviceQc>, String> {
+    let value = invoke("get_qc_status", JsValue::NULL)
+        .await
+        .map_err(|e| invoke_error("Failed to load QC status", e))?;
+    serde_wasm_bindgen::from_value::<Vec<DeviceQc>>(value)
+        .map_err(|e| format!("Failed to parse QC status: {:?}", e))
+}
+
+/// Today as `YYYY-MM-DD`, to compare with expiry dates
+fn today() -> String {
+    String::from(js_sys::Date::new_0().to_iso_string())
+        .chars()
+        .take(10)
+        .collect()
+}
+
+fn capitalize(s: &str) -> String {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// Text and background colours for a run or device state
+fn state_colors(state: &str) -> (&'static str, &'static str) {
+    match state {
+        "passed" | "valid" => ("var(--color-success)", "var(--color-success-bg)"),
+        "warning" | "missing" => ("var(--color-warning)", "var(--color-warning-bg)"),
+        "failed" | "expired" => ("var(--color-error)", "var(--color-error-bg)"),
+        _ => ("var(--color-text-secondary)", "var(--color-bg-tertiary)"),
+    }
+}
+
+fn badge(text: String, state: &str) -> impl IntoView {
+    let (color, background) = state_colors(state);
+    view! {
+        <span style=format!(
+            "padding: 0.125rem 0.5rem; border-radius: 999px; font-size: 0.75rem; font-weight: 500; color: {}; background: {};",
+            color, background
+        )>
+            {text}
+        </span>
+    }
+}
+
+#[component]
+pub fn QualityControlPage(
+    on_navigate: WriteSignal<Page>,
+    current_user: ReadSignal<Option<User>>,
+    current_test_uuid: ReadSignal<Option<String>>,
+    set_current_test_uuid: WriteSignal<Option<String>>,
+    set_current_device_id: WriteSignal<Option<String>>,
+    connected: Signal<bool>,
+    detection_loading: ReadSignal<bool>,
+    detection_result: ReadSignal<Option<DetectionData>>,
+    detection_error: ReadSignal<Option<String>>,
+) -> impl IntoView {
+    // Materials can only be changed by roles that manage settings; the backend enforces the same
+    let role = current_user
+        .get_untracked()
+        .map(|user| user.role)
+        .unwrap_or(Role::Operator);
+
+    let (materials, set_materials) = signal(Vec::<ControlMaterial>::new());
+    let (devices, set_devices) = signal(Vec::<Device>::new());
+    let (statuses, set_statuses) = signal(Vec::<DeviceQc>::new());
+    let (error, set_error) = signal(None::<String>);
+
+    // Running a control
+    let (run_device_id, set_run_device_id) = signal(String::new());
+    let (run_material_id, set_run_material_id) = signal(String::new());
+    let (active_run, set_active_run) = signal(None::<QcRun>);
+    let (finished_run, set_finished_run) = signal(None::<QcRun>);
+    let (busy, set_busy) = signal(false);
+
+    // Levey-Jennings chart
+    let (chart_material_id, set_chart_material_id) = signal(String::new());
+    let (chart_device_id, set_chart_device_id) = signal(String::new());
+    let (chart, set_chart) = signal(None::<LeveyJennings>);
+
+    // Control material form
+    let (form, set_form) = signal(MaterialForm::default());
+    let (form_message, set_form_message) = signal(None::<Result<String, String>>);
+
+    let load_materials = move || {
+        spawn_local(async move {
+            match fetch_materials().await {
+                Ok(list) => {
+                    if let Some(first) = list.iter().find(|m| m.active) {
+                        if run_material_id.get_untracked().is_empty() {
+                            set_run_material_id.set(first.id.to_string());
+                        }
+                        if chart_material_id.get_untracked().is_empty() {
+                            set_chart_material_id.set(first.id.to_string());
+                        }
+                    }
+                    set_materials.set(list);
+                }
+                Err(err) => set_error.set(Some(err)),
+            }
+        });
+    };
+
+    let load_statuses = move || {
+        spawn_local(async move {
+            match fetch_qc_status().await {
+                Ok(list) => set_statuses.set(list),
+                Err(err) => set_error.set(Some(err)),
+            }
+        });
+    };
+
+    let load_chart = move || {
+        let (Ok(material_id), device_id) = (
+            chart_material_id.get_untracked().parse::<i64>(),
+            chart_device_id.get_untracked(),
+        ) else {
+            set_chart.set(None);
+            return;
+        };
+        if device_id.is_empty() {
+            set_chart.set(None);
+            return;
+        }
+        let args = LeveyJenningsArgs {
+            material_id,
+            device_id,
+        };
+        spawn_local(async move {
+            match invoke(
+                "get_levey_jennings",
+                serde_wasm_bindgen::to_value(&args).unwrap(),
+            )
+            .await
+            {
+                Ok(value) => match serde_wasm_bindgen::from_value::<LeveyJennings>(value) {
+                    Ok(loaded) => set_chart.set(Some(loaded)),
+                    Err(e) => set_error.set(Some(format!("Failed to parse chart: {:?}", e))),
+                },
+                Err(e) => set_error.set(Some(invoke_error("Failed to load chart", e))),
+            }
+        });
+    };
+
+    let refresh = move || {
+        set_error.set(None);
+        load_materials();
+        load_statuses();
+        load_chart();
+    };
+
+    load_materials();
+    load_statuses();
+
+    // Load the readers controls can be run on
+    spawn_local(async move {
+        match invoke("list_devices", JsValue::NULL).await {
+            Ok(value) => {
+                if let Ok(device_list) = serde_wasm_bindgen::from_value::<Vec<Device>>(value) {
+                    let enabled: Vec<Device> =
+                        device_list.into_iter().filter(|d| d.enabled).collect();
+                    if let Some(first) = enabled.first() {
+                        set_run_device_id.set(first.device_id.clone());
+                        set_chart_device_id.set(first.device_id.clone());
+                    }
+                    set_devices.set(enabled);
+                }
+            }
+            Err(e) => {
+                invoke_error("Failed to list devices", e);
+            }
+        }
+    });
+
+    // Redraw the chart whenever a different lot or device is picked
+    Effect::new(move || {
+        chart_material_id.track();
+        chart_device_id.track();
+        load_chart();
+    });
+
+    let device_label = move |device_id: &str| {
+        devices
+            .with(|list| {
+                list.iter()
+                    .find(|d| d.device_id == device_id)
+                    .map(|d| d.label.clone())
+            })
+            .unwrap_or_else(|| device_id.to_string())
+    };
+
+    let start_run = move |_| {
+        let device = run_device_id.get();
+        let Ok(material_id) = run_material_id.get().parse::<i64>() else {
+            set_error.set(Some("Select the control material to run".to_string()));
+            return;
+        };
+        if device.is_empty() {
+            set_error.set(Some("Select a device to run the control on".to_string()));
+            return;
+        }
+        let args = StartQcRunArgs {
+            device_id: device.clone(),
+            material_id,
+        };
+        spawn_local(async move {
+            set_busy.set(true);
+            match invoke("start_qc_run", serde_wasm_bindgen::to_value(&args).unwrap()).await {
+                Ok(value) => match serde_wasm_bindgen::from_value::<QcRun>(value) {
+                    Ok(run) => {
+                        set_error.set(None);
+                        set_finished_run.set(None);
+                        set_current_device_id.set(Some(device));
+                        set_current_test_uuid.set(Some(run.uuid.clone()));
+                        set_active_run.set(Some(run));
+                    }
+                    Err(e) => set_error.set(Some(format!("Failed to parse QC run: {:?}", e))),
+                },
+                Err(e) => set_error.set(Some(invoke_error("Failed to start QC run", e))),
+            }
+            set_busy.set(false);
+        });
+    };
+
+    let cancel_run = move |_| {
+        let Some(run) = active_run.get() else {
+            return;
+        };
+        let args = QcRunArgs { run_uuid: run.uuid };
+        spawn_local(async move {
+            match invoke(
+                "cancel_qc_run",
+                serde_wasm_bindgen::to_value(&args).unwrap(),
+            )
+            .await
+            {
+                Ok(_) => {
+                    set_active_run.set(None);
+                    set_current_test_uuid.set(None);
+                }
+                Err(e) => set_error.set(Some(invoke_error("Failed to cancel QC run", e))),
+            }
+        });
+    };
+
+    // The backend checks the control's value before reporting it; show the outcome
+    Effect::new(move || {
+        if detection_result.with(|r| r.is_none()) {
+            return;
+        }
+        let Some(run) = active_run.get_untracked() else {
+            return;
+        };
+        if current_test_uuid.get_untracked().as_deref() != Some(run.uuid.as_str()) {
+            return;
+        }
+        set_active_run.set(None);
+        let args = QcRunArgs { run_uuid: run.uuid };
+        spawn_local(async move {
+            match invoke("get_qc_run", serde_wasm_bindgen::to_value(&args).unwrap()).await {
+                Ok(value) => match serde_wasm_bindgen::from_value::<QcRun>(value) {
+                    Ok(finished) => set_finished_run.set(Some(finished)),
+                    Err(e) => set_error.set(Some(format!("Failed to parse QC run: {:?}", e))),
+                },
+                Err(e) => set_error.set(Some(invoke_error("Failed to load QC result", e))),
+            }
+            set_current_test_uuid.set(None);
+            load_statuses();
+            load_chart();
+        });
+    });
+
+    let save_material = move |_| {
+        let request = match form.get().to_request() {
+            Ok(request) => request,
+            Err(err) => {
+                set_form_message.set(Some(Err(err)));
+                return;
+            }
+        };
+        let args = SaveControlMaterialArgs { material: request };
+        spawn_local(async move {
+            match invoke(
+                "save_control_material",
+                serde_wasm_bindgen::to_value(&args).unwrap(),
+            )
+            .await
+            {
+                Ok(value) => {
+                    if let Ok(saved) = serde_wasm_bindgen::from_value::<ControlMaterial>(value) {
+                        set_form_message.set(Some(Ok(format!(
+                            "Saved {} lot {}",
+                            saved.name, saved.lot_number
+                        ))));
+                        set_form.set(MaterialForm::default());
+                        load_materials();
+                        load_statuses();
+                        load_chart();
+                    }
+                }
+                Err(e) => set_form_message.set(Some(Err(invoke_error(
+                    "Failed to save control material",
+                    e,
+                )))),
+            }
+        });
+    };
+
+    let update_form = move |apply: fn(&mut MaterialForm, String), value: String| {
+        set_form.update(|form| apply(form, value));
+    };
+
+    let material_options = move |include_inactive: bool| {
+        let today = today();
+        materials
+            .get()
+            .into_iter()
+            .filter(|m| include_inactive || m.active)
+            .map(|m| {
+                let expired = m.expires_on < today;
+                let text = format!(
+                    "{} · {} · lot {}{}",
+                    m.name,
+                    capitalize(&m.level),
+                    m.lot_number,
+                    if expired { " (expired)" } else { "" }
+                );
+                view! { <option value=m.id.to_string()>{text}</option> }
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let device_options = move || {
+        devices
+            .get()
+            .into_iter()
+            .map(|device| {
+                let text = format!("{} ({})", device.label, device.port);
+                view! { <option value=device.device_id>{text}</option> }
+            })
+            .collect::<Vec<_>>()
+    };
+
+    view! {
+        <div class="animate-fade-in">
+            <header style="margin-bottom: 2rem;">
+                <div style="display: flex; align-items: center; justify-content: space-between; margin-bottom: 1rem;">
+                    <button
+                        class="button"
+                        on:click=move |_| on_navigate.set(Page::Landing)
+                        style="padding: 0.5rem 1rem;"
+                    >
+                        "← Back to Home"
+                    </button>
+                    <h1 style="margin: 0; font-size: 2rem; font-weight: 300;">
+                        "Quality Control"
+                    </h1>
+                    <button
+                        class="button"
+                        on:click=move |_| refresh()
+                        style="padding: 0.5rem 1rem;"
+                    >
+                        "Refresh"
+                    </button>
+                </div>
+                <p style="text-align: center; color: var(--color-text-secondary); margin: 0;">
+                    "Run positive and negative controls on each device; patient tests are refused on a device whose last QC failed or expired"
+                </p>
+            </header>
+
+            {move || error.get().map(|err| view! {
+                <div style="max-width: 900px; margin: 0 auto 1rem auto; padding: 1rem; background-color: rgba(239, 68, 68, 0.1); border: 1px solid rgba(239, 68, 68, 0.3); border-radius: 8px; color: rgb(239, 68, 68);">
+                    {err}
+                </div>
+            })}
+
+            <div class="card" style="max-width: 900px; margin: 0 auto 2rem auto;">
+                <h2 style="font-size: 1.25rem; font-weight: 500; margin: 0 0 1rem 0; color: var(--color-text-secondary);">
+                    "Device Status"
+                </h2>
+                {move || {
+                    let list = statuses.get();
+                    if list.is_empty() {
+                        return view! {
+                            <p style="margin: 0; color: var(--color-text-secondary); font-size: 0.875rem;">
+                                "No control material has been set up, so no device needs QC yet."
+                            </p>
+                        }.into_any();
+                    }
+                    view! {
+                        <div style="display: flex; flex-direction: column; gap: 0.75rem;">
+                            {list.into_iter().map(|status| {
+                                let runs = status.last_runs.iter().map(|run| {
+                                    let value = run.value.map(|v| format!("{:.3}", v)).unwrap_or_default();
+                                    let when = run.completed_at.clone().unwrap_or_default().chars().take(16).collect::<String>().replace('T', " ");
+                                    format!("{} {} {} at {}", capitalize(&run.level), value, run.status, when)
+                                }).collect::<Vec<_>>().join(" · ");
+                                let state_text = if status.blocked {
+                                    format!("{} · locked", capitalize(&status.state))
+                                } else {
+                                    capitalize(&status.state)
+                                };
+                                view! {
+                                    <div style="display: flex; justify-content: space-between; gap: 1rem; padding: 0.75rem; border: 1px solid var(--color-border-light); border-radius: 8px;">
+                                        <div>
+                                            <div style="font-weight: 500;">
+                                                {format!("{} · {}", device_label(&status.device_id), status.test_type)}
+                                            </div>
+                                            <div style="font-size: 0.8125rem; color: var(--color-text-secondary); margin-top: 0.25rem;">
+                                                {status.reason.clone().unwrap_or(runs)}
+                                            </div>
+                                        </div>
+                                        <div>{badge(state_text, &status.state)}</div>
+                                    </div>
+                                }
+                            }).collect::<Vec<_>>()}
+                        </div>
+                    }.into_any()
+                }}
+            </div>
+
+            <div class="card" style="max-width: 900px; margin: 0 auto 2rem auto;">
+                <h2 style="font-size: 1.25rem; font-weight: 500; margin: 0 0 1rem 0; color: var(--color-text-secondary);">
+                    "Run a Control"
+                </h2>
+                <div style="display: flex; gap: 1rem; align-items: flex-end; flex-wrap: wrap;">
+                    <div class="form-group" style="flex: 1; min-width: 14rem;">
+                        <label class="form-label">"Control material"</label>
+                        <select
+                            class="form-input"
+                            prop:value=move || run_material_id.get()
+                            on:change=move |e| set_run_material_id.set(event_target_value(&e))
+                            disabled=move || active_run.with(|r| r.is_some())
+                        >
+                            {move || material_options(false)}
+                        </select>
+                    </div>
+                    <div class="form-group" style="flex: 1; min-width: 12rem;">
+                        <label class="form-label">"Run on"</label>
+                        <select
+                            class="form-input"
+                            prop:value=move || run_device_id.get()
+                            on:change=move |e| set_run_device_id.set(event_target_value(&e))
+                            disabled=move || active_run.with(|r| r.is_some())
+                        >
+                            {device_options}
+                        </select>
+                    </div>
+                    <button
+                        class="button primary"
+                        on:click=start_run
+                        disabled=move || busy.get() || active_run.with(|r| r.is_some())
+                        style="padding: 0.75rem 2rem; font-weight: 500;"
+                    >
+                        "Start QC Run"
+                    </button>
+                </div>
+
+                {move || active_run.get().map(|run| {
+                    let device = device_label(&run.device_id);
+                    view! {
+                        <div style="display: flex; justify-content: space-between; align-items: center; gap: 1rem; margin-top: 1rem; padding: 0.75rem; background: var(--color-info-bg); border-radius: 6px; font-size: 0.875rem;">
+                            <div>
+                                {move || if let Some(err) = detection_error.get() {
+                                    format!("The reading failed: {}. Load the control again or cancel the run.", err)
+                                } else if detection_loading.get() {
+                                    "Analyzing the control...".to_string()
+                                } else if !connected.get() {
+                                    format!("{} is not connected", device)
+                                } else {
+                                    format!("Load the {} control, lot {}, into {}", run.level, run.lot_number, device)
+                                }}
+                            </div>
+                            <button
+                                class="button"
+                                on:click=cancel_run
+                                style="padding: 0.5rem 0.75rem; font-size: 0.875rem;"
+                            >
+                                "Cancel Run"
+                            </button>
+                        </div>
+                    }
+                })}
+
+                {move || finished_run.get().map(|run| {
+                    let (color, background) = state_colors(&run.status);
+                    let value = run.value.map(|v| format!("{:.3}", v)).unwrap_or_default();
+                    let rules = if run.violations.is_empty() {
+                        "no rules broken".to_string()
+                    } else {
+                        format!("broke {}", run.violations.join(", "))
+                    };
+                    view! {
+                        <div style=format!(
+                            "margin-top: 1rem; padding: 0.75rem; border-radius: 6px; font-size: 0.875rem; color: {}; background: {};",
+                            color, background
+                        )>
+                            {format!(
+                                "{} control {} on {}: {} ({})",
+                                capitalize(&run.level),
+                                run.status,
+                                device_label(&run.device_id),
+                                value,
+                                rules
+                            )}
+                        </div>
+                    }
+                })}
+            </div>
+
+            <div class="card" style="max-width: 900px; margin: 0 auto 2rem auto;">
+                <h2 style="font-size: 1.25rem; font-weight: 500; margin: 0 0 1rem 0; color: var(--color-text-secondary);">
+                    "Levey-Jennings Chart"
+                </h2>
+                <div style="display: flex; gap: 1rem; flex-wrap: wrap; margin-bottom: 1rem;">
+                    <div class="form-group" style="flex: 1; min-width: 14rem;">
+                        <label class="form-label">"Control material"</label>
+                        <select
+                            class="form-input"
+                            prop:value=move || chart_material_id.get()
+                            on:change=move |e| set_chart_material_id.set(event_target_value(&e))
+                        >
+                            {move || material_options(true)}
+                        </select>
+                    </div>
+                    <div class="form-group" style="flex: 1; min-width: 12rem;">
+                        <label class="form-label">"Device"</label>
+                        <select
+                            class="form-input"
+                            prop:value=move || chart_device_id.get()
+                            on:change=move |e| set_chart_device_id.set(event_target_value(&e))
+                        >
+                            {device_options}
+                        </select>
+                    </div>
+                </div>
+                {move || match chart.get() {
+                    Some(data) if !data.runs.is_empty() => view! { <LeveyJenningsChart data=data /> }.into_any(),
+                    Some(data) => view! {
+                        <p style="margin: 0; color: var(--color-text-secondary); font-size: 0.875rem;">
+                            {format!("Lot {} has not been run on this device yet.", data.material.lot_number)}
+                        </p>
+                    }.into_any(),
+                    None => view! {
+                        <p style="margin: 0; color: var(--color-text-secondary); font-size: 0.875rem;">
+                            "Pick a control material and device to chart."
+                        </p>
+                    }.into_any(),
+                }}
+            </div>
+
+            <div class="card" style="max-width: 900px; margin: 0 auto 2rem auto;">
+                <h2 style="font-size: 1.25rem; font-weight: 500; margin: 0 0 1rem 0; color: var(--color-text-secondary);">
+                    "Control Materials"
+                </h2>
+                <div style="display: flex; flex-direction: column; gap: 0.5rem;">
+                    {move || {
+                        let today = today();
+                        materials.get().into_iter().map(|material| {
+                            let expired = material.expires_on < today;
+                            let state = match (material.active, expired) {
+                                (false, _) => ("Not in use".to_string(), "cancelled"),
+                                (true, true) => ("Expired".to_string(), "failed"),
+                                (true, false) => ("In use".to_string(), "passed"),
+                            };
+                            let details = format!(
+                                "{} · {} · lot {} · expires {} · expected {:.3}–{:.3}",
+                                capitalize(&material.level),
+                                material.test_type,
+                                material.lot_number,
+                                material.expires_on,
+                                material.expected_min,
+                                material.expected_max
+                            );
+                            let editing = MaterialForm::from_material(&material);
+                            view! {
+                                <div style="display: flex; justify-content: space-between; align-items: center; gap: 1rem; padding: 0.75rem; border: 1px solid var(--color-border-light); border-radius: 8px;">
+                                    <div>
+                                        <div style="font-weight: 500;">{material.name.clone()}</div>
+                                        <div style="font-size: 0.8125rem; color: var(--color-text-secondary); margin-top: 0.25rem;">
+                                            {details}
+                                        </div>
+                                    </div>
+                                    <div style="display: flex; gap: 0.5rem; align-items: center;">
+                                        {badge(state.0, state.1)}
+                                        <Show when=move || role.can(Permission::ManageSettings)>
+                                            <button
+                                                class="button"
+                                                on:click={
+                                                    let editing = editing.clone();
+                                                    move |_| {
+                                                        set_form_message.set(None);
+                                                        set_form.set(editing.clone());
+                                                    }
+                                                }
+                                                style="padding: 0.375rem 0.75rem; font-size: 0.8125rem;"
+                                            >
+                                                "Edit"
+                                            </button>
+                                        </Show>
+                                    </div>
+                                </div>
+                            }
+                        }).collect::<Vec<_>>()
+                    }}
+                </div>
+
+                <Show when=move || role.can(Permission::ManageSettings)>
+                    <div style="margin-top: 1.5rem; display: flex; flex-direction: column; gap: 0.75rem;">
+                        <h3 style="font-size: 1rem; font-weight: 500; margin: 0; color: var(--color-text-secondary);">
+                            {move || if form.with(|f| f.id.is_some()) { "Edit Control Material" } else { "Add Control Material" }}
+                        </h3>
+                        <div style="display: flex; gap: 1rem; flex-wrap: wrap; align-items: flex-end;">
+                            <div class="form-group" style="flex: 1; min-width: 12rem;">
+                                <label class="form-label">"Name"</label>
+                                <input
+                                    type="text"
+                                    class="form-input"
+                                    placeholder="e.g. SARS-CoV-2 positive control"
+                                    prop:value=move || form.with(|f| f.name.clone())
+                                    on:input=move |e| update_form(|f, v| f.name = v, event_target_value(&e))
+                                />
+                            </div>
+                            <div class="form-group" style="width: 9rem;">
+                                <label class="form-label">"Level"</label>
+                                <select
+                                    class="form-input"
+                                    prop:value=move || form.with(|f| f.level.clone())
+                                    on:change=move |e| update_form(|f, v| f.level = v, event_target_value(&e))
+                                >
+                                    <option value="positive">"Positive"</option>
+                                    <option value="negative">"Negative"</option>
+                                </select>
+                            </div>
+                            <div class="form-group" style="width: 12rem;">
+                                <label class="form-label">"Test type"</label>
+                                <select
+                                    class="form-input"
+                                    prop:value=move || form.with(|f| f.test_type.clone())
+                                    on:change=move |e| update_form(|f, v| f.test_type = v, event_target_value(&e))
+                                >
+                                    {TestType::all().into_iter().map(|test_type| view! {
+                                        <option value=test_type.as_str()>{test_type.as_str()}</option>
+                                    }).collect::<Vec<_>>()}
+                                </select>
+                            </div>
+                        </div>
+                        <div style="display: flex; gap: 1rem; flex-wrap: wrap; align-items: flex-end;">
+                            <div class="form-group" style="width: 10rem;">
+                                <label class="form-label">"Lot number"</label>
+                                <input
+                                    type="text"
+                                    class="form-input"
+                                    prop:value=move || form.with(|f| f.lot_number.clone())
+                                    on:input=move |e| update_form(|f, v| f.lot_number = v, event_target_value(&e))
+                                />
+                            </div>
+                            <div class="form-group" style="width: 10rem;">
+                                <label class="form-label">"Expires on"</label>
+                                <input
+                                    type="date"
+                                    class="form-input"
+                                    prop:value=move || form.with(|f| f.expires_on.clone())
+                                    on:input=move |e| update_form(|f, v| f.expires_on = v, event_target_value(&e))
+                                />
+                            </div>
+                            <div class="form-group" style="width: 8rem;">
+                                <label class="form-label">"Expected min"</label>
+                                <input
+                                    type="number"
+                                    class="form-input"
+                                    min="0"
+                                    max="1"
+                                    step="0.01"
+                                    prop:value=move || form.with(|f| f.expected_min.clone())
+                                    on:input=move |e| update_form(|f, v| f.expected_min = v, event_target_value(&e))
+                                />
+                            </div>
+                            <div class="form-group" style="width: 8rem;">
+                                <label class="form-label">"Expected max"</label>
+                                <input
+                                    type="number"
+                                    class="form-input"
+                                    min="0"
+                                    max="1"
+                                    step="0.01"
+                                    prop:value=move || form.with(|f| f.expected_max.clone())
+                                    on:input=move |e| update_form(|f, v| f.expected_max = v, event_target_value(&e))
+                                />
+                            </div>
+                            <label style="display: flex; gap: 0.5rem; align-items: center; padding-bottom: 0.75rem;">
+                                <input
+                                    type="checkbox"
+                                    prop:checked=move || form.with(|f| f.active)
+                                    on:change=move |ev| {
+                                        let active = event_target_checked(&ev);
+                                        set_form.update(|f| f.active = active);
+                                    }
+                                />
+                                "In use"
+                            </label>
+                        </div>
+                        <div style="font-size: 0.8125rem; color: var(--color-text-secondary);">
+                            "The expected range is the detection probability the lot's insert gives, taken as the mean ± 3SD. A new lot number starts a new chart."
+                        </div>
+                        <div style="display: flex; justify-content: flex-end; gap: 0.5rem;">
+                            <Show when=move || form.with(|f| f.id.is_some())>
+                                <button
+                                    class="button"
+                                    on:click=move |_| set_form.set(MaterialForm::default())
+                                    style="padding: 0.5rem 0.75rem; font-size: 0.875rem;"
+                                >
+                                    "Cancel"
+                                </button>
+                            </Show>
+                            <button
+                                class="button"
+                                on:click=save_material
+                                style="padding: 0.5rem 0.75rem; font-size: 0.875rem;"
+                            >
+                                "Save"
+                            </button>
+                        </div>
+                        {move || form_message.get().map(|result| match result {
+                            Ok(msg) => view! {
+                                <div style="padding: 0.75rem; background: #d4edda; border: 1px solid #c3e6cb; border-radius: 6px; color: #155724; font-size: 0.875rem;">
+                                    {msg}
+                                </div>
+                            }.into_any(),
+                            Err(err) => view! {
+                                <div style="padding: 0.75rem; background: #f8d7da; border: 1px solid #f5c6cb; border-radius: 6px; color: #721c24; font-size: 0.875rem;">
+                                    {err}
+                                </div>
+                            }.into_any(),
+                        })}
+                    </div>
+                </Show>
+            </div>
+        </div>
+    }
+}
+
+// Chart area in SVG user units
+const CHART_WIDTH: f64 = 800.0;
+const CHART_HEIGHT: f64 = 280.0;
+const CHART_LEFT: f64 = 90.0;
+const CHART_RIGHT: f64 = 20.0;
+const CHART_TOP: f64 = 15.0;
+const CHART_BOTTOM: f64 = 30.0;
+/// Values beyond this many SD are drawn on the edge of the chart
+const CHART_SD_SPAN: f64 = 4.0;
+
+/// Runs of one lot plotted against the mean and ±1, 2 and 3SD lines
+#[component]
+fn LeveyJenningsChart(data: LeveyJennings) -> impl IntoView {
+    let plot_height = CHART_HEIGHT - CHART_TOP - CHART_BOTTOM;
+    let plot_width = CHART_WIDTH - CHART_LEFT - CHART_RIGHT;
+    let y_for = move |sd_units: f64| {
+        let clamped = sd_units.clamp(-CHART_SD_SPAN, CHART_SD_SPAN);
+        CHART_TOP + plot_height / 2.0 - clamped / CHART_SD_SPAN * plot_height / 2.0
+    };
+    let step = plot_width / data.runs.len().max(2) as f64;
+    let x_for = move |i: usize| CHART_LEFT + step * (i as f64 + 0.5);
+
+    let limits = [-3i32, -2, -1, 0, 1, 2, 3].map(|k| {
+        let y = y_for(f64::from(k));
+        let (color, dash) = match k.abs() {
+            0 => ("var(--color-text-secondary)", "none"),
+            1 => ("var(--color-border-medium)", "2 4"),
+            2 => ("var(--color-warning)", "6 4"),
+            _ => ("var(--color-error)", "none"),
+        };
+        let label = match k {
+            0 => format!("Mean {:.3}", data.mean),
+            _ => format!("{:+}SD {:.3}", k, data.mean + f64::from(k) * data.sd),
+        };
+        view! {
+            <line
+                x1=CHART_LEFT
+                x2=CHART_WIDTH - CHART_RIGHT
+                y1=y
+                y2=y
+                style=format!("stroke: {}; stroke-width: 1; stroke-dasharray: {};", color, dash)
+            />
+            <text
+                x=CHART_LEFT - 6.0
+                y=y + 4.0
+                text-anchor="end"
+                style="font-size: 11px; fill: var(--color-text-secondary);"
+            >
+                {label}
+            </text>
+        }
+    });
+
+    let points: Vec<(f64, f64, String)> = data
+        .runs
+        .iter()
+        .enumerate()
+        .filter_map(|(i, run)| {
+            let value = run.value?;
+            Some((
+                x_for(i),
+                y_for((value - data.mean) / data.sd),
+                run.status.clone(),
+            ))
+        })
+        .collect();
+    let line = points
+        .iter()
+        .map(|(x, y, _)| format!("{:.1},{:.1}", x, y))
+        .collect::<Vec<_>>()
+        .join(" ");
+    let dots = points
+        .into_iter()
+        .map(|(x, y, status)| {
+            let (color, _) = state_colors(&status);
+            view! {
+                <circle cx=x cy=y r="4" style=format!("fill: {}; stroke: white; stroke-width: 1;", color) />
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let first = data.runs.first().map(run_date).unwrap_or_default();
+    let last = data.runs.last().map(run_date).unwrap_or_default();
+    let failed = data
+        .runs
+        .iter()
+        .filter(|run| run.status == "failed")
+        .count();
+    let latest = data.runs.last().cloned();
+
+    view! {
+        <svg
+            viewBox=format!("0 0 {} {}", CHART_WIDTH, CHART_HEIGHT)
+            style="width: 100%; height: auto; display: block;"
+        >
+            {limits}
+            <polyline
+                points=line
+                style="fill: none; stroke: var(--color-accent-primary); stroke-width: 1.5;"
+            />
+            {dots}
+            <text x=CHART_LEFT y=CHART_HEIGHT - 8.0 style="font-size: 11px; fill: var(--color-text-secondary);">
+                {first}
+            </text>
+            <text
+                x=CHART_WIDTH - CHART_RIGHT
+                y=CHART_HEIGHT - 8.0
+                text-anchor="end"
+                style="font-size: 11px; fill: var(--color-text-secondary);"
+            >
+                {last}
+            </text>
+        </svg>
+        <div style="display: flex; justify-content: space-between; font-size: 0.8125rem; color: var(--color-text-secondary); margin-top: 0.5rem;">
+            <span>
+                {format!(
+                    "{} runs of lot {} · {} failed",
+                    data.runs.len(),
+                    data.material.lot_number,
+                    failed
+                )}
+            </span>
+            <span>
+                {latest.map(|run| {
+                    let rules = if run.violations.is_empty() {
+                        String::new()
+                    } else {
+                        format!(" ({})", run.violations.join(", "))
+                    };
+                    format!(
+                        "Last: {} {}{} by {}",
+                        run.value.map(|v| format!("{:.3}", v)).unwrap_or_default(),
+                        run.status,
+                        rules,
+                        run.operated_by.unwrap_or_default()
+                    )
+                })}
+            </span>
+        </div>
+    }
+}
+
+/// Day a run was read, for the chart's axis
+fn run_date(run: &QcRun) -> String {
+    run.completed_at
+        .as_deref()
+        .unwrap_or(&run.created_at)
+        .chars()
+        .take(10)
+        .collect()
+}
@@ -1,102 +1,593 @@
+use crate::app::errors::invoke_error;
 use crate::app::Page;
 use leptos::prelude::*;
 use leptos::task::spawn_local;
+use serde::{Deserialize, Serialize};
 use wasm_bindgen::prelude::*;
 
+// Use shared types
+use shared_types::{
+    ColumnSchema, Device, FlowControl, Parity, Permission, PreprocessingPipeline, Role,
+    SerialProfile, TestType, TestTypePipeline, User,
+};
+
 #[wasm_bindgen]
 extern "C" {
     #[wasm_bindgen(catch, js_namespace = ["window", "__TAURI__", "core"])]
     async fn invoke(cmd: &str, args: JsValue) -> Result<JsValue, JsValue>;
 }
 
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SaveDeviceArgs {
+    device_data: SaveDeviceRequest,
+}
+
+#[derive(Serialize)]
+struct SaveDeviceRequest {
+    device_id: String,
+    label: String,
+    port: String,
+    enabled: bool,
+    profile: SerialProfile,
+    schema: ColumnSchema,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RemoveDeviceArgs {
+    device_id: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SavePipelineArgs {
+    test_type: TestType,
+    pipeline: PreprocessingPipeline,
+}
+
+#[derive(Serialize)]
+struct GetLogsArgs {
+    lines: usize,
+}
+
+#[derive(Serialize)]
+struct SetLogFilterArgs {
+    filter: String,
+}
+
+#[derive(Serialize)]
+struct BackupDatabaseArgs {
+    destination: Option<String>,
+}
+
+#[derive(Serialize)]
+struct RestoreDatabaseArgs {
+    path: String,
+}
+
+#[derive(Serialize)]
+struct SaveBackupScheduleArgs {
+    schedule: BackupSchedule,
+}
+
+/// A database backup file
+#[derive(Deserialize, Clone)]
+struct BackupInfo {
+    path: String,
+    file_name: String,
+    kind: String,
+    size_bytes: u64,
+    created_at: Option<String>,
+}
+
+/// Automatic backup settings
+#[derive(Serialize, Deserialize, Clone)]
+struct BackupSchedule {
+    enabled: bool,
+    interval_hours: u32,
+    keep: usize,
+}
+
+#[derive(Deserialize)]
+struct RestoreReport {
+    safety_backup: BackupInfo,
+}
+
+#[derive(Deserialize)]
+struct IntegrityReport {
+    ok: bool,
+    problems: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct SetDatabaseEncryptionArgs {
+    mode: String,
+    passphrase: Option<String>,
+}
+
+/// How the database file is protected at rest
+#[derive(Deserialize, Clone)]
+struct EncryptionStatus {
+    mode: String,
+    key_file: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CreateUserArgs {
+    user_data: CreateUserRequest,
+}
+
+#[derive(Serialize)]
+struct CreateUserRequest {
+    username: String,
+    display_name: String,
+    role: Role,
+    password: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct UpdateUserArgs {
+    user_data: UpdateUserRequest,
+}
+
+#[derive(Serialize)]
+struct UpdateUserRequest {
+    uuid: String,
+    display_name: String,
+    role: Role,
+    active: bool,
+}
+
+#[derive(Serialize)]
+struct ResetUserPasswordArgs {
+    uuid: String,
+    password: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ChangePasswordArgs {
+    current_password: String,
+    new_password: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SetPinArgs {
+    current_password: String,
+    pin: Option<String>,
+}
+
+#[derive(Serialize)]
+struct SetAutoLockMinutesArgs {
+    minutes: u32,
+}
+
+#[derive(Serialize)]
+struct GetSettingArgs {
+    key: String,
+}
+
+#[derive(Serialize)]
+struct SaveSettingArgs {
+    key: String,
+    value: String,
+}
+
+/// Setting that holds completed results for a reviewer
+const REVIEW_SETTING: &str = "require_result_review";
+
+/// Clinic details printed at the top of test reports
+#[derive(Serialize, Deserialize, Clone, Default)]
+struct ReportSettings {
+    clinic_name: String,
+    address: String,
+    contact: String,
+    logo_path: Option<String>,
+}
+
+#[derive(Serialize)]
+struct SaveReportSettingsArgs {
+    settings: ReportSettings,
+}
+
+/// Where released results are sent as HL7 messages
+#[derive(Serialize, Deserialize, Clone)]
+struct Hl7Settings {
+    enabled: bool,
+    host: String,
+    port: u16,
+    sending_application: String,
+    sending_facility: String,
+    receiving_application: String,
+    receiving_facility: String,
+    ack_timeout_secs: u64,
+}
+
+#[derive(Serialize)]
+struct SaveHl7SettingsArgs {
+    settings: Hl7Settings,
+}
+
+/// FHIR server that receives released results as transaction bundles
+#[derive(Serialize, Deserialize, Clone)]
+struct FhirSettings {
+    enabled: bool,
+    base_url: String,
+    auth_token: String,
+    patient_identifier_system: String,
+    timeout_secs: u64,
+}
+
+#[derive(Serialize)]
+struct SaveFhirSettingsArgs {
+    settings: FhirSettings,
+}
+
+/// ASTM host that receives results and may download orders
+#[derive(Serialize, Deserialize, Clone)]
+struct AstmSettings {
+    enabled: bool,
+    transport: String,
+    host: String,
+    port: u16,
+    serial_port: String,
+    baud_rate: u32,
+    sender_id: String,
+    receiver_id: String,
+    reply_timeout_secs: u64,
+    accept_orders: bool,
+    order_address: String,
+    order_port: u16,
+}
+
+#[derive(Serialize)]
+struct SaveAstmSettingsArgs {
+    settings: AstmSettings,
+}
+
+/// Label printer sample tube labels are sent to
+#[derive(Serialize, Deserialize, Clone)]
+struct LabelPrinterSettings {
+    enabled: bool,
+    language: String,
+    transport: String,
+    host: String,
+    port: u16,
+    serial_port: String,
+    baud_rate: u32,
+    folder: String,
+    copies: u32,
+}
+
+#[derive(Serialize)]
+struct LabelPrinterSettingsArgs {
+    settings: LabelPrinterSettings,
+}
+
+/// When patient tests need passing QC on their device
+#[derive(Serialize, Deserialize, Clone)]
+struct QcSettings {
+    required: bool,
+    valid_hours: u32,
+}
+
+#[derive(Serialize)]
+struct QcSettingsArgs {
+    settings: QcSettings,
+}
+
+/// A queued result message and how its delivery went
+#[derive(Deserialize, Clone)]
+struct OutboundMessage {
+    id: i64,
+    protocol: String,
+    test_uuid: String,
+    control_id: String,
+    status: String,
+    attempts: i32,
+    last_error: Option<String>,
+    ack_code: Option<String>,
+    created_at: String,
+    next_attempt_at: String,
+}
+
+#[derive(Serialize)]
+struct RetryOutboundMessageArgs {
+    id: i64,
+}
+
+/// Which CSV column holds each patient field
+#[derive(Serialize, Deserialize, Clone, Default)]
+struct PatientColumnMapping {
+    first_name: Option<String>,
+    last_name: Option<String>,
+    date_of_birth: Option<String>,
+    patient_id_number: Option<String>,
+    email: Option<String>,
+    phone: Option<String>,
+    notes: Option<String>,
+    date_format: String,
+}
+
+/// Outcome for one row of a patient CSV
+#[derive(Deserialize, Clone)]
+struct ImportRow {
+    line: usize,
+    status: String,
+    name: String,
+    errors: Vec<String>,
+    duplicate_of: Option<String>,
+}
+
+/// Summary of a patient import preview or run
+#[derive(Deserialize, Clone)]
+struct ImportReport {
+    dry_run: bool,
+    headers: Vec<String>,
+    mapping: PatientColumnMapping,
+    total_rows: usize,
+    new_patients: usize,
+    duplicates: usize,
+    invalid: usize,
+    imported: usize,
+    rows: Vec<ImportRow>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ImportPatientsArgs {
+    path: String,
+    mapping: Option<PatientColumnMapping>,
+    dry_run: bool,
+}
+
+/// The part of `get_auth_status` the auto-lock setting needs
+#[derive(Deserialize)]
+struct AutoLockStatus {
+    auto_lock_minutes: u32,
+}
+
+/// Recent backend log lines, already redacted
+#[derive(Deserialize, Clone, Default)]
+struct LogSnapshot {
+    log_dir: String,
+    filter: String,
+    lines: Vec<String>,
+    truncated: bool,
+}
+
+/// Fetch the list of available serial ports
+async fn fetch_ports() -> Result<Vec<String>, String> {
+    let value = invoke("list_serial_ports", JsValue::NULL)
+        .await
+        .map_err(|e| invoke_error("Failed to list ports", e))?;
+    serde_wasm_bindgen::from_value::<Vec<String>>(value)
+        .map_err(|e| format!("Failed to parse ports: {:?}", e))
+}
+
+/// Fetch the configured devices
+async fn fetch_devices() -> Result<Vec<Device>, String> {
+    let value = invoke("list_devices", JsValue::NULL)
+        .await
+        .map_err(|e| invoke_error("Failed to list devices", e))?;
+    serde_wasm_bindgen::from_value::<Vec<Device>>(value)
+        .map_err(|e| format!("Failed to parse devices: {:?}", e))
+}
+
+/// Save a device through the backend, restarting its monitor
+async fn save_device(request: SaveDeviceRequest) -> Result<(), String> {
+    let args = SaveDeviceArgs {
+        device_data: request,
+    };
+    invoke("save_device", serde_wasm_bindgen::to_value(&args).unwrap())
+        .await
+        .map(|_| ())
+        .map_err(|e| invoke_error("Failed to save device", e))
+}
+
+/// Fetch the preprocessing pipeline configured for each test type
+async fn fetch_pipelines() -> Result<Vec<TestTypePipeline>, String> {
+    let value = invoke("get_preprocessing_pipelines", JsValue::NULL)
+        .await
+        .map_err(|e| invoke_error("Failed to load preprocessing", e))?;
+    serde_wasm_bindgen::from_value::<Vec<TestTypePipeline>>(value)
+        .map_err(|e| format!("Failed to parse preprocessing: {:?}", e))
+}
+
+/// Fetch the most recent backend log lines
+async fn fetch_logs(lines: usize) -> Result<LogSnapshot, String> {
+    let args = GetLogsArgs { lines };
+    let value = invoke("get_logs", serde_wasm_bindgen::to_value(&args).unwrap())
+        .await
+        .map_err(|e| invoke_error("Failed to load logs", e))?;
+    serde_wasm_bindgen::from_value::<LogSnapshot>(value)
+        .map_err(|e| format!("Failed to parse logs: {:?}", e))
+}
+
+/// Fetch the backups in the app's backup folder, newest first
+async fn fetch_backups() -> Result<Vec<BackupInfo>, String> {
+    let value = invoke("list_backups", JsValue::NULL)
+        .await
+        .map_err(|e| invoke_error("Failed to list backups", e))?;
+    serde_wasm_bindgen::from_value::<Vec<BackupInfo>>(value)
+        .map_err(|e| format!("Failed to parse backups: {:?}", e))
+}
+
+/// Fetch every operator account
+async fn fetch_users() -> Result<Vec<User>, String> {
+    let value = invoke("list_users", JsValue::NULL)
+        .await
+        .map_err(|e| invoke_error("Failed to list users", e))?;
+    serde_wasm_bindgen::from_value::<Vec<User>>(value)
+        .map_err(|e| format!("Failed to parse users: {:?}", e))
+}
+
+/// Fetch the clinic header used on test reports
+async fn fetch_report_settings() -> Result<ReportSettings, String> {
+    let value = invoke("get_report_settings", JsValue::NULL)
+        .await
+        .map_err(|e| invoke_error("Failed to load report settings", e))?;
+    serde_wasm_bindgen::from_value::<ReportSettings>(value)
+        .map_err(|e| format!("Failed to parse report settings: {:?}", e))
+}
+
+/// Fetch the HL7 destination for released results
+async fn fetch_hl7_settings() -> Result<Hl7Settings, String> {
+    let value = invoke("get_hl7_settings", JsValue::NULL)
+        .await
+        .map_err(|e| invoke_error("Failed to load HL7 settings", e))?;
+    serde_wasm_bindgen::from_value::<Hl7Settings>(value)
+        .map_err(|e| format!("Failed to parse HL7 settings: {:?}", e))
+}
+
+/// Fetch the FHIR server released results are POSTed to
+async fn fetch_fhir_settings() -> Result<FhirSettings, String> {
+    let value = invoke("get_fhir_settings", JsValue::NULL)
+        .await
+        .map_err(|e| invoke_error("Failed to load FHIR settings", e))?;
+    serde_wasm_bindgen::from_value::<FhirSettings>(value)
+        .map_err(|e| format!("Failed to parse FHIR settings: {:?}", e))
+}
+
+/// Fetch the ASTM host connection
+async fn fetch_astm_settings() -> Result<AstmSettings, String> {
+    let value = invoke("get_astm_settings", JsValue::NULL)
+        .await
+        .map_err(|e| invoke_error("Failed to load ASTM settings", e))?;
+    serde_wasm_bindgen::from_value::<AstmSettings>(value)
+        .map_err(|e| format!("Failed to parse ASTM settings: {:?}", e))
+}
+
+/// Fetch the label printer connection
+async fn fetch_label_printer_settings() -> Result<LabelPrinterSettings, String> {
+    let value = invoke("get_label_printer_settings", JsValue::NULL)
+        .await
+        .map_err(|e| invoke_error("Failed to load label printer settings", e))?;
+    serde_wasm_bindgen::from_value::<LabelPrinterSettings>(value)
+        .map_err(|e| format!("Failed to parse label printer settings: {:?}", e))
+}
+
+async fn fetch_qc_settings() -> Result<QcSettings, String> {
+    let value = invoke("get_qc_settings", JsValue::NULL)
+        .await
+        .map_err(|e| invoke_error("Failed to load QC settings", e))?;
+    serde_wasm_bindgen::from_value::<QcSettings>(value)
+        .map_err(|e| format!("Failed to parse QC settings: {:?}", e))
+}
+
+/// Fetch the most recent result messages, newest first
+async fn fetch_outbound_messages() -> Result<Vec<OutboundMessage>, String> {
+    let value = invoke("list_outbound_messages", JsValue::NULL)
+        .await
+        .map_err(|e| invoke_error("Failed to load delivery log", e))?;
+    serde_wasm_bindgen::from_value::<Vec<OutboundMessage>>(value)
+        .map_err(|e| format!("Failed to parse delivery log: {:?}", e))
+}
+
+/// Fetch the automatic backup schedule
+async fn fetch_backup_schedule() -> Result<BackupSchedule, String> {
+    let value = invoke("get_backup_schedule", JsValue::NULL)
+        .await
+        .map_err(|e| invoke_error("Failed to load backup schedule", e))?;
+    serde_wasm_bindgen::from_value::<BackupSchedule>(value)
+        .map_err(|e| format!("Failed to parse backup schedule: {:?}", e))
+}
+
 #[component]
-pub fn SettingsPage(on_navigate_to_home: WriteSignal<Page>) -> impl IntoView {
-    // State for port selection
+pub fn SettingsPage(
+    on_navigate_to_home: WriteSignal<Page>,
+    current_user: ReadSignal<Option<User>>,
+) -> impl IntoView {
+    // Sections the signed-in role cannot change are hidden; the backend enforces the same rules
+    let role = current_user
+        .get_untracked()
+        .map(|user| user.role)
+        .unwrap_or(Role::Operator);
+    // State for device management
     let (available_ports, set_available_ports) = signal(Vec::<String>::new());
-    let (current_port, set_current_port) = signal(String::new());
-    let (selected_port, set_selected_port) = signal(String::new());
+    let (devices, set_devices) = signal(Vec::<Device>::new());
+    let (pipelines, set_pipelines) = signal(Vec::<TestTypePipeline>::new());
+    let (new_device_id, set_new_device_id) = signal(String::new());
+    let (new_label, set_new_label) = signal(String::new());
+    let (new_port, set_new_port) = signal(String::new());
     let (loading, set_loading) = signal(false);
     let (error, set_error) = signal(None::<String>);
     let (success, set_success) = signal(None::<String>);
 
-    // Load available ports and current port on mount
-    spawn_local(async move {
-        // Get available ports
-        match invoke("list_serial_ports", JsValue::NULL).await {
-            Ok(ports_value) => {
-                if let Ok(ports_vec) = serde_wasm_bindgen::from_value::<Vec<String>>(ports_value) {
-                    set_available_ports.set(ports_vec);
-                }
-            }
-            Err(e) => {
-                leptos::logging::log!("Failed to list ports: {:?}", e);
-            }
-        }
+    // Show a success message that clears itself after 3 seconds
+    let flash_success = move |msg: String| {
+        set_success.set(Some(msg));
+        set_timeout(
+            move || {
+                set_success.set(None);
+            },
+            std::time::Duration::from_secs(3),
+        );
+    };
 
-        // Get current port
-        match invoke("get_current_port", JsValue::NULL).await {
-            Ok(port_value) => {
-                if let Some(port_str) = port_value.as_string() {
-                    set_current_port.set(port_str.clone());
-                    set_selected_port.set(port_str);
-                }
+    // Reload ports and devices from the backend
+    let reload = move || {
+        spawn_local(async move {
+            match fetch_ports().await {
+                Ok(ports) => set_available_ports.set(ports),
+                Err(e) => leptos::logging::log!("{}", e),
             }
-            Err(e) => {
-                leptos::logging::log!("Failed to get current port: {:?}", e);
+            match fetch_devices().await {
+                Ok(device_list) => set_devices.set(device_list),
+                Err(e) => set_error.set(Some(e)),
             }
-        }
-    });
+            match fetch_pipelines().await {
+                Ok(list) => set_pipelines.set(list),
+                Err(e) => set_error.set(Some(e)),
+            }
+        });
+    };
+
+    // Load available ports and devices on mount
+    reload();
 
     // Handler to refresh port list
     let refresh_ports = move |_| {
         spawn_local(async move {
-            match invoke("list_serial_ports", JsValue::NULL).await {
-                Ok(ports_value) => {
-                    if let Ok(ports_vec) =
-                        serde_wasm_bindgen::from_value::<Vec<String>>(ports_value)
-                    {
-                        set_available_ports.set(ports_vec);
-                        set_success.set(Some("Port list refreshed".to_string()));
-                        // Clear success message after 3 seconds
-                        set_timeout(
-                            move || {
-                                set_success.set(None);
-                            },
-                            std::time::Duration::from_secs(3),
-                        );
-                    }
-                }
-                Err(e) => {
-                    set_error.set(Some(format!("Failed to refresh ports: {:?}", e)));
+            match fetch_ports().await {
+                Ok(ports) => {
+                    set_available_ports.set(ports);
+                    flash_success("Port list refreshed".to_string());
                 }
+                Err(e) => set_error.set(Some(e)),
             }
         });
     };
 
-    // Handler to apply port change
-    let apply_port_change = move |_| {
-        let port = selected_port.get();
+    // Handler to add a new device
+    let add_device = move |_| {
+        let request = SaveDeviceRequest {
+            device_id: new_device_id.get(),
+            label: new_label.get(),
+            port: new_port.get(),
+            enabled: true,
+            profile: SerialProfile::default(),
+            schema: ColumnSchema::default(),
+        };
         spawn_local(async move {
             set_loading.set(true);
             set_error.set(None);
             set_success.set(None);
 
-            // Create a simple JS object for the port argument
-            let args = js_sys::Object::new();
-            js_sys::Reflect::set(&args, &JsValue::from_str("port"), &JsValue::from_str(&port))
-                .unwrap();
-
-            match invoke("set_serial_port", args.into()).await {
+            let port = request.port.clone();
+            match save_device(request).await {
                 Ok(_) => {
-                    set_current_port.set(port.clone());
-                    set_success.set(Some(format!(
-                        "Port changed to {}. Please restart the application for changes to take effect.",
-                        port
-                    )));
-                }
-                Err(e) => {
-                    set_error.set(Some(format!("Failed to change port: {:?}", e)));
+                    set_new_device_id.set(String::new());
+                    set_new_label.set(String::new());
+                    set_new_port.set(String::new());
+                    flash_success(format!("Device added on {}", port));
+                    reload();
                 }
+                Err(e) => set_error.set(Some(e)),
             }
             set_loading.set(false);
         });
@@ -149,115 +640,257 @@ pub fn SettingsPage(on_navigate_to_home: WriteSignal<Page>) -> impl IntoView {
 
                     <div class="settings-section">
                         <h3 style="font-size: 1.125rem; font-weight: 500; margin-bottom: 1rem; color: var(--color-text-secondary);">
-                            "Serial Connection"
+                            "Account"
                         </h3>
+                        <AccountPanel current_user=current_user />
+                    </div>
 
-                        // COM Port Selection
-                        <div class="settings-item" style="flex-direction: column; align-items: stretch; gap: 1rem;">
-                            <div style="display: flex; justify-content: space-between; align-items: center;">
-                                <div>
-                                    <div style="font-weight: 500; color: var(--color-text-primary);">
-                                        "Serial Port"
-                                    </div>
-                                    <div style="font-size: 0.875rem; color: var(--color-text-secondary);">
-                                        "Select the COM port for serial communication"
+                    <Show when=move || role.can(Permission::ManageUsers)>
+                        <div class="settings-divider"></div>
+
+                        <div class="settings-section">
+                            <h3 style="font-size: 1.125rem; font-weight: 500; margin-bottom: 1rem; color: var(--color-text-secondary);">
+                                "Users"
+                            </h3>
+                            <UsersPanel current_user=current_user />
+                        </div>
+                    </Show>
+
+                    <Show when=move || role.can(Permission::ManageSettings)>
+                        <div class="settings-divider"></div>
+
+                        <div class="settings-section">
+                            <h3 style="font-size: 1.125rem; font-weight: 500; margin-bottom: 1rem; color: var(--color-text-secondary);">
+                                "Session"
+                            </h3>
+                            <AutoLockPanel />
+                        </div>
+
+                        <div class="settings-divider"></div>
+
+                        <div class="settings-section">
+                            <h3 style="font-size: 1.125rem; font-weight: 500; margin-bottom: 1rem; color: var(--color-text-secondary);">
+                                "Result Review"
+                            </h3>
+                            <ReviewSettingPanel
+                                on_changed=move |msg: String| flash_success(msg)
+                                on_error=move |err: String| set_error.set(Some(err))
+                            />
+                        </div>
+
+                        <div class="settings-divider"></div>
+
+                        <div class="settings-section">
+                            <h3 style="font-size: 1.125rem; font-weight: 500; margin-bottom: 1rem; color: var(--color-text-secondary);">
+                                "Reports"
+                            </h3>
+                            <ReportSettingsPanel />
+                        </div>
+
+                        <div class="settings-divider"></div>
+
+                        <div class="settings-section">
+                            <h3 style="font-size: 1.125rem; font-weight: 500; margin-bottom: 1rem; color: var(--color-text-secondary);">
+                                "FHIR Server"
+                            </h3>
+                            <FhirSettingsPanel />
+                        </div>
+
+                        <div class="settings-divider"></div>
+
+                        <div class="settings-section">
+                            <h3 style="font-size: 1.125rem; font-weight: 500; margin-bottom: 1rem; color: var(--color-text-secondary);">
+                                "ASTM Host"
+                            </h3>
+                            <AstmSettingsPanel />
+                        </div>
+
+                        <div class="settings-divider"></div>
+
+                        <div class="settings-section">
+                            <h3 style="font-size: 1.125rem; font-weight: 500; margin-bottom: 1rem; color: var(--color-text-secondary);">
+                                "Laboratory Information System"
+                            </h3>
+                            <LisPanel />
+                        </div>
+
+                        <div class="settings-divider"></div>
+
+                        <div class="settings-section">
+                            <h3 style="font-size: 1.125rem; font-weight: 500; margin-bottom: 1rem; color: var(--color-text-secondary);">
+                                "Label Printer"
+                            </h3>
+                            <LabelPrinterPanel />
+                        </div>
+
+                        <div class="settings-divider"></div>
+
+                        <div class="settings-section">
+                            <h3 style="font-size: 1.125rem; font-weight: 500; margin-bottom: 1rem; color: var(--color-text-secondary);">
+                                "Quality Control"
+                            </h3>
+                            <QcSettingsPanel />
+                        </div>
+
+                        <div class="settings-divider"></div>
+
+                        <div class="settings-section">
+                            <h3 style="font-size: 1.125rem; font-weight: 500; margin-bottom: 1rem; color: var(--color-text-secondary);">
+                                "Patient Import"
+                            </h3>
+                            <PatientImportPanel />
+                        </div>
+                    </Show>
+
+                    <Show when=move || role.can(Permission::ManageDevices)>
+                        <div class="settings-divider"></div>
+
+                        <div class="settings-section">
+                            <h3 style="font-size: 1.125rem; font-weight: 500; margin-bottom: 1rem; color: var(--color-text-secondary);">
+                                "Serial Connection"
+                            </h3>
+
+                            // Configured devices
+                            <div class="settings-item" style="flex-direction: column; align-items: stretch; gap: 1rem;">
+                                <div style="display: flex; justify-content: space-between; align-items: center;">
+                                    <div>
+                                        <div style="font-weight: 500; color: var(--color-text-primary);">
+                                            "Devices"
+                                        </div>
+                                        <div style="font-size: 0.875rem; color: var(--color-text-secondary);">
+                                            "Each reader is monitored on its own serial port"
+                                        </div>
                                     </div>
+                                    <button
+                                        class="button"
+                                        on:click=refresh_ports
+                                        style="padding: 0.5rem 0.75rem; font-size: 0.875rem;"
+                                    >
+                                        "Refresh"
+                                    </button>
                                 </div>
-                                <button
-                                    class="button"
-                                    on:click=refresh_ports
-                                    style="padding: 0.5rem 0.75rem; font-size: 0.875rem;"
-                                >
-                                    "Refresh"
-                                </button>
-                            </div>
 
-                            <div style="display: flex; gap: 0.75rem; align-items: center;">
-                                <select
-                                    class="port-selector"
-                                    on:change=move |ev| {
-                                        let value = event_target_value(&ev);
-                                        set_selected_port.set(value);
-                                    }
-                                    prop:value=move || selected_port.get()
-                                    style="flex: 1; padding: 0.625rem; border: 1px solid var(--color-border-medium); border-radius: 6px; background: var(--color-surface); font-family: inherit; font-size: 0.875rem;"
-                                >
-                                    {move || {
-                                        let ports = available_ports.get();
-                                        if ports.is_empty() {
-                                            let empty_value = String::new();
-                                            let empty_text = "No ports available".to_string();
-                                            vec![view! {
-                                                <option value=empty_value>{empty_text}</option>
-                                            }]
-                                        } else {
-                                            ports.into_iter().map(|port| {
+                                {move || {
+                                    let ports = available_ports.get();
+                                    devices.get().into_iter().map(|device| {
+                                        view! {
+                                            <DeviceRow
+                                                device=device
+                                                ports=ports.clone()
+                                                on_changed=move |msg: String| {
+                                                    flash_success(msg);
+                                                    reload();
+                                                }
+                                                on_error=move |err: String| set_error.set(Some(err))
+                                            />
+                                        }
+                                    }).collect_view()
+                                }}
+
+                                // Add device
+                                <div style="display: flex; gap: 0.75rem; align-items: center;">
+                                    <input
+                                        type="text"
+                                        class="form-input"
+                                        placeholder="Device ID"
+                                        prop:value=move || new_device_id.get()
+                                        on:input=move |e| set_new_device_id.set(event_target_value(&e))
+                                        style="flex: 1;"
+                                    />
+                                    <input
+                                        type="text"
+                                        class="form-input"
+                                        placeholder="Label"
+                                        prop:value=move || new_label.get()
+                                        on:input=move |e| set_new_label.set(event_target_value(&e))
+                                        style="flex: 1;"
+                                    />
+                                    <select
+                                        class="port-selector"
+                                        on:change=move |ev| set_new_port.set(event_target_value(&ev))
+                                        prop:value=move || new_port.get()
+                                        style="flex: 1; padding: 0.625rem; border: 1px solid var(--color-border-medium); border-radius: 6px; background: var(--color-surface); font-family: inherit; font-size: 0.875rem;"
+                                    >
+                                        <option value="">"Select port"</option>
+                                        {move || {
+                                            available_ports.get().into_iter().map(|port| {
                                                 let port_value = port.clone();
-                                                let port_text = port;
                                                 view! {
-                                                    <option value=port_value>{port_text}</option>
+                                                    <option value=port_value>{port}</option>
                                                 }
                                             }).collect::<Vec<_>>()
-                                        }
-                                    }}
-                                </select>
+                                        }}
+                                    </select>
+                                    <button
+                                        class="button primary"
+                                        on:click=add_device
+                                        disabled=move || loading.get() || new_device_id.get().trim().is_empty() || new_port.get().is_empty()
+                                        style="padding: 0.625rem 1.25rem; font-size: 0.875rem; white-space: nowrap;"
+                                    >
+                                        {move || if loading.get() { "Adding..." } else { "Add Device" }}
+                                    </button>
+                                </div>
 
-                                <button
-                                    class="button primary"
-                                    on:click=apply_port_change
-                                    disabled=move || loading.get() || selected_port.get() == current_port.get() || selected_port.get().is_empty()
-                                    style="padding: 0.625rem 1.25rem; font-size: 0.875rem; white-space: nowrap;"
-                                >
-                                    {move || if loading.get() { "Applying..." } else { "Apply" }}
-                                </button>
+                                {move || {
+                                    if let Some(msg) = success.get() {
+                                        view! {
+                                            <div style="padding: 0.75rem; background: #d4edda; border: 1px solid #c3e6cb; border-radius: 6px; color: #155724; font-size: 0.875rem;">
+                                                {msg}
+                                            </div>
+                                        }.into_any()
+                                    } else if let Some(err) = error.get() {
+                                        view! {
+                                            <div style="padding: 0.75rem; background: #f8d7da; border: 1px solid #f5c6cb; border-radius: 6px; color: #721c24; font-size: 0.875rem;">
+                                                {err}
+                                            </div>
+                                        }.into_any()
+                                    } else {
+                                        view! { <div></div> }.into_any()
+                                    }
+                                }}
                             </div>
 
-                            {move || {
-                                if let Some(msg) = success.get() {
-                                    view! {
-                                        <div style="padding: 0.75rem; background: #d4edda; border: 1px solid #c3e6cb; border-radius: 6px; color: #155724; font-size: 0.875rem;">
-                                            {msg}
-                                        </div>
-                                    }.into_any()
-                                } else if let Some(err) = error.get() {
-                                    view! {
-                                        <div style="padding: 0.75rem; background: #f8d7da; border: 1px solid #f5c6cb; border-radius: 6px; color: #721c24; font-size: 0.875rem;">
-                                            {err}
-                                        </div>
-                                    }.into_any()
-                                } else {
-                                    view! { <div></div> }.into_any()
-                                }
-                            }}
-
-                            {move || {
-                                let current = current_port.get();
-                                if !current.is_empty() {
-                                    view! {
-                                        <div style="font-size: 0.875rem; color: var(--color-text-secondary);">
-                                            "Current port: " <strong>{current}</strong>
-                                        </div>
-                                    }.into_any()
-                                } else {
-                                    view! { <div></div> }.into_any()
-                                }
-                            }}
+                            <div class="settings-item">
+                                <div>
+                                    <div style="font-weight: 500; color: var(--color-text-primary);">
+                                        "Auto-reconnect"
+                                    </div>
+                                    <div style="font-size: 0.875rem; color: var(--color-text-secondary);">
+                                        "Automatically reconnect to devices when disconnected"
+                                    </div>
+                                </div>
+                                <div class="toggle-placeholder" style="color: var(--color-success);">
+                                    "Enabled"
+                                </div>
+                            </div>
                         </div>
+                    </Show>
 
-                        <div class="settings-item">
-                            <div>
-                                <div style="font-weight: 500; color: var(--color-text-primary);">
-                                    "Auto-reconnect"
-                                </div>
+                    <Show when=move || role.can(Permission::ManageSettings)>
+                        <div class="settings-divider"></div>
+
+                        <div class="settings-section">
+                            <h3 style="font-size: 1.125rem; font-weight: 500; margin-bottom: 1rem; color: var(--color-text-secondary);">
+                                "Signal Preprocessing"
+                            </h3>
+                            <div class="settings-item" style="flex-direction: column; align-items: stretch; gap: 0.75rem;">
                                 <div style="font-size: 0.875rem; color: var(--color-text-secondary);">
-                                    "Automatically reconnect to devices when disconnected"
+                                    "Steps applied before detection, e.g. baseline(20) | savgol(7, 2) | normalize(zscore)"
                                 </div>
-                            </div>
-                            <div class="toggle-placeholder" style="color: var(--color-success);">
-                                "Enabled"
+                                {move || {
+                                    pipelines.get().into_iter().map(|entry| {
+                                        view! {
+                                            <PipelineRow
+                                                entry=entry
+                                                on_changed=move |msg: String| flash_success(msg)
+                                                on_error=move |err: String| set_error.set(Some(err))
+                                            />
+                                        }
+                                    }).collect_view()
+                                }}
                             </div>
                         </div>
-                    </div>
+                    </Show>
 
                     <div class="settings-divider"></div>
 
@@ -280,6 +913,44 @@ pub fn SettingsPage(on_navigate_to_home: WriteSignal<Page>) -> impl IntoView {
                         </div>
                     </div>
 
+                    <Show when=move || role.can(Permission::ManageSettings)>
+                        <div class="settings-divider"></div>
+
+                        <div class="settings-section">
+                            <h3 style="font-size: 1.125rem; font-weight: 500; margin-bottom: 1rem; color: var(--color-text-secondary);">
+                                "Backups"
+                            </h3>
+                            <BackupPanel
+                                on_changed=move |msg: String| flash_success(msg)
+                                on_error=move |err: String| set_error.set(Some(err))
+                            />
+                        </div>
+
+                        <div class="settings-divider"></div>
+
+                        <div class="settings-section">
+                            <h3 style="font-size: 1.125rem; font-weight: 500; margin-bottom: 1rem; color: var(--color-text-secondary);">
+                                "Encryption"
+                            </h3>
+                            <EncryptionPanel
+                                on_changed=move |msg: String| flash_success(msg)
+                                on_error=move |err: String| set_error.set(Some(err))
+                            />
+                        </div>
+
+                        <div class="settings-divider"></div>
+
+                        <div class="settings-section">
+                            <h3 style="font-size: 1.125rem; font-weight: 500; margin-bottom: 1rem; color: var(--color-text-secondary);">
+                                "Diagnostics"
+                            </h3>
+                            <DiagnosticsPanel
+                                on_changed=move |msg: String| flash_success(msg)
+                                on_error=move |err: String| set_error.set(Some(err))
+                            />
+                        </div>
+                    </Show>
+
                     <div class="settings-divider"></div>
 
                     <div class="settings-section">
@@ -296,3 +967,2821 @@ pub fn SettingsPage(on_navigate_to_home: WriteSignal<Page>) -> impl IntoView {
         </div>
     }
 }
+
+#[component]
+fn DeviceRow(
+    device: Device,
+    ports: Vec<String>,
+    on_changed: impl Fn(String) + Copy + Send + Sync + 'static,
+    on_error: impl Fn(String) + Copy + Send + Sync + 'static,
+) -> impl IntoView {
+    let device_id = device.device_id.clone();
+    let (label, set_label) = signal(device.label.clone());
+    let (port, set_port) = signal(device.port.clone());
+    let (enabled, set_enabled) = signal(device.enabled);
+    let (profile, set_profile) = signal(device.profile.clone());
+    let (schema_spec, set_schema_spec) = signal(device.schema.to_spec());
+    let (show_profile, set_show_profile) = signal(false);
+    let (busy, set_busy) = signal(false);
+
+    // Keep the device's own port selectable even if it is currently unplugged
+    let mut port_options = ports;
+    if !port_options.contains(&device.port) {
+        port_options.insert(0, device.port.clone());
+    }
+
+    let save_id = device_id.clone();
+    let on_save = move |_| {
+        let schema = match ColumnSchema::parse_spec(&schema_spec.get()) {
+            Ok(schema) => schema,
+            Err(e) => {
+                on_error(format!("Invalid column schema: {}", e));
+                return;
+            }
+        };
+        let request = SaveDeviceRequest {
+            device_id: save_id.clone(),
+            label: label.get(),
+            port: port.get(),
+            enabled: enabled.get(),
+            profile: profile.get(),
+            schema,
+        };
+        spawn_local(async move {
+            set_busy.set(true);
+            let device_id = request.device_id.clone();
+            match save_device(request).await {
+                Ok(_) => on_changed(format!("Device {} saved", device_id)),
+                Err(e) => on_error(e),
+            }
+            set_busy.set(false);
+        });
+    };
+
+    let remove_id = device_id.clone();
+    let on_remove = move |_| {
+        let device_id = remove_id.clone();
+        spawn_local(async move {
+            set_busy.set(true);
+            let args = RemoveDeviceArgs {
+                device_id: device_id.clone(),
+            };
+            match invoke(
+                "remove_device",
+                serde_wasm_bindgen::to_value(&args).unwrap(),
+            )
+            .await
+            {
+                Ok(_) => on_changed(format!("Device {} removed", device_id)),
+                Err(e) => on_error(invoke_error("Failed to remove device", e)),
+            }
+            set_busy.set(false);
+        });
+    };
+
+    view! {
+        <div style="padding: 0.75rem; background: var(--color-surface); border: 1px solid var(--color-border-light); border-radius: 6px;">
+        <div style="display: flex; gap: 0.75rem; align-items: center;">
+            <div style="font-family: monospace; font-size: 0.875rem; color: var(--color-text-secondary); min-width: 100px;">
+                {device_id}
+            </div>
+            <input
+                type="text"
+                class="form-input"
+                prop:value=move || label.get()
+                on:input=move |e| set_label.set(event_target_value(&e))
+                style="flex: 1;"
+            />
+            <select
+                class="port-selector"
+                on:change=move |ev| set_port.set(event_target_value(&ev))
+                prop:value=move || port.get()
+                style="flex: 1; padding: 0.625rem; border: 1px solid var(--color-border-medium); border-radius: 6px; background: var(--color-surface); font-family: inherit; font-size: 0.875rem;"
+            >
+                {port_options.into_iter().map(|p| {
+                    let p_value = p.clone();
+                    view! { <option value=p_value>{p}</option> }
+                }).collect::<Vec<_>>()}
+            </select>
+            <label style="display: flex; align-items: center; gap: 0.375rem; font-size: 0.875rem; color: var(--color-text-secondary);">
+                <input
+                    type="checkbox"
+                    prop:checked=move || enabled.get()
+                    on:change=move |e| set_enabled.set(event_target_checked(&e))
+                />
+                "Enabled"
+            </label>
+            <button
+                class="button primary"
+                on:click=on_save
+                disabled=move || busy.get()
+                style="padding: 0.5rem 0.75rem; font-size: 0.875rem;"
+            >
+                "Save"
+            </button>
+            <button
+                class="button"
+                on:click=move |_| set_show_profile.update(|open| *open = !*open)
+                style="padding: 0.5rem 0.75rem; font-size: 0.875rem; white-space: nowrap;"
+            >
+                {move || profile.get().summary()}
+            </button>
+            <button
+                class="button"
+                on:click=on_remove
+                disabled=move || busy.get()
+                style="padding: 0.5rem 0.75rem; font-size: 0.875rem; color: var(--color-error);"
+            >
+                "Remove"
+            </button>
+        </div>
+        {move || show_profile.get().then(|| view! {
+            <SerialProfileEditor profile=profile set_profile=set_profile />
+            <label style="display: flex; flex-direction: column; gap: 0.25rem; margin-top: 0.75rem; font-size: 0.75rem; color: var(--color-text-secondary);">
+                "CSV columns (timestamp, index, channel [unit] (min..max), - to skip)"
+                <input
+                    type="text"
+                    class="form-input"
+                    prop:value=move || schema_spec.get()
+                    on:input=move |e| set_schema_spec.set(event_target_value(&e))
+                    style="font-family: monospace;"
+                />
+            </label>
+        })}
+        </div>
+    }
+}
+
+const BAUD_RATES: [u32; 8] = [1200, 2400, 4800, 9600, 19200, 38400, 57600, 115200];
+
+#[component]
+fn SerialProfileEditor(
+    profile: ReadSignal<SerialProfile>,
+    set_profile: WriteSignal<SerialProfile>,
+) -> impl IntoView {
+    let field_style = "display: flex; flex-direction: column; gap: 0.25rem; font-size: 0.75rem; color: var(--color-text-secondary);";
+
+    view! {
+        <div style="display: grid; grid-template-columns: repeat(auto-fit, minmax(120px, 1fr)); gap: 0.75rem; margin-top: 0.75rem; padding-top: 0.75rem; border-top: 1px solid var(--color-border-light);">
+            <label style=field_style>
+                "Baud rate"
+                <select
+                    class="form-input"
+                    prop:value=move || profile.get().baud_rate.to_string()
+                    on:change=move |e| {
+                        if let Ok(baud_rate) = event_target_value(&e).parse() {
+                            set_profile.update(|p| p.baud_rate = baud_rate);
+                        }
+                    }
+                >
+                    {BAUD_RATES.iter().map(|rate| {
+                        let value = rate.to_string();
+                        let text = value.clone();
+                        view! { <option value=value>{text}</option> }
+                    }).collect::<Vec<_>>()}
+                </select>
+            </label>
+            <label style=field_style>
+                "Data bits"
+                <select
+                    class="form-input"
+                    prop:value=move || profile.get().data_bits.to_string()
+                    on:change=move |e| {
+                        if let Ok(data_bits) = event_target_value(&e).parse() {
+                            set_profile.update(|p| p.data_bits = data_bits);
+                        }
+                    }
+                >
+                    <option value="5">"5"</option>
+                    <option value="6">"6"</option>
+                    <option value="7">"7"</option>
+                    <option value="8">"8"</option>
+                </select>
+            </label>
+            <label style=field_style>
+                "Parity"
+                <select
+                    class="form-input"
+                    prop:value=move || profile.get().parity.as_str()
+                    on:change=move |e| {
+                        if let Ok(parity) = Parity::from_str(&event_target_value(&e)) {
+                            set_profile.update(|p| p.parity = parity);
+                        }
+                    }
+                >
+                    <option value="none">"None"</option>
+                    <option value="odd">"Odd"</option>
+                    <option value="even">"Even"</option>
+                </select>
+            </label>
+            <label style=field_style>
+                "Stop bits"
+                <select
+                    class="form-input"
+                    prop:value=move || profile.get().stop_bits.to_string()
+                    on:change=move |e| {
+                        if let Ok(stop_bits) = event_target_value(&e).parse() {
+                            set_profile.update(|p| p.stop_bits = stop_bits);
+                        }
+                    }
+                >
+                    <option value="1">"1"</option>
+                    <option value="2">"2"</option>
+                </select>
+            </label>
+            <label style=field_style>
+                "Flow control"
+                <select
+                    class="form-input"
+                    prop:value=move || profile.get().flow_control.as_str()
+                    on:change=move |e| {
+                        if let Ok(flow_control) = FlowControl::from_str(&event_target_value(&e)) {
+                            set_profile.update(|p| p.flow_control = flow_control);
+                        }
+                    }
+                >
+                    <option value="none">"None"</option>
+                    <option value="software">"Software (XON/XOFF)"</option>
+                    <option value="hardware">"Hardware (RTS/CTS)"</option>
+                </select>
+            </label>
+            <label style=field_style>
+                "Read timeout (ms)"
+                <input
+                    type="number"
+                    class="form-input"
+                    min="1"
+                    max="10000"
+                    prop:value=move || profile.get().read_timeout_ms.to_string()
+                    on:change=move |e| {
+                        if let Ok(timeout) = event_target_value(&e).parse() {
+                            set_profile.update(|p| p.read_timeout_ms = timeout);
+                        }
+                    }
+                />
+            </label>
+            <label style=field_style>
+                "Chunk size (bytes)"
+                <input
+                    type="number"
+                    class="form-input"
+                    min="1"
+                    max="65536"
+                    prop:value=move || profile.get().chunk_size.to_string()
+                    on:change=move |e| {
+                        if let Ok(chunk_size) = event_target_value(&e).parse() {
+                            set_profile.update(|p| p.chunk_size = chunk_size);
+                        }
+                    }
+                />
+            </label>
+        </div>
+    }
+}
+
+#[component]
+fn PipelineRow(
+    entry: TestTypePipeline,
+    on_changed: impl Fn(String) + Copy + Send + Sync + 'static,
+    on_error: impl Fn(String) + Copy + Send + Sync + 'static,
+) -> impl IntoView {
+    let test_type = entry.test_type;
+    let name = test_type.as_str();
+    let (spec, set_spec) = signal(entry.pipeline.to_spec());
+    let (busy, set_busy) = signal(false);
+
+    let on_save = move |_| {
+        let pipeline = match PreprocessingPipeline::parse_spec(&spec.get()) {
+            Ok(pipeline) => pipeline,
+            Err(e) => {
+                on_error(format!("Invalid pipeline for {}: {}", name, e));
+                return;
+            }
+        };
+        let test_type = test_type.clone();
+        spawn_local(async move {
+            set_busy.set(true);
+            let args = SavePipelineArgs {
+                test_type,
+                pipeline,
+            };
+            match invoke(
+                "save_preprocessing_pipeline",
+                serde_wasm_bindgen::to_value(&args).unwrap(),
+            )
+            .await
+            {
+                Ok(_) => on_changed(format!("Preprocessing for {} saved", name)),
+                Err(e) => on_error(invoke_error("Failed to save preprocessing", e)),
+            }
+            set_busy.set(false);
+        });
+    };
+
+    view! {
+        <div style="display: flex; gap: 0.75rem; align-items: center;">
+            <div style="width: 140px; font-weight: 500; color: var(--color-text-primary);">
+                {name}
+            </div>
+            <input
+                type="text"
+                class="form-input"
+                placeholder="No preprocessing"
+                prop:value=move || spec.get()
+                on:input=move |e| set_spec.set(event_target_value(&e))
+                style="flex: 1; font-family: monospace; font-size: 0.8125rem;"
+            />
+            <button
+                class="button"
+                on:click=on_save
+                disabled=move || busy.get()
+                style="padding: 0.5rem 0.75rem; font-size: 0.875rem;"
+            >
+                "Save"
+            </button>
+        </div>
+    }
+}
+
+#[component]
+fn BackupPanel(
+    on_changed: impl Fn(String) + Copy + Send + Sync + 'static,
+    on_error: impl Fn(String) + Copy + Send + Sync + 'static,
+) -> impl IntoView {
+    let (backups, set_backups) = signal(Vec::<BackupInfo>::new());
+    let (enabled, set_enabled) = signal(true);
+    let (interval_hours, set_interval_hours) = signal(24u32);
+    let (keep, set_keep) = signal(14usize);
+    let (restore_path, set_restore_path) = signal(String::new());
+    // Path awaiting a second click before it replaces all data
+    let (confirming, set_confirming) = signal(None::<String>);
+    let (busy, set_busy) = signal(false);
+
+    let reload = move || {
+        spawn_local(async move {
+            match fetch_backups().await {
+                Ok(list) => set_backups.set(list),
+                Err(e) => on_error(e),
+            }
+        });
+    };
+
+    spawn_local(async move {
+        match fetch_backup_schedule().await {
+            Ok(schedule) => {
+                set_enabled.set(schedule.enabled);
+                set_interval_hours.set(schedule.interval_hours);
+                set_keep.set(schedule.keep);
+            }
+            Err(e) => on_error(e),
+        }
+    });
+    reload();
+
+    let save_schedule = move |_| {
+        let args = SaveBackupScheduleArgs {
+            schedule: BackupSchedule {
+                enabled: enabled.get(),
+                interval_hours: interval_hours.get(),
+                keep: keep.get(),
+            },
+        };
+        spawn_local(async move {
+            match invoke(
+                "save_backup_schedule",
+                serde_wasm_bindgen::to_value(&args).unwrap(),
+            )
+            .await
+            {
+                Ok(_) => on_changed("Backup schedule saved".to_string()),
+                Err(e) => on_error(invoke_error("Failed to save backup schedule", e)),
+            }
+        });
+    };
+
+    let back_up_now = move |_| {
+        let args = BackupDatabaseArgs { destination: None };
+        spawn_local(async move {
+            set_busy.set(true);
+            match invoke(
+                "backup_database",
+                serde_wasm_bindgen::to_value(&args).unwrap(),
+            )
+            .await
+            {
+                Ok(value) => {
+                    if let Ok(info) = serde_wasm_bindgen::from_value::<BackupInfo>(value) {
+                        on_changed(format!("Backup saved as {}", info.file_name));
+                    }
+                    reload();
+                }
+                Err(e) => on_error(invoke_error("Failed to back up database", e)),
+            }
+            set_busy.set(false);
+        });
+    };
+
+    let check_integrity = move |_| {
+        spawn_local(async move {
+            set_busy.set(true);
+            match invoke("check_database_integrity", JsValue::NULL).await {
+                Ok(value) => match serde_wasm_bindgen::from_value::<IntegrityReport>(value) {
+                    Ok(report) if report.ok => on_changed("Database integrity OK".to_string()),
+                    Ok(report) => on_error(format!(
+                        "Database integrity check found problems: {}",
+                        report.problems.join("; ")
+                    )),
+                    Err(e) => on_error(format!("Failed to parse integrity report: {:?}", e)),
+                },
+                Err(e) => on_error(invoke_error("Failed to check database integrity", e)),
+            }
+            set_busy.set(false);
+        });
+    };
+
+    let restore = move |path: String| {
+        set_confirming.set(None);
+        let args = RestoreDatabaseArgs { path };
+        spawn_local(async move {
+            set_busy.set(true);
+            match invoke(
+                "restore_database",
+                serde_wasm_bindgen::to_value(&args).unwrap(),
+            )
+            .await
+            {
+                Ok(value) => {
+                    if let Ok(report) = serde_wasm_bindgen::from_value::<RestoreReport>(value) {
+                        on_changed(format!(
+                            "Database restored; previous data saved as {}",
+                            report.safety_backup.file_name
+                        ));
+                    }
+                    set_restore_path.set(String::new());
+                    reload();
+                }
+                Err(e) => on_error(invoke_error("Failed to restore database", e)),
+            }
+            set_busy.set(false);
+        });
+    };
+
+    // First click arms the restore, second click runs it
+    let request_restore = move |path: String| {
+        if confirming.get().as_deref() == Some(path.as_str()) {
+            restore(path);
+        } else {
+            set_confirming.set(Some(path));
+        }
+    };
+
+    view! {
+        <div class="settings-item" style="flex-direction: column; align-items: stretch; gap: 0.75rem;">
+            <div style="display: flex; gap: 1rem; align-items: center; flex-wrap: wrap;">
+                <label style="display: flex; gap: 0.5rem; align-items: center; font-size: 0.875rem;">
+                    <input
+                        type="checkbox"
+                        prop:checked=move || enabled.get()
+                        on:change=move |e| set_enabled.set(event_target_checked(&e))
+                    />
+                    "Automatic backups"
+                </label>
+                <label style="display: flex; gap: 0.5rem; align-items: center; font-size: 0.875rem;">
+                    "Every"
+                    <input
+                        type="number"
+                        class="form-input"
+                        min="1"
+                        prop:value=move || interval_hours.get().to_string()
+                        on:input=move |e| {
+                            if let Ok(v) = event_target_value(&e).parse() {
+                                set_interval_hours.set(v);
+                            }
+                        }
+                        style="width: 5rem;"
+                    />
+                    "hours, keep"
+                    <input
+                        type="number"
+                        class="form-input"
+                        min="1"
+                        prop:value=move || keep.get().to_string()
+                        on:input=move |e| {
+                            if let Ok(v) = event_target_value(&e).parse() {
+                                set_keep.set(v);
+                            }
+                        }
+                        style="width: 5rem;"
+                    />
+                </label>
+                <button
+                    class="button"
+                    on:click=save_schedule
+                    style="padding: 0.5rem 0.75rem; font-size: 0.875rem;"
+                >
+                    "Save Schedule"
+                </button>
+            </div>
+
+            <div style="display: flex; gap: 0.75rem;">
+                <button
+                    class="button"
+                    on:click=back_up_now
+                    disabled=move || busy.get()
+                    style="padding: 0.5rem 0.75rem; font-size: 0.875rem;"
+                >
+                    "Back Up Now"
+                </button>
+                <button
+                    class="button"
+                    on:click=check_integrity
+                    disabled=move || busy.get()
+                    style="padding: 0.5rem 0.75rem; font-size: 0.875rem;"
+                >
+                    "Check Integrity"
+                </button>
+            </div>
+
+            <div style="display: flex; flex-direction: column; gap: 0.5rem; max-height: 240px; overflow: auto;">
+                {move || {
+                    let list = backups.get();
+                    if list.is_empty() {
+                        view! {
+                            <div style="font-size: 0.875rem; color: var(--color-text-secondary);">
+                                "No backups yet"
+                            </div>
+                        }
+                            .into_any()
+                    } else {
+                        list.into_iter()
+                            .map(|backup| {
+                                let path = backup.path.clone();
+                                let armed = {
+                                    let path = path.clone();
+                                    move || confirming.get().as_deref() == Some(path.as_str())
+                                };
+                                view! {
+                                    <div style="display: flex; justify-content: space-between; align-items: center; font-size: 0.8125rem;">
+                                        <div>
+                                            <div style="font-family: monospace;">{backup.file_name.clone()}</div>
+                                            <div style="color: var(--color-text-secondary);">
+                                                {format!(
+                                                    "{} · {:.1} MB · {}",
+                                                    backup.kind,
+                                                    backup.size_bytes as f64 / 1_048_576.0,
+                                                    backup.created_at.clone().unwrap_or_default()
+                                                )}
+                                            </div>
+                                        </div>
+                                        <button
+                                            class="button"
+                                            on:click=move |_| request_restore(path.clone())
+                                            disabled=move || busy.get()
+                                            style="padding: 0.25rem 0.5rem; font-size: 0.8125rem;"
+                                        >
+                                            {move || if armed() { "Confirm Restore" } else { "Restore" }}
+                                        </button>
+                                    </div>
+                                }
+                            })
+                            .collect_view()
+                            .into_any()
+                    }
+                }}
+            </div>
+
+            <div style="display: flex; gap: 0.75rem; align-items: center;">
+                <input
+                    type="text"
+                    class="form-input"
+                    placeholder="Restore from another file, e.g. D:\\ebers-backup.db"
+                    prop:value=move || restore_path.get()
+                    on:input=move |e| set_restore_path.set(event_target_value(&e))
+                    style="flex: 1; font-family: monospace; font-size: 0.8125rem;"
+                />
+                <button
+                    class="button"
+                    on:click=move |_| request_restore(restore_path.get())
+                    disabled=move || busy.get() || restore_path.get().trim().is_empty()
+                    style="padding: 0.5rem 0.75rem; font-size: 0.875rem; white-space: nowrap;"
+                >
+                    {move || {
+                        let path = restore_path.get();
+                        if !path.is_empty() && confirming.get().as_deref() == Some(path.as_str()) {
+                            "Confirm Restore"
+                        } else {
+                            "Restore File"
+                        }
+                    }}
+                </button>
+            </div>
+            <div style="font-size: 0.75rem; color: var(--color-text-secondary);">
+                "Restoring replaces all patients and tests; the current data is backed up first"
+            </div>
+        </div>
+    }
+}
+
+/// Label for an encryption mode as the backend names it
+fn encryption_label(mode: &str) -> &'static str {
+    match mode {
+        "keyring" => "Encrypted, key in OS keyring",
+        "passphrase" => "Encrypted, passphrase at startup",
+        _ => "Not encrypted",
+    }
+}
+
+#[component]
+fn EncryptionPanel(
+    on_changed: impl Fn(String) + Copy + Send + Sync + 'static,
+    on_error: impl Fn(String) + Copy + Send + Sync + 'static,
+) -> impl IntoView {
+    let (status, set_status) = signal(None::<EncryptionStatus>);
+    let (mode, set_mode) = signal(String::from("none"));
+    let (passphrase, set_passphrase) = signal(String::new());
+    let (confirm, set_confirm) = signal(String::new());
+    // Rewriting the database is slow and hard to undo, so ask twice
+    let (armed, set_armed) = signal(false);
+    let (busy, set_busy) = signal(false);
+
+    spawn_local(async move {
+        match invoke("get_encryption_status", JsValue::NULL).await {
+            Ok(value) => match serde_wasm_bindgen::from_value::<EncryptionStatus>(value) {
+                Ok(current) => {
+                    set_mode.set(current.mode.clone());
+                    set_status.set(Some(current));
+                }
+                Err(e) => on_error(format!("Failed to parse encryption status: {:?}", e)),
+            },
+            Err(e) => on_error(invoke_error("Failed to load encryption status", e)),
+        }
+    });
+
+    let apply = move |_| {
+        if !armed.get() {
+            set_armed.set(true);
+            return;
+        }
+        set_armed.set(false);
+
+        let target = mode.get();
+        if target == "passphrase" && passphrase.get() != confirm.get() {
+            on_error("Passphrases do not match".to_string());
+            return;
+        }
+        let args = SetDatabaseEncryptionArgs {
+            passphrase: (target == "passphrase").then(|| passphrase.get()),
+            mode: target,
+        };
+        spawn_local(async move {
+            set_busy.set(true);
+            match invoke(
+                "set_database_encryption",
+                serde_wasm_bindgen::to_value(&args).unwrap(),
+            )
+            .await
+            {
+                Ok(value) => {
+                    if let Ok(updated) = serde_wasm_bindgen::from_value::<EncryptionStatus>(value) {
+                        on_changed(format!(
+                            "Database now: {}",
+                            encryption_label(&updated.mode).to_lowercase()
+                        ));
+                        set_status.set(Some(updated));
+                    }
+                    set_passphrase.set(String::new());
+                    set_confirm.set(String::new());
+                }
+                Err(e) => on_error(invoke_error("Failed to change database encryption", e)),
+            }
+            set_busy.set(false);
+        });
+    };
+
+    view! {
+        <div class="settings-item" style="flex-direction: column; align-items: stretch; gap: 0.75rem;">
+            <div>
+                <div style="font-weight: 500; color: var(--color-text-primary);">
+                    {move || status.get().map(|s| encryption_label(&s.mode)).unwrap_or("Loading...")}
+                </div>
+                {move || status.get().and_then(|s| s.key_file).map(|path| view! {
+                    <div style="font-size: 0.875rem; color: var(--color-warning);">
+                        {format!("No OS keyring available; the key is stored in {}", path)}
+                    </div>
+                })}
+            </div>
+
+            <div style="display: flex; gap: 0.75rem; align-items: center; flex-wrap: wrap;">
+                <select
+                    class="port-selector"
+                    on:change=move |ev| {
+                        set_mode.set(event_target_value(&ev));
+                        set_armed.set(false);
+                    }
+                    prop:value=move || mode.get()
+                    style="flex: 1; padding: 0.625rem; border: 1px solid var(--color-border-medium); border-radius: 6px; background: var(--color-surface); font-family: inherit; font-size: 0.875rem;"
+                >
+                    <option value="none">{encryption_label("none")}</option>
+                    <option value="keyring">{encryption_label("keyring")}</option>
+                    <option value="passphrase">{encryption_label("passphrase")}</option>
+                </select>
+                <button
+                    class="button"
+                    on:click=apply
+                    disabled=move || busy.get()
+                    style="padding: 0.5rem 0.75rem; font-size: 0.875rem; white-space: nowrap;"
+                >
+                    {move || if busy.get() {
+                        "Applying..."
+                    } else if armed.get() {
+                        "Confirm Change"
+                    } else {
+                        "Apply"
+                    }}
+                </button>
+            </div>
+
+            <Show when=move || mode.get() == "passphrase">
+                <div style="display: flex; gap: 0.75rem;">
+                    <input
+                        type="password"
+                        class="form-input"
+                        placeholder="New passphrase (12+ characters)"
+                        prop:value=move || passphrase.get()
+                        on:input=move |e| set_passphrase.set(event_target_value(&e))
+                        style="flex: 1;"
+                    />
+                    <input
+                        type="password"
+                        class="form-input"
+                        placeholder="Confirm passphrase"
+                        prop:value=move || confirm.get()
+                        on:input=move |e| set_confirm.set(event_target_value(&e))
+                        style="flex: 1;"
+                    />
+                </div>
+            </Show>
+
+            <div style="font-size: 0.75rem; color: var(--color-text-secondary);">
+                "The database is rewritten under the new key. Existing backups keep their previous protection; a lost passphrase cannot be recovered."
+            </div>
+        </div>
+    }
+}
+
+#[component]
+fn DiagnosticsPanel(
+    on_changed: impl Fn(String) + Copy + Send + Sync + 'static,
+    on_error: impl Fn(String) + Copy + Send + Sync + 'static,
+) -> impl IntoView {
+    let (snapshot, set_snapshot) = signal(None::<LogSnapshot>);
+    let (filter, set_filter) = signal(String::new());
+    let (busy, set_busy) = signal(false);
+
+    let load_logs = move || {
+        spawn_local(async move {
+            set_busy.set(true);
+            match fetch_logs(500).await {
+                Ok(logs) => {
+                    set_filter.set(logs.filter.clone());
+                    set_snapshot.set(Some(logs));
+                }
+                Err(e) => on_error(e),
+            }
+            set_busy.set(false);
+        });
+    };
+
+    let apply_filter = move |_| {
+        let args = SetLogFilterArgs {
+            filter: filter.get(),
+        };
+        spawn_local(async move {
+            match invoke(
+                "set_log_filter",
+                serde_wasm_bindgen::to_value(&args).unwrap(),
+            )
+            .await
+            {
+                Ok(value) => {
+                    let applied = value.as_string().unwrap_or_default();
+                    set_filter.set(applied.clone());
+                    on_changed(format!("Log filter set to {}", applied));
+                }
+                Err(e) => on_error(invoke_error("Failed to set log filter", e)),
+            }
+        });
+    };
+
+    view! {
+        <div class="settings-item" style="flex-direction: column; align-items: stretch; gap: 0.75rem;">
+            <div style="display: flex; justify-content: space-between; align-items: center;">
+                <div>
+                    <div style="font-weight: 500; color: var(--color-text-primary);">
+                        "Application logs"
+                    </div>
+                    <div style="font-size: 0.875rem; color: var(--color-text-secondary);">
+                        "Patient names, dates of birth, emails and phone numbers are redacted"
+                    </div>
+                </div>
+                <button
+                    class="button"
+                    on:click=move |_| load_logs()
+                    disabled=move || busy.get()
+                    style="padding: 0.5rem 0.75rem; font-size: 0.875rem;"
+                >
+                    {move || if busy.get() { "Loading..." } else { "Load Logs" }}
+                </button>
+            </div>
+
+            <div style="display: flex; gap: 0.75rem; align-items: center;">
+                <input
+                    type="text"
+                    class="form-input"
+                    placeholder="info,ebers_lib::serial_handler=debug"
+                    prop:value=move || filter.get()
+                    on:input=move |e| set_filter.set(event_target_value(&e))
+                    style="flex: 1; font-family: monospace; font-size: 0.8125rem;"
+                />
+                <button
+                    class="button"
+                    on:click=apply_filter
+                    style="padding: 0.5rem 0.75rem; font-size: 0.875rem; white-space: nowrap;"
+                >
+                    "Apply Filter"
+                </button>
+            </div>
+
+            {move || snapshot.get().map(|logs| {
+                view! {
+                    <div style="font-size: 0.75rem; color: var(--color-text-secondary);">
+                        {format!(
+                            "{} lines from {}{}",
+                            logs.lines.len(),
+                            logs.log_dir,
+                            if logs.truncated { " (older lines omitted)" } else { "" }
+                        )}
+                    </div>
+                    <pre style="max-height: 320px; overflow: auto; margin: 0; padding: 0.75rem; background: var(--color-surface); border: 1px solid var(--color-border-medium); border-radius: 6px; font-size: 0.75rem; white-space: pre-wrap;">
+                        {logs.lines.join("\n")}
+                    </pre>
+                }
+            })}
+        </div>
+    }
+}
+
+#[component]
+fn AccountPanel(current_user: ReadSignal<Option<User>>) -> impl IntoView {
+    let (current_password, set_current_password) = signal(String::new());
+    let (new_password, set_new_password) = signal(String::new());
+    let (confirm, set_confirm) = signal(String::new());
+    let (busy, set_busy) = signal(false);
+    let (pin_password, set_pin_password) = signal(String::new());
+    let (pin, set_pin) = signal(String::new());
+    let (has_pin, set_has_pin) = signal(
+        current_user
+            .get_untracked()
+            .is_some_and(|user| user.has_pin),
+    );
+    // Only admins see the shared message box, so this panel keeps its own
+    let (message, set_message) = signal(None::<Result<String, String>>);
+
+    // An empty PIN removes it; unlocking then needs the password
+    let save_pin = move |pin: Option<String>| {
+        let removing = pin.is_none();
+        let args = SetPinArgs {
+            current_password: pin_password.get(),
+            pin,
+        };
+        spawn_local(async move {
+            set_busy.set(true);
+            match invoke("set_pin", serde_wasm_bindgen::to_value(&args).unwrap()).await {
+                Ok(value) => {
+                    set_pin_password.set(String::new());
+                    set_pin.set(String::new());
+                    if let Ok(user) = serde_wasm_bindgen::from_value::<User>(value) {
+                        set_has_pin.set(user.has_pin);
+                    }
+                    let msg = if removing { "PIN removed" } else { "PIN saved" };
+                    set_message.set(Some(Ok(msg.to_string())));
+                }
+                Err(e) => set_message.set(Some(Err(invoke_error("Failed to save PIN", e)))),
+            }
+            set_busy.set(false);
+        });
+    };
+
+    let change_password = move |_| {
+        if new_password.get() != confirm.get() {
+            set_message.set(Some(Err("Passwords do not match".to_string())));
+            return;
+        }
+        let args = ChangePasswordArgs {
+            current_password: current_password.get(),
+            new_password: new_password.get(),
+        };
+        spawn_local(async move {
+            set_busy.set(true);
+            match invoke(
+                "change_password",
+                serde_wasm_bindgen::to_value(&args).unwrap(),
+            )
+            .await
+            {
+                Ok(_) => {
+                    set_current_password.set(String::new());
+                    set_new_password.set(String::new());
+                    set_confirm.set(String::new());
+                    set_message.set(Some(Ok("Password changed".to_string())));
+                }
+                Err(e) => set_message.set(Some(Err(invoke_error("Failed to change password", e)))),
+            }
+            set_busy.set(false);
+        });
+    };
+
+    view! {
+        <div class="settings-item" style="flex-direction: column; align-items: stretch; gap: 0.75rem;">
+            <div>
+                <div style="font-weight: 500; color: var(--color-text-primary);">
+                    {move || current_user.get().map(|u| u.display_name).unwrap_or_default()}
+                </div>
+                <div style="font-size: 0.875rem; color: var(--color-text-secondary);">
+                    {move || current_user.get().map(|u| format!("{} · {}", u.username, u.role.label())).unwrap_or_default()}
+                </div>
+            </div>
+
+            <div style="display: flex; gap: 0.75rem; align-items: center;">
+                <input
+                    type="password"
+                    class="form-input"
+                    placeholder="Current password"
+                    prop:value=move || current_password.get()
+                    on:input=move |e| set_current_password.set(event_target_value(&e))
+                    style="flex: 1;"
+                />
+                <input
+                    type="password"
+                    class="form-input"
+                    placeholder="New password"
+                    prop:value=move || new_password.get()
+                    on:input=move |e| set_new_password.set(event_target_value(&e))
+                    style="flex: 1;"
+                />
+                <input
+                    type="password"
+                    class="form-input"
+                    placeholder="Confirm new password"
+                    prop:value=move || confirm.get()
+                    on:input=move |e| set_confirm.set(event_target_value(&e))
+                    style="flex: 1;"
+                />
+                <button
+                    class="button"
+                    on:click=change_password
+                    disabled=move || busy.get() || current_password.get().is_empty() || new_password.get().is_empty()
+                    style="padding: 0.5rem 0.75rem; font-size: 0.875rem; white-space: nowrap;"
+                >
+                    "Change Password"
+                </button>
+            </div>
+
+            <div style="font-size: 0.875rem; color: var(--color-text-secondary);">
+                {move || if has_pin.get() {
+                    "A PIN is set; it unlocks a locked session instead of your password"
+                } else {
+                    "Set a 4 to 8 digit PIN to unlock a locked session quickly"
+                }}
+            </div>
+            <div style="display: flex; gap: 0.75rem; align-items: center;">
+                <input
+                    type="password"
+                    class="form-input"
+                    placeholder="Current password"
+                    prop:value=move || pin_password.get()
+                    on:input=move |e| set_pin_password.set(event_target_value(&e))
+                    style="flex: 1;"
+                />
+                <input
+                    type="password"
+                    class="form-input"
+                    inputmode="numeric"
+                    placeholder="New PIN"
+                    prop:value=move || pin.get()
+                    on:input=move |e| set_pin.set(event_target_value(&e))
+                    style="flex: 1;"
+                />
+                <button
+                    class="button"
+                    on:click=move |_| save_pin(Some(pin.get()))
+                    disabled=move || busy.get() || pin_password.get().is_empty() || pin.get().is_empty()
+                    style="padding: 0.5rem 0.75rem; font-size: 0.875rem; white-space: nowrap;"
+                >
+                    "Set PIN"
+                </button>
+                <Show when=move || has_pin.get()>
+                    <button
+                        class="button"
+                        on:click=move |_| save_pin(None)
+                        disabled=move || busy.get() || pin_password.get().is_empty()
+                        style="padding: 0.5rem 0.75rem; font-size: 0.875rem; white-space: nowrap;"
+                    >
+                        "Remove PIN"
+                    </button>
+                </Show>
+            </div>
+
+            {move || message.get().map(|result| match result {
+                Ok(msg) => view! {
+                    <div style="padding: 0.75rem; background: #d4edda; border: 1px solid #c3e6cb; border-radius: 6px; color: #155724; font-size: 0.875rem;">
+                        {msg}
+                    </div>
+                }.into_any(),
+                Err(err) => view! {
+                    <div style="padding: 0.75rem; background: #f8d7da; border: 1px solid #f5c6cb; border-radius: 6px; color: #721c24; font-size: 0.875rem;">
+                        {err}
+                    </div>
+                }.into_any(),
+            })}
+        </div>
+    }
+}
+
+/// Whether completed results wait for a reviewer before release
+#[component]
+fn ReviewSettingPanel(
+    on_changed: impl Fn(String) + Copy + Send + Sync + 'static,
+    on_error: impl Fn(String) + Copy + Send + Sync + 'static,
+) -> impl IntoView {
+    let (required, set_required) = signal(false);
+
+    spawn_local(async move {
+        let args = GetSettingArgs {
+            key: REVIEW_SETTING.to_string(),
+        };
+        match invoke("get_setting", serde_wasm_bindgen::to_value(&args).unwrap()).await {
+            Ok(value) => {
+                if let Ok(Some(value)) = serde_wasm_bindgen::from_value::<Option<String>>(value) {
+                    set_required.set(value == "true");
+                }
+            }
+            Err(e) => on_error(invoke_error("Failed to load review setting", e)),
+        }
+    });
+
+    let toggle = move |ev| {
+        let enabled = event_target_checked(&ev);
+        let args = SaveSettingArgs {
+            key: REVIEW_SETTING.to_string(),
+            value: enabled.to_string(),
+        };
+        spawn_local(async move {
+            match invoke("save_setting", serde_wasm_bindgen::to_value(&args).unwrap()).await {
+                Ok(_) => {
+                    set_required.set(enabled);
+                    on_changed(if enabled {
+                        "Results now wait for review before release".to_string()
+                    } else {
+                        "Results are released on completion".to_string()
+                    });
+                }
+                Err(e) => on_error(invoke_error("Failed to save review setting", e)),
+            }
+        });
+    };
+
+    view! {
+        <div class="settings-item">
+            <div>
+                <div style="font-weight: 500; color: var(--color-text-primary);">
+                    "Require review before release"
+                </div>
+                <div style="font-size: 0.875rem; color: var(--color-text-secondary);">
+                    "Completed results wait for a reviewer to accept, override or reject them; only released results can be exported"
+                </div>
+            </div>
+            <input type="checkbox" prop:checked=move || required.get() on:change=toggle />
+        </div>
+    }
+}
+
+/// Clinic name, address, contact line and logo shown on PDF reports
+#[component]
+fn ReportSettingsPanel() -> impl IntoView {
+    let (clinic_name, set_clinic_name) = signal(String::new());
+    let (address, set_address) = signal(String::new());
+    let (contact, set_contact) = signal(String::new());
+    let (logo_path, set_logo_path) = signal(String::new());
+    let (busy, set_busy) = signal(false);
+    let (message, set_message) = signal(None::<Result<String, String>>);
+
+    let apply = move |settings: ReportSettings| {
+        set_clinic_name.set(settings.clinic_name);
+        set_address.set(settings.address);
+        set_contact.set(settings.contact);
+        set_logo_path.set(settings.logo_path.unwrap_or_default());
+    };
+
+    spawn_local(async move {
+        match fetch_report_settings().await {
+            Ok(settings) => apply(settings),
+            Err(err) => set_message.set(Some(Err(err))),
+        }
+    });
+
+    let save = move |_| {
+        let args = SaveReportSettingsArgs {
+            settings: ReportSettings {
+                clinic_name: clinic_name.get(),
+                address: address.get(),
+                contact: contact.get(),
+                logo_path: Some(logo_path.get()),
+            },
+        };
+        spawn_local(async move {
+            set_busy.set(true);
+            match invoke(
+                "save_report_settings",
+                serde_wasm_bindgen::to_value(&args).unwrap(),
+            )
+            .await
+            {
+                Ok(value) => {
+                    if let Ok(settings) = serde_wasm_bindgen::from_value::<ReportSettings>(value) {
+                        apply(settings);
+                    }
+                    set_message.set(Some(Ok("Report header saved".to_string())));
+                }
+                Err(e) => {
+                    set_message.set(Some(Err(invoke_error("Failed to save report settings", e))))
+                }
+            }
+            set_busy.set(false);
+        });
+    };
+
+    view! {
+        <div class="settings-item" style="flex-direction: column; align-items: stretch; gap: 0.75rem;">
+            <div style="font-size: 0.875rem; color: var(--color-text-secondary);">
+                "Printed at the top of every PDF test report"
+            </div>
+            <div class="form-group">
+                <label class="form-label">"Clinic name"</label>
+                <input
+                    type="text"
+                    class="form-input"
+                    prop:value=move || clinic_name.get()
+                    on:input=move |e| set_clinic_name.set(event_target_value(&e))
+                />
+            </div>
+            <div class="form-group">
+                <label class="form-label">"Address"</label>
+                <textarea
+                    class="form-input"
+                    rows="3"
+                    prop:value=move || address.get()
+                    on:input=move |e| set_address.set(event_target_value(&e))
+                ></textarea>
+            </div>
+            <div class="form-group">
+                <label class="form-label">"Contact"</label>
+                <input
+                    type="text"
+                    class="form-input"
+                    placeholder="Phone, email or website"
+                    prop:value=move || contact.get()
+                    on:input=move |e| set_contact.set(event_target_value(&e))
+                />
+            </div>
+            <div class="form-group">
+                <label class="form-label">"Logo file (PNG or JPEG)"</label>
+                <input
+                    type="text"
+                    class="form-input"
+                    placeholder="Full path to the image; leave empty for no logo"
+                    prop:value=move || logo_path.get()
+                    on:input=move |e| set_logo_path.set(event_target_value(&e))
+                />
+            </div>
+            <div style="display: flex; justify-content: flex-end;">
+                <button
+                    class="button"
+                    on:click=save
+                    disabled=move || busy.get()
+                    style="padding: 0.5rem 0.75rem; font-size: 0.875rem;"
+                >
+                    "Save"
+                </button>
+            </div>
+
+            {move || message.get().map(|result| match result {
+                Ok(msg) => view! {
+                    <div style="padding: 0.75rem; background: #d4edda; border: 1px solid #c3e6cb; border-radius: 6px; color: #155724; font-size: 0.875rem;">
+                        {msg}
+                    </div>
+                }.into_any(),
+                Err(err) => view! {
+                    <div style="padding: 0.75rem; background: #f8d7da; border: 1px solid #f5c6cb; border-radius: 6px; color: #721c24; font-size: 0.875rem;">
+                        {err}
+                    </div>
+                }.into_any(),
+            })}
+        </div>
+    }
+}
+
+/// FHIR R4 endpoint that released results are POSTed to as transaction bundles
+#[component]
+fn FhirSettingsPanel() -> impl IntoView {
+    let (settings, set_settings) = signal(None::<FhirSettings>);
+    let (busy, set_busy) = signal(false);
+    let (message, set_message) = signal(None::<Result<String, String>>);
+
+    spawn_local(async move {
+        match fetch_fhir_settings().await {
+            Ok(loaded) => set_settings.set(Some(loaded)),
+            Err(err) => set_message.set(Some(Err(err))),
+        }
+    });
+
+    let update = move |apply: fn(&mut FhirSettings, String), value: String| {
+        set_settings.update(|settings| {
+            if let Some(settings) = settings {
+                apply(settings, value);
+            }
+        });
+    };
+
+    let save = move |_| {
+        let Some(settings) = settings.get() else {
+            return;
+        };
+        let args = SaveFhirSettingsArgs { settings };
+        spawn_local(async move {
+            set_busy.set(true);
+            match invoke(
+                "save_fhir_settings",
+                serde_wasm_bindgen::to_value(&args).unwrap(),
+            )
+            .await
+            {
+                Ok(value) => {
+                    if let Ok(saved) = serde_wasm_bindgen::from_value::<FhirSettings>(value) {
+                        let msg = if saved.enabled {
+                            format!("Released results will be sent to {}", saved.base_url)
+                        } else {
+                            "FHIR delivery turned off".to_string()
+                        };
+                        set_settings.set(Some(saved));
+                        set_message.set(Some(Ok(msg)));
+                    }
+                }
+                Err(e) => {
+                    set_message.set(Some(Err(invoke_error("Failed to save FHIR settings", e))))
+                }
+            }
+            set_busy.set(false);
+        });
+    };
+
+    view! {
+        <div class="settings-item" style="flex-direction: column; align-items: stretch; gap: 0.75rem;">
+            <label style="display: flex; gap: 0.5rem; align-items: center; font-weight: 500; color: var(--color-text-primary);">
+                <input
+                    type="checkbox"
+                    prop:checked=move || settings.with(|s| s.as_ref().is_some_and(|s| s.enabled))
+                    on:change=move |ev| {
+                        let enabled = event_target_checked(&ev);
+                        set_settings.update(|s| {
+                            if let Some(s) = s {
+                                s.enabled = enabled;
+                            }
+                        });
+                    }
+                />
+                "POST released results as FHIR R4 transaction bundles"
+            </label>
+            <div class="form-group">
+                <label class="form-label">"Base URL"</label>
+                <input
+                    type="text"
+                    class="form-input"
+                    placeholder="e.g. https://ehr.example.org/fhir"
+                    prop:value=move || settings.with(|s| s.as_ref().map(|s| s.base_url.clone()).unwrap_or_default())
+                    on:input=move |e| update(|s, v| s.base_url = v, event_target_value(&e))
+                />
+            </div>
+            <div style="display: flex; gap: 1rem; flex-wrap: wrap;">
+                <div class="form-group" style="flex: 1; min-width: 12rem;">
+                    <label class="form-label">"Bearer token"</label>
+                    <input
+                        type="password"
+                        class="form-input"
+                        placeholder="Leave empty if the server needs none"
+                        prop:value=move || settings.with(|s| s.as_ref().map(|s| s.auth_token.clone()).unwrap_or_default())
+                        on:input=move |e| update(|s, v| s.auth_token = v, event_target_value(&e))
+                    />
+                </div>
+                <div class="form-group" style="flex: 1; min-width: 12rem;">
+                    <label class="form-label">"Patient identifier system"</label>
+                    <input
+                        type="text"
+                        class="form-input"
+                        placeholder="e.g. urn:oid:1.2.36.146.595.217.0.1"
+                        prop:value=move || settings.with(|s| s.as_ref().map(|s| s.patient_identifier_system.clone()).unwrap_or_default())
+                        on:input=move |e| update(|s, v| s.patient_identifier_system = v, event_target_value(&e))
+                    />
+                </div>
+                <div class="form-group" style="width: 9rem;">
+                    <label class="form-label">"Timeout (s)"</label>
+                    <input
+                        type="number"
+                        class="form-input"
+                        min="1"
+                        max="300"
+                        prop:value=move || settings.with(|s| s.as_ref().map(|s| s.timeout_secs.to_string()).unwrap_or_default())
+                        on:input=move |e| {
+                            if let Ok(secs) = event_target_value(&e).parse() {
+                                set_settings.update(|s| {
+                                    if let Some(s) = s {
+                                        s.timeout_secs = secs;
+                                    }
+                                });
+                            }
+                        }
+                    />
+                </div>
+            </div>
+            <div style="display: flex; justify-content: flex-end;">
+                <button
+                    class="button"
+                    on:click=save
+                    disabled=move || busy.get() || settings.with(|s| s.is_none())
+                    style="padding: 0.5rem 0.75rem; font-size: 0.875rem;"
+                >
+                    "Save"
+                </button>
+            </div>
+
+            {move || message.get().map(|result| match result {
+                Ok(msg) => view! {
+                    <div style="padding: 0.75rem; background: #d4edda; border: 1px solid #c3e6cb; border-radius: 6px; color: #155724; font-size: 0.875rem;">
+                        {msg}
+                    </div>
+                }.into_any(),
+                Err(err) => view! {
+                    <div style="padding: 0.75rem; background: #f8d7da; border: 1px solid #f5c6cb; border-radius: 6px; color: #721c24; font-size: 0.875rem;">
+                        {err}
+                    </div>
+                }.into_any(),
+            })}
+        </div>
+    }
+}
+
+/// ASTM E1394 host: results out over TCP or serial, orders in over TCP
+#[component]
+fn AstmSettingsPanel() -> impl IntoView {
+    let (settings, set_settings) = signal(None::<AstmSettings>);
+    let (busy, set_busy) = signal(false);
+    let (message, set_message) = signal(None::<Result<String, String>>);
+
+    spawn_local(async move {
+        match fetch_astm_settings().await {
+            Ok(loaded) => set_settings.set(Some(loaded)),
+            Err(err) => set_message.set(Some(Err(err))),
+        }
+    });
+
+    let update = move |apply: fn(&mut AstmSettings, String), value: String| {
+        set_settings.update(|settings| {
+            if let Some(settings) = settings {
+                apply(settings, value);
+            }
+        });
+    };
+    let is_serial = move || settings.with(|s| s.as_ref().is_some_and(|s| s.transport == "serial"));
+
+    let save = move |_| {
+        let Some(settings) = settings.get() else {
+            return;
+        };
+        let args = SaveAstmSettingsArgs { settings };
+        spawn_local(async move {
+            set_busy.set(true);
+            match invoke(
+                "save_astm_settings",
+                serde_wasm_bindgen::to_value(&args).unwrap(),
+            )
+            .await
+            {
+                Ok(value) => {
+                    if let Ok(saved) = serde_wasm_bindgen::from_value::<AstmSettings>(value) {
+                        let mut msg = if !saved.enabled {
+                            "ASTM result delivery turned off".to_string()
+                        } else if saved.transport == "serial" {
+                            format!("Released results will be sent on {}", saved.serial_port)
+                        } else {
+                            format!(
+                                "Released results will be sent to {}:{}",
+                                saved.host, saved.port
+                            )
+                        };
+                        if saved.accept_orders {
+                            msg.push_str(&format!(
+                                "; orders from {} accepted on {}:{}",
+                                saved.host, saved.order_address, saved.order_port
+                            ));
+                        }
+                        set_settings.set(Some(saved));
+                        set_message.set(Some(Ok(msg)));
+                    }
+                }
+                Err(e) => {
+                    set_message.set(Some(Err(invoke_error("Failed to save ASTM settings", e))))
+                }
+            }
+            set_busy.set(false);
+        });
+    };
+
+    let number_field = move |label: &'static str,
+                             width: &'static str,
+                             get: fn(&AstmSettings) -> String,
+                             apply: fn(&mut AstmSettings, String)| {
+        view! {
+            <div class="form-group" style=format!("width: {};", width)>
+                <label class="form-label">{label}</label>
+                <input
+                    type="number"
+                    class="form-input"
+                    min="1"
+                    prop:value=move || settings.with(|s| s.as_ref().map(get).unwrap_or_default())
+                    on:input=move |e| update(apply, event_target_value(&e))
+                />
+            </div>
+        }
+    };
+
+    view! {
+        <div class="settings-item" style="flex-direction: column; align-items: stretch; gap: 0.75rem;">
+            <label style="display: flex; gap: 0.5rem; align-items: center; font-weight: 500; color: var(--color-text-primary);">
+                <input
+                    type="checkbox"
+                    prop:checked=move || settings.with(|s| s.as_ref().is_some_and(|s| s.enabled))
+                    on:change=move |ev| {
+                        let enabled = event_target_checked(&ev);
+                        set_settings.update(|s| {
+                            if let Some(s) = s {
+                                s.enabled = enabled;
+                            }
+                        });
+                    }
+                />
+                "Send released results as ASTM E1394 records"
+            </label>
+            <div style="display: flex; gap: 1rem; flex-wrap: wrap; align-items: flex-end;">
+                <div class="form-group" style="width: 8rem;">
+                    <label class="form-label">"Transport"</label>
+                    <select
+                        class="form-input"
+                        prop:value=move || settings.with(|s| s.as_ref().map(|s| s.transport.clone()).unwrap_or_default())
+                        on:change=move |e| update(|s, v| s.transport = v, event_target_value(&e))
+                    >
+                        <option value="tcp">"TCP"</option>
+                        <option value="serial">"Serial"</option>
+                    </select>
+                </div>
+                <Show
+                    when=is_serial
+                    fallback=move || view! {
+                        <div class="form-group" style="flex: 1; min-width: 12rem;">
+                            <label class="form-label">"Host"</label>
+                            <input
+                                type="text"
+                                class="form-input"
+                                placeholder="e.g. lis.example.org"
+                                prop:value=move || settings.with(|s| s.as_ref().map(|s| s.host.clone()).unwrap_or_default())
+                                on:input=move |e| update(|s, v| s.host = v, event_target_value(&e))
+                            />
+                        </div>
+                        {number_field("Port", "7rem", |s| s.port.to_string(), |s, v| {
+                            if let Ok(port) = v.parse() {
+                                s.port = port;
+                            }
+                        })}
+                    }
+                >
+                    <div class="form-group" style="flex: 1; min-width: 12rem;">
+                        <label class="form-label">"Serial port"</label>
+                        <input
+                            type="text"
+                            class="form-input"
+                            placeholder="e.g. COM4 or /dev/ttyUSB1"
+                            prop:value=move || settings.with(|s| s.as_ref().map(|s| s.serial_port.clone()).unwrap_or_default())
+                            on:input=move |e| update(|s, v| s.serial_port = v, event_target_value(&e))
+                        />
+                    </div>
+                    {number_field("Baud rate", "8rem", |s| s.baud_rate.to_string(), |s, v| {
+                        if let Ok(baud) = v.parse() {
+                            s.baud_rate = baud;
+                        }
+                    })}
+                </Show>
+            </div>
+            <div style="display: flex; gap: 1rem; flex-wrap: wrap;">
+                <div class="form-group" style="flex: 1; min-width: 12rem;">
+                    <label class="form-label">"Sender ID"</label>
+                    <input
+                        type="text"
+                        class="form-input"
+                        prop:value=move || settings.with(|s| s.as_ref().map(|s| s.sender_id.clone()).unwrap_or_default())
+                        on:input=move |e| update(|s, v| s.sender_id = v, event_target_value(&e))
+                    />
+                </div>
+                <div class="form-group" style="flex: 1; min-width: 12rem;">
+                    <label class="form-label">"Receiver ID"</label>
+                    <input
+                        type="text"
+                        class="form-input"
+                        prop:value=move || settings.with(|s| s.as_ref().map(|s| s.receiver_id.clone()).unwrap_or_default())
+                        on:input=move |e| update(|s, v| s.receiver_id = v, event_target_value(&e))
+                    />
+                </div>
+                {number_field("Reply timeout (s)", "9rem", |s| s.reply_timeout_secs.to_string(), |s, v| {
+                    if let Ok(secs) = v.parse() {
+                        s.reply_timeout_secs = secs;
+                    }
+                })}
+            </div>
+            <div style="display: flex; gap: 1rem; flex-wrap: wrap; align-items: flex-end;">
+                <label style="display: flex; gap: 0.5rem; align-items: center; flex: 1; min-width: 12rem; color: var(--color-text-primary);">
+                    <input
+                        type="checkbox"
+                        prop:checked=move || settings.with(|s| s.as_ref().is_some_and(|s| s.accept_orders))
+                        on:change=move |ev| {
+                            let accept = event_target_checked(&ev);
+                            set_settings.update(|s| {
+                                if let Some(s) = s {
+                                    s.accept_orders = accept;
+                                }
+                            });
+                        }
+                    />
+                    "Accept order downloads from the host (TCP) as pending tests"
+                </label>
+                <div class="form-group" style="width: 10rem;">
+                    <label class="form-label">"Listen address"</label>
+                    <input
+                        type="text"
+                        class="form-input"
+                        placeholder="127.0.0.1"
+                        prop:value=move || settings.with(|s| s.as_ref().map(|s| s.order_address.clone()).unwrap_or_default())
+                        on:input=move |e| update(|s, v| s.order_address = v, event_target_value(&e))
+                    />
+                </div>
+                {number_field("Order port", "7rem", |s| s.order_port.to_string(), |s, v| {
+                    if let Ok(port) = v.parse() {
+                        s.order_port = port;
+                    }
+                })}
+            </div>
+            <div style="display: flex; justify-content: flex-end;">
+                <button
+                    class="button"
+                    on:click=save
+                    disabled=move || busy.get() || settings.with(|s| s.is_none())
+                    style="padding: 0.5rem 0.75rem; font-size: 0.875rem;"
+                >
+                    "Save"
+                </button>
+            </div>
+
+            {move || message.get().map(|result| match result {
+                Ok(msg) => view! {
+                    <div style="padding: 0.75rem; background: #d4edda; border: 1px solid #c3e6cb; border-radius: 6px; color: #155724; font-size: 0.875rem;">
+                        {msg}
+                    </div>
+                }.into_any(),
+                Err(err) => view! {
+                    <div style="padding: 0.75rem; background: #f8d7da; border: 1px solid #f5c6cb; border-radius: 6px; color: #721c24; font-size: 0.875rem;">
+                        {err}
+                    </div>
+                }.into_any(),
+            })}
+        </div>
+    }
+}
+
+/// ZPL or EPL label printer on raw TCP or a serial port, or print-to-file
+#[component]
+fn LabelPrinterPanel() -> impl IntoView {
+    let (settings, set_settings) = signal(None::<LabelPrinterSettings>);
+    let (busy, set_busy) = signal(false);
+    let (message, set_message) = signal(None::<Result<String, String>>);
+
+    spawn_local(async move {
+        match fetch_label_printer_settings().await {
+            Ok(loaded) => set_settings.set(Some(loaded)),
+            Err(err) => set_message.set(Some(Err(err))),
+        }
+    });
+
+    let update = move |apply: fn(&mut LabelPrinterSettings, String), value: String| {
+        set_settings.update(|settings| {
+            if let Some(settings) = settings {
+                apply(settings, value);
+            }
+        });
+    };
+    let transport =
+        move || settings.with(|s| s.as_ref().map(|s| s.transport.clone()).unwrap_or_default());
+
+    let save = move |_| {
+        let Some(settings) = settings.get() else {
+            return;
+        };
+        let args = LabelPrinterSettingsArgs { settings };
+        spawn_local(async move {
+            set_busy.set(true);
+            match invoke(
+                "save_label_printer_settings",
+                serde_wasm_bindgen::to_value(&args).unwrap(),
+            )
+            .await
+            {
+                Ok(value) => {
+                    if let Ok(saved) = serde_wasm_bindgen::from_value::<LabelPrinterSettings>(value)
+                    {
+                        let msg = if !saved.enabled {
+                            "Labels will be saved as PDFs in the labels folder in Documents"
+                                .to_string()
+                        } else {
+                            match saved.transport.as_str() {
+                                "serial" => {
+                                    format!("Labels will be printed on {}", saved.serial_port)
+                                }
+                                "file" => "Label jobs will be written to files".to_string(),
+                                _ => format!(
+                                    "Labels will be printed on {}:{}",
+                                    saved.host, saved.port
+                                ),
+                            }
+                        };
+                        set_settings.set(Some(saved));
+                        set_message.set(Some(Ok(msg)));
+                    }
+                }
+                Err(e) => set_message.set(Some(Err(invoke_error(
+                    "Failed to save label printer settings",
+                    e,
+                )))),
+            }
+            set_busy.set(false);
+        });
+    };
+
+    // Uses the settings as entered, so they can be tried before saving
+    let print_sample = move |_| {
+        let Some(settings) = settings.get() else {
+            return;
+        };
+        let args = LabelPrinterSettingsArgs { settings };
+        spawn_local(async move {
+            set_busy.set(true);
+            let result = match invoke(
+                "print_sample_label",
+                serde_wasm_bindgen::to_value(&args).unwrap(),
+            )
+            .await
+            {
+                Ok(value) => serde_wasm_bindgen::from_value::<String>(value)
+                    .map(|destination| format!("Sample label sent to {}", destination))
+                    .map_err(|e| format!("Failed to parse label destination: {:?}", e)),
+                Err(e) => Err(invoke_error("Failed to print sample label", e)),
+            };
+            set_message.set(Some(result));
+            set_busy.set(false);
+        });
+    };
+
+    let number_field = move |label: &'static str,
+                             width: &'static str,
+                             get: fn(&LabelPrinterSettings) -> String,
+                             apply: fn(&mut LabelPrinterSettings, String)| {
+        view! {
+            <div class="form-group" style=format!("width: {};", width)>
+                <label class="form-label">{label}</label>
+                <input
+                    type="number"
+                    class="form-input"
+                    min="1"
+                    prop:value=move || settings.with(|s| s.as_ref().map(get).unwrap_or_default())
+                    on:input=move |e| update(apply, event_target_value(&e))
+                />
+            </div>
+        }
+    };
+
+    view! {
+        <div class="settings-item" style="flex-direction: column; align-items: stretch; gap: 0.75rem;">
+            <label style="display: flex; gap: 0.5rem; align-items: center; font-weight: 500; color: var(--color-text-primary);">
+                <input
+                    type="checkbox"
+                    prop:checked=move || settings.with(|s| s.as_ref().is_some_and(|s| s.enabled))
+                    on:change=move |ev| {
+                        let enabled = event_target_checked(&ev);
+                        set_settings.update(|s| {
+                            if let Some(s) = s {
+                                s.enabled = enabled;
+                            }
+                        });
+                    }
+                />
+                "Print tube labels when a test is created"
+            </label>
+            <div style="font-size: 0.875rem; color: var(--color-text-secondary);">
+                "Labels show the patient's name and date of birth, the test type and the accession barcode. Without a printer, the Labels buttons save a PDF instead."
+            </div>
+            <div style="display: flex; gap: 1rem; flex-wrap: wrap; align-items: flex-end;">
+                <div class="form-group" style="width: 8rem;">
+                    <label class="form-label">"Language"</label>
+                    <select
+                        class="form-input"
+                        prop:value=move || settings.with(|s| s.as_ref().map(|s| s.language.clone()).unwrap_or_default())
+                        on:change=move |e| update(|s, v| s.language = v, event_target_value(&e))
+                    >
+                        <option value="zpl">"ZPL"</option>
+                        <option value="epl">"EPL"</option>
+                    </select>
+                </div>
+                <div class="form-group" style="width: 8rem;">
+                    <label class="form-label">"Transport"</label>
+                    <select
+                        class="form-input"
+                        prop:value=transport
+                        on:change=move |e| update(|s, v| s.transport = v, event_target_value(&e))
+                    >
+                        <option value="tcp">"TCP"</option>
+                        <option value="serial">"Serial"</option>
+                        <option value="file">"File"</option>
+                    </select>
+                </div>
+                {move || match transport().as_str() {
+                    "serial" => view! {
+                        <div class="form-group" style="flex: 1; min-width: 12rem;">
+                            <label class="form-label">"Serial port"</label>
+                            <input
+                                type="text"
+                                class="form-input"
+                                placeholder="e.g. COM5 or /dev/ttyUSB2"
+                                prop:value=move || settings.with(|s| s.as_ref().map(|s| s.serial_port.clone()).unwrap_or_default())
+                                on:input=move |e| update(|s, v| s.serial_port = v, event_target_value(&e))
+                            />
+                        </div>
+                        {number_field("Baud rate", "8rem", |s| s.baud_rate.to_string(), |s, v| {
+                            if let Ok(baud) = v.parse() {
+                                s.baud_rate = baud;
+                            }
+                        })}
+                    }.into_any(),
+                    "file" => view! {
+                        <div class="form-group" style="flex: 1; min-width: 12rem;">
+                            <label class="form-label">"Folder"</label>
+                            <input
+                                type="text"
+                                class="form-input"
+                                placeholder="labels (in Documents)"
+                                prop:value=move || settings.with(|s| s.as_ref().map(|s| s.folder.clone()).unwrap_or_default())
+                                on:input=move |e| update(|s, v| s.folder = v, event_target_value(&e))
+                                style="font-family: monospace; font-size: 0.8125rem;"
+                            />
+                        </div>
+                    }.into_any(),
+                    _ => view! {
+                        <div class="form-group" style="flex: 1; min-width: 12rem;">
+                            <label class="form-label">"Host"</label>
+                            <input
+                                type="text"
+                                class="form-input"
+                                placeholder="e.g. 192.168.1.50"
+                                prop:value=move || settings.with(|s| s.as_ref().map(|s| s.host.clone()).unwrap_or_default())
+                                on:input=move |e| update(|s, v| s.host = v, event_target_value(&e))
+                            />
+                        </div>
+                        {number_field("Port", "7rem", |s| s.port.to_string(), |s, v| {
+                            if let Ok(port) = v.parse() {
+                                s.port = port;
+                            }
+                        })}
+                    }.into_any(),
+                }}
+                {number_field("Copies", "6rem", |s| s.copies.to_string(), |s, v| {
+                    if let Ok(copies) = v.parse() {
+                        s.copies = copies;
+                    }
+                })}
+            </div>
+            <div style="display: flex; justify-content: flex-end; gap: 0.5rem;">
+                <button
+                    class="button"
+                    on:click=print_sample
+                    disabled=move || busy.get() || settings.with(|s| s.is_none())
+                    style="padding: 0.5rem 0.75rem; font-size: 0.875rem;"
+                >
+                    "Print Sample Label"
+                </button>
+                <button
+                    class="button"
+                    on:click=save
+                    disabled=move || busy.get() || settings.with(|s| s.is_none())
+                    style="padding: 0.5rem 0.75rem; font-size: 0.875rem;"
+                >
+                    "Save"
+                </button>
+            </div>
+
+            {move || message.get().map(|result| match result {
+                Ok(msg) => view! {
+                    <div style="padding: 0.75rem; background: #d4edda; border: 1px solid #c3e6cb; border-radius: 6px; color: #155724; font-size: 0.875rem;">
+                        {msg}
+                    </div>
+                }.into_any(),
+                Err(err) => view! {
+                    <div style="padding: 0.75rem; background: #f8d7da; border: 1px solid #f5c6cb; border-radius: 6px; color: #721c24; font-size: 0.875rem;">
+                        {err}
+                    </div>
+                }.into_any(),
+            })}
+        </div>
+    }
+}
+
+/// How long QC covers a device and whether devices without QC are locked
+#[component]
+fn QcSettingsPanel() -> impl IntoView {
+    let (settings, set_settings) = signal(None::<QcSettings>);
+    let (busy, set_busy) = signal(false);
+    let (message, set_message) = signal(None::<Result<String, String>>);
+
+    spawn_local(async move {
+        match fetch_qc_settings().await {
+            Ok(loaded) => set_settings.set(Some(loaded)),
+            Err(err) => set_message.set(Some(Err(err))),
+        }
+    });
+
+    let save = move |_| {
+        let Some(settings) = settings.get() else {
+            return;
+        };
+        let args = QcSettingsArgs { settings };
+        spawn_local(async move {
+            set_busy.set(true);
+            match invoke(
+                "save_qc_settings",
+                serde_wasm_bindgen::to_value(&args).unwrap(),
+            )
+            .await
+            {
+                Ok(value) => {
+                    if let Ok(saved) = serde_wasm_bindgen::from_value::<QcSettings>(value) {
+                        let msg = format!(
+                            "Passed QC now covers a device for {} hours",
+                            saved.valid_hours
+                        );
+                        set_settings.set(Some(saved));
+                        set_message.set(Some(Ok(msg)));
+                    }
+                }
+                Err(e) => set_message.set(Some(Err(invoke_error("Failed to save QC settings", e)))),
+            }
+            set_busy.set(false);
+        });
+    };
+
+    view! {
+        <div class="settings-item" style="flex-direction: column; align-items: stretch; gap: 0.75rem;">
+            <div style="font-size: 0.875rem; color: var(--color-text-secondary);">
+                "Patient tests are refused on a device whose last positive or negative control failed, was run too long ago or used an expired lot. Control materials are managed on the Quality Control page."
+            </div>
+            <label style="display: flex; gap: 0.5rem; align-items: center; font-weight: 500; color: var(--color-text-primary);">
+                <input
+                    type="checkbox"
+                    prop:checked=move || settings.with(|s| s.as_ref().is_some_and(|s| s.required))
+                    on:change=move |ev| {
+                        let required = event_target_checked(&ev);
+                        set_settings.update(|s| {
+                            if let Some(s) = s {
+                                s.required = required;
+                            }
+                        });
+                    }
+                />
+                "Also lock devices that have never run QC"
+            </label>
+            <div style="display: flex; gap: 1rem; align-items: flex-end;">
+                <div class="form-group" style="width: 10rem;">
+                    <label class="form-label">"QC valid for (hours)"</label>
+                    <input
+                        type="number"
+                        class="form-input"
+                        min="1"
+                        prop:value=move || settings.with(|s| s.as_ref().map(|s| s.valid_hours.to_string()).unwrap_or_default())
+                        on:input=move |e| {
+                            if let Ok(hours) = event_target_value(&e).parse() {
+                                set_settings.update(|s| {
+                                    if let Some(s) = s {
+                                        s.valid_hours = hours;
+                                    }
+                                });
+                            }
+                        }
+                    />
+                </div>
+                <div style="flex: 1;"></div>
+                <button
+                    class="button"
+                    on:click=save
+                    disabled=move || busy.get() || settings.with(|s| s.is_none())
+                    style="padding: 0.5rem 0.75rem; font-size: 0.875rem;"
+                >
+                    "Save"
+                </button>
+            </div>
+
+            {move || message.get().map(|result| match result {
+                Ok(msg) => view! {
+                    <div style="padding: 0.75rem; background: #d4edda; border: 1px solid #c3e6cb; border-radius: 6px; color: #155724; font-size: 0.875rem;">
+                        {msg}
+                    </div>
+                }.into_any(),
+                Err(err) => view! {
+                    <div style="padding: 0.75rem; background: #f8d7da; border: 1px solid #f5c6cb; border-radius: 6px; color: #721c24; font-size: 0.875rem;">
+                        {err}
+                    </div>
+                }.into_any(),
+            })}
+        </div>
+    }
+}
+
+/// HL7 destination for released results and the log of everything sent
+#[component]
+fn LisPanel() -> impl IntoView {
+    let (settings, set_settings) = signal(None::<Hl7Settings>);
+    let (messages, set_messages) = signal(Vec::<OutboundMessage>::new());
+    let (busy, set_busy) = signal(false);
+    let (message, set_message) = signal(None::<Result<String, String>>);
+
+    let load_messages = move || {
+        spawn_local(async move {
+            match fetch_outbound_messages().await {
+                Ok(list) => set_messages.set(list),
+                Err(err) => set_message.set(Some(Err(err))),
+            }
+        });
+    };
+
+    spawn_local(async move {
+        match fetch_hl7_settings().await {
+            Ok(loaded) => set_settings.set(Some(loaded)),
+            Err(err) => set_message.set(Some(Err(err))),
+        }
+    });
+    load_messages();
+
+    let update = move |apply: fn(&mut Hl7Settings, String), value: String| {
+        set_settings.update(|settings| {
+            if let Some(settings) = settings {
+                apply(settings, value);
+            }
+        });
+    };
+
+    let save = move |_| {
+        let Some(settings) = settings.get() else {
+            return;
+        };
+        let args = SaveHl7SettingsArgs { settings };
+        spawn_local(async move {
+            set_busy.set(true);
+            match invoke(
+                "save_hl7_settings",
+                serde_wasm_bindgen::to_value(&args).unwrap(),
+            )
+            .await
+            {
+                Ok(value) => {
+                    if let Ok(saved) = serde_wasm_bindgen::from_value::<Hl7Settings>(value) {
+                        let msg = if saved.enabled {
+                            format!(
+                                "Released results will be sent to {}:{}",
+                                saved.host, saved.port
+                            )
+                        } else {
+                            "Result messaging turned off".to_string()
+                        };
+                        set_settings.set(Some(saved));
+                        set_message.set(Some(Ok(msg)));
+                    }
+                }
+                Err(e) => {
+                    set_message.set(Some(Err(invoke_error("Failed to save HL7 settings", e))))
+                }
+            }
+            set_busy.set(false);
+        });
+    };
+
+    let retry = move |id: i64| {
+        let args = RetryOutboundMessageArgs { id };
+        spawn_local(async move {
+            match invoke(
+                "retry_outbound_message",
+                serde_wasm_bindgen::to_value(&args).unwrap(),
+            )
+            .await
+            {
+                Ok(_) => {
+                    set_message.set(Some(Ok("Message queued again".to_string())));
+                    load_messages();
+                }
+                Err(e) => set_message.set(Some(Err(invoke_error("Failed to retry message", e)))),
+            }
+        });
+    };
+
+    let text_field = move |label: &'static str,
+                           placeholder: &'static str,
+                           get: fn(&Hl7Settings) -> String,
+                           apply: fn(&mut Hl7Settings, String)| {
+        view! {
+            <div class="form-group" style="flex: 1; min-width: 12rem;">
+                <label class="form-label">{label}</label>
+                <input
+                    type="text"
+                    class="form-input"
+                    placeholder=placeholder
+                    prop:value=move || settings.with(|s| s.as_ref().map(get).unwrap_or_default())
+                    on:input=move |e| update(apply, event_target_value(&e))
+                />
+            </div>
+        }
+    };
+
+    view! {
+        <div class="settings-item" style="flex-direction: column; align-items: stretch; gap: 0.75rem;">
+            <label style="display: flex; gap: 0.5rem; align-items: center; font-weight: 500; color: var(--color-text-primary);">
+                <input
+                    type="checkbox"
+                    prop:checked=move || settings.with(|s| s.as_ref().is_some_and(|s| s.enabled))
+                    on:change=move |ev| {
+                        let enabled = event_target_checked(&ev);
+                        set_settings.update(|s| {
+                            if let Some(s) = s {
+                                s.enabled = enabled;
+                            }
+                        });
+                    }
+                />
+                "Send released results as HL7 ORU^R01 over MLLP"
+            </label>
+            <div style="display: flex; gap: 1rem; flex-wrap: wrap;">
+                {text_field("Host", "e.g. lis.example.org", |s| s.host.clone(), |s, v| s.host = v)}
+                <div class="form-group" style="width: 7rem;">
+                    <label class="form-label">"Port"</label>
+                    <input
+                        type="number"
+                        class="form-input"
+                        min="1"
+                        max="65535"
+                        prop:value=move || settings.with(|s| s.as_ref().map(|s| s.port.to_string()).unwrap_or_default())
+                        on:input=move |e| {
+                            if let Ok(port) = event_target_value(&e).parse() {
+                                set_settings.update(|s| {
+                                    if let Some(s) = s {
+                                        s.port = port;
+                                    }
+                                });
+                            }
+                        }
+                    />
+                </div>
+                <div class="form-group" style="width: 9rem;">
+                    <label class="form-label">"ACK timeout (s)"</label>
+                    <input
+                        type="number"
+                        class="form-input"
+                        min="1"
+                        max="300"
+                        prop:value=move || settings.with(|s| s.as_ref().map(|s| s.ack_timeout_secs.to_string()).unwrap_or_default())
+                        on:input=move |e| {
+                            if let Ok(secs) = event_target_value(&e).parse() {
+                                set_settings.update(|s| {
+                                    if let Some(s) = s {
+                                        s.ack_timeout_secs = secs;
+                                    }
+                                });
+                            }
+                        }
+                    />
+                </div>
+            </div>
+            <div style="display: flex; gap: 1rem; flex-wrap: wrap;">
+                {text_field("Sending application", "MSH-3", |s| s.sending_application.clone(), |s, v| s.sending_application = v)}
+                {text_field("Sending facility", "MSH-4", |s| s.sending_facility.clone(), |s, v| s.sending_facility = v)}
+            </div>
+            <div style="display: flex; gap: 1rem; flex-wrap: wrap;">
+                {text_field("Receiving application", "MSH-5", |s| s.receiving_application.clone(), |s, v| s.receiving_application = v)}
+                {text_field("Receiving facility", "MSH-6", |s| s.receiving_facility.clone(), |s, v| s.receiving_facility = v)}
+            </div>
+            <div style="display: flex; justify-content: flex-end;">
+                <button
+                    class="button"
+                    on:click=save
+                    disabled=move || busy.get() || settings.with(|s| s.is_none())
+                    style="padding: 0.5rem 0.75rem; font-size: 0.875rem;"
+                >
+                    "Save"
+                </button>
+            </div>
+
+            {move || message.get().map(|result| match result {
+                Ok(msg) => view! {
+                    <div style="padding: 0.75rem; background: #d4edda; border: 1px solid #c3e6cb; border-radius: 6px; color: #155724; font-size: 0.875rem;">
+                        {msg}
+                    </div>
+                }.into_any(),
+                Err(err) => view! {
+                    <div style="padding: 0.75rem; background: #f8d7da; border: 1px solid #f5c6cb; border-radius: 6px; color: #721c24; font-size: 0.875rem;">
+                        {err}
+                    </div>
+                }.into_any(),
+            })}
+
+            <div style="display: flex; justify-content: space-between; align-items: center;">
+                <div style="font-weight: 500; color: var(--color-text-primary);">"Delivery log"</div>
+                <button
+                    class="button"
+                    on:click=move |_| load_messages()
+                    style="padding: 0.25rem 0.5rem; font-size: 0.8125rem;"
+                >
+                    "Refresh"
+                </button>
+            </div>
+            <div style="display: flex; flex-direction: column; gap: 0.5rem; max-height: 240px; overflow: auto;">
+                {move || {
+                    let list = messages.get();
+                    if list.is_empty() {
+                        view! {
+                            <div style="font-size: 0.875rem; color: var(--color-text-secondary);">
+                                "No results sent yet"
+                            </div>
+                        }
+                            .into_any()
+                    } else {
+                        list.into_iter()
+                            .map(|msg| {
+                                let id = msg.id;
+                                let color = match msg.status.as_str() {
+                                    "acked" => "var(--color-success)",
+                                    "failed" => "var(--color-error)",
+                                    _ => "var(--color-warning)",
+                                };
+                                let detail = match msg.status.as_str() {
+                                    "acked" => format!("acknowledged {}", msg.ack_code.clone().unwrap_or_default()),
+                                    "failed" => format!("failed after {} attempt(s)", msg.attempts),
+                                    _ if msg.attempts > 0 => format!("retrying at {}", msg.next_attempt_at),
+                                    _ => "waiting to send".to_string(),
+                                };
+                                let retryable = msg.status != "acked";
+                                view! {
+                                    <div style="display: flex; justify-content: space-between; align-items: center; gap: 1rem; font-size: 0.8125rem;">
+                                        <div style="min-width: 0;">
+                                            <div>
+                                                <span style="font-family: monospace;">{msg.control_id.clone()}</span>
+                                                " · "
+                                                <span style=format!("color: {}; font-weight: 500;", color)>{detail}</span>
+                                            </div>
+                                            <div style="color: var(--color-text-secondary);">
+                                                {format!("{} · test {} · queued {}", msg.protocol, msg.test_uuid, msg.created_at)}
+                                            </div>
+                                            {msg.last_error.clone().map(|err| view! {
+                                                <div style="color: var(--color-error);">{err}</div>
+                                            })}
+                                        </div>
+                                        {retryable.then(|| view! {
+                                            <button
+                                                class="button"
+                                                on:click=move |_| retry(id)
+                                                style="padding: 0.25rem 0.5rem; font-size: 0.8125rem;"
+                                            >
+                                                "Retry"
+                                            </button>
+                                        })}
+                                    </div>
+                                }
+                            })
+                            .collect_view()
+                            .into_any()
+                    }
+                }}
+            </div>
+        </div>
+    }
+}
+
+/// Bring patients over from another system's CSV export
+#[component]
+fn PatientImportPanel() -> impl IntoView {
+    let (path, set_path) = signal(String::new());
+    let (report, set_report) = signal(None::<ImportReport>);
+    let (mapping, set_mapping) = signal(None::<PatientColumnMapping>);
+    let (busy, set_busy) = signal(false);
+    let (message, set_message) = signal(None::<Result<String, String>>);
+
+    let run = move |dry_run: bool| {
+        let args = ImportPatientsArgs {
+            path: path.get(),
+            mapping: mapping.get(),
+            dry_run,
+        };
+        spawn_local(async move {
+            set_busy.set(true);
+            set_message.set(None);
+            match invoke(
+                "import_patients",
+                serde_wasm_bindgen::to_value(&args).unwrap(),
+            )
+            .await
+            {
+                Ok(value) => match serde_wasm_bindgen::from_value::<ImportReport>(value) {
+                    Ok(loaded) => {
+                        if !loaded.dry_run {
+                            set_message.set(Some(Ok(format!(
+                                "Imported {} patient(s); {} duplicate(s) and {} invalid row(s) skipped",
+                                loaded.imported, loaded.duplicates, loaded.invalid
+                            ))));
+                        } else if loaded.total_rows == 0 && !loaded.headers.is_empty() {
+                            set_message.set(Some(Err(
+                                "Choose the columns holding first and last names, then preview again"
+                                    .to_string(),
+                            )));
+                        }
+                        set_mapping.set(Some(loaded.mapping.clone()));
+                        set_report.set(Some(loaded));
+                    }
+                    Err(e) => set_message
+                        .set(Some(Err(format!("Failed to parse import report: {:?}", e)))),
+                },
+                Err(e) => {
+                    let action = if dry_run {
+                        "Failed to preview import"
+                    } else {
+                        "Failed to import patients"
+                    };
+                    set_message.set(Some(Err(invoke_error(action, e))));
+                }
+            }
+            set_busy.set(false);
+        });
+    };
+
+    let update = move |apply: fn(&mut PatientColumnMapping, Option<String>), value: String| {
+        set_mapping.update(|mapping| {
+            if let Some(mapping) = mapping {
+                apply(mapping, Some(value).filter(|v| !v.is_empty()));
+            }
+        });
+        // A changed mapping invalidates the preview until it is run again
+        set_report.update(|report| {
+            if let Some(report) = report {
+                report.dry_run = true;
+                report.new_patients = 0;
+            }
+        });
+    };
+
+    let column_field =
+        move |label: &'static str,
+              get: fn(&PatientColumnMapping) -> Option<String>,
+              apply: fn(&mut PatientColumnMapping, Option<String>)| {
+            view! {
+                <div class="form-group" style="flex: 1; min-width: 10rem;">
+                    <label class="form-label">{label}</label>
+                    <select
+                        class="form-input"
+                        prop:value=move || mapping.with(|m| m.as_ref().and_then(get).unwrap_or_default())
+                        on:change=move |e| update(apply, event_target_value(&e))
+                    >
+                        <option value="">"(not in file)"</option>
+                        {move || {
+                            report
+                                .with(|r| r.as_ref().map(|r| r.headers.clone()).unwrap_or_default())
+                                .into_iter()
+                                .map(|header| view! { <option value=header.clone()>{header.clone()}</option> })
+                                .collect_view()
+                        }}
+                    </select>
+                </div>
+            }
+        };
+
+    let has_path = move || !path.get().trim().is_empty();
+
+    view! {
+        <div class="settings-item" style="flex-direction: column; align-items: stretch; gap: 0.75rem;">
+            <div style="font-size: 0.875rem; color: var(--color-text-secondary);">
+                "Preview a CSV of patients, check the column mapping, then import the new ones in one go"
+            </div>
+            <div style="display: flex; gap: 0.75rem; align-items: center;">
+                <input
+                    type="text"
+                    class="form-input"
+                    placeholder="e.g. D:\\patients.csv"
+                    prop:value=move || path.get()
+                    on:input=move |e| {
+                        set_path.set(event_target_value(&e));
+                        set_report.set(None);
+                        set_mapping.set(None);
+                    }
+                    style="flex: 1; font-family: monospace; font-size: 0.8125rem;"
+                />
+                <button
+                    class="button"
+                    on:click=move |_| run(true)
+                    disabled=move || busy.get() || !has_path()
+                    style="padding: 0.5rem 0.75rem; font-size: 0.875rem; white-space: nowrap;"
+                >
+                    "Preview"
+                </button>
+            </div>
+
+            <Show when=move || report.with(|r| r.is_some())>
+                <div style="display: flex; gap: 1rem; flex-wrap: wrap;">
+                    {column_field("First name", |m| m.first_name.clone(), |m, v| m.first_name = v)}
+                    {column_field("Last name", |m| m.last_name.clone(), |m, v| m.last_name = v)}
+                    {column_field("Date of birth", |m| m.date_of_birth.clone(), |m, v| m.date_of_birth = v)}
+                    <div class="form-group" style="flex: 1; min-width: 10rem;">
+                        <label class="form-label">"Date format"</label>
+                        <select
+                            class="form-input"
+                            prop:value=move || mapping.with(|m| m.as_ref().map(|m| m.date_format.clone()).unwrap_or_default())
+                            on:change=move |e| update(|m, v| m.date_format = v.unwrap_or_default(), event_target_value(&e))
+                        >
+                            <option value="iso">"1990-01-31"</option>
+                            <option value="day_first">"31/01/1990"</option>
+                            <option value="month_first">"01/31/1990"</option>
+                        </select>
+                    </div>
+                </div>
+                <div style="display: flex; gap: 1rem; flex-wrap: wrap;">
+                    {column_field("Patient ID", |m| m.patient_id_number.clone(), |m, v| m.patient_id_number = v)}
+                    {column_field("Email", |m| m.email.clone(), |m, v| m.email = v)}
+                    {column_field("Phone", |m| m.phone.clone(), |m, v| m.phone = v)}
+                    {column_field("Notes", |m| m.notes.clone(), |m, v| m.notes = v)}
+                </div>
+                {move || report.get().map(|report| {
+                    let summary = format!(
+                        "{} row(s): {} new, {} duplicate(s), {} invalid",
+                        report.total_rows, report.new_patients, report.duplicates, report.invalid
+                    );
+                    let importable = report.dry_run && report.new_patients > 0;
+                    let count = report.new_patients;
+                    view! {
+                        <div style="display: flex; justify-content: space-between; align-items: center; gap: 1rem;">
+                            <div style="font-weight: 500; color: var(--color-text-primary);">{summary}</div>
+                            <div style="display: flex; gap: 0.5rem;">
+                                <button
+                                    class="button"
+                                    on:click=move |_| run(true)
+                                    disabled=move || busy.get()
+                                    style="padding: 0.5rem 0.75rem; font-size: 0.875rem;"
+                                >
+                                    "Preview Again"
+                                </button>
+                                {importable.then(|| view! {
+                                    <button
+                                        class="button"
+                                        on:click=move |_| run(false)
+                                        disabled=move || busy.get()
+                                        style="padding: 0.5rem 0.75rem; font-size: 0.875rem;"
+                                    >
+                                        {format!("Import {} Patient(s)", count)}
+                                    </button>
+                                })}
+                            </div>
+                        </div>
+                        <div style="display: flex; flex-direction: column; gap: 0.5rem; max-height: 240px; overflow: auto;">
+                            {report.rows.into_iter().map(|row| {
+                                let (color, label) = match row.status.as_str() {
+                                    "new" => ("var(--color-success)", "new".to_string()),
+                                    "duplicate" => (
+                                        "var(--color-warning)",
+                                        format!("duplicate of {}", row.duplicate_of.clone().unwrap_or_default()),
+                                    ),
+                                    _ => ("var(--color-error)", "invalid".to_string()),
+                                };
+                                view! {
+                                    <div style="font-size: 0.8125rem;">
+                                        <div>
+                                            <span style="font-family: monospace;">{format!("line {}", row.line)}</span>
+                                            " · "
+                                            {row.name.clone()}
+                                            " · "
+                                            <span style=format!("color: {}; font-weight: 500;", color)>{label}</span>
+                                        </div>
+                                        {(!row.errors.is_empty()).then(|| view! {
+                                            <div style="color: var(--color-error);">{row.errors.join("; ")}</div>
+                                        })}
+                                    </div>
+                                }
+                            }).collect_view()}
+                        </div>
+                    }
+                })}
+            </Show>
+
+            {move || message.get().map(|result| match result {
+                Ok(msg) => view! {
+                    <div style="padding: 0.75rem; background: #d4edda; border: 1px solid #c3e6cb; border-radius: 6px; color: #155724; font-size: 0.875rem;">
+                        {msg}
+                    </div>
+                }.into_any(),
+                Err(err) => view! {
+                    <div style="padding: 0.75rem; background: #f8d7da; border: 1px solid #f5c6cb; border-radius: 6px; color: #721c24; font-size: 0.875rem;">
+                        {err}
+                    </div>
+                }.into_any(),
+            })}
+        </div>
+    }
+}
+
+/// Inactivity period before any session locks; applies to every operator
+#[component]
+fn AutoLockPanel() -> impl IntoView {
+    let (minutes, set_minutes) = signal(String::new());
+    let (busy, set_busy) = signal(false);
+    let (message, set_message) = signal(None::<Result<String, String>>);
+
+    spawn_local(async move {
+        match invoke("get_auth_status", JsValue::NULL).await {
+            Ok(value) => {
+                if let Ok(status) = serde_wasm_bindgen::from_value::<AutoLockStatus>(value) {
+                    set_minutes.set(status.auto_lock_minutes.to_string());
+                }
+            }
+            Err(e) => set_message.set(Some(Err(invoke_error("Failed to load auto-lock", e)))),
+        }
+    });
+
+    let save = move |_| {
+        let Ok(value) = minutes.get().trim().parse::<u32>() else {
+            set_message.set(Some(Err("Enter a whole number of minutes".to_string())));
+            return;
+        };
+        let args = SetAutoLockMinutesArgs { minutes: value };
+        spawn_local(async move {
+            set_busy.set(true);
+            match invoke(
+                "set_auto_lock_minutes",
+                serde_wasm_bindgen::to_value(&args).unwrap(),
+            )
+            .await
+            {
+                Ok(_) => {
+                    let msg = if value == 0 {
+                        "Auto-lock turned off".to_string()
+                    } else {
+                        format!("Sessions lock after {} minutes without activity", value)
+                    };
+                    set_message.set(Some(Ok(msg)));
+                }
+                Err(e) => set_message.set(Some(Err(invoke_error("Failed to save auto-lock", e)))),
+            }
+            set_busy.set(false);
+        });
+    };
+
+    view! {
+        <div class="settings-item" style="flex-direction: column; align-items: stretch; gap: 0.75rem;">
+            <div style="display: flex; justify-content: space-between; align-items: center; gap: 1rem;">
+                <div>
+                    <div style="font-weight: 500; color: var(--color-text-primary);">
+                        "Auto-lock"
+                    </div>
+                    <div style="font-size: 0.875rem; color: var(--color-text-secondary);">
+                        "Minutes without activity before the screen locks; 0 turns it off"
+                    </div>
+                </div>
+                <div style="display: flex; gap: 0.75rem; align-items: center;">
+                    <input
+                        type="number"
+                        class="form-input"
+                        min="0"
+                        max="240"
+                        prop:value=move || minutes.get()
+                        on:input=move |e| set_minutes.set(event_target_value(&e))
+                        style="width: 6rem;"
+                    />
+                    <button
+                        class="button"
+                        on:click=save
+                        disabled=move || busy.get()
+                        style="padding: 0.5rem 0.75rem; font-size: 0.875rem;"
+                    >
+                        "Save"
+                    </button>
+                </div>
+            </div>
+
+            {move || message.get().map(|result| match result {
+                Ok(msg) => view! {
+                    <div style="padding: 0.75rem; background: #d4edda; border: 1px solid #c3e6cb; border-radius: 6px; color: #155724; font-size: 0.875rem;">
+                        {msg}
+                    </div>
+                }.into_any(),
+                Err(err) => view! {
+                    <div style="padding: 0.75rem; background: #f8d7da; border: 1px solid #f5c6cb; border-radius: 6px; color: #721c24; font-size: 0.875rem;">
+                        {err}
+                    </div>
+                }.into_any(),
+            })}
+        </div>
+    }
+}
+
+#[component]
+fn RoleSelect(role: ReadSignal<Role>, set_role: WriteSignal<Role>) -> impl IntoView {
+    view! {
+        <select
+            class="port-selector"
+            on:change=move |ev| {
+                if let Ok(selected) = Role::from_str(&event_target_value(&ev)) {
+                    set_role.set(selected);
+                }
+            }
+            prop:value=move || role.get().as_str()
+            style="padding: 0.625rem; border: 1px solid var(--color-border-medium); border-radius: 6px; background: var(--color-surface); font-family: inherit; font-size: 0.875rem;"
+        >
+            {Role::all().into_iter().map(|r| view! {
+                <option value=r.as_str()>{r.label()}</option>
+            }).collect::<Vec<_>>()}
+        </select>
+    }
+}
+
+#[component]
+fn UsersPanel(current_user: ReadSignal<Option<User>>) -> impl IntoView {
+    let (users, set_users) = signal(Vec::<User>::new());
+    let (username, set_username) = signal(String::new());
+    let (display_name, set_display_name) = signal(String::new());
+    let (role, set_role) = signal(Role::Operator);
+    let (password, set_password) = signal(String::new());
+    let (busy, set_busy) = signal(false);
+    let (message, set_message) = signal(None::<Result<String, String>>);
+
+    let reload = move || {
+        spawn_local(async move {
+            match fetch_users().await {
+                Ok(list) => set_users.set(list),
+                Err(e) => set_message.set(Some(Err(e))),
+            }
+        });
+    };
+    reload();
+
+    let on_changed = move |msg: String| {
+        set_message.set(Some(Ok(msg)));
+        reload();
+    };
+    let on_error = move |err: String| set_message.set(Some(Err(err)));
+
+    let create_user = move |_| {
+        let args = CreateUserArgs {
+            user_data: CreateUserRequest {
+                username: username.get(),
+                display_name: display_name.get(),
+                role: role.get(),
+                password: password.get(),
+            },
+        };
+        spawn_local(async move {
+            set_busy.set(true);
+            match invoke("create_user", serde_wasm_bindgen::to_value(&args).unwrap()).await {
+                Ok(value) => {
+                    if let Ok(user) = serde_wasm_bindgen::from_value::<User>(value) {
+                        on_changed(format!("Account {} created", user.username));
+                    }
+                    set_username.set(String::new());
+                    set_display_name.set(String::new());
+                    set_password.set(String::new());
+                }
+                Err(e) => on_error(invoke_error("Failed to create account", e)),
+            }
+            set_busy.set(false);
+        });
+    };
+
+    view! {
+        <div class="settings-item" style="flex-direction: column; align-items: stretch; gap: 1rem;">
+            <div style="font-size: 0.875rem; color: var(--color-text-secondary);">
+                "Operators run tests, reviewers also release results, administrators manage users, devices and settings"
+            </div>
+
+            {move || {
+                let own_uuid = current_user.get().map(|u| u.uuid);
+                users.get().into_iter().map(|user| {
+                    let is_self = own_uuid.as_deref() == Some(user.uuid.as_str());
+                    view! {
+                        <UserRow
+                            user=user
+                            is_self=is_self
+                            on_changed=on_changed
+                            on_error=on_error
+                        />
+                    }
+                }).collect_view()
+            }}
+
+            // Add account
+            <div style="display: flex; gap: 0.75rem; align-items: center;">
+                <input
+                    type="text"
+                    class="form-input"
+                    placeholder="Username"
+                    prop:value=move || username.get()
+                    on:input=move |e| set_username.set(event_target_value(&e))
+                    style="flex: 1;"
+                />
+                <input
+                    type="text"
+                    class="form-input"
+                    placeholder="Display name"
+                    prop:value=move || display_name.get()
+                    on:input=move |e| set_display_name.set(event_target_value(&e))
+                    style="flex: 1;"
+                />
+                <RoleSelect role=role set_role=set_role />
+                <input
+                    type="password"
+                    class="form-input"
+                    placeholder="Password (8+ characters)"
+                    prop:value=move || password.get()
+                    on:input=move |e| set_password.set(event_target_value(&e))
+                    style="flex: 1;"
+                />
+                <button
+                    class="button primary"
+                    on:click=create_user
+                    disabled=move || busy.get() || username.get().trim().is_empty() || password.get().is_empty()
+                    style="padding: 0.625rem 1.25rem; font-size: 0.875rem; white-space: nowrap;"
+                >
+                    "Add User"
+                </button>
+            </div>
+
+            {move || message.get().map(|result| match result {
+                Ok(msg) => view! {
+                    <div style="padding: 0.75rem; background: #d4edda; border: 1px solid #c3e6cb; border-radius: 6px; color: #155724; font-size: 0.875rem;">
+                        {msg}
+                    </div>
+                }.into_any(),
+                Err(err) => view! {
+                    <div style="padding: 0.75rem; background: #f8d7da; border: 1px solid #f5c6cb; border-radius: 6px; color: #721c24; font-size: 0.875rem;">
+                        {err}
+                    </div>
+                }.into_any(),
+            })}
+        </div>
+    }
+}
+
+#[component]
+fn UserRow(
+    user: User,
+    is_self: bool,
+    on_changed: impl Fn(String) + Copy + Send + Sync + 'static,
+    on_error: impl Fn(String) + Copy + Send + Sync + 'static,
+) -> impl IntoView {
+    let uuid = user.uuid.clone();
+    let username = user.username.clone();
+    let (display_name, set_display_name) = signal(user.display_name.clone());
+    let (role, set_role) = signal(user.role);
+    let (active, set_active) = signal(user.active);
+    let (new_password, set_new_password) = signal(String::new());
+    let (busy, set_busy) = signal(false);
+
+    let save_uuid = uuid.clone();
+    let save_name = username.clone();
+    let on_save = move |_| {
+        let args = UpdateUserArgs {
+            user_data: UpdateUserRequest {
+                uuid: save_uuid.clone(),
+                display_name: display_name.get(),
+                role: role.get(),
+                active: active.get(),
+            },
+        };
+        let username = save_name.clone();
+        spawn_local(async move {
+            set_busy.set(true);
+            match invoke("update_user", serde_wasm_bindgen::to_value(&args).unwrap()).await {
+                Ok(_) => on_changed(format!("Account {} saved", username)),
+                Err(e) => on_error(invoke_error("Failed to save account", e)),
+            }
+            set_busy.set(false);
+        });
+    };
+
+    let reset_name = username.clone();
+    let on_reset = move |_| {
+        let args = ResetUserPasswordArgs {
+            uuid: uuid.clone(),
+            password: new_password.get(),
+        };
+        let username = reset_name.clone();
+        spawn_local(async move {
+            set_busy.set(true);
+            match invoke(
+                "reset_user_password",
+                serde_wasm_bindgen::to_value(&args).unwrap(),
+            )
+            .await
+            {
+                Ok(_) => {
+                    set_new_password.set(String::new());
+                    on_changed(format!("Password of {} reset", username));
+                }
+                Err(e) => on_error(invoke_error("Failed to reset password", e)),
+            }
+            set_busy.set(false);
+        });
+    };
+
+    let last_login = user
+        .last_login_at
+        .as_deref()
+        .and_then(|t| t.get(..16))
+        .map(|t| format!("Last sign-in {}", t.replace('T', " ")))
+        .unwrap_or_else(|| "Never signed in".to_string());
+
+    view! {
+        <div style="padding: 0.75rem; background: var(--color-surface); border: 1px solid var(--color-border-light); border-radius: 6px;">
+            <div style="display: flex; gap: 0.75rem; align-items: center;">
+                <div style="font-family: monospace; font-size: 0.875rem; color: var(--color-text-secondary); min-width: 100px;">
+                    {username}
+                </div>
+                <input
+                    type="text"
+                    class="form-input"
+                    prop:value=move || display_name.get()
+                    on:input=move |e| set_display_name.set(event_target_value(&e))
+                    style="flex: 1;"
+                />
+                <RoleSelect role=role set_role=set_role />
+                <label style="display: flex; align-items: center; gap: 0.375rem; font-size: 0.875rem; color: var(--color-text-secondary);">
+                    <input
+                        type="checkbox"
+                        prop:checked=move || active.get()
+                        disabled=is_self
+                        on:change=move |e| set_active.set(event_target_checked(&e))
+                    />
+                    "Active"
+                </label>
+                <button
+                    class="button primary"
+                    on:click=on_save
+                    disabled=move || busy.get()
+                    style="padding: 0.5rem 0.75rem; font-size: 0.875rem;"
+                >
+                    "Save"
+                </button>
+            </div>
+            <div style="display: flex; gap: 0.75rem; align-items: center; margin-top: 0.5rem;">
+                <div style="flex: 1; font-size: 0.75rem; color: var(--color-text-secondary);">
+                    {last_login}
+                </div>
+                <input
+                    type="password"
+                    class="form-input"
+                    placeholder="New password"
+                    prop:value=move || new_password.get()
+                    on:input=move |e| set_new_password.set(event_target_value(&e))
+                    style="flex: 1;"
+                />
+                <button
+                    class="button"
+                    on:click=on_reset
+                    disabled=move || busy.get() || new_password.get().is_empty()
+                    style="padding: 0.5rem 0.75rem; font-size: 0.875rem; white-space: nowrap;"
+                >
+                    "Reset Password"
+                </button>
+            </div>
+        </div>
+    }
+}
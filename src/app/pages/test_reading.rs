@@ -1,9 +1,28 @@
+use crate::app::components::LabelButton;
+use crate::app::errors::invoke_error;
 use crate::app::Page;
 use leptos::prelude::*;
+use leptos::task::spawn_local;
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(catch, js_namespace = ["window", "__TAURI__", "core"])]
+    async fn invoke(cmd: &str, args: JsValue) -> Result<JsValue, JsValue>;
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct UpdateTestStatusArgs {
+    test_uuid: String,
+    status: String,
+}
 
 #[component]
 pub fn TestReadingPage(
-    connected: ReadSignal<bool>,
+    connected: Signal<bool>,
+    current_test_uuid: ReadSignal<Option<String>>,
     on_navigate: WriteSignal<Page>,
     detection_loading: ReadSignal<bool>,
     detection_result: ReadSignal<Option<crate::app::serial::DetectionData>>,
@@ -18,7 +37,25 @@ pub fn TestReadingPage(
     });
 
     let on_cancel = move |_| {
-        // TODO: Cancel the test and clean up
+        // Cancel the test so its device is released for the next one
+        if detection_result.get_untracked().is_none() && detection_error.get_untracked().is_none() {
+            if let Some(test_uuid) = current_test_uuid.get_untracked() {
+                spawn_local(async move {
+                    let args = UpdateTestStatusArgs {
+                        test_uuid,
+                        status: "cancelled".to_string(),
+                    };
+                    if let Err(e) = invoke(
+                        "update_test_status",
+                        serde_wasm_bindgen::to_value(&args).unwrap(),
+                    )
+                    .await
+                    {
+                        invoke_error("Failed to cancel test", e);
+                    }
+                });
+            }
+        }
         on_navigate.set(Page::Landing);
     };
 
@@ -200,6 +237,16 @@ pub fn TestReadingPage(
                     }
                 }}
 
+                // Sample labels, so the tube and cartridge stay linked to this test
+                {move || current_test_uuid.get().map(|test_uuid| view! {
+                    <div style="display: flex; justify-content: center; align-items: flex-start; gap: 1rem;">
+                        <span style="color: var(--color-text-secondary); font-size: 0.875rem; padding-top: 0.5rem;">
+                            "Label the tube and cartridge"
+                        </span>
+                        <LabelButton test_uuid=test_uuid />
+                    </div>
+                })}
+
                 // Cancel Button
                 <div style="margin-top: 2rem; padding-top: 2rem; border-top: 1px solid var(--color-border-light);">
                     <button
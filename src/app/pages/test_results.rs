@@ -1,7 +1,7 @@
+use crate::app::errors::invoke_error;
 use crate::app::Page;
 use leptos::prelude::*;
 use leptos::task::spawn_local;
-use leptos::web_sys::console;
 use serde::{Deserialize, Serialize};
 use wasm_bindgen::prelude::*;
 
@@ -24,8 +24,15 @@ struct CompleteTestArgs {
     data: CompleteTestData,
 }
 
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SaveTestFileArgs {
+    test_uuid: String,
+    path: String,
+}
+
 // Use shared types
-use shared_types::{Patient, Test};
+use shared_types::{Patient, Test, INCONCLUSIVE_THRESHOLD, POSITIVE_THRESHOLD};
 
 #[component]
 pub fn TestResultsPage(
@@ -34,8 +41,11 @@ pub fn TestResultsPage(
     current_test_uuid: ReadSignal<Option<String>>,
 ) -> impl IntoView {
     let (current_patient, set_current_patient) = signal(None::<Patient>);
-    let (_current_test, set_current_test) = signal(None::<Test>);
+    let (current_test, set_current_test) = signal(None::<Test>);
     let (loading_patient, set_loading_patient) = signal(false);
+    let (report_path, set_report_path) = signal(String::new());
+    let (saving_report, set_saving_report) = signal(false);
+    let (report_message, set_report_message) = signal(None::<Result<String, String>>);
 
     // Fetch test and patient data when test UUID changes
     Effect::new(move || {
@@ -79,19 +89,13 @@ pub fn TestResultsPage(
                                     }
                                 }
                                 Err(e) => {
-                                    console::log_1(&JsValue::from_str(&format!(
-                                        "Failed to fetch patients: {:?}",
-                                        e
-                                    )));
+                                    invoke_error("Failed to fetch patients", e);
                                 }
                             }
                         }
                     }
                     Err(e) => {
-                        console::log_1(&JsValue::from_str(&format!(
-                            "Failed to fetch test: {:?}",
-                            e
-                        )));
+                        invoke_error("Failed to fetch test", e);
                     }
                 }
 
@@ -103,9 +107,9 @@ pub fn TestResultsPage(
     Effect::new(move || {
         if let (Some(result), Some(test_uuid)) = (detection_result.get(), current_test_uuid.get()) {
             // Determine detection result based on probability
-            let detection_result_str = if result.probability >= 0.7 {
+            let detection_result_str = if result.probability >= POSITIVE_THRESHOLD {
                 "positive"
-            } else if result.probability >= 0.3 {
+            } else if result.probability >= INCONCLUSIVE_THRESHOLD {
                 "inconclusive"
             } else {
                 "negative"
@@ -132,16 +136,21 @@ pub fn TestResultsPage(
                     data: complete_data,
                 };
 
-                if let Err(e) = invoke(
+                match invoke(
                     "complete_test",
                     serde_wasm_bindgen::to_value(&args).unwrap(),
                 )
                 .await
                 {
-                    console::log_1(&JsValue::from_str(&format!(
-                        "Failed to save test results: {:?}",
-                        e
-                    )));
+                    // Released at once, or held for a reviewer
+                    Ok(value) => {
+                        if let Ok(test) = serde_wasm_bindgen::from_value::<Test>(value) {
+                            set_current_test.set(Some(test));
+                        }
+                    }
+                    Err(e) => {
+                        invoke_error("Failed to save test results", e);
+                    }
                 }
             });
         }
@@ -155,6 +164,36 @@ pub fn TestResultsPage(
         on_navigate.set(Page::History);
     };
 
+    // Both the PDF report and the FHIR bundle are written to the path entered
+    let save_file = move |command: &'static str, what: &'static str| {
+        let Some(test) = current_test.get() else {
+            return;
+        };
+        let args = SaveTestFileArgs {
+            test_uuid: test.uuid,
+            path: report_path.get(),
+        };
+        spawn_local(async move {
+            set_saving_report.set(true);
+            set_report_message.set(None);
+            let message = match invoke(command, serde_wasm_bindgen::to_value(&args).unwrap()).await
+            {
+                Ok(value) => serde_wasm_bindgen::from_value::<String>(value)
+                    .map(|path| format!("{} saved to {}", what, path))
+                    .map_err(|e| format!("Failed to parse saved path: {:?}", e)),
+                Err(e) => Err(invoke_error(&format!("Failed to save {}", what), e)),
+            };
+            set_report_message.set(Some(message));
+            set_saving_report.set(false);
+        });
+    };
+
+    let cannot_save = move || {
+        saving_report.get()
+            || report_path.get().trim().is_empty()
+            || !current_test.get().is_some_and(|test| test.is_released())
+    };
+
     view! {
         <div class="animate-fade-in">
             // Header
@@ -269,9 +308,9 @@ pub fn TestResultsPage(
                                 <div>
                                     <div style=format!(
                                         "padding: 1.5rem; border-radius: 8px; margin-bottom: 1.5rem; text-align: center; {}",
-                                        if result.probability >= 0.7 {
+                                        if result.probability >= POSITIVE_THRESHOLD {
                                             "background-color: #fee2e2; border: 2px solid #dc2626;"
-                                        } else if result.probability >= 0.3 {
+                                        } else if result.probability >= INCONCLUSIVE_THRESHOLD {
                                             "background-color: #fef3c7; border: 2px solid #f59e0b;"
                                         } else {
                                             "background-color: #dcfce7; border: 2px solid #16a34a;"
@@ -280,18 +319,18 @@ pub fn TestResultsPage(
                                         <div style="margin-bottom: 0.5rem;">
                                             <div style=format!(
                                                 "width: 64px; height: 64px; border-radius: 50%; margin: 0 auto; display: flex; align-items: center; justify-content: center; {}",
-                                                if result.probability >= 0.7 {
+                                                if result.probability >= POSITIVE_THRESHOLD {
                                                     "background-color: #dc2626;"
-                                                } else if result.probability >= 0.3 {
+                                                } else if result.probability >= INCONCLUSIVE_THRESHOLD {
                                                     "background-color: #f59e0b;"
                                                 } else {
                                                     "background-color: #16a34a;"
                                                 }
                                             )>
                                                 <svg width="32" height="32" viewBox="0 0 24 24" fill="none" xmlns="http://www.w3.org/2000/svg" style="color: white;">
-                                                    {if result.probability >= 0.7 {
+                                                    {if result.probability >= POSITIVE_THRESHOLD {
                                                         view! { <path d="M12 2C6.48 2 2 6.48 2 12C2 17.52 6.48 22 12 22C17.52 22 22 17.52 22 12C22 6.48 17.52 2 12 2ZM13 17H11V15H13V17ZM13 13H11V7H13V13Z" fill="currentColor"/> }
-                                                    } else if result.probability >= 0.3 {
+                                                    } else if result.probability >= INCONCLUSIVE_THRESHOLD {
                                                         view! { <path d="M12 2C6.48 2 2 6.48 2 12C2 17.52 6.48 22 12 22C17.52 22 22 17.52 22 12C22 6.48 17.52 2 12 2ZM13 17H11V15H13V17ZM13 13H11V7H13V13Z" fill="currentColor"/> }
                                                     } else {
                                                         view! { <path d="M9 16.2L4.8 12L3.4 13.4L9 19L21 7L19.6 5.6L9 16.2Z" fill="currentColor"/> }
@@ -301,17 +340,17 @@ pub fn TestResultsPage(
                                         </div>
                                         <div style=format!(
                                             "font-size: 1.5rem; font-weight: 600; margin-bottom: 0.5rem; {}",
-                                            if result.probability >= 0.7 {
+                                            if result.probability >= POSITIVE_THRESHOLD {
                                                 "color: #991b1b;"
-                                            } else if result.probability >= 0.3 {
+                                            } else if result.probability >= INCONCLUSIVE_THRESHOLD {
                                                 "color: #92400e;"
                                             } else {
                                                 "color: #166534;"
                                             }
                                         )>
-                                            {if result.probability >= 0.7 {
+                                            {if result.probability >= POSITIVE_THRESHOLD {
                                                 "POSITIVE DETECTION"
-                                            } else if result.probability >= 0.3 {
+                                            } else if result.probability >= INCONCLUSIVE_THRESHOLD {
                                                 "INCONCLUSIVE"
                                             } else {
                                                 "NEGATIVE"
@@ -319,9 +358,9 @@ pub fn TestResultsPage(
                                         </div>
                                         <div style=format!(
                                             "font-size: 1rem; font-weight: 500; {}",
-                                            if result.probability >= 0.7 {
+                                            if result.probability >= POSITIVE_THRESHOLD {
                                                 "color: #7f1d1d;"
-                                            } else if result.probability >= 0.3 {
+                                            } else if result.probability >= INCONCLUSIVE_THRESHOLD {
                                                 "color: #78350f;"
                                             } else {
                                                 "color: #14532d;"
@@ -406,9 +445,22 @@ pub fn TestResultsPage(
                             <div style="font-size: 0.875rem; color: var(--color-text-secondary); margin-bottom: 0.25rem;">
                                 "Status"
                             </div>
-                            <div style="font-size: 1rem; color: var(--color-success); font-weight: 500;">
-                                "Completed"
-                            </div>
+                            {move || {
+                                let (label, color) = match current_test.get() {
+                                    Some(test) if test.status == "awaiting_review" => {
+                                        ("Awaiting review", "var(--color-warning)")
+                                    }
+                                    Some(test) if test.is_released() => {
+                                        ("Released", "var(--color-success)")
+                                    }
+                                    _ => ("Completed", "var(--color-success)"),
+                                };
+                                view! {
+                                    <div style=format!("font-size: 1rem; color: {}; font-weight: 500;", color)>
+                                        {label}
+                                    </div>
+                                }
+                            }}
                         </div>
                         <div>
                             <div style="font-size: 0.875rem; color: var(--color-text-secondary); margin-bottom: 0.25rem;">
@@ -421,6 +473,61 @@ pub fn TestResultsPage(
                     </div>
                 </div>
 
+                // Report Card
+                <div class="card">
+                    <h2 style="font-size: 1.25rem; font-weight: 500; margin-bottom: 1.5rem; color: var(--color-text-secondary); border-bottom: 1px solid var(--color-border-light); padding-bottom: 0.5rem;">
+                        "Report"
+                    </h2>
+                    <p style="font-size: 0.875rem; color: var(--color-text-secondary); margin: 0 0 1rem;">
+                        {move || if current_test.get().is_some_and(|test| test.is_released()) {
+                            "Save a printable PDF of this result, or a FHIR R4 bundle for an EHR. A file name alone is saved in your Documents folder."
+                        } else {
+                            "A PDF report or FHIR bundle can be saved once the result has been released."
+                        }}
+                    </p>
+                    <div style="display: flex; gap: 1rem; align-items: center;">
+                        <input
+                            type="text"
+                            class="form-input"
+                            style="flex: 1;"
+                            placeholder="e.g. report.pdf or C:\\Reports\\report.pdf"
+                            prop:value=move || report_path.get()
+                            on:input=move |e| set_report_path.set(event_target_value(&e))
+                        />
+                        <button
+                            class="button"
+                            on:click=move |_| save_file("generate_test_report", "Report")
+                            disabled=cannot_save
+                        >
+                            {move || if saving_report.get() { "Saving..." } else { "Save PDF Report" }}
+                        </button>
+                        <button
+                            class="button"
+                            on:click=move |_| save_file("export_fhir_bundle", "FHIR bundle")
+                            disabled=cannot_save
+                        >
+                            "Save FHIR Bundle"
+                        </button>
+                    </div>
+                    {move || report_message.get().map(|message| {
+                        let (text, style) = match message {
+                            Ok(saved) => (
+                                saved,
+                                "background-color: #d4edda; border: 1px solid #c3e6cb; color: #155724;",
+                            ),
+                            Err(err) => (
+                                err,
+                                "background-color: #f8d7da; border: 1px solid #f5c6cb; color: #721c24;",
+                            ),
+                        };
+                        view! {
+                            <div style=format!("margin-top: 1rem; padding: 0.75rem; border-radius: 4px; {}", style)>
+                                {text}
+                            </div>
+                        }
+                    })}
+                </div>
+
                 // Action Buttons
                 <div style="display: flex; gap: 1rem; justify-content: center; padding-top: 1rem;">
                     <button
@@ -0,0 +1,94 @@
+use crate::app::errors::invoke_error;
+use leptos::prelude::*;
+use leptos::task::spawn_local;
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(catch, js_namespace = ["window", "__TAURI__", "core"])]
+    async fn invoke(cmd: &str, args: JsValue) -> Result<JsValue, JsValue>;
+}
+
+#[derive(Serialize)]
+struct UnlockDatabaseArgs {
+    passphrase: String,
+}
+
+/// Shown at startup while a passphrase-protected database is still locked
+#[component]
+pub fn UnlockPage(on_unlocked: impl Fn() + Copy + Send + Sync + 'static) -> impl IntoView {
+    let (passphrase, set_passphrase) = signal(String::new());
+    let (unlocking, set_unlocking) = signal(false);
+    let (error, set_error) = signal(None::<String>);
+
+    let on_submit = move || {
+        let args = UnlockDatabaseArgs {
+            passphrase: passphrase.get(),
+        };
+        spawn_local(async move {
+            set_unlocking.set(true);
+            set_error.set(None);
+            match invoke(
+                "unlock_database",
+                serde_wasm_bindgen::to_value(&args).unwrap(),
+            )
+            .await
+            {
+                Ok(_) => {
+                    set_passphrase.set(String::new());
+                    on_unlocked();
+                }
+                Err(e) => set_error.set(Some(invoke_error("Failed to unlock database", e))),
+            }
+            set_unlocking.set(false);
+        });
+    };
+
+    view! {
+        <div class="animate-fade-in">
+            <header style="margin-bottom: 2rem; text-align: center;">
+                <h1 style="margin: 0; font-size: 2rem; font-weight: 300;">
+                    "Database Locked"
+                </h1>
+                <p style="color: var(--color-text-secondary); margin: 0.5rem 0 0;">
+                    "Enter the database passphrase to continue"
+                </p>
+            </header>
+
+            <div class="card" style="max-width: 480px; margin: 0 auto;">
+                <form on:submit=move |e| {
+                    e.prevent_default();
+                    on_submit();
+                }>
+                    <div class="form-group">
+                        <label class="form-label">"Passphrase"</label>
+                        <input
+                            type="password"
+                            class="form-input"
+                            autofocus
+                            prop:value=move || passphrase.get()
+                            on:input=move |e| set_passphrase.set(event_target_value(&e))
+                        />
+                    </div>
+
+                    {move || error.get().map(|err| view! {
+                        <div style="padding: 1rem; background-color: rgba(239, 68, 68, 0.1); border: 1px solid rgba(239, 68, 68, 0.3); border-radius: 8px; color: rgb(239, 68, 68); margin-bottom: 1rem;">
+                            {err}
+                        </div>
+                    })}
+
+                    <div style="display: flex; justify-content: flex-end;">
+                        <button
+                            type="submit"
+                            class="button"
+                            disabled=move || unlocking.get() || passphrase.get().is_empty()
+                        >
+                            {move || if unlocking.get() { "Unlocking..." } else { "Unlock" }}
+                        </button>
+                    </div>
+                </form>
+            </div>
+        </div>
+    }
+}
@@ -0,0 +1,688 @@
+use crate::app::components::LabelButton;
+use crate::app::errors::invoke_error;
+use crate::app::scanner::on_barcode_scan;
+use crate::app::Page;
+use leptos::prelude::*;
+use leptos::task::spawn_local;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+// Use shared types
+use shared_types::{Device, Patient, TestStatus, TestType, TestWithPatient};
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(catch, js_namespace = ["window", "__TAURI__", "core"])]
+    async fn invoke(cmd: &str, args: JsValue) -> Result<JsValue, JsValue>;
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct StartOrderArgs {
+    test_uuid: String,
+    device_id: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct UpdateTestStatusArgs {
+    test_uuid: String,
+    status: String,
+}
+
+#[derive(Serialize)]
+struct CreateOrderRequest {
+    patient_uuid: String,
+    test_type: String,
+    specimen_id: Option<String>,
+}
+
+#[derive(Serialize)]
+struct CreateOrderArgs {
+    order: CreateOrderRequest,
+}
+
+#[derive(Serialize)]
+struct LookupBarcodeArgs {
+    code: String,
+}
+
+/// What a scanned barcode identifies
+#[derive(Deserialize, Clone)]
+struct ScanMatch {
+    code: String,
+    test: Option<TestWithPatient>,
+    patient: Option<Patient>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ImportOrdersArgs {
+    path: String,
+    dry_run: bool,
+}
+
+/// Outcome for one row of an order file
+#[derive(Deserialize, Clone)]
+struct OrderImportRow {
+    line: usize,
+    status: String,
+    patient_id_number: String,
+    patient_name: Option<String>,
+    test_type: String,
+    specimen_id: Option<String>,
+    errors: Vec<String>,
+    duplicate_of: Option<String>,
+}
+
+/// Summary of an order file preview or import
+#[derive(Deserialize, Clone)]
+struct OrderImportReport {
+    dry_run: bool,
+    total_rows: usize,
+    new_orders: usize,
+    duplicates: usize,
+    invalid: usize,
+    imported: usize,
+    rows: Vec<OrderImportRow>,
+}
+
+/// Fetch the pending orders, oldest first
+async fn fetch_worklist() -> Result<Vec<TestWithPatient>, String> {
+    let value = invoke("get_worklist", JsValue::NULL)
+        .await
+        .map_err(|e| invoke_error("Failed to load worklist", e))?;
+    serde_wasm_bindgen::from_value::<Vec<TestWithPatient>>(value)
+        .map_err(|e| format!("Failed to parse worklist: {:?}", e))
+}
+
+/// The order a scan points at, or why there is none
+fn scanned_order(found: &ScanMatch, orders: &[TestWithPatient]) -> Result<String, String> {
+    if let Some(test) = &found.test {
+        if test.test.status != TestStatus::Pending.as_str() {
+            return Err(format!(
+                "{} is a {} test that is already {}",
+                found.code, test.test.test_type, test.test.status
+            ));
+        }
+        return Ok(test.test.uuid.clone());
+    }
+    match &found.patient {
+        Some(patient) => orders
+            .iter()
+            .find(|o| o.patient.uuid == patient.uuid)
+            .map(|o| o.test.uuid.clone())
+            .ok_or_else(|| {
+                format!(
+                    "{} {} has no pending order",
+                    patient.first_name, patient.last_name
+                )
+            }),
+        None => Err(format!("No order or patient matches {}", found.code)),
+    }
+}
+
+/// Where an order came from, for the queue
+fn order_source(entry: &TestWithPatient) -> String {
+    match (&entry.test.retest_of, &entry.test.specimen_id) {
+        (Some(_), _) => "Retest".to_string(),
+        (None, Some(specimen)) => format!("Specimen {}", specimen),
+        (None, None) => "Ordered".to_string(),
+    }
+}
+
+#[component]
+pub fn WorklistPage(
+    on_navigate: WriteSignal<Page>,
+    set_current_test_uuid: WriteSignal<Option<String>>,
+    set_current_device_id: WriteSignal<Option<String>>,
+) -> impl IntoView {
+    let (orders, set_orders) = signal(Vec::<TestWithPatient>::new());
+    let (loading, set_loading) = signal(true);
+    let (devices, set_devices) = signal(Vec::<Device>::new());
+    let (device_id, set_device_id) = signal(String::new());
+    let (busy, set_busy) = signal(false);
+    let (error, set_error) = signal(None::<String>);
+    // Order picked out by the last barcode scanned
+    let (scanned_uuid, set_scanned_uuid) = signal(None::<String>);
+
+    let load_orders = move || {
+        spawn_local(async move {
+            set_loading.set(true);
+            match fetch_worklist().await {
+                Ok(list) => {
+                    set_orders.set(list);
+                    set_error.set(None);
+                }
+                Err(err) => set_error.set(Some(err)),
+            }
+            set_loading.set(false);
+        });
+    };
+    load_orders();
+
+    // Load the readers an order can be run on
+    spawn_local(async move {
+        match invoke("list_devices", JsValue::NULL).await {
+            Ok(value) => {
+                if let Ok(device_list) = serde_wasm_bindgen::from_value::<Vec<Device>>(value) {
+                    let enabled: Vec<Device> =
+                        device_list.into_iter().filter(|d| d.enabled).collect();
+                    if let Some(first) = enabled.first() {
+                        set_device_id.set(first.device_id.clone());
+                    }
+                    set_devices.set(enabled);
+                }
+            }
+            Err(e) => {
+                invoke_error("Failed to list devices", e);
+            }
+        }
+    });
+
+    on_barcode_scan(move |code| {
+        spawn_local(async move {
+            let args = LookupBarcodeArgs { code };
+            match invoke(
+                "lookup_barcode",
+                serde_wasm_bindgen::to_value(&args).unwrap(),
+            )
+            .await
+            {
+                Ok(value) => match serde_wasm_bindgen::from_value::<ScanMatch>(value) {
+                    Ok(found) => match orders.with_untracked(|o| scanned_order(&found, o)) {
+                        Ok(test_uuid) => {
+                            set_error.set(None);
+                            set_scanned_uuid.set(Some(test_uuid));
+                        }
+                        Err(err) => {
+                            set_scanned_uuid.set(None);
+                            set_error.set(Some(err));
+                        }
+                    },
+                    Err(e) => set_error.set(Some(format!("Failed to parse scan: {:?}", e))),
+                },
+                Err(e) => set_error.set(Some(invoke_error("Failed to look up barcode", e))),
+            }
+        });
+    });
+
+    let start = move |test_uuid: String| {
+        let device = device_id.get();
+        if device.is_empty() {
+            set_error.set(Some("Select a device to run the test on".to_string()));
+            return;
+        }
+        let args = StartOrderArgs {
+            test_uuid: test_uuid.clone(),
+            device_id: device.clone(),
+        };
+        spawn_local(async move {
+            set_busy.set(true);
+            match invoke("start_order", serde_wasm_bindgen::to_value(&args).unwrap()).await {
+                Ok(_) => {
+                    set_current_device_id.set(Some(device));
+                    set_current_test_uuid.set(Some(test_uuid));
+                    on_navigate.set(Page::TestReading);
+                }
+                Err(e) => set_error.set(Some(invoke_error("Failed to start order", e))),
+            }
+            set_busy.set(false);
+        });
+    };
+
+    let cancel = move |test_uuid: String| {
+        let args = UpdateTestStatusArgs {
+            test_uuid,
+            status: "cancelled".to_string(),
+        };
+        spawn_local(async move {
+            match invoke(
+                "update_test_status",
+                serde_wasm_bindgen::to_value(&args).unwrap(),
+            )
+            .await
+            {
+                Ok(_) => load_orders(),
+                Err(e) => set_error.set(Some(invoke_error("Failed to cancel order", e))),
+            }
+        });
+    };
+
+    view! {
+        <div class="animate-fade-in">
+            <header style="margin-bottom: 2rem;">
+                <div style="display: flex; align-items: center; justify-content: space-between; margin-bottom: 1rem;">
+                    <button
+                        class="button"
+                        on:click=move |_| on_navigate.set(Page::Landing)
+                        style="padding: 0.5rem 1rem;"
+                    >
+                        "← Back to Home"
+                    </button>
+                    <h1 style="margin: 0; font-size: 2rem; font-weight: 300;">
+                        "Worklist"
+                    </h1>
+                    <button
+                        class="button"
+                        on:click=move |_| load_orders()
+                        style="padding: 0.5rem 1rem;"
+                    >
+                        "Refresh"
+                    </button>
+                </div>
+                <p style="text-align: center; color: var(--color-text-secondary); margin: 0;">
+                    "Orders waiting to be run, oldest first; scan a sample or patient barcode to find one"
+                </p>
+            </header>
+
+            <div class="card" style="max-width: 800px; margin: 0 auto 2rem auto;">
+                <div style="display: flex; gap: 1rem; align-items: flex-end;">
+                    <div class="form-group" style="flex: 1;">
+                        <label class="form-label">"Run on"</label>
+                        <select
+                            class="form-input"
+                            prop:value=move || device_id.get()
+                            on:change=move |e| set_device_id.set(event_target_value(&e))
+                        >
+                            {move || {
+                                let device_list = devices.get();
+                                if device_list.is_empty() {
+                                    vec![view! {
+                                        <option value="">"No devices configured"</option>
+                                    }.into_any()]
+                                } else {
+                                    device_list.into_iter().map(|device| {
+                                        let text = format!("{} ({})", device.label, device.port);
+                                        view! {
+                                            <option value=device.device_id>{text}</option>
+                                        }.into_any()
+                                    }).collect::<Vec<_>>()
+                                }
+                            }}
+                        </select>
+                    </div>
+                    <button
+                        class="button primary"
+                        on:click=move |_| {
+                            if let Some(next) = orders.get().first() {
+                                start(next.test.uuid.clone());
+                            }
+                        }
+                        disabled=move || busy.get() || orders.with(|o| o.is_empty())
+                        style="padding: 0.75rem 2rem; font-weight: 500;"
+                    >
+                        "Start Next Order →"
+                    </button>
+                </div>
+
+                {move || error.get().map(|err| view! {
+                    <div style="padding: 1rem; background-color: rgba(239, 68, 68, 0.1); border: 1px solid rgba(239, 68, 68, 0.3); border-radius: 8px; color: rgb(239, 68, 68); margin-top: 1rem;">
+                        {err}
+                    </div>
+                })}
+            </div>
+
+            <div style="max-width: 800px; margin: 0 auto 2rem auto; display: flex; flex-direction: column; gap: 1rem;">
+                {move || {
+                    if loading.get() {
+                        view! {
+                            <div style="text-align: center; padding: 3rem;">
+                                <p>"Loading worklist..."</p>
+                            </div>
+                        }.into_any()
+                    } else if orders.with(|o| o.is_empty()) {
+                        view! {
+                            <div style="text-align: center; padding: 3rem;">
+                                <p style="color: var(--color-text-secondary);">
+                                    "No orders waiting"
+                                </p>
+                            </div>
+                        }.into_any()
+                    } else {
+                        orders.get().into_iter().map(|entry| {
+                            let uuid = entry.test.uuid.clone();
+                            let cancel_uuid = entry.test.uuid.clone();
+                            let source = order_source(&entry);
+                            let label_uuid = entry.test.uuid.clone();
+                            let highlight_uuid = entry.test.uuid.clone();
+                            let patient_id = entry.patient.patient_id_number.clone();
+                            let accession = entry.test.accession_number.clone();
+                            let card_style = move || format!(
+                                "padding: 1rem 1.5rem; display: flex; justify-content: space-between; align-items: center; gap: 1rem;{}",
+                                if scanned_uuid.get().as_ref() == Some(&highlight_uuid) {
+                                    " outline: 2px solid var(--color-accent-primary);"
+                                } else {
+                                    ""
+                                }
+                            );
+                            view! {
+                                <div class="card" style=card_style>
+                                    <div style="min-width: 0;">
+                                        <div style="font-weight: 500; color: var(--color-text-primary);">
+                                            {format!("{} {}", entry.patient.first_name, entry.patient.last_name)}
+                                            {patient_id.map(|id| view! {
+                                                <span style="font-weight: 400; color: var(--color-text-secondary);">
+                                                    {format!(" · {}", id)}
+                                                </span>
+                                            })}
+                                        </div>
+                                        <div style="font-size: 0.875rem; color: var(--color-text-secondary);">
+                                            {format!("{} · {} · ordered {}", entry.test.test_type, source, entry.test.created_at)}
+                                        </div>
+                                        {accession.map(|number| view! {
+                                            <div style="font-size: 0.75rem; font-family: monospace; color: var(--color-text-secondary);">
+                                                {format!("Accession {}", number)}
+                                            </div>
+                                        })}
+                                        {entry.test.device_id.clone().map(|device| view! {
+                                            <div style="font-size: 0.75rem; color: var(--color-warning);">
+                                                {format!("Started on {} but not run yet", device)}
+                                            </div>
+                                        })}
+                                    </div>
+                                    <div style="display: flex; gap: 0.5rem; align-items: flex-start;">
+                                        <LabelButton test_uuid=label_uuid />
+                                        <button
+                                            class="button"
+                                            on:click=move |_| cancel(cancel_uuid.clone())
+                                            disabled=move || busy.get()
+                                            style="padding: 0.5rem 0.75rem; font-size: 0.875rem;"
+                                        >
+                                            "Cancel"
+                                        </button>
+                                        <button
+                                            class="button"
+                                            on:click=move |_| start(uuid.clone())
+                                            disabled=move || busy.get()
+                                            style="padding: 0.5rem 0.75rem; font-size: 0.875rem; background-color: var(--color-accent-primary); color: white;"
+                                        >
+                                            "Start"
+                                        </button>
+                                    </div>
+                                </div>
+                            }
+                        }).collect_view().into_any()
+                    }
+                }}
+            </div>
+
+            <div class="card" style="max-width: 800px; margin: 0 auto 2rem auto;">
+                <h2 style="font-size: 1.25rem; font-weight: 500; margin-bottom: 1.5rem; color: var(--color-text-secondary); border-bottom: 1px solid var(--color-border-light); padding-bottom: 0.5rem;">
+                    "New Order"
+                </h2>
+                <NewOrderPanel on_added=load_orders />
+            </div>
+
+            <div class="card" style="max-width: 800px; margin: 0 auto 2rem auto;">
+                <h2 style="font-size: 1.25rem; font-weight: 500; margin-bottom: 1.5rem; color: var(--color-text-secondary); border-bottom: 1px solid var(--color-border-light); padding-bottom: 0.5rem;">
+                    "Import Orders"
+                </h2>
+                <OrderImportPanel on_imported=load_orders />
+            </div>
+        </div>
+    }
+}
+
+/// Order a test for a registered patient
+#[component]
+fn NewOrderPanel(on_added: impl Fn() + Copy + Send + Sync + 'static) -> impl IntoView {
+    let (patients, set_patients) = signal(Vec::<Patient>::new());
+    let (patient_uuid, set_patient_uuid) = signal(String::new());
+    let (test_type, set_test_type) = signal(TestType::Covid19.as_str().to_string());
+    let (specimen_id, set_specimen_id) = signal(String::new());
+    let (busy, set_busy) = signal(false);
+    let (message, set_message) = signal(None::<Result<String, String>>);
+
+    spawn_local(async move {
+        match invoke("get_all_patients", JsValue::NULL).await {
+            Ok(value) => match serde_wasm_bindgen::from_value::<Vec<Patient>>(value) {
+                Ok(mut list) => {
+                    list.sort_by(|a, b| {
+                        (&a.last_name, &a.first_name).cmp(&(&b.last_name, &b.first_name))
+                    });
+                    set_patients.set(list);
+                }
+                Err(e) => set_message.set(Some(Err(format!("Failed to parse patients: {:?}", e)))),
+            },
+            Err(e) => set_message.set(Some(Err(invoke_error("Failed to load patients", e)))),
+        }
+    });
+
+    let add = move |_| {
+        let specimen = specimen_id.get();
+        let args = CreateOrderArgs {
+            order: CreateOrderRequest {
+                patient_uuid: patient_uuid.get(),
+                test_type: test_type.get(),
+                specimen_id: Some(specimen).filter(|s| !s.trim().is_empty()),
+            },
+        };
+        spawn_local(async move {
+            set_busy.set(true);
+            match invoke("create_order", serde_wasm_bindgen::to_value(&args).unwrap()).await {
+                Ok(_) => {
+                    set_specimen_id.set(String::new());
+                    set_message.set(Some(Ok("Order added to the worklist".to_string())));
+                    on_added();
+                }
+                Err(e) => set_message.set(Some(Err(invoke_error("Failed to add order", e)))),
+            }
+            set_busy.set(false);
+        });
+    };
+
+    view! {
+        <div style="display: flex; flex-direction: column; gap: 1rem;">
+            <div style="display: grid; grid-template-columns: 2fr 1fr 1fr; gap: 1rem;">
+                <div class="form-group">
+                    <label class="form-label">"Patient"</label>
+                    <select
+                        class="form-input"
+                        prop:value=move || patient_uuid.get()
+                        on:change=move |e| set_patient_uuid.set(event_target_value(&e))
+                    >
+                        <option value="">"Select a patient"</option>
+                        {move || patients.get().into_iter().map(|patient| {
+                            let text = match &patient.patient_id_number {
+                                Some(id) => format!("{}, {} ({})", patient.last_name, patient.first_name, id),
+                                None => format!("{}, {}", patient.last_name, patient.first_name),
+                            };
+                            view! { <option value=patient.uuid>{text}</option> }
+                        }).collect_view()}
+                    </select>
+                </div>
+                <div class="form-group">
+                    <label class="form-label">"Test Type"</label>
+                    <select
+                        class="form-input"
+                        prop:value=move || test_type.get()
+                        on:change=move |e| set_test_type.set(event_target_value(&e))
+                    >
+                        {TestType::all().into_iter().map(|t| view! {
+                            <option value=t.as_str()>{t.as_str()}</option>
+                        }).collect_view()}
+                    </select>
+                </div>
+                <div class="form-group">
+                    <label class="form-label">"Specimen ID"</label>
+                    <input
+                        type="text"
+                        class="form-input"
+                        placeholder="Optional"
+                        prop:value=move || specimen_id.get()
+                        on:input=move |e| set_specimen_id.set(event_target_value(&e))
+                    />
+                </div>
+            </div>
+            <div style="display: flex; justify-content: flex-end;">
+                <button
+                    class="button"
+                    on:click=add
+                    disabled=move || busy.get() || patient_uuid.get().is_empty()
+                    style="padding: 0.5rem 0.75rem; font-size: 0.875rem;"
+                >
+                    "Add to Worklist"
+                </button>
+            </div>
+
+            {move || message.get().map(|result| match result {
+                Ok(msg) => view! {
+                    <div style="padding: 0.75rem; background: #d4edda; border: 1px solid #c3e6cb; border-radius: 6px; color: #155724; font-size: 0.875rem;">
+                        {msg}
+                    </div>
+                }.into_any(),
+                Err(err) => view! {
+                    <div style="padding: 0.75rem; background: #f8d7da; border: 1px solid #f5c6cb; border-radius: 6px; color: #721c24; font-size: 0.875rem;">
+                        {err}
+                    </div>
+                }.into_any(),
+            })}
+        </div>
+    }
+}
+
+/// Add the orders in a CSV file after previewing them
+#[component]
+fn OrderImportPanel(on_imported: impl Fn() + Copy + Send + Sync + 'static) -> impl IntoView {
+    let (path, set_path) = signal(String::new());
+    let (report, set_report) = signal(None::<OrderImportReport>);
+    let (busy, set_busy) = signal(false);
+    let (message, set_message) = signal(None::<Result<String, String>>);
+
+    let run = move |dry_run: bool| {
+        let args = ImportOrdersArgs {
+            path: path.get(),
+            dry_run,
+        };
+        spawn_local(async move {
+            set_busy.set(true);
+            set_message.set(None);
+            match invoke(
+                "import_orders",
+                serde_wasm_bindgen::to_value(&args).unwrap(),
+            )
+            .await
+            {
+                Ok(value) => match serde_wasm_bindgen::from_value::<OrderImportReport>(value) {
+                    Ok(loaded) => {
+                        if !loaded.dry_run {
+                            set_message.set(Some(Ok(format!(
+                                "Added {} order(s); {} duplicate(s) and {} invalid row(s) skipped",
+                                loaded.imported, loaded.duplicates, loaded.invalid
+                            ))));
+                            on_imported();
+                        }
+                        set_report.set(Some(loaded));
+                    }
+                    Err(e) => set_message
+                        .set(Some(Err(format!("Failed to parse import report: {:?}", e)))),
+                },
+                Err(e) => {
+                    let action = if dry_run {
+                        "Failed to preview orders"
+                    } else {
+                        "Failed to import orders"
+                    };
+                    set_message.set(Some(Err(invoke_error(action, e))));
+                }
+            }
+            set_busy.set(false);
+        });
+    };
+
+    view! {
+        <div style="display: flex; flex-direction: column; gap: 0.75rem;">
+            <div style="font-size: 0.875rem; color: var(--color-text-secondary);">
+                "Columns: Patient ID, Test Type and optionally Specimen ID; patients must already be registered"
+            </div>
+            <div style="display: flex; gap: 0.75rem; align-items: center;">
+                <input
+                    type="text"
+                    class="form-input"
+                    placeholder="e.g. D:\\orders.csv"
+                    prop:value=move || path.get()
+                    on:input=move |e| {
+                        set_path.set(event_target_value(&e));
+                        set_report.set(None);
+                    }
+                    style="flex: 1; font-family: monospace; font-size: 0.8125rem;"
+                />
+                <button
+                    class="button"
+                    on:click=move |_| run(true)
+                    disabled=move || busy.get() || path.get().trim().is_empty()
+                    style="padding: 0.5rem 0.75rem; font-size: 0.875rem; white-space: nowrap;"
+                >
+                    "Preview"
+                </button>
+            </div>
+
+            {move || report.get().map(|report| {
+                let summary = format!(
+                    "{} row(s): {} new, {} duplicate(s), {} invalid",
+                    report.total_rows, report.new_orders, report.duplicates, report.invalid
+                );
+                let importable = report.dry_run && report.new_orders > 0;
+                let count = report.new_orders;
+                view! {
+                    <div style="display: flex; justify-content: space-between; align-items: center; gap: 1rem;">
+                        <div style="font-weight: 500; color: var(--color-text-primary);">{summary}</div>
+                        {importable.then(|| view! {
+                            <button
+                                class="button"
+                                on:click=move |_| run(false)
+                                disabled=move || busy.get()
+                                style="padding: 0.5rem 0.75rem; font-size: 0.875rem;"
+                            >
+                                {format!("Add {} Order(s)", count)}
+                            </button>
+                        })}
+                    </div>
+                    <div style="display: flex; flex-direction: column; gap: 0.5rem; max-height: 240px; overflow: auto;">
+                        {report.rows.into_iter().map(|row| {
+                            let (color, label) = match row.status.as_str() {
+                                "new" => ("var(--color-success)", "new".to_string()),
+                                "duplicate" => (
+                                    "var(--color-warning)",
+                                    format!("duplicate of {}", row.duplicate_of.clone().unwrap_or_default()),
+                                ),
+                                _ => ("var(--color-error)", "invalid".to_string()),
+                            };
+                            let who = row.patient_name.clone().unwrap_or(row.patient_id_number.clone());
+                            let what = match &row.specimen_id {
+                                Some(specimen) => format!("{} ({})", row.test_type, specimen),
+                                None => row.test_type.clone(),
+                            };
+                            view! {
+                                <div style="font-size: 0.8125rem;">
+                                    <div>
+                                        <span style="font-family: monospace;">{format!("line {}", row.line)}</span>
+                                        {format!(" · {} · {} · ", who, what)}
+                                        <span style=format!("color: {}; font-weight: 500;", color)>{label}</span>
+                                    </div>
+                                    {(!row.errors.is_empty()).then(|| view! {
+                                        <div style="color: var(--color-error);">{row.errors.join("; ")}</div>
+                                    })}
+                                </div>
+                            }
+                        }).collect_view()}
+                    </div>
+                }
+            })}
+
+            {move || message.get().map(|result| match result {
+                Ok(msg) => view! {
+                    <div style="padding: 0.75rem; background: #d4edda; border: 1px solid #c3e6cb; border-radius: 6px; color: #155724; font-size: 0.875rem;">
+                        {msg}
+                    </div>
+                }.into_any(),
+                Err(err) => view! {
+                    <div style="padding: 0.75rem; background: #f8d7da; border: 1px solid #f5c6cb; border-radius: 6px; color: #721c24; font-size: 0.875rem;">
+                        {err}
+                    </div>
+                }.into_any(),
+            })}
+        </div>
+    }
+}
@@ -0,0 +1,66 @@
+use leptos::ev;
+use leptos::prelude::*;
+use leptos::web_sys::HtmlInputElement;
+use wasm_bindgen::JsCast;
+
+/// Keyboard-wedge scanners send each character within a few milliseconds;
+/// people type with at least this much time between keys
+const MAX_KEY_GAP_MS: f64 = 35.0;
+/// Bursts shorter than this are taken as fast typing, not a scan
+const MIN_SCAN_LENGTH: usize = 4;
+
+/// Call `on_scan` with each barcode read by a keyboard-wedge scanner while the
+/// calling component is mounted
+///
+/// A scan is a burst of characters ending in Enter with no gap longer than
+/// `MAX_KEY_GAP_MS`. The Enter is swallowed so it doesn't submit a form, and
+/// the code is taken back out of the focused input it was typed into.
+pub fn on_barcode_scan(on_scan: impl Fn(String) + 'static) {
+    // Characters of the current burst and when the last one arrived
+    let burst = StoredValue::new((String::new(), 0.0f64));
+
+    let handle = window_event_listener(ev::keydown, move |e| {
+        let now = js_sys::Date::now();
+        let key = e.key();
+        let (code, last_key_at) = burst.get_value();
+        let in_burst = now - last_key_at <= MAX_KEY_GAP_MS;
+
+        if e.ctrl_key() || e.alt_key() || e.meta_key() {
+            burst.set_value((String::new(), 0.0));
+        } else if key == "Enter" {
+            burst.set_value((String::new(), 0.0));
+            if in_burst && code.chars().count() >= MIN_SCAN_LENGTH {
+                e.prevent_default();
+                remove_from_focused_input(&code);
+                on_scan(code);
+            }
+        } else if key.chars().count() == 1 {
+            let code = if in_burst { code + &key } else { key };
+            burst.set_value((code, now));
+        } else if key != "Shift" {
+            // Scanners hold Shift for capitals; any other key is a person
+            burst.set_value((String::new(), 0.0));
+        }
+    });
+    on_cleanup(move || handle.remove());
+}
+
+/// Undo the scanned characters in the focused text input, if they landed there
+fn remove_from_focused_input(code: &str) {
+    let Some(input) = document()
+        .active_element()
+        .and_then(|el| el.dyn_into::<HtmlInputElement>().ok())
+    else {
+        return;
+    };
+    let value = input.value();
+    let Some(typed) = value.strip_suffix(code) else {
+        return;
+    };
+    input.set_value(typed);
+    // Let the input's handler see the new value, as if the user had deleted it
+    if let Ok(event) = document().create_event("Event") {
+        event.init_event_with_bubbles("input", true);
+        let _ = input.dispatch_event(&event);
+    }
+}
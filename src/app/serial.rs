@@ -1,3 +1,4 @@
+use crate::app::errors::invoke_error;
 use js_sys::Function;
 use leptos::prelude::*;
 use leptos::web_sys::console;
@@ -5,6 +6,9 @@ use serde::{Deserialize, Serialize};
 use wasm_bindgen::closure::Closure;
 use wasm_bindgen::{prelude::*, JsCast};
 
+// Use shared types
+use shared_types::DeviceStatus;
+
 /// Detection data received from the API
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DetectionData {
@@ -12,6 +16,10 @@ pub struct DetectionData {
     pub confidence: Option<f64>,
     pub dataset_id: String,
     pub processed_at: String,
+    #[serde(default)]
+    pub device_id: Option<String>,
+    #[serde(default)]
+    pub test_uuid: Option<String>,
 }
 
 #[wasm_bindgen]
@@ -28,28 +36,60 @@ extern "C" {
 /// This function:
 /// - Invokes the backend `start_serial` command
 /// - Sets up a listener for `serial:data` events to receive serial data
-/// - Sets up a listener for `serial:status` events to track connection status
+/// - Sets up a listener for `serial:device_status` events to track each device
 /// - Sets up listeners for detection events (loading, result, error)
+///
+/// Detection events are only applied when they belong to the current test,
+/// so datasets from other devices don't affect the test being run.
 pub async fn initialize_serial(
     set_latest_serial: WriteSignal<String>,
-    set_connected: WriteSignal<bool>,
+    set_device_statuses: WriteSignal<Vec<DeviceStatus>>,
+    current_test_uuid: ReadSignal<Option<String>>,
     set_detection_loading: WriteSignal<bool>,
     set_detection_result: WriteSignal<Option<DetectionData>>,
     set_detection_error: WriteSignal<Option<String>>,
 ) {
     // Start the serial communication in the backend
-    let _ = invoke("start_serial", JsValue::NULL).await;
+    if let Err(e) = invoke("start_serial", JsValue::NULL).await {
+        invoke_error("Failed to start serial monitoring", e);
+    }
 
     // Set up serial data event handler
     setup_serial_data_listener(set_latest_serial).await;
 
-    // Set up connection status event handler
-    setup_status_listener(set_connected).await;
+    // Set up device status event handler and load the initial snapshot
+    setup_device_status_listener(set_device_statuses).await;
+    if let Ok(value) = invoke("get_device_statuses", JsValue::NULL).await {
+        if let Ok(statuses) = serde_wasm_bindgen::from_value::<Vec<DeviceStatus>>(value) {
+            set_device_statuses.set(statuses);
+        }
+    }
 
     // Set up detection event handlers
-    setup_detection_loading_listener(set_detection_loading).await;
-    setup_detection_result_listener(set_detection_result, set_detection_loading).await;
-    setup_detection_error_listener(set_detection_error, set_detection_loading).await;
+    setup_detection_loading_listener(set_detection_loading, current_test_uuid).await;
+    setup_detection_result_listener(
+        set_detection_result,
+        set_detection_loading,
+        current_test_uuid,
+    )
+    .await;
+    setup_detection_error_listener(
+        set_detection_error,
+        set_detection_loading,
+        current_test_uuid,
+    )
+    .await;
+}
+
+/// Whether a detection event payload belongs to the current test
+fn is_for_current_test(payload: &JsValue, current_test_uuid: ReadSignal<Option<String>>) -> bool {
+    let event_test = js_sys::Reflect::get(payload, &JsValue::from_str("test_uuid"))
+        .ok()
+        .and_then(|v| v.as_string());
+    match (event_test, current_test_uuid.get_untracked()) {
+        (Some(event_test), Some(current)) => event_test == current,
+        _ => false,
+    }
 }
 
 /// Set up listener for serial data events
@@ -57,9 +97,18 @@ async fn setup_serial_data_listener(set_latest_serial: WriteSignal<String>) {
     let update_latest_serial = set_latest_serial;
     let serial_event_handler = Closure::wrap(Box::new(move |event: JsValue| {
         if let Ok(payload) = js_sys::Reflect::get(&event, &JsValue::from_str("payload")) {
-            if let Some(s) = payload.as_string() {
-                update_latest_serial.set(s.clone());
-                console::log_1(&JsValue::from_str(&format!("serial:data: {}", s)));
+            let device = js_sys::Reflect::get(&payload, &JsValue::from_str("device_id"))
+                .ok()
+                .and_then(|v| v.as_string());
+            let line = js_sys::Reflect::get(&payload, &JsValue::from_str("line"))
+                .ok()
+                .and_then(|v| v.as_string());
+            if let (Some(device), Some(line)) = (device, line) {
+                update_latest_serial.set(line.clone());
+                console::log_1(&JsValue::from_str(&format!(
+                    "serial:data [{}]: {}",
+                    device, line
+                )));
             } else {
                 console::log_1(&payload);
             }
@@ -72,37 +121,44 @@ async fn setup_serial_data_listener(set_latest_serial: WriteSignal<String>) {
     serial_event_handler.forget();
 }
 
-/// Set up listener for connection status events
-async fn setup_status_listener(set_connected: WriteSignal<bool>) {
-    let update_connected = set_connected;
+/// Set up listener for per-device status events
+async fn setup_device_status_listener(set_device_statuses: WriteSignal<Vec<DeviceStatus>>) {
+    let update_statuses = set_device_statuses;
     let status_event_handler = Closure::wrap(Box::new(move |event: JsValue| {
         if let Ok(payload) = js_sys::Reflect::get(&event, &JsValue::from_str("payload")) {
-            // Try payload.connected boolean first
-            if let Ok(val) = js_sys::Reflect::get(&payload, &JsValue::from_str("connected")) {
-                if let Some(b) = val.as_bool() {
-                    update_connected.set(b);
+            match serde_wasm_bindgen::from_value::<DeviceStatus>(payload) {
+                Ok(status) => {
                     console::log_1(&JsValue::from_str(&format!(
-                        "serial:status connected={}",
-                        b
+                        "serial:device_status [{}] connected={}",
+                        status.device_id, status.connected
+                    )));
+                    update_statuses.update(|statuses| {
+                        match statuses
+                            .iter_mut()
+                            .find(|s| s.device_id == status.device_id)
+                        {
+                            Some(existing) => *existing = status,
+                            None => {
+                                statuses.push(status);
+                                statuses.sort_by(|a, b| a.device_id.cmp(&b.device_id));
+                            }
+                        }
+                    });
+                }
+                Err(e) => {
+                    console::log_1(&JsValue::from_str(&format!(
+                        "Failed to parse device status: {:?}",
+                        e
                     )));
                 }
-            } else if let Some(b) = payload.as_bool() {
-                // Or a bare boolean payload
-                update_connected.set(b);
-                console::log_1(&JsValue::from_str(&format!(
-                    "serial:status connected={} (bool)",
-                    b
-                )));
-            } else {
-                console::log_1(&payload);
             }
         } else {
-            console::log_1(&JsValue::from_str("serial:status: <no payload>"));
+            console::log_1(&JsValue::from_str("serial:device_status: <no payload>"));
         }
     }) as Box<dyn FnMut(JsValue)>);
 
     let _unlisten_status = listen(
-        "serial:status",
+        "serial:device_status",
         status_event_handler.as_ref().unchecked_ref(),
     )
     .await;
@@ -110,10 +166,16 @@ async fn setup_status_listener(set_connected: WriteSignal<bool>) {
 }
 
 /// Set up listener for detection loading events
-async fn setup_detection_loading_listener(set_detection_loading: WriteSignal<bool>) {
+async fn setup_detection_loading_listener(
+    set_detection_loading: WriteSignal<bool>,
+    current_test_uuid: ReadSignal<Option<String>>,
+) {
     let update_loading = set_detection_loading;
     let loading_event_handler = Closure::wrap(Box::new(move |event: JsValue| {
         if let Ok(payload) = js_sys::Reflect::get(&event, &JsValue::from_str("payload")) {
+            if !is_for_current_test(&payload, current_test_uuid) {
+                return;
+            }
             if let Ok(loading_val) = js_sys::Reflect::get(&payload, &JsValue::from_str("loading")) {
                 if let Some(loading) = loading_val.as_bool() {
                     update_loading.set(loading);
@@ -138,11 +200,15 @@ async fn setup_detection_loading_listener(set_detection_loading: WriteSignal<boo
 async fn setup_detection_result_listener(
     set_detection_result: WriteSignal<Option<DetectionData>>,
     set_detection_loading: WriteSignal<bool>,
+    current_test_uuid: ReadSignal<Option<String>>,
 ) {
     let update_result = set_detection_result;
     let update_loading = set_detection_loading;
     let result_event_handler = Closure::wrap(Box::new(move |event: JsValue| {
         if let Ok(payload) = js_sys::Reflect::get(&event, &JsValue::from_str("payload")) {
+            if !is_for_current_test(&payload, current_test_uuid) {
+                return;
+            }
             // Try to deserialize the payload into DetectionData
             match serde_wasm_bindgen::from_value::<DetectionData>(payload.clone()) {
                 Ok(detection) => {
@@ -175,11 +241,15 @@ async fn setup_detection_result_listener(
 async fn setup_detection_error_listener(
     set_detection_error: WriteSignal<Option<String>>,
     set_detection_loading: WriteSignal<bool>,
+    current_test_uuid: ReadSignal<Option<String>>,
 ) {
     let update_error = set_detection_error;
     let update_loading = set_detection_loading;
     let error_event_handler = Closure::wrap(Box::new(move |event: JsValue| {
         if let Ok(payload) = js_sys::Reflect::get(&event, &JsValue::from_str("payload")) {
+            if !is_for_current_test(&payload, current_test_uuid) {
+                return;
+            }
             if let Ok(error_val) = js_sys::Reflect::get(&payload, &JsValue::from_str("error")) {
                 if let Some(error_msg) = error_val.as_string() {
                     console::log_1(&JsValue::from_str(&format!(